@@ -0,0 +1,289 @@
+use crate::cli::settings::auto_stop::AutoStopOptions;
+use crate::cli::settings::packet_manipulation::PacketManipulationSettings;
+use crate::cli::settings::runtime_options::RuntimeOptions;
+use crate::cli::Cli;
+use crate::network::core::packet_data::MAX_BATCH_SIZE;
+use crate::network::utils::filter::validate_filter_with_docs;
+use clap::{Args, Subcommand};
+use std::path::PathBuf;
+
+/// Exit status of a command that completed successfully.
+pub const EXIT_SUCCESS: i32 = 0;
+/// Exit status of a command that failed, e.g. because a configuration file is invalid or missing.
+pub const EXIT_FAILURE: i32 = 1;
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Manipulate packets matching the filter until stopped with Ctrl+C
+    Run(SessionArgs),
+    /// Manipulate packets while configuring the settings in the Text User Interface
+    Tui(SessionArgs),
+    /// Manage configuration files
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    /// Inject the packets of a pcap capture through the manipulation modules, keeping their timing
+    Replay(ReplayArgs),
+    /// List built-in configurations for common network conditions
+    Presets {
+        #[command(subcommand)]
+        command: Option<PresetCommand>,
+    },
+    /// Measure the processing cost per packet of each manipulation module on synthetic packets
+    Bench(BenchArgs),
+}
+
+/// Options shared by the commands that manipulate captured packets.
+#[derive(Args, Debug, Default)]
+pub struct SessionArgs {
+    /// Filter expression for capturing packets
+    #[arg(short, long, value_parser = validate_filter_with_docs)]
+    pub filter: Option<String>,
+
+    /// Use an existing configuration file based on specified name or path
+    #[arg(long)]
+    pub use_config: Option<String>,
+
+    /// Watch the configuration file given with `--use-config` and apply changes while running
+    #[arg(long, requires = "use_config")]
+    pub watch_config: bool,
+
+    #[command(flatten)]
+    pub options: RuntimeOptions,
+
+    #[command(flatten)]
+    pub auto_stop: AutoStopOptions,
+
+    #[command(flatten)]
+    pub packet_manipulation_settings: PacketManipulationSettings,
+}
+
+impl SessionArgs {
+    /// Moves the options into `cli`, keeping the ones `cli` already has where these are unset.
+    pub fn apply_to(self, cli: &mut Cli) {
+        cli.filter = self.filter.or(cli.filter.take());
+        cli.config.use_config = self.use_config.or(cli.config.use_config.take());
+        cli.config.watch_config |= self.watch_config;
+        cli.options.seed = self.options.seed.or(cli.options.seed);
+        cli.options.drain_policy = self.options.drain_policy.or(cli.options.drain_policy);
+        cli.options.workers = self.options.workers.or(cli.options.workers);
+
+        let auto_stop = self.auto_stop;
+        let current = &mut cli.auto_stop;
+        current.duration = auto_stop.duration.or(current.duration);
+        current.packet_limit = auto_stop.packet_limit.or(current.packet_limit);
+        current.report = auto_stop.report.or(current.report.take());
+
+        let settings = self.packet_manipulation_settings;
+        let current = &mut cli.packet_manipulation_settings;
+        current.drop = settings.drop.or(current.drop.take());
+        current.delay = settings.delay.or(current.delay.take());
+        current.throttle = settings.throttle.or(current.throttle.take());
+        current.reorder = settings.reorder.or(current.reorder.take());
+        current.tamper = settings.tamper.or(current.tamper.take());
+        current.duplicate = settings.duplicate.or(current.duplicate.take());
+        current.bandwidth = settings.bandwidth.or(current.bandwidth.take());
+        current.dns = settings.dns.or(current.dns.take());
+        current.topology = settings.topology.or(current.topology.take());
+        current.trace = settings.trace.or(current.trace.take());
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct ReplayArgs {
+    /// Path of the pcap capture to replay. Captures of raw IP or Ethernet frames are supported
+    pub file: PathBuf,
+
+    /// Playback speed relative to the recorded timing, e.g. 2 replays twice as fast
+    #[arg(long, default_value_t = 1.0, value_parser = parse_speed)]
+    pub speed: f64,
+
+    /// Inject the packets as inbound instead of outbound
+    #[arg(long)]
+    pub inbound: bool,
+
+    /// Use an existing configuration file based on specified name or path
+    #[arg(long)]
+    pub use_config: Option<String>,
+
+    #[command(flatten)]
+    pub options: RuntimeOptions,
+
+    #[command(flatten)]
+    pub auto_stop: AutoStopOptions,
+
+    #[command(flatten)]
+    pub packet_manipulation_settings: PacketManipulationSettings,
+}
+
+#[derive(Args, Debug)]
+pub struct BenchArgs {
+    /// Number of packets to process for each module
+    #[arg(long, default_value_t = 100_000)]
+    pub packets: usize,
+
+    /// Size of each packet in bytes, including the IPv4 and UDP headers
+    #[arg(long, default_value_t = 512, value_parser = clap::value_parser!(u16).range(28..))]
+    pub size: u16,
+
+    /// Number of packets processed together, like a batch received from WinDivert
+    #[arg(long, default_value_t = MAX_BATCH_SIZE, value_parser = parse_batch_size)]
+    pub batch: usize,
+}
+
+fn parse_batch_size(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(size) if size > 0 => Ok(size),
+        _ => Err(format!("`{}` is not a positive number", value)),
+    }
+}
+
+fn parse_speed(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(speed) if speed > 0.0 && speed.is_finite() => Ok(speed),
+        _ => Err(format!("`{}` is not a positive number", value)),
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Create a configuration file with all settings commented out
+    Create {
+        /// Name of the configuration
+        name: String,
+    },
+    /// List the configurations in the fumble config directory
+    List,
+    /// Print a configuration file
+    Show {
+        /// Name of a configuration in the fumble config directory, or a path to a file
+        config: String,
+    },
+    /// Delete a configuration file
+    Delete {
+        /// Name of a configuration in the fumble config directory
+        name: String,
+
+        /// Delete without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Open a configuration in the editor set in `VISUAL` or `EDITOR` and validate it afterwards.
+    /// The configuration is created if it does not exist
+    Edit {
+        /// Name of a configuration in the fumble config directory, or a path to a file
+        config: String,
+    },
+    /// Validate a configuration and write it to a file, or print it if no output is given
+    Export {
+        /// Name of a configuration in the fumble config directory, or a path to a file
+        config: String,
+
+        /// Path of the file to write
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Export a compact URL-safe string instead of the file, e.g. to paste into a ticket.
+        /// Comments are not included
+        #[arg(short, long)]
+        string: bool,
+    },
+    /// Store a configuration file or string in the fumble config directory after validating it.
+    /// Asks before replacing an existing configuration
+    Import {
+        /// Path of the file to import, or a string created with `fumble config export --string`
+        source: String,
+
+        /// Name to store the configuration under, defaults to the file name. Required when
+        /// importing a string
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Replace an existing configuration with the same name without asking
+        #[arg(long)]
+        force: bool,
+    },
+    /// Check a configuration file and report every problem with its line, column and field.
+    /// Exits with a non-zero status if the file is invalid.
+    Validate {
+        /// Name of a configuration in the fumble config directory, or a path to a file
+        config: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PresetCommand {
+    /// List the built-in presets
+    List,
+    /// Print the configuration of a preset
+    Show {
+        /// Name of the preset
+        name: String,
+    },
+    /// Save a preset to the fumble config directory so it can be used with `--use-config`
+    Save {
+        /// Name of the preset
+        name: String,
+
+        /// Name to store the configuration under, defaults to the preset name
+        #[arg(long = "as")]
+        config_name: Option<String>,
+
+        /// Replace an existing configuration with the same name
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn parse(args: &[&str]) -> Cli {
+        let mut cli = Cli::try_parse_from(args).unwrap();
+        if let Some(Command::Run(session) | Command::Tui(session)) = cli.command.take() {
+            session.apply_to(&mut cli);
+        }
+        cli
+    }
+
+    #[test]
+    fn test_run_applies_session_args() {
+        let cli = parse(&[
+            "fumble",
+            "run",
+            "--drop-probability",
+            "0.1",
+            "--seed",
+            "7",
+            "--packet-limit",
+            "1000",
+        ]);
+        assert!(cli.packet_manipulation_settings.drop.is_some());
+        assert_eq!(cli.options.seed, Some(7));
+        assert_eq!(cli.auto_stop.packet_limit, Some(1000));
+    }
+
+    #[test]
+    fn test_subcommand_options_take_precedence() {
+        let cli = parse(&[
+            "fumble",
+            "--seed",
+            "1",
+            "--use-config",
+            "lossy",
+            "run",
+            "--seed",
+            "2",
+        ]);
+        assert_eq!(cli.options.seed, Some(2));
+        assert_eq!(cli.config.use_config.as_deref(), Some("lossy"));
+    }
+
+    #[test]
+    fn test_invalid_numbers_are_rejected() {
+        assert!(Cli::try_parse_from(["fumble", "replay", "capture.pcap", "--speed", "0"]).is_err());
+        assert!(Cli::try_parse_from(["fumble", "bench", "--batch", "0"]).is_err());
+        assert!(Cli::try_parse_from(["fumble", "bench", "--size", "20"]).is_err());
+    }
+}
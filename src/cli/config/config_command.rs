@@ -0,0 +1,301 @@
+use crate::cli::command::{ConfigCommand, EXIT_FAILURE, EXIT_SUCCESS};
+use crate::cli::config::config_options::{
+    get_config_file_path, resolve_config_path, ConfigOptions,
+};
+use crate::cli::config::config_share::{from_share_string, is_share_string, to_share_string};
+use crate::cli::config::config_validation::{validate_config, ConfigIssue};
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::{env, fs, io};
+
+/// Runs a `fumble config` command, printing its output. Returns the exit status.
+pub fn run_config_command(command: ConfigCommand) -> i32 {
+    match command {
+        ConfigCommand::Create { name } => create_config(&name),
+        ConfigCommand::List => list_configs(),
+        ConfigCommand::Show { config } => match read_config(&config) {
+            Ok((_, content)) => {
+                print!("{}", content);
+                EXIT_SUCCESS
+            }
+            Err(status) => status,
+        },
+        ConfigCommand::Delete { name, yes } => delete_config(&name, yes),
+        ConfigCommand::Edit { config } => edit_config(&config),
+        ConfigCommand::Export {
+            config,
+            output,
+            string,
+        } => export_config(&config, output.as_deref(), string),
+        ConfigCommand::Import {
+            source,
+            name,
+            force,
+        } => import_config(&source, name, force),
+        ConfigCommand::Validate { config } => match read_config(&config) {
+            Ok((path, content)) => report_validation(&path, &content),
+            Err(status) => status,
+        },
+    }
+}
+
+pub fn create_config(name: &str) -> i32 {
+    let path = get_config_file_path(name);
+    if path.exists() {
+        eprintln!(
+            "Configuration {:?} already exists at {}",
+            name,
+            path.display()
+        );
+        return EXIT_FAILURE;
+    }
+    match ConfigOptions::create_default_config(name) {
+        Ok(()) => {
+            println!("Created {}", path.display());
+            EXIT_SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Failed to create configuration {:?}: {}", name, e);
+            EXIT_FAILURE
+        }
+    }
+}
+
+pub fn list_configs() -> i32 {
+    match ConfigOptions::list_all_configs() {
+        Ok(mut configs) => {
+            configs.sort();
+            for config in configs {
+                println!("{}", config);
+            }
+            EXIT_SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Failed to list configs: {}", e);
+            EXIT_FAILURE
+        }
+    }
+}
+
+/// Reads the configuration given by name or path. On failure the error is printed and the exit
+/// status returned.
+fn read_config(config: &str) -> Result<(PathBuf, String), i32> {
+    let path = resolve_config_path(config);
+    match fs::read_to_string(&path) {
+        Ok(content) => Ok((path, content)),
+        Err(e) => {
+            eprintln!("{}: {}", path.display(), e);
+            Err(EXIT_FAILURE)
+        }
+    }
+}
+
+/// Validates `content`, printing every problem found prefixed with `path`.
+fn report_validation(path: &Path, content: &str) -> i32 {
+    let issues = validate_config(content);
+    if issues.is_empty() {
+        println!("{}: valid", path.display());
+        return EXIT_SUCCESS;
+    }
+    print_issues(path, &issues);
+    EXIT_FAILURE
+}
+
+fn print_issues(path: &Path, issues: &[ConfigIssue]) {
+    for issue in issues {
+        eprintln!("{}:{}", path.display(), issue);
+    }
+    eprintln!("{} problem(s) found", issues.len());
+}
+
+fn delete_config(name: &str, yes: bool) -> i32 {
+    let path = get_config_file_path(name);
+    if !path.is_file() {
+        eprintln!("Configuration {:?} does not exist", name);
+        return EXIT_FAILURE;
+    }
+    if !yes && !confirm(&format!("Delete {}?", path.display())) {
+        eprintln!("Nothing deleted");
+        return EXIT_FAILURE;
+    }
+    match ConfigOptions::delete_config(name) {
+        Ok(path) => {
+            println!("Deleted {}", path.display());
+            EXIT_SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Failed to delete configuration {:?}: {}", name, e);
+            EXIT_FAILURE
+        }
+    }
+}
+
+/// Asks a yes or no question on the terminal, defaulting to no.
+pub fn confirm(question: &str) -> bool {
+    print!("{} [y/N] ", question);
+    if io::stdout().flush().is_err() {
+        return false;
+    }
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+fn edit_config(config: &str) -> i32 {
+    let path = resolve_config_path(config);
+    if !path.exists() {
+        let status = create_config(config);
+        if status != EXIT_SUCCESS {
+            return status;
+        }
+    }
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| default_editor().to_string());
+    // The editor may come with arguments, e.g. `code --wait`
+    let mut parts = editor.split_whitespace();
+    let program = match parts.next() {
+        Some(program) => program,
+        None => {
+            eprintln!("No editor set, set the VISUAL or EDITOR environment variable");
+            return EXIT_FAILURE;
+        }
+    };
+    match Command::new(program).args(parts).arg(&path).status() {
+        Ok(status) if status.success() => {}
+        Ok(status) => {
+            eprintln!("Editor {:?} exited with {}", editor, status);
+            return EXIT_FAILURE;
+        }
+        Err(e) => {
+            eprintln!("Failed to start editor {:?}: {}", editor, e);
+            return EXIT_FAILURE;
+        }
+    }
+    match read_config(&path.to_string_lossy()) {
+        Ok((path, content)) => report_validation(&path, &content),
+        Err(status) => status,
+    }
+}
+
+fn default_editor() -> &'static str {
+    if cfg!(windows) {
+        "notepad"
+    } else {
+        "vi"
+    }
+}
+
+fn export_config(config: &str, output: Option<&Path>, string: bool) -> i32 {
+    let (path, content) = match read_config(config) {
+        Ok(config) => config,
+        Err(status) => return status,
+    };
+    let issues = validate_config(&content);
+    if !issues.is_empty() {
+        print_issues(&path, &issues);
+        return EXIT_FAILURE;
+    }
+    let exported = if string {
+        match to_share_string(&content) {
+            Ok(shared) => format!("{}\n", shared),
+            Err(e) => {
+                eprintln!("{}: {}", path.display(), e);
+                return EXIT_FAILURE;
+            }
+        }
+    } else {
+        content
+    };
+    match output {
+        Some(output) => match fs::write(output, exported) {
+            Ok(()) => {
+                println!("Exported to {}", output.display());
+                EXIT_SUCCESS
+            }
+            Err(e) => {
+                eprintln!("Failed to write {}: {}", output.display(), e);
+                EXIT_FAILURE
+            }
+        },
+        None => {
+            print!("{}", exported);
+            EXIT_SUCCESS
+        }
+    }
+}
+
+fn import_config(source: &str, name: Option<String>, force: bool) -> i32 {
+    let (content, name) = if is_share_string(source) {
+        match from_share_string(source) {
+            Ok(content) => (content, name),
+            Err(e) => {
+                eprintln!("{}", e);
+                return EXIT_FAILURE;
+            }
+        }
+    } else {
+        let file = Path::new(source);
+        match fs::read_to_string(file) {
+            Ok(content) => (
+                content,
+                name.or_else(|| {
+                    file.file_stem()
+                        .map(|stem| stem.to_string_lossy().to_string())
+                }),
+            ),
+            Err(e) => {
+                eprintln!("{}: {}", file.display(), e);
+                return EXIT_FAILURE;
+            }
+        }
+    };
+    let issues = validate_config(&content);
+    if !issues.is_empty() {
+        let origin = if is_share_string(source) {
+            "configuration string"
+        } else {
+            source
+        };
+        print_issues(Path::new(origin), &issues);
+        return EXIT_FAILURE;
+    }
+    match name {
+        Some(name) => store_config(&name, &content, force),
+        None if is_share_string(source) => {
+            eprintln!("Configuration strings hold no name, use --name");
+            EXIT_FAILURE
+        }
+        None => {
+            eprintln!("Cannot derive a name from {:?}, use --name", source);
+            EXIT_FAILURE
+        }
+    }
+}
+
+/// Stores validated configuration content under `name`. An existing configuration is only
+/// replaced if `force` is set or the user confirms it on the terminal.
+pub fn store_config(name: &str, content: &str, force: bool) -> i32 {
+    match ConfigOptions::store_config_content(name, content, force) {
+        Ok(path) => {
+            println!("Saved {}", path.display());
+            EXIT_SUCCESS
+        }
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+            if io::stdin().is_terminal()
+                && confirm(&format!("Configuration {:?} exists, replace it?", name))
+            {
+                return store_config(name, content, true);
+            }
+            eprintln!("{}, use --force to replace it", e);
+            EXIT_FAILURE
+        }
+        Err(e) => {
+            eprintln!("Failed to save configuration {:?}: {}", name, e);
+            EXIT_FAILURE
+        }
+    }
+}
@@ -0,0 +1,391 @@
+use crate::cli::config::config_validation::{validate_config, ConfigValidationError};
+use crate::cli::settings::packet_manipulation::PacketManipulationSettings;
+use crate::cli::settings::runtime_options::RuntimeOptions;
+use crate::cli::Cli;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+use std::{fs, io};
+use toml_edit::{DocumentMut, Item, TableLike, Value};
+
+/// Schema version written to new configuration files.
+pub const CURRENT_VERSION: u32 = 1;
+
+const VERSION_KEY: &str = "version";
+const FILTER_KEY: &str = "filter";
+const OPTIONS_KEY: &str = "options";
+pub(crate) const TUI_KEY: &str = "tui";
+
+/// Upgrades applied to a configuration table when loading, where the migration at index `i`
+/// upgrades a table of version `i` to version `i + 1`. Files written before the format was
+/// versioned have no version and are treated as version 0.
+const MIGRATIONS: &[fn(&mut toml::Table)] = &[migrate_v0_to_v1];
+
+/// Version 0 files hold the module sections and optionally a filter at the top level,
+/// which version 1 keeps as is.
+fn migrate_v0_to_v1(_table: &mut toml::Table) {}
+
+/// Which TUI panes are open on start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct TuiPreferences {
+    pub show_logs: bool,
+    pub show_graphs: bool,
+    pub show_inspector: bool,
+}
+
+/// Content of a configuration file: the packet filter, runtime options, TUI preferences and the
+/// manipulation settings. The module sections are stored at the top level next to the other keys.
+#[derive(Debug, Default)]
+pub struct ConfigDocument {
+    pub filter: Option<String>,
+    pub options: RuntimeOptions,
+    pub tui: TuiPreferences,
+    pub settings: PacketManipulationSettings,
+}
+
+impl ConfigDocument {
+    /// Load a configuration file, migrating it from older schema versions
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let content = fs::read_to_string(&path)?;
+        let mut document = Self::from_toml_str(&content)?;
+        // A relative trace file is next to the config file
+        if let Some(trace) = &mut document.settings.trace {
+            trace.base_dir = path.as_ref().parent().map(Path::to_path_buf);
+        }
+        Ok(document)
+    }
+
+    /// Parse a configuration, failing with a [`ConfigValidationError`] listing every problem
+    /// found if it is invalid
+    pub fn from_toml_str(content: &str) -> io::Result<Self> {
+        let issues = validate_config(content);
+        if !issues.is_empty() {
+            return Err(invalid_data(ConfigValidationError { issues }));
+        }
+        let mut table: toml::Table = toml::from_str(content).map_err(invalid_data)?;
+        migrate(&mut table)?;
+        table.remove(VERSION_KEY);
+
+        let filter = match table.remove(FILTER_KEY) {
+            Some(toml::Value::String(filter)) => Some(filter),
+            Some(other) => {
+                return Err(invalid_data(format!(
+                    "filter must be a string, found {}",
+                    other.type_str()
+                )))
+            }
+            None => None,
+        };
+        let options = match table.remove(OPTIONS_KEY) {
+            Some(options) => options.try_into().map_err(invalid_data)?,
+            None => RuntimeOptions::default(),
+        };
+        let tui = match table.remove(TUI_KEY) {
+            Some(tui) => tui.try_into().map_err(invalid_data)?,
+            None => TuiPreferences::default(),
+        };
+        let settings = toml::Value::Table(table).try_into().map_err(invalid_data)?;
+        Ok(ConfigDocument {
+            filter,
+            options,
+            tui,
+            settings,
+        })
+    }
+
+    /// Save the configuration to a file in the current schema version
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut file = fs::File::create(path)?;
+        file.write_all(self.to_toml_string()?.as_bytes())
+    }
+
+    /// Serialize the configuration into the content of an existing configuration file, keeping
+    /// its comments and the formatting of values that did not change. Content that cannot be
+    /// parsed is replaced as a whole
+    pub fn update_toml_string(&self, existing: &str) -> io::Result<String> {
+        let mut document: DocumentMut = match existing.parse() {
+            Ok(document) => document,
+            Err(_) => return self.to_toml_string(),
+        };
+        let updated: DocumentMut = self.to_toml_string()?.parse().map_err(invalid_data)?;
+        merge_table(document.as_table_mut(), updated.as_table());
+        Ok(document.to_string())
+    }
+
+    pub fn to_toml_string(&self) -> io::Result<String> {
+        toml::to_string_pretty(&self.to_toml_table()?).map_err(invalid_data)
+    }
+
+    pub fn to_toml_table(&self) -> io::Result<toml::Table> {
+        document_table(
+            self.filter.as_deref(),
+            &self.options,
+            &self.tui,
+            &self.settings,
+        )
+    }
+
+    /// Create a default configuration file with the version set and all other fields
+    /// set to default values but commented out
+    pub fn create_default_config_file<P: AsRef<Path>>(path: P) -> io::Result<()> {
+        let content = format!(
+            "{} = {}\n# {} = \"outbound\"\n\n[{}]\n# seed = 0\n\n[{}]\n{}\n{}",
+            VERSION_KEY,
+            CURRENT_VERSION,
+            FILTER_KEY,
+            OPTIONS_KEY,
+            TUI_KEY,
+            toml::to_string_pretty(&TuiPreferences::default())
+                .map_err(invalid_data)?
+                .lines()
+                .map(|line| format!("# {}\n", line))
+                .collect::<String>(),
+            PacketManipulationSettings::commented_default_toml()?
+        );
+        let mut file = fs::File::create(path)?;
+        file.write_all(content.as_bytes())
+    }
+}
+
+/// Serializes the configuration held by `cli` the same way it would be saved to a file.
+pub fn cli_to_toml_table(cli: &Cli) -> io::Result<toml::Table> {
+    document_table(
+        cli.filter.as_deref(),
+        &cli.options,
+        &cli.tui_preferences,
+        &cli.packet_manipulation_settings,
+    )
+}
+
+fn document_table(
+    filter: Option<&str>,
+    options: &RuntimeOptions,
+    tui: &TuiPreferences,
+    settings: &PacketManipulationSettings,
+) -> io::Result<toml::Table> {
+    let mut table = settings.to_toml_table()?;
+    table.insert(
+        VERSION_KEY.to_string(),
+        toml::Value::Integer(CURRENT_VERSION.into()),
+    );
+    if let Some(filter) = filter {
+        table.insert(FILTER_KEY.to_string(), toml::Value::from(filter));
+    }
+    table.insert(
+        OPTIONS_KEY.to_string(),
+        toml::Value::try_from(options).map_err(invalid_data)?,
+    );
+    table.insert(
+        TUI_KEY.to_string(),
+        toml::Value::try_from(tui).map_err(invalid_data)?,
+    );
+    Ok(table)
+}
+
+/// Makes `existing` hold the keys and values of `updated`, leaving the items whose values are
+/// unchanged untouched.
+fn merge_table(existing: &mut dyn TableLike, updated: &dyn TableLike) {
+    let removed: Vec<String> = existing
+        .iter()
+        .filter(|(key, _)| !updated.contains_key(key))
+        .map(|(key, _)| key.to_string())
+        .collect();
+    for key in removed {
+        existing.remove(&key);
+    }
+    for (key, item) in updated.iter() {
+        match existing.get_mut(key) {
+            Some(current) => merge_item(current, item),
+            None => {
+                let mut item = item.clone();
+                move_to_end(&mut item);
+                existing.insert(key, item);
+            }
+        }
+    }
+}
+
+fn merge_item(current: &mut Item, updated: &Item) {
+    if let (Some(current), Some(updated)) = (current.as_table_like_mut(), updated.as_table_like()) {
+        merge_table(current, updated);
+        return;
+    }
+    if let (Some(current), Some(updated)) = (current.as_value_mut(), updated.as_value()) {
+        if !same_value(current, updated) {
+            // Keep the whitespace and trailing comment around the value
+            let decor = current.decor().clone();
+            *current = updated.clone();
+            *current.decor_mut() = decor;
+        }
+        return;
+    }
+    *current = updated.clone();
+    move_to_end(current);
+}
+
+/// Compares values by their meaning rather than their formatting, e.g. `0.10` equals `0.1`.
+fn same_value(a: &Value, b: &Value) -> bool {
+    let parse = |value: &Value| format!("value = {}", value).parse::<toml::Table>().ok();
+    let a = parse(a);
+    a.is_some() && a == parse(b)
+}
+
+/// Places tables copied from another document after the tables of the document they are added to.
+fn move_to_end(item: &mut Item) {
+    if let Some(table) = item.as_table_mut() {
+        table.set_position(usize::MAX);
+        for (_, child) in table.iter_mut() {
+            move_to_end(child);
+        }
+    } else if let Some(tables) = item.as_array_of_tables_mut() {
+        for table in tables.iter_mut() {
+            table.set_position(usize::MAX);
+        }
+    }
+}
+
+/// Upgrades `table` to the current schema version, rejecting files written by newer versions.
+fn migrate(table: &mut toml::Table) -> io::Result<()> {
+    let version = match table.get(VERSION_KEY) {
+        None => 0,
+        Some(toml::Value::Integer(version)) if *version >= 0 => *version as u64,
+        Some(other) => {
+            return Err(invalid_data(format!(
+                "version must be a non-negative integer, found {}",
+                other
+            )))
+        }
+    };
+    if version > CURRENT_VERSION as u64 {
+        return Err(invalid_data(format!(
+            "config version {} is newer than the supported version {}, please update fumble",
+            version, CURRENT_VERSION
+        )));
+    }
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(table);
+    }
+    table.insert(
+        VERSION_KEY.to_string(),
+        toml::Value::Integer(CURRENT_VERSION.into()),
+    );
+    Ok(())
+}
+
+fn invalid_data<E: Into<Box<dyn std::error::Error + Send + Sync>>>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::settings::delay::DelayOptions;
+    use crate::cli::settings::duplicate::DuplicateOptions;
+    use crate::network::types::duration_spec::DurationSpec;
+    use crate::network::types::probability::Probability;
+
+    #[test]
+    fn test_roundtrip() {
+        let document = ConfigDocument {
+            filter: Some("udp.DstPort == 53".to_string()),
+            options: RuntimeOptions {
+                seed: Some(7),
+                ..Default::default()
+            },
+            tui: TuiPreferences {
+                show_logs: true,
+                ..Default::default()
+            },
+            settings: PacketManipulationSettings {
+                delay: Some(DelayOptions {
+                    duration: DurationSpec::from_millis(120),
+                    content_match: None,
+                }),
+                ..Default::default()
+            },
+        };
+        let content = document.to_toml_string().unwrap();
+        assert!(content.starts_with("filter = \"udp.DstPort == 53\"\nversion = 1\n"));
+
+        let loaded = ConfigDocument::from_toml_str(&content).unwrap();
+        assert_eq!(loaded.filter, document.filter);
+        assert_eq!(loaded.options, document.options);
+        assert_eq!(loaded.tui, document.tui);
+        assert_eq!(
+            loaded.settings.delay.unwrap().duration,
+            DurationSpec::from_millis(120)
+        );
+        assert!(loaded.settings.drop.is_none());
+    }
+
+    #[test]
+    fn test_load_unversioned_file() {
+        let loaded =
+            ConfigDocument::from_toml_str("filter = \"outbound\"\n[drop]\nprobability = 0.2\n")
+                .unwrap();
+        assert_eq!(loaded.filter.as_deref(), Some("outbound"));
+        assert_eq!(loaded.options, RuntimeOptions::default());
+        assert_eq!(loaded.settings.drop.unwrap().probability.value(), 0.2);
+    }
+
+    #[test]
+    fn test_reject_newer_version() {
+        let error = ConfigDocument::from_toml_str("version = 99\n").unwrap_err();
+        assert!(error.to_string().contains("newer"));
+        assert!(ConfigDocument::from_toml_str("version = \"1\"\n").is_err());
+    }
+
+    #[test]
+    fn test_update_keeps_comments() {
+        let existing = "# Shared with the QA team\nversion = 1\n\n[drop]\n# Keep this low\nprobability = 0.10 # percent / 100\n\n[delay]\nduration = 50\n";
+        let mut document = ConfigDocument::from_toml_str(existing).unwrap();
+        document.settings.delay = None;
+        document.settings.duplicate = Some(DuplicateOptions {
+            probability: Probability::new(0.5).unwrap(),
+            count: 2,
+            content_match: None,
+        });
+        document.filter = Some("udp".to_string());
+
+        let updated = document.update_toml_string(existing).unwrap();
+        assert!(updated.starts_with("# Shared with the QA team\n"));
+        assert!(updated.contains("# Keep this low\nprobability = 0.10 # percent / 100\n"));
+        assert!(!updated.contains("[delay]"));
+        let loaded = ConfigDocument::from_toml_str(&updated).unwrap();
+        assert_eq!(loaded.filter.as_deref(), Some("udp"));
+        assert_eq!(loaded.settings.duplicate.unwrap().count, 2);
+        assert_eq!(loaded.settings.drop.unwrap().probability.value(), 0.1);
+
+        document.settings.drop.as_mut().unwrap().probability = Probability::new(0.2).unwrap();
+        let updated = document.update_toml_string(&updated).unwrap();
+        assert!(updated.contains("probability = 0.2 # percent / 100\n"));
+    }
+
+    #[test]
+    fn test_default_config_file_loads() {
+        let path = std::env::temp_dir().join("fumble_test_default_config_document.toml");
+        ConfigDocument::create_default_config_file(&path).unwrap();
+        let loaded = ConfigDocument::load_from_file(&path).unwrap();
+        assert_eq!(loaded.filter, None);
+        assert_eq!(loaded.tui, TuiPreferences::default());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_trace_file_is_relative_to_config_file() {
+        let path = std::env::temp_dir().join("fumble_test_trace_config_document.toml");
+        fs::write(&path, "[trace]\nfile = \"traces/lte.csv\"\n").unwrap();
+        let loaded = ConfigDocument::load_from_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let trace = loaded.settings.trace.as_ref().unwrap();
+        assert_eq!(
+            trace.resolved_file(),
+            Some(std::env::temp_dir().join("traces/lte.csv"))
+        );
+        // Saving the config keeps the path as written
+        let saved = loaded.to_toml_string().unwrap();
+        assert!(saved.contains("file = \"traces/lte.csv\""), "{}", saved);
+    }
+}
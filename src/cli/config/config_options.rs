@@ -1,4 +1,4 @@
-use crate::cli::settings::packet_manipulation::PacketManipulationSettings;
+use crate::cli::config::config_document::ConfigDocument;
 use clap::Parser;
 use dirs::config_dir;
 use std::path::PathBuf;
@@ -7,15 +7,23 @@ use std::{fs, io};
 /// Manage configurations for fumble.
 #[derive(Parser, Debug, Default)]
 pub struct ConfigOptions {
-    /// Command to create a default configuration file with the specified name.
+    /// Deprecated, use `fumble config create`. Create a default configuration file with the specified name.
     #[arg(long, help_heading = "Configuration Management")]
     pub create_default: Option<String>,
 
-    /// Command to use an existing configuration file based on specified name.
+    /// Command to use an existing configuration file based on specified name or path.
     #[arg(long, help_heading = "Configuration Management")]
     pub use_config: Option<String>,
 
-    /// Command to list all available configuration files.
+    /// Watch the configuration file given with `--use-config` and apply changes while running.
+    #[arg(
+        long,
+        requires = "use_config",
+        help_heading = "Configuration Management"
+    )]
+    pub watch_config: bool,
+
+    /// Deprecated, use `fumble config list`. List all available configuration files.
     #[arg(long, help_heading = "Configuration Management")]
     pub list_configs: bool,
 }
@@ -24,9 +32,7 @@ impl ConfigOptions {
     /// Create a default configuration file with all fields commented out.
     pub fn create_default_config(file_name: &str) -> io::Result<()> {
         ensure_config_dir_exists().unwrap();
-        PacketManipulationSettings::create_default_config_file(
-            get_config_dir().join(ensure_toml_extension(file_name)),
-        )
+        ConfigDocument::create_default_config_file(get_config_file_path(file_name))
     }
 
     /// List all configuration files in the specified directory.
@@ -52,15 +58,68 @@ impl ConfigOptions {
         Ok(config_files)
     }
 
-    /// Load an existing configuration file.
-    pub fn load_existing_config(file_name: &str) -> io::Result<PacketManipulationSettings> {
+    /// Load an existing configuration file, migrating it from older schema versions.
+    pub fn load_existing_config(file_name: &str) -> io::Result<ConfigDocument> {
         ensure_config_dir_exists().unwrap();
-        PacketManipulationSettings::load_from_file(
-            get_config_dir().join(ensure_toml_extension(file_name)),
-        )
+        ConfigDocument::load_from_file(resolve_config_path(file_name))
+    }
+
+    /// Store the raw content of a configuration file under a name, keeping its comments and
+    /// formatting. Fails with `AlreadyExists` if the name is taken and `overwrite` is not set.
+    pub fn store_config_content(
+        file_name: &str,
+        content: &str,
+        overwrite: bool,
+    ) -> io::Result<PathBuf> {
+        ensure_config_dir_exists()?;
+        let path = get_config_file_path(file_name);
+        if path.exists() && !overwrite {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("configuration {:?} already exists", file_name),
+            ));
+        }
+        fs::write(&path, content)?;
+        Ok(path)
+    }
+
+    /// Delete a configuration file from the config directory.
+    pub fn delete_config(file_name: &str) -> io::Result<PathBuf> {
+        let path = get_config_file_path(file_name);
+        fs::remove_file(&path)?;
+        Ok(path)
+    }
+
+    /// Save a configuration to a file, replacing any existing file with the same name while
+    /// keeping its comments. Returns the path of the written file.
+    pub fn save_config(file_name: &str, document: &ConfigDocument) -> io::Result<PathBuf> {
+        ensure_config_dir_exists()?;
+        let path = get_config_file_path(file_name);
+        match fs::read_to_string(&path) {
+            Ok(existing) => fs::write(&path, document.update_toml_string(&existing)?)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => document.save_to_file(&path)?,
+            Err(e) => return Err(e),
+        }
+        Ok(path)
     }
 }
 
+/// Returns the path of an existing file given as `name_or_path`, or otherwise the path of the
+/// configuration file with that name in the config directory.
+pub fn resolve_config_path(name_or_path: &str) -> PathBuf {
+    let path = PathBuf::from(name_or_path);
+    if path.is_file() {
+        path
+    } else {
+        get_config_file_path(name_or_path)
+    }
+}
+
+/// Returns the path of the configuration file with the given name in the config directory.
+pub fn get_config_file_path(file_name: &str) -> PathBuf {
+    get_config_dir().join(ensure_toml_extension(file_name))
+}
+
 pub fn get_config_dir() -> PathBuf {
     let mut config_path = config_dir().unwrap_or_else(|| {
         // Fallback to home directory if config_dir() fails
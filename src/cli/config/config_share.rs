@@ -0,0 +1,142 @@
+use crate::cli::config::config_document::{ConfigDocument, TuiPreferences, TUI_KEY};
+use std::io;
+use thiserror::Error;
+
+/// Prefix of a configuration string, naming the version of its encoding.
+pub const SHARE_PREFIX: &str = "fumble1.";
+
+/// URL-safe base64 alphabet from RFC 4648, section 5.
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ShareError {
+    #[error("Not a configuration string, expected it to start with `{SHARE_PREFIX}`")]
+    MissingPrefix,
+    #[error("Invalid character {0:?} in configuration string")]
+    InvalidCharacter(char),
+    #[error("Configuration string has an invalid length, it may have been cut off")]
+    InvalidLength,
+    #[error("Configuration string does not hold text")]
+    InvalidText,
+}
+
+/// Returns whether `value` looks like a configuration string rather than a file path.
+pub fn is_share_string(value: &str) -> bool {
+    value.trim().starts_with(SHARE_PREFIX)
+}
+
+/// Encodes a configuration into a compact string that can be pasted into tickets or URLs.
+/// The configuration is validated and stored without its comments.
+pub fn to_share_string(content: &str) -> io::Result<String> {
+    let document = ConfigDocument::from_toml_str(content)?;
+    let mut table = document.to_toml_table()?;
+    // Leave out what loading a configuration defaults to anyway, keeping the string short
+    if document.tui == TuiPreferences::default() {
+        table.remove(TUI_KEY);
+    }
+    table.retain(|_, value| !value.as_table().is_some_and(|table| table.is_empty()));
+    let compact =
+        toml::to_string(&table).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(format!("{}{}", SHARE_PREFIX, encode(compact.as_bytes())))
+}
+
+/// Decodes a string created by [`to_share_string`] back into the content of a configuration file.
+pub fn from_share_string(value: &str) -> Result<String, ShareError> {
+    let encoded = value
+        .trim()
+        .strip_prefix(SHARE_PREFIX)
+        .ok_or(ShareError::MissingPrefix)?;
+    String::from_utf8(decode(encoded)?).map_err(|_| ShareError::InvalidText)
+}
+
+/// Encodes `data` as unpadded URL-safe base64.
+fn encode(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, byte)| {
+            bits | (*byte as u32) << (16 - 8 * i)
+        });
+        // A chunk of n bytes needs n + 1 characters
+        for i in 0..=chunk.len() {
+            encoded.push(ALPHABET[(bits >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    encoded
+}
+
+/// Decodes URL-safe base64, with or without padding.
+fn decode(encoded: &str) -> Result<Vec<u8>, ShareError> {
+    let encoded = encoded.trim_end_matches('=');
+    if encoded.len() % 4 == 1 {
+        return Err(ShareError::InvalidLength);
+    }
+    let mut data = Vec::with_capacity(encoded.len() * 3 / 4);
+    for chunk in encoded.as_bytes().chunks(4) {
+        let mut bits = 0u32;
+        for (i, character) in chunk.iter().enumerate() {
+            let value = ALPHABET
+                .iter()
+                .position(|c| c == character)
+                .ok_or(ShareError::InvalidCharacter(*character as char))?;
+            bits |= (value as u32) << (18 - 6 * i);
+        }
+        // A chunk of n characters holds n - 1 bytes
+        for i in 0..chunk.len() - 1 {
+            data.push((bits >> (16 - 8 * i)) as u8);
+        }
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_roundtrip() {
+        assert_eq!(encode(b""), "");
+        assert_eq!(encode(b"f"), "Zg");
+        assert_eq!(encode(b"fo"), "Zm8");
+        assert_eq!(encode(b"foo"), "Zm9v");
+        assert_eq!(encode(&[0xfb, 0xff]), "-_8");
+        for length in 0..16 {
+            let data: Vec<u8> = (0..length).map(|i| (i * 37) as u8).collect();
+            assert_eq!(decode(&encode(&data)).unwrap(), data);
+        }
+        assert_eq!(decode("Zm8=").unwrap(), b"fo");
+    }
+
+    #[test]
+    fn test_share_string_roundtrip() {
+        let content = "# Lossy\nversion = 1\nfilter = \"udp\"\n\n[drop]\nprobability = 0.1\n";
+        let shared = to_share_string(content).unwrap();
+        assert!(is_share_string(&shared));
+        assert!(shared[SHARE_PREFIX.len()..]
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+
+        let decoded = from_share_string(&format!(" {}\n", shared)).unwrap();
+        assert!(!decoded.contains("# Lossy"));
+        let document = ConfigDocument::from_toml_str(&decoded).unwrap();
+        assert_eq!(document.filter.as_deref(), Some("udp"));
+        assert_eq!(document.settings.drop.unwrap().probability.value(), 0.1);
+    }
+
+    #[test]
+    fn test_invalid_share_strings() {
+        assert_eq!(from_share_string("Zm9v"), Err(ShareError::MissingPrefix));
+        assert_eq!(
+            from_share_string("fumble1.Zm9+"),
+            Err(ShareError::InvalidCharacter('+'))
+        );
+        assert_eq!(
+            from_share_string("fumble1.Zm9vZ"),
+            Err(ShareError::InvalidLength)
+        );
+        assert_eq!(
+            from_share_string("fumble1.gA"),
+            Err(ShareError::InvalidText)
+        );
+        assert!(to_share_string("[drop]\nprobability = 2.0\n").is_err());
+    }
+}
@@ -0,0 +1,515 @@
+use crate::cli::config::config_document::{TuiPreferences, CURRENT_VERSION};
+use crate::cli::settings::drop::DropCurve;
+use crate::cli::settings::packet_manipulation::PacketManipulationSettings;
+use crate::cli::settings::runtime_options::RuntimeOptions;
+use crate::network::types::bit_rate::BitRate;
+use crate::network::types::duration_spec::DurationSpec;
+use serde::Deserialize;
+use std::fmt;
+use std::fmt::Formatter;
+use std::ops::Range;
+use thiserror::Error;
+use toml_edit::{ImDocument, Item, TableLike, Value};
+
+/// Top-level keys that may appear in a configuration file.
+const KNOWN_KEYS: &[&str] = &[
+    "version",
+    "filter",
+    "options",
+    "tui",
+    "drop",
+    "delay",
+    "throttle",
+    "reorder",
+    "tamper",
+    "duplicate",
+    "bandwidth",
+    "dns",
+    "topology",
+    "trace",
+];
+
+/// Values a field is allowed to take.
+#[derive(Debug, Clone, Copy)]
+enum Allowed {
+    /// A number between 0.0 and 1.0
+    Probability,
+    /// An integer of at least the given value
+    AtLeast(i64),
+    /// An integer of at least the given value that must be set when its section is present,
+    /// as leaving it out defaults it to 0
+    Required(i64),
+    /// A duration longer than zero
+    PositiveDuration,
+    /// A duration longer than zero that must be set when its section is present, as leaving it
+    /// out defaults it to 0
+    RequiredDuration,
+}
+
+const FIELD_RULES: &[(&str, &str, Allowed)] = &[
+    ("drop", "probability", Allowed::Probability),
+    ("drop", "capacity", Allowed::AtLeast(1)),
+    ("throttle", "probability", Allowed::Probability),
+    ("throttle", "duration", Allowed::PositiveDuration),
+    ("reorder", "probability", Allowed::Probability),
+    ("reorder", "max_delay", Allowed::RequiredDuration),
+    ("tamper", "probability", Allowed::Probability),
+    ("tamper", "amount", Allowed::Probability),
+    ("duplicate", "probability", Allowed::Probability),
+    ("duplicate", "count", Allowed::Required(1)),
+];
+
+/// A problem found in a configuration file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigIssue {
+    /// Line of the problem, starting at 1
+    pub line: usize,
+    /// Column of the problem, starting at 1
+    pub column: usize,
+    /// Dotted path of the offending field, e.g. `drop.probability`. Empty for syntax errors.
+    pub field: String,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: ", self.line, self.column)?;
+        if !self.field.is_empty() {
+            write!(f, "{}: ", self.field)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{}", display_issues(issues))]
+pub struct ConfigValidationError {
+    pub issues: Vec<ConfigIssue>,
+}
+
+fn display_issues(issues: &[ConfigIssue]) -> String {
+    issues
+        .iter()
+        .map(ConfigIssue::to_string)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Top-level keys besides the module sections, only deserialized to report their errors.
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct DocumentHeader {
+    filter: Option<String>,
+    options: Option<RuntimeOptions>,
+    tui: Option<TuiPreferences>,
+}
+
+/// Checks the content of a configuration file in the current schema version and returns every
+/// problem found, ordered by position. Besides syntax and type errors, this checks value ranges
+/// and constraints between fields that deserialization alone would accept.
+pub fn validate_config(content: &str) -> Vec<ConfigIssue> {
+    let document = match ImDocument::parse(content) {
+        Ok(document) => document,
+        Err(e) => {
+            return vec![issue_at(
+                content,
+                e.span().map_or(0, |span| span.start),
+                "",
+                e.message().to_string(),
+            )]
+        }
+    };
+    let root = document.as_table();
+    let mut issues = Vec::new();
+
+    for (key, item) in root.iter() {
+        if !KNOWN_KEYS.contains(&key) {
+            issues.push(issue_at(
+                content,
+                key_offset(root, key, item),
+                key,
+                format!("unknown key, expected one of {}", KNOWN_KEYS.join(", ")),
+            ));
+        }
+    }
+
+    if let Some(item) = root.get("version") {
+        match item.as_integer() {
+            Some(version) if (0..=CURRENT_VERSION as i64).contains(&version) => {}
+            Some(version) if version > CURRENT_VERSION as i64 => issues.push(issue_at(
+                content,
+                span_start(item),
+                "version",
+                format!(
+                    "version {} is newer than the supported version {}, please update fumble",
+                    version, CURRENT_VERSION
+                ),
+            )),
+            _ => issues.push(issue_at(
+                content,
+                span_start(item),
+                "version",
+                format!("must be an integer between 0 and {}", CURRENT_VERSION),
+            )),
+        }
+    }
+
+    for (section, field, allowed) in FIELD_RULES {
+        if let Some(section_item) = root.get(section) {
+            check_field(content, section, section_item, field, *allowed, &mut issues);
+        }
+    }
+
+    check_drop_curve(content, root, &mut issues);
+    check_bandwidth_limits(content, root, &mut issues);
+    check_trace_file(content, root, &mut issues);
+
+    // Remaining type errors, skipping those already reported with their allowed values
+    let errors = [
+        toml::from_str::<PacketManipulationSettings>(content).err(),
+        toml::from_str::<DocumentHeader>(content).err(),
+    ];
+    for error in errors.into_iter().flatten() {
+        let offset = error.span().map_or(0, |span| span.start);
+        let field = field_at(root, offset, "").unwrap_or_default();
+        let (line, column) = line_and_column(content, offset);
+        let already_reported = issues
+            .iter()
+            .any(|issue| issue.field == field && issue.line == line);
+        if !already_reported {
+            issues.push(ConfigIssue {
+                line,
+                column,
+                field,
+                message: error.message().to_string(),
+            });
+        }
+    }
+
+    issues.sort_by_key(|issue| (issue.line, issue.column));
+    issues
+}
+
+fn check_field(
+    content: &str,
+    section: &str,
+    section_item: &Item,
+    field: &str,
+    allowed: Allowed,
+    issues: &mut Vec<ConfigIssue>,
+) {
+    let path = format!("{}.{}", section, field);
+    let item = match section_item.get(field) {
+        Some(item) => item,
+        None => {
+            let minimum = match allowed {
+                Allowed::Required(min) => format!("at least {}", min),
+                Allowed::RequiredDuration => "longer than 0".to_string(),
+                _ => return,
+            };
+            if section_item.is_table_like() {
+                issues.push(issue_at(
+                    content,
+                    span_start(section_item),
+                    &path,
+                    format!("missing, must be {}", minimum),
+                ));
+            }
+            return;
+        }
+    };
+    let value = match item.as_value() {
+        Some(value) => value,
+        None => return,
+    };
+    let message = match allowed {
+        Allowed::Probability => {
+            let number = value
+                .as_float()
+                .or_else(|| value.as_integer().map(|integer| integer as f64));
+            match number {
+                Some(number) if (0.0..=1.0).contains(&number) => return,
+                Some(number) => format!("{} is out of range, allowed: 0.0 to 1.0", number),
+                // Wrong types are reported by deserialization
+                None => return,
+            }
+        }
+        Allowed::AtLeast(min) | Allowed::Required(min) => match value.as_integer() {
+            Some(integer) if integer >= min => return,
+            Some(integer) => format!("{} is out of range, allowed: {} or more", integer, min),
+            None => return,
+        },
+        Allowed::PositiveDuration | Allowed::RequiredDuration => match duration(value) {
+            Some(duration) if duration.is_zero() => {
+                "0 is out of range, allowed: longer than 0".to_string()
+            }
+            _ => return,
+        },
+    };
+    issues.push(issue_at(content, span_start(item), &path, message));
+}
+
+/// A drop curve other than linear ramps towards the capacity, so it needs one.
+fn check_drop_curve(content: &str, root: &toml_edit::Table, issues: &mut Vec<ConfigIssue>) {
+    let drop = match root.get("drop") {
+        Some(drop) => drop,
+        None => return,
+    };
+    let curve = match drop.get("curve") {
+        Some(curve) => curve,
+        None => return,
+    };
+    let ramps = curve
+        .as_str()
+        .is_some_and(|curve| curve != DropCurve::Linear.to_string());
+    if ramps && drop.get("capacity").is_none() {
+        issues.push(issue_at(
+            content,
+            span_start(curve),
+            "drop.curve",
+            "requires drop.capacity to be set".to_string(),
+        ));
+    }
+}
+
+/// A bandwidth section without any limit would let every packet through, and a limit of zero
+/// would let none through.
+fn check_bandwidth_limits(content: &str, root: &toml_edit::Table, issues: &mut Vec<ConfigIssue>) {
+    let bandwidth = match root.get("bandwidth") {
+        Some(bandwidth) if bandwidth.is_table_like() => bandwidth,
+        _ => return,
+    };
+    let mut limited = false;
+    for field in ["limit", "upload", "download"] {
+        let Some(item) = bandwidth.get(field) else {
+            continue;
+        };
+        limited = true;
+        if item
+            .as_value()
+            .and_then(bit_rate)
+            .is_some_and(|rate| rate.is_zero())
+        {
+            issues.push(issue_at(
+                content,
+                span_start(item),
+                &format!("bandwidth.{}", field),
+                "0 is out of range, allowed: more than 0".to_string(),
+            ));
+        }
+    }
+    if !limited {
+        issues.push(issue_at(
+            content,
+            span_start(bandwidth),
+            "bandwidth.limit",
+            "missing, set limit, upload or download".to_string(),
+        ));
+    }
+}
+
+/// A trace section without a file enables the trace module with nothing to replay.
+fn check_trace_file(content: &str, root: &toml_edit::Table, issues: &mut Vec<ConfigIssue>) {
+    match root.get("trace") {
+        Some(trace) if trace.is_table_like() && trace.get("file").is_none() => {
+            issues.push(issue_at(
+                content,
+                span_start(trace),
+                "trace.file",
+                "missing, set the trace file to replay".to_string(),
+            ));
+        }
+        _ => {}
+    }
+}
+
+/// Reads a bandwidth like deserialization does. Returns `None` for values it rejects, as it
+/// reports them.
+fn bit_rate(value: &Value) -> Option<BitRate> {
+    match value {
+        Value::Integer(integer) => u64::try_from(*integer.value())
+            .ok()
+            .map(BitRate::from_kibibytes),
+        Value::Float(float) => BitRate::from_kibibytes_f64(*float.value()),
+        Value::String(text) => text.value().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Reads a duration like deserialization does. Returns `None` for values it rejects, as it
+/// reports them.
+fn duration(value: &Value) -> Option<DurationSpec> {
+    match value {
+        Value::Integer(integer) => u64::try_from(*integer.value())
+            .ok()
+            .map(DurationSpec::from_millis),
+        Value::Float(float) => DurationSpec::from_millis_f64(*float.value()),
+        Value::String(text) => text.value().parse().ok(),
+        _ => None,
+    }
+}
+
+fn span_start(item: &Item) -> usize {
+    item.span().map_or(0, |span| span.start)
+}
+
+fn key_offset(table: &toml_edit::Table, key: &str, item: &Item) -> usize {
+    table
+        .key(key)
+        .and_then(|key| key.span())
+        .or_else(|| item.span())
+        .map_or(0, |span| span.start)
+}
+
+/// Returns the dotted path of the most deeply nested field whose value contains `offset`.
+fn field_at(table: &dyn TableLike, offset: usize, prefix: &str) -> Option<String> {
+    let mut found = None;
+    for (key, item) in table.iter() {
+        let path = if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        if let Some(nested) = item
+            .as_table_like()
+            .and_then(|nested| field_at(nested, offset, &path))
+        {
+            return Some(nested);
+        }
+        if item
+            .span()
+            .as_ref()
+            .is_some_and(|span| contains(span, offset))
+        {
+            found = Some(path);
+        }
+    }
+    found
+}
+
+fn contains(span: &Range<usize>, offset: usize) -> bool {
+    span.start <= offset && offset <= span.end
+}
+
+fn issue_at(content: &str, offset: usize, field: &str, message: String) -> ConfigIssue {
+    let (line, column) = line_and_column(content, offset);
+    ConfigIssue {
+        line,
+        column,
+        field: field.to_string(),
+        message,
+    }
+}
+
+/// Converts a byte offset into a line and column, both starting at 1.
+fn line_and_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_config_has_no_issues() {
+        let content = "version = 1\nfilter = \"outbound\"\n\n[options]\nseed = 4\n\n\
+            [drop]\nprobability = 0.2\ncapacity = 100\ncurve = \"red\"\n\n\
+            [duplicate]\nprobability = 1\ncount = 2\n";
+        assert_eq!(validate_config(content), vec![]);
+    }
+
+    #[test]
+    fn test_reports_ranges_with_position() {
+        let content = "[drop]\nprobability = 1.5\n\n[duplicate]\nprobability = 0.5\ncount = 0\n";
+        assert_eq!(
+            validate_config(content),
+            vec![
+                ConfigIssue {
+                    line: 2,
+                    column: 15,
+                    field: "drop.probability".to_string(),
+                    message: "1.5 is out of range, allowed: 0.0 to 1.0".to_string(),
+                },
+                ConfigIssue {
+                    line: 6,
+                    column: 9,
+                    field: "duplicate.count".to_string(),
+                    message: "0 is out of range, allowed: 1 or more".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reports_missing_and_cross_field_constraints() {
+        let content = "[bandwidth]\n\n[drop]\ncurve = \"red\"\n\n[trace]\nend = \"stop\"\n";
+        let fields: Vec<String> = validate_config(content)
+            .into_iter()
+            .map(|issue| issue.field)
+            .collect();
+        assert_eq!(fields, vec!["bandwidth.limit", "drop.curve", "trace.file"]);
+    }
+
+    #[test]
+    fn test_reports_zero_bandwidth_limits() {
+        let content = "[bandwidth]\nlimit = 0\nupload = \"0\"\ndownload = \"0kbit\"\n";
+        let issues: Vec<(String, usize)> = validate_config(content)
+            .into_iter()
+            .map(|issue| (issue.field, issue.line))
+            .collect();
+        assert_eq!(
+            issues,
+            vec![
+                ("bandwidth.limit".to_string(), 2),
+                ("bandwidth.upload".to_string(), 3),
+                ("bandwidth.download".to_string(), 4),
+            ]
+        );
+        assert_eq!(validate_config("[bandwidth]\nlimit = \"1bps\"\n"), vec![]);
+    }
+
+    #[test]
+    fn test_reports_zero_durations() {
+        for max_delay in ["0", "0.0", "\"0s\"", "\"0ms\""] {
+            let content = format!("[reorder]\nmax_delay = {}\n", max_delay);
+            let issues = validate_config(&content);
+            assert_eq!(issues.len(), 1, "{}", max_delay);
+            assert_eq!(issues[0].field, "reorder.max_delay");
+            assert_eq!(issues[0].line, 2);
+        }
+        let issues =
+            validate_config("[reorder]\nprobability = 0.1\n\n[throttle]\nduration = \"0us\"\n");
+        let fields: Vec<&str> = issues.iter().map(|issue| issue.field.as_str()).collect();
+        assert_eq!(fields, vec!["reorder.max_delay", "throttle.duration"]);
+        assert_eq!(issues[0].message, "missing, must be longer than 0");
+
+        let content = "[reorder]\nmax_delay = 0.5\n\n[throttle]\nduration = \"250us\"\n";
+        assert_eq!(validate_config(content), vec![]);
+    }
+
+    #[test]
+    fn test_reports_type_and_syntax_errors() {
+        let issues = validate_config("[reorder]\nmax_delay = \"long\"\n");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].field, "reorder.max_delay");
+        assert_eq!((issues[0].line, issues[0].column), (2, 13));
+
+        let issues = validate_config("[drop\nprobability = 0.1\n");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].field, "");
+        assert_eq!(issues[0].line, 1);
+
+        let issues = validate_config("dorp = 1\nversion = 7\n");
+        let fields: Vec<&str> = issues.iter().map(|issue| issue.field.as_str()).collect();
+        assert_eq!(fields, vec!["dorp", "version"]);
+    }
+
+    #[test]
+    fn test_line_and_column() {
+        assert_eq!(line_and_column("ab\ncd", 0), (1, 1));
+        assert_eq!(line_and_column("ab\ncd", 4), (2, 2));
+    }
+}
@@ -0,0 +1,186 @@
+use crate::cli::config::config_document::{cli_to_toml_table, ConfigDocument};
+use crate::cli::Cli;
+use crate::network::utils::filter::validate_filter;
+use log::{error, info};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use std::{fs, io, thread};
+
+/// How often the watched file is checked for modifications.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Watches the configuration file at `path` and swaps its settings into `cli` whenever it changes.
+/// The file is polled for its modification time, which also catches editors that save by
+/// replacing the file. Invalid files are reported and the previous settings are kept.
+pub fn watch_config(path: PathBuf, cli: Arc<Mutex<Cli>>, running: Arc<AtomicBool>) {
+    info!("Watching configuration file {:?} for changes.", path);
+    let mut last_modified = modified_time(&path).ok();
+
+    while running.load(Ordering::SeqCst) {
+        thread::sleep(POLL_INTERVAL);
+        let modified = match modified_time(&path) {
+            Ok(modified) => modified,
+            // The file may briefly disappear while an editor replaces it
+            Err(_) => continue,
+        };
+        if last_modified == Some(modified) {
+            continue;
+        }
+        last_modified = Some(modified);
+
+        match reload_config(&path, &cli) {
+            Ok(changes) if changes.is_empty() => {
+                info!("Configuration file {:?} changed, no settings differ.", path)
+            }
+            Ok(changes) => {
+                info!("Reloaded configuration file {:?}:", path);
+                for change in changes {
+                    info!("  {}", change);
+                }
+            }
+            Err(e) => error!(
+                "Keeping previous settings, configuration file {:?} is invalid: {}",
+                path, e
+            ),
+        }
+    }
+}
+
+fn modified_time(path: &Path) -> io::Result<SystemTime> {
+    fs::metadata(path)?.modified()
+}
+
+/// Parses and validates the configuration file at `path` and swaps it into `cli`.
+/// Returns a description of each setting that changed. `cli` is left untouched on error.
+pub fn reload_config(path: &Path, cli: &Arc<Mutex<Cli>>) -> io::Result<Vec<String>> {
+    let document = ConfigDocument::load_from_file(path)?;
+    if let Some(filter) = &document.filter {
+        validate_filter(filter).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    }
+
+    let mut cli = cli.lock().map_err(|e| io::Error::other(e.to_string()))?;
+    let mut old_table = cli_to_toml_table(&cli)?;
+    let mut new_table = document.to_toml_table()?;
+    // TUI preferences only apply on start and the filter is only replaced, never cleared
+    for table in [&mut old_table, &mut new_table] {
+        table.remove("tui");
+    }
+    if document.filter.is_none() {
+        old_table.remove("filter");
+    }
+    let changes = settings_changes(&old_table, &new_table);
+
+    if document.filter.is_some() {
+        cli.filter = document.filter;
+    }
+    cli.options = document.options;
+    cli.packet_manipulation_settings = document.settings;
+    Ok(changes)
+}
+
+/// Describes the differences between two serialized configurations, e.g. `delay.duration: 50 -> 120`.
+/// Sections present in only one of them are reported as enabled or disabled.
+pub fn settings_changes(old: &toml::Table, new: &toml::Table) -> Vec<String> {
+    let mut changes = Vec::new();
+    let sections: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    for section in sections {
+        match (old.get(section), new.get(section)) {
+            (None, Some(toml::Value::Table(_))) => changes.push(format!("{}: enabled", section)),
+            (Some(toml::Value::Table(_)), None) => changes.push(format!("{}: disabled", section)),
+            (old_value, new_value) => {
+                let old_values = old_value.map_or_else(BTreeMap::new, |v| flatten(section, v));
+                let new_values = new_value.map_or_else(BTreeMap::new, |v| flatten(section, v));
+                let keys: BTreeSet<&String> = old_values.keys().chain(new_values.keys()).collect();
+                for key in keys {
+                    let old_value = old_values.get(key);
+                    if old_value != new_values.get(key) {
+                        changes.push(format!(
+                            "{}: {} -> {}",
+                            key,
+                            old_value.map_or("unset", String::as_str),
+                            new_values.get(key).map_or("unset", String::as_str)
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    changes
+}
+
+/// Flattens nested tables into dotted key paths mapped to their displayed values.
+fn flatten(prefix: &str, value: &toml::Value) -> BTreeMap<String, String> {
+    let mut values = BTreeMap::new();
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table {
+                values.extend(flatten(&format!("{}.{}", prefix, key), value));
+            }
+        }
+        value => {
+            values.insert(prefix.to_string(), value.to_string());
+        }
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::types::duration_spec::DurationSpec;
+
+    fn table(toml: &str) -> toml::Table {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn test_settings_changes() {
+        let old = table("[delay]\nduration = 50\n[drop]\nprobability = 0.1\n");
+        let new = table(
+            "[delay]\nduration = 120\n[delay.match]\ndns_query = [\"*.example.com\"]\n\
+            [tamper]\nprobability = 0.2\n",
+        );
+        assert_eq!(
+            settings_changes(&old, &new),
+            vec![
+                "delay.duration: 50 -> 120".to_string(),
+                "delay.match.dns_query: unset -> [\"*.example.com\"]".to_string(),
+                "drop: disabled".to_string(),
+                "tamper: enabled".to_string(),
+            ]
+        );
+        assert!(settings_changes(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_reload_keeps_settings_when_invalid() {
+        let path = std::env::temp_dir().join("fumble_test_reload_keeps_settings.toml");
+        let cli = Arc::new(Mutex::new(Cli::default()));
+
+        fs::write(&path, "[delay]\nduration = 120\n").unwrap();
+        let changes = reload_config(&path, &cli).unwrap();
+        assert_eq!(changes, vec!["delay: enabled".to_string()]);
+
+        fs::write(&path, "[options]\nseed = 3\n[delay]\nduration = 120\n").unwrap();
+        let changes = reload_config(&path, &cli).unwrap();
+        assert_eq!(changes, vec!["options.seed: unset -> 3".to_string()]);
+        assert_eq!(cli.lock().unwrap().options.seed, Some(3));
+
+        fs::write(&path, "[drop]\nprobability = \"high\"\n").unwrap();
+        assert!(reload_config(&path, &cli).is_err());
+        let cli = cli.lock().unwrap();
+        assert_eq!(
+            cli.packet_manipulation_settings
+                .delay
+                .as_ref()
+                .unwrap()
+                .duration,
+            DurationSpec::from_millis(120)
+        );
+        assert!(cli.packet_manipulation_settings.drop.is_none());
+        fs::remove_file(&path).unwrap();
+    }
+}
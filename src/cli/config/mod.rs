@@ -1 +1,7 @@
+pub mod config_command;
+pub mod config_document;
 pub mod config_options;
+pub mod config_share;
+pub mod config_validation;
+pub mod config_watcher;
+pub mod presets;
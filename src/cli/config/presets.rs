@@ -0,0 +1,165 @@
+use crate::cli::command::{PresetCommand, EXIT_FAILURE, EXIT_SUCCESS};
+use crate::cli::config::config_command::store_config;
+
+/// A built-in configuration for a common network condition.
+pub struct Preset {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Content of the configuration file
+    pub config: &'static str,
+}
+
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        name: "lossy-wifi",
+        description: "Busy wireless network with light loss, jitter and the odd duplicate",
+        config: "version = 1
+
+[drop]
+probability = 0.02
+
+[delay]
+duration = \"20ms\"
+
+[reorder]
+probability = 0.01
+max_delay = \"30ms\"
+
+[duplicate]
+probability = 0.005
+count = 1
+",
+    },
+    Preset {
+        name: "3g",
+        description: "Mobile connection with high latency and limited bandwidth",
+        config: "version = 1
+
+[drop]
+probability = 0.01
+
+[delay]
+duration = \"150ms\"
+
+[bandwidth]
+download = \"768kbit\"
+upload = \"384kbit\"
+",
+    },
+    Preset {
+        name: "satellite",
+        description: "Geostationary satellite link with very high latency",
+        config: "version = 1
+
+[drop]
+probability = 0.005
+
+[delay]
+duration = \"600ms\"
+
+[bandwidth]
+limit = \"2Mbit\"
+",
+    },
+    Preset {
+        name: "congested",
+        description: "Saturated link where loss ramps up with throughput and packets stall",
+        config: "version = 1
+
+[drop]
+probability = 0.1
+capacity = 500
+curve = \"red\"
+
+[delay]
+duration = \"80ms\"
+
+[throttle]
+probability = 0.05
+duration = \"30ms\"
+",
+    },
+    Preset {
+        name: "slow-dns",
+        description: "DNS responses arriving after 1.5 seconds",
+        config: "version = 1
+filter = \"udp.SrcPort == 53\"
+
+[dns]
+action = \"delay\"
+delay = 1500
+",
+    },
+];
+
+pub fn find_preset(name: &str) -> Option<&'static Preset> {
+    PRESETS.iter().find(|preset| preset.name == name)
+}
+
+/// Runs a `fumble presets` command, listing the presets if none is given. Returns the exit status.
+pub fn run_presets_command(command: Option<PresetCommand>) -> i32 {
+    match command.unwrap_or(PresetCommand::List) {
+        PresetCommand::List => {
+            let width = PRESETS.iter().map(|preset| preset.name.len()).max();
+            for preset in PRESETS {
+                println!(
+                    "{:width$}  {}",
+                    preset.name,
+                    preset.description,
+                    width = width.unwrap_or_default()
+                );
+            }
+            EXIT_SUCCESS
+        }
+        PresetCommand::Show { name } => match find_preset(&name) {
+            Some(preset) => {
+                print!("{}", preset.config);
+                EXIT_SUCCESS
+            }
+            None => unknown_preset(&name),
+        },
+        PresetCommand::Save {
+            name,
+            config_name,
+            force,
+        } => match find_preset(&name) {
+            Some(preset) => store_config(
+                config_name.as_deref().unwrap_or(preset.name),
+                preset.config,
+                force,
+            ),
+            None => unknown_preset(&name),
+        },
+    }
+}
+
+fn unknown_preset(name: &str) -> i32 {
+    let names: Vec<&str> = PRESETS.iter().map(|preset| preset.name).collect();
+    eprintln!(
+        "Unknown preset {:?}, available presets: {}",
+        name,
+        names.join(", ")
+    );
+    EXIT_FAILURE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::config::config_document::ConfigDocument;
+    use crate::cli::config::config_validation::validate_config;
+
+    #[test]
+    fn test_presets_are_valid() {
+        for preset in PRESETS {
+            assert_eq!(validate_config(preset.config), vec![], "{}", preset.name);
+            assert!(ConfigDocument::from_toml_str(preset.config).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_find_preset() {
+        assert_eq!(find_preset("3g").map(|preset| preset.name), Some("3g"));
+        assert!(find_preset("5g").is_none());
+    }
+}
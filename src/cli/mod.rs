@@ -1,8 +1,13 @@
+use crate::cli::command::Command;
+use crate::cli::config::config_document::TuiPreferences;
 use crate::cli::config::config_options::ConfigOptions;
+use crate::cli::settings::auto_stop::AutoStopOptions;
 use crate::cli::settings::packet_manipulation::PacketManipulationSettings;
+use crate::cli::settings::runtime_options::RuntimeOptions;
 use crate::network::utils::filter::validate_filter_with_docs;
 use clap::Parser;
 
+pub mod command;
 pub mod config;
 pub mod settings;
 pub mod tui;
@@ -12,7 +17,7 @@ pub mod utils;
 #[command(
     name = "fumble",
     about = "A network manipulation tool for simulating various network conditions.",
-    long_about = "fumble is a network manipulation tool that can introduce packet drops, delays, throttling, reordering, duplication, and bandwidth limitations.\n\n\
+    long_about = "fumble is a network manipulation tool that can introduce packet drops, delays, throttling, reordering, duplication, bandwidth limitations and DNS impairments.\n\n\
     ## Logging\n\
     The tool uses the `env_logger` crate for logging. By default, informational messages are displayed.\n\n\
     To customize the verbosity of logs, set the `RUST_LOG` environment variable before running `fumble`.\n\n\
@@ -20,6 +25,9 @@ pub mod utils;
 )]
 #[derive(Default)]
 pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Filter expression for capturing packets
     #[arg(short, long, value_parser = validate_filter_with_docs)]
     pub filter: Option<String>,
@@ -27,6 +35,16 @@ pub struct Cli {
     #[command(flatten)]
     pub config: ConfigOptions,
 
+    #[command(flatten)]
+    pub options: RuntimeOptions,
+
+    #[command(flatten)]
+    pub auto_stop: AutoStopOptions,
+
+    /// TUI panes to open on start, set from the configuration file
+    #[arg(skip)]
+    pub tui_preferences: TuiPreferences,
+
     #[command(flatten)]
     pub packet_manipulation_settings: PacketManipulationSettings,
 
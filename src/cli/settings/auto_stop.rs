@@ -0,0 +1,66 @@
+use crate::network::types::duration_spec::parse_duration;
+use clap::Parser;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Options for stopping a run on its own and reporting on it, e.g. in CI.
+#[derive(Parser, Debug, Clone, PartialEq, Eq, Default)]
+pub struct AutoStopOptions {
+    /// Stop after running for this long, e.g. `90s`, `5m` or `1h`. A bare number is in seconds
+    #[arg(long, value_parser = parse_run_duration, help_heading = "Auto Stop")]
+    pub duration: Option<Duration>,
+
+    /// Stop once this many packets were received
+    #[arg(long, help_heading = "Auto Stop")]
+    pub packet_limit: Option<usize>,
+
+    /// Write the summary report printed at the end of a headless run to this file as TOML
+    #[arg(long, help_heading = "Auto Stop")]
+    pub report: Option<PathBuf>,
+}
+
+impl AutoStopOptions {
+    /// Returns whether a run that took `elapsed` so far and received `received` packets is done.
+    pub fn should_stop(&self, elapsed: Duration, received: usize) -> bool {
+        self.duration.is_some_and(|duration| elapsed >= duration)
+            || self.packet_limit.is_some_and(|limit| received >= limit)
+    }
+}
+
+/// Parses a duration such as `500ms`, `90s`, `5m` or `1.5h`, treating a bare number as seconds.
+pub fn parse_run_duration(value: &str) -> Result<Duration, String> {
+    match parse_duration(value, Duration::from_secs(1))? {
+        duration if duration.is_zero() => Err(format!("`{}` is not a positive duration", value)),
+        duration => Ok(duration),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_run_duration() {
+        assert_eq!(parse_run_duration("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(parse_run_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_run_duration("1.5s"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_run_duration("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_run_duration("2h"), Ok(Duration::from_secs(7200)));
+        assert!(parse_run_duration("0s").is_err());
+        assert!(parse_run_duration("5d").is_err());
+        assert!(parse_run_duration("m").is_err());
+    }
+
+    #[test]
+    fn test_should_stop() {
+        let options = AutoStopOptions {
+            duration: Some(Duration::from_secs(10)),
+            packet_limit: Some(100),
+            ..Default::default()
+        };
+        assert!(!options.should_stop(Duration::from_secs(5), 50));
+        assert!(options.should_stop(Duration::from_secs(10), 50));
+        assert!(options.should_stop(Duration::from_secs(5), 100));
+        assert!(!AutoStopOptions::default().should_stop(Duration::MAX, usize::MAX));
+    }
+}
@@ -1,10 +1,48 @@
+use crate::cli::settings::content_match::ContentMatchOptions;
+use crate::network::types::bit_rate::BitRate;
 use clap::Parser;
 use serde::{Deserialize, Serialize};
 
-#[derive(Parser, Debug, Serialize, Deserialize, Default)]
+#[derive(Parser, Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct BandwidthOptions {
-    /// Maximum bandwidth limit in KB/s
-    #[arg(long = "bandwidth-limit", id = "bandwidth-limit", default_value_t = 0)]
+    /// Maximum bandwidth in each direction, e.g. 512kbit, 2Mbps or 1.5MB/s. A number without a unit is in KB/s
+    #[arg(long = "bandwidth-limit", id = "bandwidth-limit")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<BitRate>,
+
+    /// Maximum bandwidth of outbound packets, instead of the limit
+    #[arg(long = "bandwidth-upload", id = "bandwidth-upload")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upload: Option<BitRate>,
+
+    /// Maximum bandwidth of inbound packets, instead of the limit
+    #[arg(long = "bandwidth-download", id = "bandwidth-download")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download: Option<BitRate>,
+
+    /// Bytes counted against the limit for every packet on top of its IP packet, for the framing of the link layer, e.g. 38 for Ethernet
+    #[arg(
+        long = "bandwidth-overhead",
+        id = "bandwidth-overhead",
+        default_value_t = 0
+    )]
     #[serde(default)]
-    pub limit: usize,
+    pub overhead: usize,
+
+    /// Restricts this manipulation to packets matching the content filter (config file only)
+    #[arg(skip)]
+    #[serde(default, rename = "match", skip_serializing_if = "Option::is_none")]
+    pub content_match: Option<ContentMatchOptions>,
+}
+
+impl BandwidthOptions {
+    /// Returns the limit of packets going out, or in if `outbound` is false. Packets in a
+    /// direction without a limit are not held back.
+    pub fn limit_for(&self, outbound: bool) -> Option<BitRate> {
+        if outbound {
+            self.upload.or(self.limit)
+        } else {
+            self.download.or(self.limit)
+        }
+    }
 }
@@ -0,0 +1,49 @@
+use crate::network::types::byte_pattern::BytePattern;
+use crate::network::types::name_pattern::NamePattern;
+use crate::network::types::payload_regex::PayloadRegex;
+use serde::{Deserialize, Serialize};
+
+/// Restricts a manipulation to packets whose contents match at least one of the criteria.
+/// Packets that don't match pass through that manipulation untouched.
+///
+/// Configured in a config file as a `match` table inside a manipulation section:
+///
+/// ```toml
+/// [delay]
+/// duration = 500
+///
+/// [delay.match]
+/// dns_query = ["*.example.com"]
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct ContentMatchOptions {
+    /// Hex byte sequences to search for anywhere in the transport payload
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub payload_bytes: Vec<BytePattern>,
+
+    /// Regular expression matched against the transport payload
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload_regex: Option<PayloadRegex>,
+
+    /// Name patterns matched against the server name of TLS ClientHello messages
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tls_sni: Vec<NamePattern>,
+
+    /// Name patterns matched against the question of DNS queries and responses
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dns_query: Vec<NamePattern>,
+
+    /// Name patterns matched against the Host header of HTTP requests
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub http_host: Vec<NamePattern>,
+}
+
+impl ContentMatchOptions {
+    pub fn is_empty(&self) -> bool {
+        self.payload_bytes.is_empty()
+            && self.payload_regex.is_none()
+            && self.tls_sni.is_empty()
+            && self.dns_query.is_empty()
+            && self.http_host.is_empty()
+    }
+}
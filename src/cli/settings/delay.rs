@@ -1,10 +1,17 @@
+use crate::cli::settings::content_match::ContentMatchOptions;
+use crate::network::types::duration_spec::DurationSpec;
 use clap::Parser;
 use serde::{Deserialize, Serialize};
 
-#[derive(Parser, Debug, Serialize, Deserialize, Default)]
+#[derive(Parser, Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct DelayOptions {
-    /// Delay in milliseconds to introduce for each packet
-    #[arg(long = "delay-duration", id = "delay-duration", default_value_t = 0)]
+    /// Delay to introduce for each packet, e.g. 150ms, 0.5ms or 1.5s. A number without a unit is in milliseconds
+    #[arg(long = "delay-duration", id = "delay-duration", default_value_t = DurationSpec::default())]
     #[serde(default)]
-    pub duration: u64,
+    pub duration: DurationSpec,
+
+    /// Restricts this manipulation to packets matching the content filter (config file only)
+    #[arg(skip)]
+    #[serde(default, rename = "match", skip_serializing_if = "Option::is_none")]
+    pub content_match: Option<ContentMatchOptions>,
 }
@@ -0,0 +1,82 @@
+use crate::cli::settings::content_match::ContentMatchOptions;
+use crate::network::types::duration_spec::DurationSpec;
+use crate::network::types::name_pattern::NamePattern;
+use clap::{Parser, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fmt::Formatter;
+
+/// What to do with DNS responses to queries matching the configured names.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DnsAction {
+    /// Hold the response back for the configured delay
+    #[default]
+    Delay,
+    /// Drop the response so the resolver times out
+    Drop,
+    /// Rewrite the response into a SERVFAIL
+    #[value(name = "servfail")]
+    ServFail,
+    /// Rewrite the response into an NXDOMAIN
+    #[value(name = "nxdomain")]
+    NxDomain,
+}
+
+impl DnsAction {
+    /// Returns the action following this one, wrapping around. Used to cycle through actions in the TUI.
+    pub fn next(self) -> Self {
+        match self {
+            DnsAction::Delay => DnsAction::Drop,
+            DnsAction::Drop => DnsAction::ServFail,
+            DnsAction::ServFail => DnsAction::NxDomain,
+            DnsAction::NxDomain => DnsAction::Delay,
+        }
+    }
+}
+
+impl fmt::Display for DnsAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DnsAction::Delay => "delay",
+            DnsAction::Drop => "drop",
+            DnsAction::ServFail => "servfail",
+            DnsAction::NxDomain => "nxdomain",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Parser, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DnsOptions {
+    /// Name patterns of DNS queries whose responses should be impaired, e.g. `*.example.com`. Matches all queries if empty
+    #[arg(long = "dns-name", id = "dns-name", value_delimiter = ',')]
+    #[serde(default)]
+    pub names: Vec<NamePattern>,
+
+    /// Impairment applied to matching DNS responses
+    #[arg(long = "dns-action", id = "dns-action", value_enum, default_value_t = DnsAction::Delay)]
+    #[serde(default)]
+    pub action: DnsAction,
+
+    /// Delay applied to matching DNS responses when the action is `delay`, e.g. 500ms or 2s. A number without a unit is in milliseconds
+    #[arg(long = "dns-delay", id = "dns-delay", default_value_t = DurationSpec::from_millis(500))]
+    #[serde(default)]
+    pub delay: DurationSpec,
+
+    /// Restricts this manipulation to packets matching the content filter (config file only)
+    #[arg(skip)]
+    #[serde(default, rename = "match", skip_serializing_if = "Option::is_none")]
+    pub content_match: Option<ContentMatchOptions>,
+}
+
+impl Default for DnsOptions {
+    fn default() -> Self {
+        DnsOptions {
+            names: Vec::new(),
+            action: DnsAction::Delay,
+            delay: DurationSpec::from_millis(500),
+            content_match: None,
+        }
+    }
+}
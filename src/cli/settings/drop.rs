@@ -1,11 +1,61 @@
+use crate::cli::settings::content_match::ContentMatchOptions;
 use crate::network::types::probability::Probability;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fmt::Formatter;
 
-#[derive(Parser, Debug, Serialize, Deserialize, Default)]
+/// How the drop probability ramps up as throughput approaches the configured capacity.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DropCurve {
+    /// Loss grows linearly with utilization, reaching the drop probability at capacity
+    #[default]
+    Linear,
+    /// Random early detection: no loss below half the capacity, ramping to the drop probability
+    /// at capacity and on to dropping everything at twice the capacity
+    Red,
+}
+
+impl DropCurve {
+    /// Returns the curve following this one, wrapping around. Used to cycle through curves in the TUI.
+    pub fn next(self) -> Self {
+        match self {
+            DropCurve::Linear => DropCurve::Red,
+            DropCurve::Red => DropCurve::Linear,
+        }
+    }
+}
+
+impl fmt::Display for DropCurve {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DropCurve::Linear => "linear",
+            DropCurve::Red => "red",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Parser, Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct DropOptions {
-    /// Probability of dropping packets, ranging from 0.0 to 1.0
+    /// Probability of dropping packets, ranging from 0.0 to 1.0. With a capacity set, this is the probability reached at capacity
     #[arg(long = "drop-probability", id = "drop-probability", default_value_t = Probability::default())]
     #[serde(default)]
     pub probability: Probability,
+
+    /// Capacity in KB/s of the simulated link. When set, the drop probability ramps with the throughput instead of being constant
+    #[arg(long = "drop-capacity", id = "drop-capacity")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<usize>,
+
+    /// Curve used to ramp the drop probability towards the capacity
+    #[arg(long = "drop-curve", id = "drop-curve", value_enum, default_value_t = DropCurve::Linear)]
+    #[serde(default)]
+    pub curve: DropCurve,
+
+    /// Restricts this manipulation to packets matching the content filter (config file only)
+    #[arg(skip)]
+    #[serde(default, rename = "match", skip_serializing_if = "Option::is_none")]
+    pub content_match: Option<ContentMatchOptions>,
 }
@@ -1,8 +1,9 @@
+use crate::cli::settings::content_match::ContentMatchOptions;
 use crate::network::types::probability::Probability;
 use clap::Parser;
 use serde::{Deserialize, Serialize};
 
-#[derive(Parser, Debug, Serialize, Deserialize)]
+#[derive(Parser, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DuplicateOptions {
     /// Probability of duplicating packets, ranging from 0.0 to 1.0
     #[arg(long = "duplicate-probability", id = "duplicate-probability", default_value_t = Probability::default())]
@@ -13,6 +14,11 @@ pub struct DuplicateOptions {
     #[arg(long = "duplicate-count", default_value_t = 1, id = "duplicate-count")]
     #[serde(default)]
     pub count: usize,
+
+    /// Restricts this manipulation to packets matching the content filter (config file only)
+    #[arg(skip)]
+    #[serde(default, rename = "match", skip_serializing_if = "Option::is_none")]
+    pub content_match: Option<ContentMatchOptions>,
 }
 
 impl Default for DuplicateOptions {
@@ -20,6 +26,7 @@ impl Default for DuplicateOptions {
         DuplicateOptions {
             count: 1,
             probability: Probability::default(),
+            content_match: None,
         }
     }
 }
@@ -1,8 +1,14 @@
+pub mod auto_stop;
 pub mod bandwidth;
+pub mod content_match;
 pub mod delay;
+pub mod dns;
 pub mod drop;
 pub mod duplicate;
 pub mod packet_manipulation;
 pub mod reorder;
+pub mod runtime_options;
 pub mod tamper;
 pub mod throttle;
+pub mod topology;
+pub mod trace;
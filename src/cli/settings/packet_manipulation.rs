@@ -1,10 +1,13 @@
 use crate::cli::settings::bandwidth::BandwidthOptions;
 use crate::cli::settings::delay::DelayOptions;
+use crate::cli::settings::dns::DnsOptions;
 use crate::cli::settings::drop::DropOptions;
 use crate::cli::settings::duplicate::DuplicateOptions;
 use crate::cli::settings::reorder::ReorderOptions;
 use crate::cli::settings::tamper::TamperOptions;
 use crate::cli::settings::throttle::ThrottleOptions;
+use crate::cli::settings::topology::TopologyOptions;
+use crate::cli::settings::trace::TraceOptions;
 use crate::cli::utils::serialization::serialize_option;
 use clap::Parser;
 use serde::{Deserialize, Serialize};
@@ -12,7 +15,7 @@ use std::io::Write;
 use std::path::Path;
 use std::{fs, io};
 
-#[derive(Parser, Debug, Serialize, Deserialize, Default)]
+#[derive(Parser, Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub struct PacketManipulationSettings {
     #[command(flatten)]
     #[serde(serialize_with = "serialize_option")]
@@ -41,6 +44,18 @@ pub struct PacketManipulationSettings {
     #[command(flatten)]
     #[serde(serialize_with = "serialize_option")]
     pub bandwidth: Option<BandwidthOptions>,
+
+    #[command(flatten)]
+    #[serde(serialize_with = "serialize_option")]
+    pub dns: Option<DnsOptions>,
+
+    #[command(flatten)]
+    #[serde(serialize_with = "serialize_option")]
+    pub topology: Option<TopologyOptions>,
+
+    #[command(flatten)]
+    #[serde(serialize_with = "serialize_option")]
+    pub trace: Option<TraceOptions>,
 }
 
 impl PacketManipulationSettings {
@@ -54,16 +69,55 @@ impl PacketManipulationSettings {
 
     /// Save current configuration to a TOML file
     pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
-        let content = toml::to_string_pretty(self)
+        let content = toml::to_string_pretty(&self.to_toml_table()?)
             .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
         let mut file = fs::File::create(path)?;
         file.write_all(content.as_bytes())?;
         Ok(())
     }
 
-    /// Create a default configuration file with all fields set to default values
+    /// Serialize the enabled modules into a TOML table, one section per module
+    pub fn to_toml_table(&self) -> io::Result<toml::Table> {
+        let mut table = toml::Table::try_from(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        // Disabled modules are serialized with default values, leave them out
+        for section in self.disabled_sections() {
+            table.remove(section);
+        }
+        Ok(table)
+    }
+
+    /// Names of the config sections whose module is disabled
+    fn disabled_sections(&self) -> Vec<&'static str> {
+        [
+            ("drop", self.drop.is_none()),
+            ("delay", self.delay.is_none()),
+            ("throttle", self.throttle.is_none()),
+            ("reorder", self.reorder.is_none()),
+            ("tamper", self.tamper.is_none()),
+            ("duplicate", self.duplicate.is_none()),
+            ("bandwidth", self.bandwidth.is_none()),
+            ("dns", self.dns.is_none()),
+            ("topology", self.topology.is_none()),
+            ("trace", self.trace.is_none()),
+        ]
+        .into_iter()
+        .filter(|(_, disabled)| *disabled)
+        .map(|(section, _)| section)
+        .collect()
+    }
+
+    /// Create a default configuration file with all sections and fields set to default values
     /// but commented out
     pub fn create_default_config_file<P: AsRef<Path>>(path: P) -> io::Result<()> {
+        let mut file = fs::File::create(path)?;
+        file.write_all(Self::commented_default_toml()?.as_bytes())?;
+        Ok(())
+    }
+
+    /// Serialize the default configuration to TOML with all sections and fields commented out.
+    /// Uncommenting a section enables its module.
+    pub fn commented_default_toml() -> io::Result<String> {
         let default_cli = Self::default();
 
         // Serialize the default configuration to TOML
@@ -71,20 +125,44 @@ impl PacketManipulationSettings {
             .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
 
         // Comment out all lines
-        let commented_out = serialized
+        Ok(serialized
             .lines()
             .map(|line| {
-                if line.trim().is_empty() || line.starts_with('[') {
+                if line.trim().is_empty() {
                     line.to_string()
                 } else {
                     format!("# {}", line)
                 }
             })
             .collect::<Vec<String>>()
-            .join("\n");
+            .join("\n"))
+    }
+}
 
-        let mut file = fs::File::create(path)?;
-        file.write_all(commented_out.as_bytes())?;
-        Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::settings::delay::DelayOptions;
+    use crate::network::types::duration_spec::DurationSpec;
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir().join("fumble_test_save_and_load.toml");
+        let settings = PacketManipulationSettings {
+            delay: Some(DelayOptions {
+                duration: DurationSpec::from_millis(120),
+                content_match: None,
+            }),
+            ..Default::default()
+        };
+        settings.save_to_file(&path).unwrap();
+
+        let loaded = PacketManipulationSettings::load_from_file(&path).unwrap();
+        assert_eq!(
+            loaded.delay.unwrap().duration,
+            DurationSpec::from_millis(120)
+        );
+        assert!(loaded.drop.is_none());
+        fs::remove_file(&path).unwrap();
     }
 }
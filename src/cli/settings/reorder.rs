@@ -1,28 +1,36 @@
+use crate::cli::settings::content_match::ContentMatchOptions;
+use crate::network::types::duration_spec::DurationSpec;
 use crate::network::types::probability::Probability;
 use clap::Parser;
 use serde::{Deserialize, Serialize};
 
-#[derive(Parser, Debug, Serialize, Deserialize)]
+#[derive(Parser, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ReorderOptions {
     /// Probability of reordering packets, ranging from 0.0 to 1.0
     #[arg(long = "reorder-probability", id = "reorder-probability", default_value_t = Probability::default())]
     #[serde(default)]
     pub probability: Probability,
-    /// Maximum random delay in milliseconds to apply when reordering packets
+    /// Maximum random delay to apply when reordering packets, e.g. 100ms or 500us. A number without a unit is in milliseconds
     #[arg(
         long = "reorder-max-delay",
         id = "reorder-max-delay",
-        default_value_t = 100
+        default_value_t = DurationSpec::from_millis(100)
     )]
     #[serde(default)]
-    pub max_delay: u64,
+    pub max_delay: DurationSpec,
+
+    /// Restricts this manipulation to packets matching the content filter (config file only)
+    #[arg(skip)]
+    #[serde(default, rename = "match", skip_serializing_if = "Option::is_none")]
+    pub content_match: Option<ContentMatchOptions>,
 }
 
 impl Default for ReorderOptions {
     fn default() -> Self {
         ReorderOptions {
             probability: Probability::default(),
-            max_delay: 100,
+            max_delay: DurationSpec::from_millis(100),
+            content_match: None,
         }
     }
 }
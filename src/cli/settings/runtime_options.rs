@@ -0,0 +1,66 @@
+use clap::{Parser, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fmt::Formatter;
+
+/// What happens to the packets held by the delay, DNS, throttle, reorder, topology, trace and
+/// bandwidth modules when processing stops, is paused or the module holding them is turned off.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DrainPolicy {
+    /// Send all held packets immediately
+    #[default]
+    #[value(alias = "send")]
+    Flush,
+    /// Keep releasing the held packets at the times the module would have, until none are left
+    Schedule,
+    /// Discard all held packets
+    #[value(alias = "drop")]
+    Discard,
+}
+
+impl fmt::Display for DrainPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DrainPolicy::Flush => "flush",
+            DrainPolicy::Schedule => "schedule",
+            DrainPolicy::Discard => "discard",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Options controlling how fumble runs, independent of the manipulation modules.
+#[derive(Parser, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct RuntimeOptions {
+    /// Seed for the random decisions of the manipulation modules, making runs repeatable
+    #[arg(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+
+    /// What to do with packets held by the modules when stopping, pausing or turning a module off
+    /// [default: flush]
+    #[arg(long, value_enum, alias = "flush-policy")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drain_policy: Option<DrainPolicy>,
+
+    /// Number of threads processing packets, each handling its own share of the flows. Packets of
+    /// a flow stay in order. Only read when processing starts [default: 1]
+    #[arg(long, value_parser = parse_worker_count)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workers: Option<usize>,
+}
+
+impl RuntimeOptions {
+    /// Returns the number of processing threads to start.
+    pub fn worker_count(&self) -> usize {
+        self.workers.unwrap_or(1).max(1)
+    }
+}
+
+fn parse_worker_count(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(count) if count > 0 => Ok(count),
+        _ => Err(format!("`{}` is not a positive number", value)),
+    }
+}
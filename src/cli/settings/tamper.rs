@@ -1,8 +1,9 @@
+use crate::cli::settings::content_match::ContentMatchOptions;
 use crate::network::types::probability::Probability;
 use clap::Parser;
 use serde::{Deserialize, Serialize};
 
-#[derive(Parser, Debug, Serialize, Deserialize)]
+#[derive(Parser, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TamperOptions {
     /// Probability of tampering packets, ranging from 0.0 to 1.0
     #[arg(long = "tamper-probability", id = "tamper-probability", default_value_t = Probability::default())]
@@ -21,6 +22,11 @@ pub struct TamperOptions {
     )]
     #[serde(default)]
     pub recalculate_checksums: Option<bool>,
+
+    /// Restricts this manipulation to packets matching the content filter (config file only)
+    #[arg(skip)]
+    #[serde(default, rename = "match", skip_serializing_if = "Option::is_none")]
+    pub content_match: Option<ContentMatchOptions>,
 }
 
 impl Default for TamperOptions {
@@ -29,6 +35,7 @@ impl Default for TamperOptions {
             probability: Probability::default(),
             amount: Probability::new(0.1).unwrap(),
             recalculate_checksums: Some(true),
+            content_match: None,
         }
     }
 }
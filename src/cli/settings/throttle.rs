@@ -1,35 +1,43 @@
+use crate::cli::settings::content_match::ContentMatchOptions;
+use crate::network::types::duration_spec::DurationSpec;
 use crate::network::types::probability::Probability;
 use clap::Parser;
 use serde::{Deserialize, Serialize};
 
-#[derive(Parser, Debug, Serialize, Deserialize)]
+#[derive(Parser, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ThrottleOptions {
     /// Probability of triggering a throttle event, ranging from 0.0 to 1.0
     #[arg(long = "throttle-probability", id = "throttle-probability", default_value_t = Probability::default())]
     #[serde(default)]
     pub probability: Probability,
 
-    /// Duration in milliseconds for which throttling should be applied
+    /// Duration for which throttling should be applied, e.g. 30ms or 2s. A number without a unit is in milliseconds
     #[arg(
         long = "throttle-duration",
-        default_value_t = 30,
+        default_value_t = DurationSpec::from_millis(30),
         id = "throttle-duration"
     )]
     #[serde(default)]
-    pub duration: u64,
+    pub duration: DurationSpec,
 
     /// Indicates whether throttled packets should be dropped
     #[arg(long = "throttle-drop", default_value_t = false, id = "throttle-drop")]
     #[serde(default)]
     pub drop: bool,
+
+    /// Restricts this manipulation to packets matching the content filter (config file only)
+    #[arg(skip)]
+    #[serde(default, rename = "match", skip_serializing_if = "Option::is_none")]
+    pub content_match: Option<ContentMatchOptions>,
 }
 
 impl Default for ThrottleOptions {
     fn default() -> Self {
         ThrottleOptions {
             probability: Probability::default(),
-            duration: 30,
+            duration: DurationSpec::from_millis(30),
             drop: false,
+            content_match: None,
         }
     }
 }
@@ -0,0 +1,231 @@
+use crate::cli::settings::content_match::ContentMatchOptions;
+use crate::network::types::duration_spec::DurationSpec;
+use crate::network::types::ip_cidr::IpCidr;
+use crate::network::types::probability::Probability;
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fmt::Formatter;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// A named latency and loss profile, written as `<name>: <delay>[, <loss>% loss]`,
+/// e.g. `ap-south: 220ms, 1% loss`. The delay takes the units of [`DurationSpec`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TopologyProfile {
+    pub name: String,
+    pub delay: DurationSpec,
+    pub loss: Probability,
+}
+
+impl FromStr for TopologyProfile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, properties) = s
+            .split_once(':')
+            .ok_or_else(|| format!("`{}` is missing a `<name>:` prefix", s.trim()))?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Profile name cannot be empty".to_string());
+        }
+
+        let mut profile = TopologyProfile {
+            name: name.to_string(),
+            delay: DurationSpec::default(),
+            loss: Probability::default(),
+        };
+        for property in properties.split(',').map(str::trim) {
+            if let Some(loss) = property.strip_suffix("loss") {
+                let percent: f64 = loss
+                    .trim()
+                    .trim_end_matches('%')
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("`{}` is not a valid loss percentage", property))?;
+                profile.loss = Probability::new(percent / 100.0)
+                    .map_err(|_| format!("Loss of {}% is not between 0% and 100%", percent))?;
+            } else if !property.is_empty() {
+                profile.delay = property.parse().map_err(|e| {
+                    format!(
+                        "Unknown profile property `{}`, expected a delay such as `30ms` or `<n>% loss`: {}",
+                        property, e
+                    )
+                })?;
+            }
+        }
+        Ok(profile)
+    }
+}
+
+impl TryFrom<String> for TopologyProfile {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<TopologyProfile> for String {
+    fn from(profile: TopologyProfile) -> Self {
+        profile.to_string()
+    }
+}
+
+impl fmt::Display for TopologyProfile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.delay)?;
+        if self.loss.value() > 0.0 {
+            // Round away floating point noise such as 7.000000000000001
+            let percent = (self.loss.value() * 100.0 * 1e6).round() / 1e6;
+            write!(f, ", {}% loss", percent)?;
+        }
+        Ok(())
+    }
+}
+
+/// Maps the remote addresses of a network to a profile, written as `<cidr> = <profile>`,
+/// e.g. `10.1.0.0/16 = eu-west: 30ms`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TopologyRoute {
+    pub network: IpCidr,
+    pub profile: TopologyProfile,
+}
+
+impl FromStr for TopologyRoute {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (network, profile) = s
+            .split_once('=')
+            .ok_or_else(|| format!("`{}` is not of the form `<cidr> = <profile>`", s.trim()))?;
+        Ok(TopologyRoute {
+            network: network.parse()?,
+            profile: profile.parse()?,
+        })
+    }
+}
+
+impl TryFrom<String> for TopologyRoute {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<TopologyRoute> for String {
+    fn from(route: TopologyRoute) -> Self {
+        route.to_string()
+    }
+}
+
+impl fmt::Display for TopologyRoute {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} = {}", self.network, self.profile)
+    }
+}
+
+#[derive(Parser, Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct TopologyOptions {
+    /// Route applying a latency and loss profile to a remote network, e.g. `10.2.0.0/16 = ap-south: 220ms, 1% loss`. Can be repeated
+    #[arg(long = "topology-route", id = "topology-route")]
+    #[serde(default)]
+    pub routes: Vec<TopologyRoute>,
+
+    /// Profile applied to remote addresses not matched by any route, e.g. `default: 10ms`
+    #[arg(long = "topology-default", id = "topology-default")]
+    #[serde(default, rename = "default", skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<TopologyProfile>,
+
+    /// Restricts this manipulation to packets matching the content filter (config file only)
+    #[arg(skip)]
+    #[serde(default, rename = "match", skip_serializing_if = "Option::is_none")]
+    pub content_match: Option<ContentMatchOptions>,
+}
+
+impl TopologyOptions {
+    /// Returns the profile of the most specific route containing `address`,
+    /// falling back to the default profile.
+    pub fn profile_for(&self, address: IpAddr) -> Option<&TopologyProfile> {
+        self.routes
+            .iter()
+            .filter(|route| route.network.contains(address))
+            .max_by_key(|route| route.network.prefix_len())
+            .map(|route| &route.profile)
+            .or(self.default_profile.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_parse_profile() {
+        let profile: TopologyProfile = "ap-south: 220ms, 1% loss".parse().unwrap();
+        assert_eq!(profile.name, "ap-south");
+        assert_eq!(profile.delay, DurationSpec::from_millis(220));
+        assert_eq!(profile.loss.value(), 0.01);
+        assert_eq!(profile.to_string(), "ap-south: 220ms, 1% loss");
+
+        let profile: TopologyProfile = "eu-west: 30ms".parse().unwrap();
+        assert_eq!(profile.loss.value(), 0.0);
+        assert_eq!(profile.to_string(), "eu-west: 30ms");
+
+        let profile: TopologyProfile = "us-east: 1.5s, 2% loss".parse().unwrap();
+        assert_eq!(profile.delay.duration(), Duration::from_millis(1500));
+        assert_eq!(profile.to_string(), "us-east: 1500ms, 2% loss");
+        assert_eq!(profile.to_string().parse::<TopologyProfile>(), Ok(profile));
+    }
+
+    #[test]
+    fn test_invalid_profiles() {
+        assert!("30ms".parse::<TopologyProfile>().is_err());
+        assert!(": 30ms".parse::<TopologyProfile>().is_err());
+        assert!("eu: 30 seconds".parse::<TopologyProfile>().is_err());
+        assert!("eu: 30ms, 150% loss".parse::<TopologyProfile>().is_err());
+    }
+
+    #[test]
+    fn test_longest_prefix_match() {
+        let options = TopologyOptions {
+            routes: vec![
+                "10.0.0.0/8 = internal: 5ms".parse().unwrap(),
+                "10.2.0.0/16 = ap-south: 220ms, 1% loss".parse().unwrap(),
+            ],
+            default_profile: Some("default: 1ms".parse().unwrap()),
+            content_match: None,
+        };
+        let profile_name = |address: &str| {
+            options
+                .profile_for(address.parse().unwrap())
+                .unwrap()
+                .name
+                .clone()
+        };
+        assert_eq!(profile_name("10.2.3.4"), "ap-south");
+        assert_eq!(profile_name("10.3.3.4"), "internal");
+        assert_eq!(profile_name("8.8.8.8"), "default");
+    }
+
+    #[test]
+    fn test_config_roundtrip() {
+        let toml = r#"
+routes = ["10.1.0.0/16 = eu-west: 30ms", "10.2.0.0/16 = ap-south: 220ms, 1% loss"]
+default = "default: 10ms"
+"#;
+        let options: TopologyOptions = toml::from_str(toml).unwrap();
+        assert_eq!(options.routes.len(), 2);
+        assert_eq!(
+            options.default_profile.as_ref().unwrap().delay,
+            DurationSpec::from_millis(10)
+        );
+        let serialized = toml::to_string(&options).unwrap();
+        let reparsed: TopologyOptions = toml::from_str(&serialized).unwrap();
+        assert_eq!(reparsed.routes, options.routes);
+    }
+}
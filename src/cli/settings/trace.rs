@@ -0,0 +1,158 @@
+use crate::cli::settings::content_match::ContentMatchOptions;
+use crate::network::types::trace::Trace;
+use clap::{Parser, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fmt::Formatter;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// What happens once the replay reaches the end of the trace.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceEnd {
+    /// Start over from the beginning of the trace
+    #[default]
+    Loop,
+    /// Stop replaying and let packets through unchanged
+    Stop,
+}
+
+impl TraceEnd {
+    /// Returns the behavior following this one, wrapping around. Used to cycle through them in the TUI.
+    pub fn next(self) -> Self {
+        match self {
+            TraceEnd::Loop => TraceEnd::Stop,
+            TraceEnd::Stop => TraceEnd::Loop,
+        }
+    }
+}
+
+impl fmt::Display for TraceEnd {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TraceEnd::Loop => "loop",
+            TraceEnd::Stop => "stop",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A trace file read for a run. The replay of the trace starts when it is read.
+#[derive(Debug, Clone)]
+pub struct TraceFile {
+    pub path: PathBuf,
+    pub trace: Arc<Trace>,
+    pub started: Instant,
+}
+
+impl TraceFile {
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read trace {}: {}", path.display(), e))?;
+        let trace = Trace::parse(&content)
+            .map_err(|e| format!("Invalid trace {}: {}", path.display(), e))?;
+        Ok(TraceFile {
+            path: path.to_path_buf(),
+            trace: Arc::new(trace),
+            started: Instant::now(),
+        })
+    }
+}
+
+impl PartialEq for TraceFile {
+    /// Only the same reading of a trace file is equal, as reading it again starts its replay over
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.trace, &other.trace)
+    }
+}
+
+impl fmt::Display for TraceFile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let format = match self.trace.as_ref() {
+            Trace::DeliveryOpportunities(_) => "Mahimahi",
+            Trace::Conditions(_) => "CSV",
+        };
+        write!(
+            f,
+            "{} trace {} lasting {:?}",
+            format,
+            self.path.display(),
+            self.trace.period()
+        )
+    }
+}
+
+#[derive(Parser, Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct TraceOptions {
+    /// Trace of recorded conditions to replay: a Mahimahi packet delivery trace, or a CSV of `timestamp,latency,bandwidth,loss` rows
+    #[arg(long = "trace-file", id = "trace-file")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+
+    /// Directory a relative `file` is read from: that of the config file it was loaded from, or
+    /// the working directory if not set
+    #[arg(skip)]
+    #[serde(skip)]
+    pub base_dir: Option<PathBuf>,
+
+    /// What to do at the end of the trace
+    #[arg(long = "trace-end", id = "trace-end", value_enum, default_value_t = TraceEnd::Loop)]
+    #[serde(default)]
+    pub end: TraceEnd,
+
+    /// Restricts this manipulation to packets matching the content filter (config file only)
+    #[arg(skip)]
+    #[serde(default, rename = "match", skip_serializing_if = "Option::is_none")]
+    pub content_match: Option<ContentMatchOptions>,
+}
+
+impl TraceOptions {
+    /// Returns the path the trace file is read from.
+    pub fn resolved_file(&self) -> Option<PathBuf> {
+        let file = self.file.as_ref()?;
+        Some(match &self.base_dir {
+            Some(base_dir) => base_dir.join(file),
+            None => file.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_roundtrip() {
+        let toml = "file = \"traces/lte.csv\"\nend = \"stop\"\n";
+        // The file is only read when the run starts
+        let mut options: TraceOptions = toml::from_str(toml).unwrap();
+        assert_eq!(options.file, Some(PathBuf::from("traces/lte.csv")));
+        assert_eq!(options.end, TraceEnd::Stop);
+        assert_eq!(options.resolved_file(), options.file);
+
+        options.base_dir = Some(PathBuf::from("configs"));
+        assert_eq!(
+            options.resolved_file(),
+            Some(Path::new("configs/traces/lte.csv").into())
+        );
+        assert_eq!(toml::to_string(&options).unwrap(), toml);
+    }
+
+    #[test]
+    fn test_read() {
+        let path = std::env::temp_dir().join("fumble_test_trace_read.csv");
+        fs::write(&path, "0,50\n1s,80\n").unwrap();
+        let file = TraceFile::read(&path).unwrap();
+        assert_eq!(file.trace.period(), std::time::Duration::from_secs(2));
+        assert!(file.to_string().starts_with("CSV trace"));
+        // Reading the file again starts another replay
+        assert_ne!(TraceFile::read(&path).unwrap(), file);
+
+        fs::remove_file(&path).unwrap();
+        let error = TraceFile::read(&path).unwrap_err();
+        assert!(error.starts_with("Failed to read trace"));
+    }
+}
@@ -1,18 +1,26 @@
+use crate::cli::config::config_document::{ConfigDocument, TuiPreferences};
+use crate::cli::config::config_options::ConfigOptions;
 use crate::cli::settings::bandwidth::BandwidthOptions;
 use crate::cli::settings::delay::DelayOptions;
+use crate::cli::settings::dns::DnsOptions;
 use crate::cli::settings::drop::DropOptions;
 use crate::cli::settings::duplicate::DuplicateOptions;
+use crate::cli::settings::packet_manipulation::PacketManipulationSettings;
 use crate::cli::settings::reorder::ReorderOptions;
 use crate::cli::settings::tamper::TamperOptions;
 use crate::cli::settings::throttle::ThrottleOptions;
+use crate::cli::settings::topology::TopologyOptions;
+use crate::cli::settings::trace::TraceOptions;
 use crate::cli::tui::state::TuiState;
 use crate::cli::tui::traits::IsActive;
+use crate::cli::tui::widgets::config_widget::ConfigRequest;
 use crate::cli::tui::widgets::custom_widget::CustomWidget;
 use crate::cli::Cli;
 use crate::network::modules::stats::PacketProcessingStatistics;
-use log::error;
-use std::sync::{Arc, Mutex, RwLock};
+use crate::network::types::duration_spec::DurationSpec;
 use crate::network::types::probability::Probability;
+use log::{error, info};
+use std::sync::{Arc, Mutex};
 
 pub trait TuiStateExt {
     /// Creates a `TuiState` instance from the current state of the `Cli` object.
@@ -21,7 +29,13 @@ pub trait TuiStateExt {
 
     /// Updates the `TuiState` with the latest statistics from the packet processing.
     /// This function refreshes the widgets in the `TuiState` using data from the provided `PacketProcessingStatistics`.
-    fn update_from(&mut self, statistics: &Arc<RwLock<PacketProcessingStatistics>>);
+    fn update_from(&mut self, statistics: &Arc<PacketProcessingStatistics>);
+
+    /// Carries out the save or load action confirmed in the config dialog, if any.
+    /// Saving writes the settings of the TUI, its filter, runtime options and open panes to the
+    /// fumble config directory, loading replaces them with the ones from the chosen config file.
+    /// A config file without a filter keeps the current one, as nothing is captured without it.
+    fn handle_config_request(&mut self);
 }
 
 impl TuiStateExt for TuiState<'_> {
@@ -31,9 +45,17 @@ impl TuiStateExt for TuiState<'_> {
         state
     }
 
-    fn update_from(&mut self, statistics: &Arc<RwLock<PacketProcessingStatistics>>) {
+    fn update_from(&mut self, statistics: &Arc<PacketProcessingStatistics>) {
         update_tui_state_from_statistics(self, statistics);
     }
+
+    fn handle_config_request(&mut self) {
+        match self.config_widget.take_request() {
+            Some(ConfigRequest::Save(name)) => save_tui_state_to_config(self, &name),
+            Some(ConfigRequest::Load(name)) => load_tui_state_from_config(self, &name),
+            None => {}
+        }
+    }
 }
 
 pub trait CliExt {
@@ -65,6 +87,9 @@ impl CliExt for Arc<Mutex<Cli>> {
         cli.packet_manipulation_settings.tamper = None;
         cli.packet_manipulation_settings.duplicate = None;
         cli.packet_manipulation_settings.bandwidth = None;
+        cli.packet_manipulation_settings.dns = None;
+        cli.packet_manipulation_settings.topology = None;
+        cli.packet_manipulation_settings.trace = None;
     }
 }
 
@@ -77,50 +102,85 @@ fn init_tui_state_from_cli(state: &mut TuiState, cli: &Arc<Mutex<Cli>>) {
         }
     };
 
-    if let Some(filter) = &cli.filter {
+    state.runtime_options = cli.options;
+    apply_tui_preferences(state, &cli.tui_preferences);
+    init_tui_state_from_settings(
+        state,
+        cli.filter.as_deref(),
+        &cli.packet_manipulation_settings,
+    );
+}
+
+fn apply_tui_preferences(state: &mut TuiState, preferences: &TuiPreferences) {
+    state.logs_widget.open = preferences.show_logs;
+    state.graphs_widget.open = preferences.show_graphs;
+    state.inspector_widget.open = preferences.show_inspector;
+}
+
+fn tui_preferences_from_state(state: &TuiState) -> TuiPreferences {
+    TuiPreferences {
+        show_logs: state.logs_widget.open,
+        show_graphs: state.graphs_widget.open,
+        show_inspector: state.inspector_widget.open,
+    }
+}
+
+fn init_tui_state_from_settings(
+    state: &mut TuiState,
+    filter: Option<&str>,
+    settings: &PacketManipulationSettings,
+) {
+    if let Some(filter) = filter {
         state.filter_widget.set_filter(filter);
     }
     for section in state.sections.iter_mut() {
         match section {
             CustomWidget::Drop(ref mut drop_widget) => {
-                if let Some(drop) = &cli.packet_manipulation_settings.drop {
+                if let Some(drop) = &settings.drop {
+                    drop_widget.content_match = drop.content_match.clone();
                     drop_widget.set_probability(drop.probability);
+                    drop_widget.set_capacity(drop.capacity);
+                    drop_widget.curve = drop.curve;
                     drop_widget.set_active(true);
                 } else {
                     drop_widget.set_probability(Probability::new(0.1).unwrap());
                 }
             }
             CustomWidget::Delay(ref mut delay_widget) => {
-                if let Some(delay) = &cli.packet_manipulation_settings.delay {
+                if let Some(delay) = &settings.delay {
+                    delay_widget.content_match = delay.content_match.clone();
                     delay_widget.set_delay(delay.duration);
                     delay_widget.set_active(true);
                 } else {
-                    delay_widget.set_delay(50);
+                    delay_widget.set_delay(DurationSpec::from_millis(50));
                 }
             }
             CustomWidget::Throttle(ref mut throttle_widget) => {
-                if let Some(throttle) = &cli.packet_manipulation_settings.throttle {
+                if let Some(throttle) = &settings.throttle {
+                    throttle_widget.content_match = throttle.content_match.clone();
                     throttle_widget.set_probability(throttle.probability);
                     throttle_widget.set_throttle_duration(throttle.duration);
                     throttle_widget.drop = throttle.drop;
                     throttle_widget.set_active(true);
                 } else {
                     throttle_widget.set_probability(Probability::new(0.1).unwrap());
-                    throttle_widget.set_throttle_duration(30);
+                    throttle_widget.set_throttle_duration(DurationSpec::from_millis(30));
                 }
             }
             CustomWidget::Reorder(ref mut reorder_widget) => {
-                if let Some(reorder) = &cli.packet_manipulation_settings.reorder {
+                if let Some(reorder) = &settings.reorder {
+                    reorder_widget.content_match = reorder.content_match.clone();
                     reorder_widget.set_probability(reorder.probability);
                     reorder_widget.set_delay_duration(reorder.max_delay);
                     reorder_widget.set_active(true);
                 } else {
                     reorder_widget.set_probability(Probability::new(0.1).unwrap());
-                    reorder_widget.set_delay_duration(30);
+                    reorder_widget.set_delay_duration(DurationSpec::from_millis(30));
                 }
             }
             CustomWidget::Tamper(ref mut tamper_widget) => {
-                if let Some(tamper) = &cli.packet_manipulation_settings.tamper {
+                if let Some(tamper) = &settings.tamper {
+                    tamper_widget.content_match = tamper.content_match.clone();
                     tamper_widget.set_probability(tamper.probability);
                     tamper_widget.set_tamper_amount(tamper.amount);
                     if let Some(recalculate_checksums) = tamper.recalculate_checksums {
@@ -133,7 +193,8 @@ fn init_tui_state_from_cli(state: &mut TuiState, cli: &Arc<Mutex<Cli>>) {
                 }
             }
             CustomWidget::Duplicate(ref mut duplicate_widget) => {
-                if let Some(duplicate) = &cli.packet_manipulation_settings.duplicate {
+                if let Some(duplicate) = &settings.duplicate {
+                    duplicate_widget.content_match = duplicate.content_match.clone();
                     duplicate_widget.set_probability(duplicate.probability);
                     duplicate_widget.set_duplicate_count(duplicate.count);
                     duplicate_widget.set_active(true);
@@ -143,11 +204,43 @@ fn init_tui_state_from_cli(state: &mut TuiState, cli: &Arc<Mutex<Cli>>) {
                 }
             }
             CustomWidget::Bandwidth(ref mut bandwidth_widget) => {
-                if let Some(bandwidth) = &cli.packet_manipulation_settings.bandwidth {
+                if let Some(bandwidth) = &settings.bandwidth {
+                    bandwidth_widget.content_match = bandwidth.content_match.clone();
                     bandwidth_widget.set_limit(bandwidth.limit);
+                    bandwidth_widget.upload = bandwidth.upload;
+                    bandwidth_widget.download = bandwidth.download;
+                    bandwidth_widget.overhead = bandwidth.overhead;
                     bandwidth_widget.set_active(true);
                 }
             }
+            CustomWidget::Dns(ref mut dns_widget) => {
+                if let Some(dns) = &settings.dns {
+                    dns_widget.content_match = dns.content_match.clone();
+                    dns_widget.set_names(&dns.names);
+                    dns_widget.action = dns.action;
+                    dns_widget.set_delay(dns.delay);
+                    dns_widget.set_active(true);
+                } else {
+                    dns_widget.set_delay(DurationSpec::from_millis(500));
+                }
+            }
+            CustomWidget::Topology(ref mut topology_widget) => {
+                if let Some(topology) = &settings.topology {
+                    topology_widget.content_match = topology.content_match.clone();
+                    topology_widget.routes = topology.routes.clone();
+                    topology_widget.set_default_profile(topology.default_profile.as_ref());
+                    topology_widget.set_active(true);
+                }
+            }
+            CustomWidget::Trace(ref mut trace_widget) => {
+                if let Some(trace) = &settings.trace {
+                    trace_widget.content_match = trace.content_match.clone();
+                    trace_widget.base_dir = trace.base_dir.clone();
+                    trace_widget.set_file(trace.file.as_deref());
+                    trace_widget.end = trace.end;
+                    trace_widget.set_active(true);
+                }
+            }
         }
     }
 }
@@ -164,31 +257,77 @@ fn update_cli_from_tui_state(state: &mut TuiState, cli: &Arc<Mutex<Cli>>) {
     if let Ok(filter) = &state.filter_widget.filter {
         cli.filter = Some(filter.to_string());
     }
+    cli.options = state.runtime_options;
 
-    for section in state.sections.iter_mut() {
+    update_settings_from_tui_state(state, &mut cli.packet_manipulation_settings);
+}
+
+fn save_tui_state_to_config(state: &TuiState, name: &str) {
+    let mut document = ConfigDocument {
+        filter: state.filter_widget.filter.as_ref().ok().cloned(),
+        options: state.runtime_options,
+        tui: tui_preferences_from_state(state),
+        settings: PacketManipulationSettings::default(),
+    };
+    update_settings_from_tui_state(state, &mut document.settings);
+    match ConfigOptions::save_config(name, &document) {
+        Ok(path) => info!("Saved configuration to {:?}", path),
+        Err(e) => error!("Failed to save configuration {:?}: {}", name, e),
+    }
+}
+
+fn load_tui_state_from_config(state: &mut TuiState, name: &str) {
+    let document = match ConfigOptions::load_existing_config(name) {
+        Ok(document) => document,
+        Err(e) => {
+            error!("Failed to load configuration {:?}: {}", name, e);
+            return;
+        }
+    };
+    // Start from fresh sections so modules missing from the config end up disabled
+    state.sections = TuiState::new().sections;
+    state.interacting = None;
+    state.runtime_options = document.options;
+    apply_tui_preferences(state, &document.tui);
+    // Like a reloaded config file, a document without a filter keeps the current one rather
+    // than leaving nothing to capture
+    init_tui_state_from_settings(state, document.filter.as_deref(), &document.settings);
+    info!("Loaded configuration from {:?}", name);
+}
+
+fn update_settings_from_tui_state(state: &TuiState, settings: &mut PacketManipulationSettings) {
+    for section in state.sections.iter() {
         match section {
-            CustomWidget::Drop(ref mut drop_widget) => {
-                cli.packet_manipulation_settings.drop = if !drop_widget.is_active() {
+            CustomWidget::Drop(ref drop_widget) => {
+                settings.drop = if !drop_widget.is_active() {
                     None
                 } else {
-                    match drop_widget.probability {
-                        Ok(probability) => Some(DropOptions { probability }),
-                        Err(_) => None,
+                    match (&drop_widget.probability, &drop_widget.capacity) {
+                        (Ok(probability), Ok(capacity)) => Some(DropOptions {
+                            probability: *probability,
+                            capacity: *capacity,
+                            curve: drop_widget.curve,
+                            content_match: drop_widget.content_match.clone(),
+                        }),
+                        _ => None,
                     }
                 }
             }
-            CustomWidget::Delay(ref mut delay_widget) => {
-                cli.packet_manipulation_settings.delay = if !delay_widget.is_active() {
+            CustomWidget::Delay(ref delay_widget) => {
+                settings.delay = if !delay_widget.is_active() {
                     None
                 } else {
                     match delay_widget.delay {
-                        Ok(duration) => Some(DelayOptions { duration }),
+                        Ok(duration) => Some(DelayOptions {
+                            duration,
+                            content_match: delay_widget.content_match.clone(),
+                        }),
                         Err(_) => None,
                     }
                 }
             }
-            CustomWidget::Throttle(ref mut throttle_widget) => {
-                cli.packet_manipulation_settings.throttle = if !throttle_widget.is_active() {
+            CustomWidget::Throttle(ref throttle_widget) => {
+                settings.throttle = if !throttle_widget.is_active() {
                     None
                 } else {
                     throttle_widget
@@ -204,12 +343,13 @@ fn update_cli_from_tui_state(state: &mut TuiState, cli: &Arc<Mutex<Cli>>) {
                                     probability: *probability,
                                     duration: *duration,
                                     drop: throttle_widget.drop,
+                                    content_match: throttle_widget.content_match.clone(),
                                 })
                         })
                 }
             }
             CustomWidget::Reorder(ref reorder_widget) => {
-                cli.packet_manipulation_settings.reorder = if !reorder_widget.is_active() {
+                settings.reorder = if !reorder_widget.is_active() {
                     None
                 } else {
                     reorder_widget
@@ -224,13 +364,14 @@ fn update_cli_from_tui_state(state: &mut TuiState, cli: &Arc<Mutex<Cli>>) {
                                 .map(|max_delay| ReorderOptions {
                                     probability: *probability,
                                     max_delay: *max_delay,
+                                    content_match: reorder_widget.content_match.clone(),
                                 })
                         })
                 }
             }
 
             CustomWidget::Tamper(ref tamper_widget) => {
-                cli.packet_manipulation_settings.tamper =
+                settings.tamper =
                     if !tamper_widget.is_active() {
                         None
                     } else {
@@ -246,13 +387,14 @@ fn update_cli_from_tui_state(state: &mut TuiState, cli: &Arc<Mutex<Cli>>) {
                                         recalculate_checksums: Some(
                                             tamper_widget.recalculate_checksums,
                                         ),
+                                        content_match: tamper_widget.content_match.clone(),
                                     }
                                 })
                             })
                     }
             }
             CustomWidget::Duplicate(ref duplicate_widget) => {
-                cli.packet_manipulation_settings.duplicate = if !duplicate_widget.is_active() {
+                settings.duplicate = if !duplicate_widget.is_active() {
                     None
                 } else {
                     duplicate_widget
@@ -264,36 +406,87 @@ fn update_cli_from_tui_state(state: &mut TuiState, cli: &Arc<Mutex<Cli>>) {
                                 DuplicateOptions {
                                     probability: *probability,
                                     count: *count,
+                                    content_match: duplicate_widget.content_match.clone(),
                                 }
                             })
                         })
                 }
             }
             CustomWidget::Bandwidth(ref bandwidth_widget) => {
-                cli.packet_manipulation_settings.bandwidth = if !bandwidth_widget.is_active() {
+                settings.bandwidth = if !bandwidth_widget.is_active() {
                     None
                 } else {
                     match bandwidth_widget.limit {
-                        Ok(limit) => Some(BandwidthOptions { limit }),
+                        Ok(limit) => Some(BandwidthOptions {
+                            limit,
+                            upload: bandwidth_widget.upload,
+                            download: bandwidth_widget.download,
+                            overhead: bandwidth_widget.overhead,
+                            content_match: bandwidth_widget.content_match.clone(),
+                        }),
+                        Err(_) => None,
+                    }
+                }
+            }
+            CustomWidget::Dns(ref dns_widget) => {
+                settings.dns = if !dns_widget.is_active() {
+                    None
+                } else {
+                    match (&dns_widget.names, &dns_widget.delay) {
+                        (Ok(names), Ok(delay)) => Some(DnsOptions {
+                            names: names.clone(),
+                            action: dns_widget.action,
+                            delay: *delay,
+                            content_match: dns_widget.content_match.clone(),
+                        }),
+                        _ => None,
+                    }
+                }
+            }
+            CustomWidget::Topology(ref topology_widget) => {
+                settings.topology = if !topology_widget.is_active() {
+                    None
+                } else {
+                    match &topology_widget.default_profile {
+                        Ok(default_profile) => Some(TopologyOptions {
+                            routes: topology_widget.routes.clone(),
+                            default_profile: default_profile.clone(),
+                            content_match: topology_widget.content_match.clone(),
+                        }),
                         Err(_) => None,
                     }
                 }
             }
+            CustomWidget::Trace(ref trace_widget) => {
+                settings.trace = if !trace_widget.is_active() {
+                    None
+                } else {
+                    trace_widget.file.as_ref().map(|file| TraceOptions {
+                        file: Some(file.clone()),
+                        base_dir: trace_widget.base_dir.clone(),
+                        end: trace_widget.end,
+                        content_match: trace_widget.content_match.clone(),
+                    })
+                }
+            }
         }
     }
 }
 
 fn update_tui_state_from_statistics(
     state: &mut TuiState,
-    statistics: &Arc<RwLock<PacketProcessingStatistics>>,
+    statistics: &Arc<PacketProcessingStatistics>,
 ) {
-    let stats = match statistics.read() {
-        Ok(stats) => stats,
-        Err(e) => {
-            error!("Failed to lock statistics read RwLock. {}", e);
-            return;
-        }
-    };
+    if state.reset_statistics {
+        state.reset_statistics = false;
+        statistics.reset();
+        info!("Statistics reset.");
+    }
+    statistics.set_inspecting(state.inspector_widget.is_recording());
+
+    let stats = statistics.snapshot();
+    state.graphs_widget.update_data(&stats);
+    state.inspector_widget.update_data(statistics);
 
     for section in state.sections.iter_mut() {
         if section.is_active() {
@@ -302,7 +495,10 @@ fn update_tui_state_from_statistics(
                     drop_widget.update_data(&stats.drop_stats);
                 }
                 CustomWidget::Delay(ref mut delay_widget) => {
-                    delay_widget.update_data(&stats.delay_stats);
+                    delay_widget.update_data(
+                        &stats.delay_stats,
+                        stats.latency_stats.modules.get("delay"),
+                    );
                 }
                 CustomWidget::Throttle(ref mut throttle_widget) => {
                     throttle_widget.update_data(&stats.throttle_stats);
@@ -319,6 +515,15 @@ fn update_tui_state_from_statistics(
                 CustomWidget::Bandwidth(ref mut bandwidth_widget) => {
                     bandwidth_widget.update_data(&stats.bandwidth_stats)
                 }
+                CustomWidget::Dns(ref mut dns_widget) => {
+                    dns_widget.update_data(&stats.dns_stats)
+                }
+                CustomWidget::Topology(ref mut topology_widget) => {
+                    topology_widget.update_data(&stats.topology_stats)
+                }
+                CustomWidget::Trace(ref mut trace_widget) => {
+                    trace_widget.update_data(&stats.trace_stats)
+                }
             }
         }
     }
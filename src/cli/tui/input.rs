@@ -14,7 +14,10 @@ pub fn handle_input(state: &mut TuiState) -> io::Result<bool> {
             }
 
             match state.focused {
-                LayoutSection::Filter | LayoutSection::Logging => {
+                LayoutSection::Filter
+                | LayoutSection::Logging
+                | LayoutSection::Inspector
+                | LayoutSection::Config => {
                     if handle_widget_input(state, key) {
                         return Ok(false);
                     }
@@ -55,24 +58,37 @@ fn handle_section_input(state: &mut TuiState, key: KeyEvent) -> bool {
     false
 }
 
-// Function to handle input for widgets (filter and logs)
+// Function to handle input for widgets (config dialog, filter, logs and inspector)
 fn handle_widget_input(state: &mut TuiState, key: KeyEvent) -> bool {
     if key.kind == KeyEventKind::Press {
-        if state.filter_widget.inputting {
+        if state.config_widget.is_open() {
+            state.config_widget.input(key);
+            return true;
+        } else if state.filter_widget.inputting {
             state.filter_widget.input(key);
             return true;
         } else if state.logs_widget.focused {
             state.logs_widget.input(key);
             return true;
+        } else if state.inspector_widget.focused {
+            state.inspector_widget.input(key);
+            return true;
         }
     }
     false
 }
 
+// Maps the digit keys 1-9 to the first nine sections and 0 to the tenth
+fn section_index(c: char) -> Option<usize> {
+    let digit = c.to_digit(10)? as usize;
+    Some(if digit == 0 { 9 } else { digit - 1 })
+}
+
 // Function to handle main menu navigation and commands
 fn handle_main_menu_input(state: &mut TuiState, key: KeyEvent) -> bool {
     match key.code {
         KeyCode::Char('q') => return true,
+        KeyCode::Char('r') => state.reset_statistics = true,
         KeyCode::Up => {
             if state.selected > 0 {
                 state.selected -= 1;
@@ -87,11 +103,10 @@ fn handle_main_menu_input(state: &mut TuiState, key: KeyEvent) -> bool {
             let active_state = state.sections[state.selected].is_active();
             state.sections[state.selected].set_active(!active_state);
         }
-        KeyCode::Char(c) if c.is_numeric() => {
-            for (i, _) in state.sections.iter().enumerate() {
-                if c == char::from_digit((i + 1) as u32, 10).unwrap() {
-                    state.selected = i;
-                    break;
+        KeyCode::Char(c) => {
+            if let Some(index) = section_index(c) {
+                if index < state.sections.len() {
+                    state.selected = index;
                 }
             }
         }
@@ -101,5 +116,34 @@ fn handle_main_menu_input(state: &mut TuiState, key: KeyEvent) -> bool {
     // Pass the key event to widgets if it's not handled by menu navigation
     state.filter_widget.input(key);
     state.logs_widget.input(key);
+    state.inspector_widget.input(key);
+    state.graphs_widget.input(key);
+    state.config_widget.input(key);
     false
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::crossterm::event::KeyModifiers;
+
+    fn press(state: &mut TuiState, c: char) {
+        handle_main_menu_input(state, KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+    }
+
+    #[test]
+    fn test_digits_select_sections() {
+        let mut state = TuiState::new();
+        assert_eq!(state.sections.len(), 10);
+
+        press(&mut state, '3');
+        assert_eq!(state.selected, 2);
+        press(&mut state, '0');
+        assert_eq!(state.selected, 9);
+
+        state.sections.truncate(9);
+        state.selected = 0;
+        press(&mut state, '0');
+        assert_eq!(state.selected, 0);
+    }
+}
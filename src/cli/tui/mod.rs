@@ -3,6 +3,7 @@ pub mod custom_logger;
 pub mod input;
 pub mod logging_util;
 pub mod state;
+pub mod statistics_history;
 pub mod terminal;
 pub mod traits;
 pub mod ui;
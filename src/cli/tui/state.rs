@@ -1,20 +1,34 @@
+use crate::cli::settings::runtime_options::RuntimeOptions;
 use crate::cli::tui::ui::LayoutSection;
 use crate::cli::tui::widgets::bandwidth_widget::BandwidthWidget;
+use crate::cli::tui::widgets::config_widget::ConfigWidget;
 use crate::cli::tui::widgets::custom_widget::CustomWidget;
 use crate::cli::tui::widgets::delay_widget::DelayWidget;
+use crate::cli::tui::widgets::dns_widget::DnsWidget;
 use crate::cli::tui::widgets::drop_widget::DropWidget;
 use crate::cli::tui::widgets::duplicate_widget::DuplicateWidget;
 use crate::cli::tui::widgets::filter_widget::FilterWidget;
+use crate::cli::tui::widgets::graphs_widget::GraphsWidget;
+use crate::cli::tui::widgets::inspector_widget::InspectorWidget;
 use crate::cli::tui::widgets::logs_widget::LogsWidget;
 use crate::cli::tui::widgets::reorder_widget::ReorderWidget;
 use crate::cli::tui::widgets::tamper_widget::TamperWidget;
 use crate::cli::tui::widgets::throttle_widget::ThrottleWidget;
+use crate::cli::tui::widgets::topology_widget::TopologyWidget;
+use crate::cli::tui::widgets::trace_widget::TraceWidget;
 
 pub struct TuiState<'a> {
     pub processing: bool,
+    /// Set when the user asked to zero the statistics
+    pub reset_statistics: bool,
     pub filter_widget: FilterWidget<'a>,
     pub sections: Vec<CustomWidget<'a>>,
     pub logs_widget: LogsWidget,
+    pub graphs_widget: GraphsWidget,
+    pub inspector_widget: InspectorWidget,
+    pub config_widget: ConfigWidget<'a>,
+    /// Options without a widget, kept so they survive saving and loading configs
+    pub runtime_options: RuntimeOptions,
     pub selected: usize,
     pub interacting: Option<usize>,
     pub focused: LayoutSection,
@@ -30,6 +44,7 @@ impl<'a> TuiState<'a> {
     pub fn new() -> Self {
         TuiState {
             processing: true,
+            reset_statistics: false,
             filter_widget: FilterWidget::new(),
             sections: vec![
                 CustomWidget::Drop(DropWidget::new()),
@@ -39,10 +54,17 @@ impl<'a> TuiState<'a> {
                 CustomWidget::Tamper(TamperWidget::new()),
                 CustomWidget::Duplicate(DuplicateWidget::new()),
                 CustomWidget::Bandwidth(BandwidthWidget::new()),
+                CustomWidget::Dns(DnsWidget::new()),
+                CustomWidget::Topology(TopologyWidget::new()),
+                CustomWidget::Trace(TraceWidget::new()),
             ],
             selected: 0,
             interacting: None,
             logs_widget: LogsWidget::new(),
+            graphs_widget: GraphsWidget::new(),
+            inspector_widget: InspectorWidget::new(),
+            config_widget: ConfigWidget::new(),
+            runtime_options: RuntimeOptions::default(),
             focused: LayoutSection::Main,
         }
     }
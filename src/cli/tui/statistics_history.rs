@@ -0,0 +1,134 @@
+use crate::network::modules::stats::StatisticsSnapshot;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// A rolling window of samples taken from the [`StatisticsSnapshot`]s at a fixed interval,
+/// used to draw the graphs in the TUI.
+pub struct StatisticsHistory {
+    capacity: usize,
+    sample_interval: Duration,
+    last_sample: Option<(Instant, usize, usize)>,
+    pub packets_in: VecDeque<f64>,
+    pub packets_out: VecDeque<f64>,
+    pub drop_rate: VecDeque<f64>,
+    pub delay_queue: VecDeque<f64>,
+    pub reorder_heap: VecDeque<f64>,
+    pub throughput: VecDeque<f64>,
+}
+
+impl Default for StatisticsHistory {
+    fn default() -> Self {
+        Self::new(120, Duration::from_millis(250))
+    }
+}
+
+impl StatisticsHistory {
+    /// Creates a history keeping the last `capacity` samples taken every `sample_interval`.
+    pub fn new(capacity: usize, sample_interval: Duration) -> Self {
+        StatisticsHistory {
+            capacity,
+            sample_interval,
+            last_sample: None,
+            packets_in: VecDeque::with_capacity(capacity),
+            packets_out: VecDeque::with_capacity(capacity),
+            drop_rate: VecDeque::with_capacity(capacity),
+            delay_queue: VecDeque::with_capacity(capacity),
+            reorder_heap: VecDeque::with_capacity(capacity),
+            throughput: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The time span covered by a full history.
+    pub fn window(&self) -> Duration {
+        self.sample_interval * self.capacity as u32
+    }
+
+    /// Takes a sample if the sample interval has elapsed since the previous one.
+    pub fn sample(&mut self, stats: &StatisticsSnapshot) {
+        self.sample_at(Instant::now(), stats);
+    }
+
+    fn sample_at(&mut self, now: Instant, stats: &StatisticsSnapshot) {
+        let (received, sent) = (stats.received_packets, stats.sent_packets);
+        let (last_time, last_received, last_sent) = match self.last_sample {
+            Some(last_sample) => last_sample,
+            None => {
+                // The first call only establishes the baseline for the packet rates
+                self.last_sample = Some((now, received, sent));
+                return;
+            }
+        };
+        let elapsed = now.duration_since(last_time);
+        if elapsed < self.sample_interval {
+            return;
+        }
+        self.last_sample = Some((now, received, sent));
+
+        let seconds = elapsed.as_secs_f64();
+        let rate = |current: usize, last: usize| current.saturating_sub(last) as f64 / seconds;
+        let capacity = self.capacity;
+        let push = |series: &mut VecDeque<f64>, value: f64| {
+            if series.len() == capacity {
+                series.pop_front();
+            }
+            series.push_back(value);
+        };
+        push(&mut self.packets_in, rate(received, last_received));
+        push(&mut self.packets_out, rate(sent, last_sent));
+        push(&mut self.drop_rate, stats.drop_stats.recent_drop_rate);
+        push(
+            &mut self.delay_queue,
+            stats.delay_stats.delayed_package_count as f64,
+        );
+        push(
+            &mut self.reorder_heap,
+            stats.reorder_stats.delayed_packets as f64,
+        );
+        push(
+            &mut self.throughput,
+            stats.bandwidth_stats.recent_throughput,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_samples_packet_rates() {
+        let mut stats = StatisticsSnapshot::default();
+        let mut history = StatisticsHistory::new(3, Duration::from_millis(500));
+        let start = Instant::now();
+
+        history.sample_at(start, &stats);
+        assert!(history.packets_in.is_empty());
+
+        stats.received_packets += 100;
+        stats.sent_packets += 50;
+        history.sample_at(start + Duration::from_millis(100), &stats);
+        assert!(history.packets_in.is_empty());
+
+        history.sample_at(start + Duration::from_secs(1), &stats);
+        assert_eq!(history.packets_in, [100.0]);
+        assert_eq!(history.packets_out, [50.0]);
+    }
+
+    #[test]
+    fn test_keeps_rolling_window() {
+        let stats = StatisticsSnapshot::default();
+        let mut history = StatisticsHistory::new(3, Duration::from_secs(1));
+        let start = Instant::now();
+
+        for second in 0..10 {
+            history.sample_at(start + Duration::from_secs(second), &stats);
+        }
+        assert_eq!(history.packets_in.len(), 3);
+        assert_eq!(history.throughput.len(), 3);
+        assert_eq!(history.window(), Duration::from_secs(3));
+    }
+}
@@ -12,13 +12,22 @@ pub fn ui(frame: &mut Frame, state: &mut TuiState) {
     update_focus(state);
     let (header_area, middle_area, footer_area) = setup_layout(frame);
     let (main_area, log_area) = arrange_middle_area(state, middle_area);
+    let [main_area, inspector_area, graphs_area] = Layout::vertical([
+        Constraint::Min(0),
+        Constraint::Length(if state.inspector_widget.open { 14 } else { 1 }),
+        Constraint::Length(if state.graphs_widget.open { 12 } else { 1 }),
+    ])
+    .areas(main_area);
 
     let [filter_area, start_stop_toggle_area] =
         Layout::horizontal([Constraint::Fill(1), Constraint::Max(8)]).areas(header_area);
     render_start_stop_toggle(frame, state, filter_area, start_stop_toggle_area);
     render_sections(frame, state, main_area);
+    frame.render_widget(&mut state.inspector_widget, inspector_area);
+    frame.render_widget(&mut state.graphs_widget, graphs_area);
     frame.render_widget(&mut state.logs_widget, log_area);
     render_keybindings(frame, state, footer_area);
+    frame.render_widget(&mut state.config_widget, frame.area());
 }
 
 #[derive(PartialEq)]
@@ -26,13 +35,19 @@ pub enum LayoutSection {
     Filter,
     Main,
     Logging,
+    Inspector,
+    Config,
 }
 
 fn update_focus(state: &mut TuiState) {
-    if state.filter_widget.inputting {
+    if state.config_widget.is_open() {
+        state.focused = LayoutSection::Config;
+    } else if state.filter_widget.inputting {
         state.focused = LayoutSection::Filter;
     } else if state.logs_widget.focused {
         state.focused = LayoutSection::Logging;
+    } else if state.inspector_widget.focused {
+        state.focused = LayoutSection::Inspector;
     } else {
         state.focused = LayoutSection::Main;
     }
@@ -132,7 +147,7 @@ fn render_sections(frame: &mut Frame, state: &mut TuiState, main_area: Rect) {
         })
         .collect();
 
-    let section_areas: [Rect; 7] = Layout::vertical(constraints).areas(available_rect);
+    let section_areas = Layout::vertical(constraints).split(available_rect);
 
     let mut main_block =
         Block::roundedt("Main").title_bottom(Line::from("This is the main area").right_aligned());
@@ -140,7 +155,8 @@ fn render_sections(frame: &mut Frame, state: &mut TuiState, main_area: Rect) {
     frame.render_widget(main_block, main_area);
 
     for (i, option) in state.sections.iter_mut().enumerate() {
-        let mut area_block = Block::rounded().title(format!("[{}]-{}", i + 1, option.name()));
+        let mut area_block =
+            Block::rounded().title(format!("[{}]-{}", (i + 1) % 10, option.name()));
         if !option.is_active() {
             area_block = area_block.fg(Color::DarkGray);
         }
@@ -171,7 +187,9 @@ fn render_sections(frame: &mut Frame, state: &mut TuiState, main_area: Rect) {
 }
 
 fn render_keybindings(frame: &mut Frame, state: &mut TuiState, key_bind_area: Rect) {
-    let mut keybinds = "Quit: q | Toggle: Space | Navigation: Up and Down".to_string();
+    let mut keybinds =
+        "Quit: q | Toggle: Space | Navigation: Up and Down | Reset stats: r | Save: s | Load: o | Inspector: i | Graphs: g | Logs: l"
+            .to_string();
     match state.focused {
         LayoutSection::Filter => {
             keybinds = state.filter_widget.key_bindings();
@@ -184,6 +202,12 @@ fn render_keybindings(frame: &mut Frame, state: &mut TuiState, key_bind_area: Re
         LayoutSection::Logging => {
             keybinds = state.logs_widget.key_bindings();
         }
+        LayoutSection::Inspector => {
+            keybinds = state.inspector_widget.key_bindings();
+        }
+        LayoutSection::Config => {
+            keybinds = state.config_widget.key_bindings();
+        }
     }
 
     frame.render_widget(Paragraph::new(keybinds).style(Color::Cyan), key_bind_area)
@@ -1,9 +1,11 @@
+use crate::cli::settings::content_match::ContentMatchOptions;
 use crate::cli::tui::traits::{DisplayName, HandleInput, IsActive, KeyBindings};
 use crate::cli::tui::widgets::utils::block_ext::RoundedBlockExt;
 use crate::cli::tui::widgets::utils::style_textarea_based_on_validation;
 use crate::cli::tui::widgets::utils::textarea_ext::TextAreaExt;
 use crate::cli::tui::widgets::utils::textarea_parsing::ParseFromTextArea;
-use crate::network::modules::stats::bandwidth_stats::BandwidthStats;
+use crate::network::modules::stats::bandwidth_stats::BandwidthSnapshot;
+use crate::network::types::bit_rate::BitRate;
 use ratatui::buffer::Buffer;
 use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
 use ratatui::layout::{Constraint, Layout, Margin, Rect};
@@ -16,7 +18,13 @@ pub struct BandwidthWidget<'a> {
     limit_text_area: TextArea<'a>,
     is_active: bool,
     interacting: bool,
-    pub limit: Result<usize, String>,
+    pub content_match: Option<ContentMatchOptions>,
+    /// Limit of both directions, `None` while the text area is empty
+    pub limit: Result<Option<BitRate>, String>,
+    /// Per-direction limits and overhead from the settings, which have no text area
+    pub upload: Option<BitRate>,
+    pub download: Option<BitRate>,
+    pub overhead: usize,
     throughput: f64,
     stored_packet_count: usize,
 }
@@ -34,19 +42,24 @@ impl BandwidthWidget<'_> {
             limit_text_area: TextArea::default(),
             is_active: false,
             interacting: false,
-            limit: Ok(0),
+            content_match: None,
+            limit: Ok(None),
+            upload: None,
+            download: None,
+            overhead: 0,
             throughput: 0.0,
             stored_packet_count: 0,
         }
     }
 
-    pub fn set_limit(&mut self, limit: usize) {
-        self.limit_text_area.set_text(&limit.to_string());
+    pub fn set_limit(&mut self, limit: Option<BitRate>) {
+        let text = limit.map(|limit| limit.to_string()).unwrap_or_default();
+        self.limit_text_area.set_text(&text);
         self.limit = Ok(limit);
     }
 
-    pub(crate) fn update_data(&mut self, stats: &BandwidthStats) {
-        self.throughput = stats.recent_throughput();
+    pub(crate) fn update_data(&mut self, stats: &BandwidthSnapshot) {
+        self.throughput = stats.recent_throughput;
         self.stored_packet_count = stats.storage_packet_count;
     }
 }
@@ -64,7 +77,11 @@ impl HandleInput for BandwidthWidget<'_> {
                 return false;
             }
             if self.limit_text_area.input(key) {
-                self.limit = usize::parse_from_text_area(&self.limit_text_area);
+                self.limit = if self.limit_text_area.lines()[0].trim().is_empty() {
+                    Ok(None)
+                } else {
+                    BitRate::parse_from_text_area(&self.limit_text_area).map(Some)
+                };
             }
             return true;
         }
@@ -108,10 +125,10 @@ impl Widget for &mut BandwidthWidget<'_> {
             ));
 
         self.limit_text_area.set_cursor_visibility(self.interacting);
-        self.limit_text_area.set_dim_placeholder("No limit");
+        self.limit_text_area.set_dim_placeholder("e.g. 2Mbps");
         self.limit_text_area.set_cursor_line_style(Style::default());
         self.limit_text_area
-            .set_block(Block::roundedt("KBps Limit").highlight_if(self.interacting));
+            .set_block(Block::roundedt("Limit").highlight_if(self.interacting));
         if !self.limit_text_area.lines()[0].is_empty() {
             style_textarea_based_on_validation(&mut self.limit_text_area, &self.limit);
         }
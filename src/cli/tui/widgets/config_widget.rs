@@ -0,0 +1,262 @@
+use crate::cli::config::config_options::ConfigOptions;
+use crate::cli::tui::traits::KeyBindings;
+use crate::cli::tui::widgets::utils::block_ext::RoundedBlockExt;
+use crate::cli::tui::widgets::utils::style_textarea_based_on_validation;
+use crate::cli::tui::widgets::utils::textarea_ext::TextAreaExt;
+use log::error;
+use ratatui::buffer::Buffer;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Margin, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::widgets::{Block, Clear, List, ListItem, Paragraph, Widget};
+use tui_textarea::TextArea;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigDialog {
+    Closed,
+    Save,
+    Load,
+}
+
+/// Action confirmed in the config dialog, carried out by the TUI loop.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigRequest {
+    Save(String),
+    Load(String),
+}
+
+pub struct ConfigWidget<'a> {
+    pub(crate) dialog: ConfigDialog,
+    name_text_area: TextArea<'a>,
+    pub name: Result<String, String>,
+    configs: Vec<String>,
+    selected: usize,
+    request: Option<ConfigRequest>,
+}
+
+impl Default for ConfigWidget<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConfigWidget<'_> {
+    pub fn new() -> Self {
+        ConfigWidget {
+            dialog: ConfigDialog::Closed,
+            name_text_area: TextArea::default(),
+            name: Err("Name cannot be empty".to_string()),
+            configs: vec![],
+            selected: 0,
+            request: None,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.dialog != ConfigDialog::Closed
+    }
+
+    /// Returns the confirmed action, if any, leaving none behind.
+    pub fn take_request(&mut self) -> Option<ConfigRequest> {
+        self.request.take()
+    }
+
+    pub fn input(&mut self, key: KeyEvent) {
+        match self.dialog {
+            ConfigDialog::Closed => match key.code {
+                KeyCode::Char('s') => self.open_save_dialog(),
+                KeyCode::Char('o') => self.open_load_dialog(),
+                _ => {}
+            },
+            ConfigDialog::Save => match key.code {
+                KeyCode::Esc => self.dialog = ConfigDialog::Closed,
+                KeyCode::Enter => {
+                    if let Ok(name) = &self.name {
+                        self.request = Some(ConfigRequest::Save(name.clone()));
+                        self.dialog = ConfigDialog::Closed;
+                    }
+                }
+                _ => {
+                    if self.name_text_area.input(key) {
+                        self.name = parse_config_name(&self.name_text_area.lines()[0]);
+                    }
+                }
+            },
+            ConfigDialog::Load => match key.code {
+                KeyCode::Esc => self.dialog = ConfigDialog::Closed,
+                KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+                KeyCode::Down if self.selected + 1 < self.configs.len() => self.selected += 1,
+                KeyCode::Enter => {
+                    if let Some(name) = self.configs.get(self.selected) {
+                        self.request = Some(ConfigRequest::Load(name.clone()));
+                        self.dialog = ConfigDialog::Closed;
+                    }
+                }
+                _ => {}
+            },
+        }
+    }
+
+    fn open_save_dialog(&mut self) {
+        self.name_text_area.set_text("");
+        self.name = parse_config_name("");
+        self.dialog = ConfigDialog::Save;
+    }
+
+    fn open_load_dialog(&mut self) {
+        self.configs = match ConfigOptions::list_all_configs() {
+            Ok(mut configs) => {
+                configs.sort();
+                configs
+            }
+            Err(e) => {
+                error!("Failed to list configs: {}", e);
+                vec![]
+            }
+        };
+        self.selected = 0;
+        self.dialog = ConfigDialog::Load;
+    }
+
+    fn render_save_dialog(&mut self, area: Rect, buf: &mut Buffer) {
+        let [name_area, hint_area] =
+            Layout::vertical([Constraint::Length(3), Constraint::Length(1)]).areas(area);
+        self.name_text_area.set_cursor_line_style(Style::default());
+        self.name_text_area.set_dim_placeholder("my-config");
+        self.name_text_area
+            .set_block(Block::roundedt("Name").highlight_if(true));
+        if !self.name_text_area.lines()[0].is_empty() {
+            style_textarea_based_on_validation(&mut self.name_text_area, &self.name);
+        }
+        self.name_text_area.render(name_area, buf);
+        Paragraph::new("Saves the current settings and filter. Existing files are replaced.")
+            .fg(Color::DarkGray)
+            .render(hint_area, buf);
+    }
+
+    fn render_load_dialog(&self, area: Rect, buf: &mut Buffer) {
+        if self.configs.is_empty() {
+            Paragraph::new("No configuration files found.")
+                .fg(Color::DarkGray)
+                .render(area, buf);
+            return;
+        }
+        let visible_rows = area.height as usize;
+        let start = (self.selected + 1).saturating_sub(visible_rows);
+        let items: Vec<ListItem> = self
+            .configs
+            .iter()
+            .enumerate()
+            .skip(start)
+            .map(|(i, name)| {
+                let item = ListItem::new(name.as_str());
+                if i == self.selected {
+                    item.add_modifier(Modifier::REVERSED)
+                } else {
+                    item
+                }
+            })
+            .collect();
+        List::new(items).render(area, buf);
+    }
+}
+
+/// Validates a configuration name entered in the save dialog.
+fn parse_config_name(text: &str) -> Result<String, String> {
+    let name = text.trim();
+    if name.is_empty() {
+        return Err("Name cannot be empty".to_string());
+    }
+    if name.contains(['/', '\\']) {
+        return Err("Name cannot contain path separators".to_string());
+    }
+    Ok(name.to_string())
+}
+
+impl KeyBindings for ConfigWidget<'_> {
+    fn key_bindings(&self) -> String {
+        match self.dialog {
+            ConfigDialog::Load => "Cancel: Esc | Navigation: Up and Down | Load: Enter",
+            _ => "Cancel: Esc | Save: Enter",
+        }
+        .to_string()
+    }
+}
+
+impl Widget for &mut ConfigWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        let title = match self.dialog {
+            ConfigDialog::Closed => return,
+            ConfigDialog::Save => "Save config",
+            ConfigDialog::Load => "Load config",
+        };
+        let [_, popup_area, _] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(12),
+            Constraint::Fill(1),
+        ])
+        .areas(area);
+        let [_, popup_area, _] = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(60),
+            Constraint::Fill(1),
+        ])
+        .areas(popup_area);
+
+        Clear.render(popup_area, buf);
+        Block::roundedt(title)
+            .highlight_if(true)
+            .render(popup_area, buf);
+        let inner_area = popup_area.inner(Margin {
+            horizontal: 2,
+            vertical: 1,
+        });
+        match self.dialog {
+            ConfigDialog::Save => self.render_save_dialog(inner_area, buf),
+            ConfigDialog::Load => self.render_load_dialog(inner_area, buf),
+            ConfigDialog::Closed => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::crossterm::event::KeyModifiers;
+
+    fn press(widget: &mut ConfigWidget, code: KeyCode) {
+        widget.input(KeyEvent::new(code, KeyModifiers::NONE));
+    }
+
+    #[test]
+    fn test_save_dialog_requests_save() {
+        let mut widget = ConfigWidget::new();
+        press(&mut widget, KeyCode::Char('s'));
+        assert_eq!(widget.dialog, ConfigDialog::Save);
+
+        // An empty name cannot be confirmed
+        press(&mut widget, KeyCode::Enter);
+        assert!(widget.is_open());
+
+        for c in "lossy".chars() {
+            press(&mut widget, KeyCode::Char(c));
+        }
+        press(&mut widget, KeyCode::Enter);
+        assert!(!widget.is_open());
+        assert_eq!(
+            widget.take_request(),
+            Some(ConfigRequest::Save("lossy".to_string()))
+        );
+        assert_eq!(widget.take_request(), None);
+    }
+
+    #[test]
+    fn test_parse_config_name() {
+        assert_eq!(parse_config_name(" lossy "), Ok("lossy".to_string()));
+        assert!(parse_config_name("  ").is_err());
+        assert!(parse_config_name("../lossy").is_err());
+    }
+}
@@ -1,11 +1,14 @@
 use crate::cli::tui::traits::{DisplayName, HandleInput, IsActive, KeyBindings};
 use crate::cli::tui::widgets::bandwidth_widget::BandwidthWidget;
 use crate::cli::tui::widgets::delay_widget::DelayWidget;
+use crate::cli::tui::widgets::dns_widget::DnsWidget;
 use crate::cli::tui::widgets::drop_widget::DropWidget;
 use crate::cli::tui::widgets::duplicate_widget::DuplicateWidget;
 use crate::cli::tui::widgets::reorder_widget::ReorderWidget;
 use crate::cli::tui::widgets::tamper_widget::TamperWidget;
 use crate::cli::tui::widgets::throttle_widget::ThrottleWidget;
+use crate::cli::tui::widgets::topology_widget::TopologyWidget;
+use crate::cli::tui::widgets::trace_widget::TraceWidget;
 use ratatui::buffer::Buffer;
 use ratatui::crossterm::event::KeyEvent;
 use ratatui::layout::Rect;
@@ -19,6 +22,9 @@ pub enum CustomWidget<'a> {
     Tamper(TamperWidget<'a>),
     Duplicate(DuplicateWidget<'a>),
     Bandwidth(BandwidthWidget<'a>),
+    Dns(DnsWidget<'a>),
+    Topology(TopologyWidget<'a>),
+    Trace(TraceWidget<'a>),
 }
 
 macro_rules! impl_widget_traits_for_enum {
@@ -79,5 +85,8 @@ impl_widget_traits_for_enum!(
     Reorder,
     Tamper,
     Duplicate,
-    Bandwidth
+    Bandwidth,
+    Dns,
+    Topology,
+    Trace
 );
@@ -1,14 +1,18 @@
+use crate::cli::settings::content_match::ContentMatchOptions;
 use crate::cli::tui::traits::{DisplayName, HandleInput, IsActive, KeyBindings};
 use crate::cli::tui::widgets::utils::block_ext::RoundedBlockExt;
 use crate::cli::tui::widgets::utils::style_textarea_based_on_validation;
 use crate::cli::tui::widgets::utils::textarea_ext::TextAreaExt;
 use crate::cli::tui::widgets::utils::textarea_parsing::ParseFromTextArea;
-use crate::network::modules::stats::delay_stats::DelayStats;
+use crate::network::modules::stats::delay_stats::DelaySnapshot;
+use crate::network::modules::stats::util::histogram::LatencyPercentiles;
+use crate::network::types::duration_spec::DurationSpec;
 use ratatui::buffer::Buffer;
 use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
 use ratatui::layout::{Constraint, Layout, Margin, Rect};
 use ratatui::style::Style;
 use ratatui::widgets::{Block, Paragraph, Widget};
+use std::time::Duration;
 use tui_textarea::TextArea;
 
 pub struct DelayWidget<'a> {
@@ -16,8 +20,11 @@ pub struct DelayWidget<'a> {
     delay_duration: TextArea<'a>,
     is_active: bool,
     interacting: bool,
-    pub delay: Result<u64, String>,
+    pub content_match: Option<ContentMatchOptions>,
+    pub delay: Result<DurationSpec, String>,
     delayed_packet_count: usize,
+    /// Time the delayed packets actually spent inside fumble
+    achieved: Option<LatencyPercentiles>,
 }
 
 impl Default for DelayWidget<'_> {
@@ -33,18 +40,21 @@ impl DelayWidget<'_> {
             delay_duration: TextArea::default(),
             is_active: false,
             interacting: false,
-            delay: Ok(0),
+            content_match: None,
+            delay: Ok(DurationSpec::default()),
             delayed_packet_count: 0,
+            achieved: None,
         }
     }
 
-    pub fn set_delay(&mut self, duration_ms: u64) {
-        self.delay_duration.set_text(&duration_ms.to_string());
-        self.delay = Ok(duration_ms);
+    pub fn set_delay(&mut self, duration: DurationSpec) {
+        self.delay_duration.set_text(&duration.to_string());
+        self.delay = Ok(duration);
     }
 
-    pub fn update_data(&mut self, stats: &DelayStats) {
+    pub fn update_data(&mut self, stats: &DelaySnapshot, latency: Option<&LatencyPercentiles>) {
         self.delayed_packet_count = stats.delayed_package_count;
+        self.achieved = latency.copied();
     }
 }
 
@@ -61,7 +71,7 @@ impl HandleInput for DelayWidget<'_> {
                 return false;
             }
             if self.delay_duration.input(key) {
-                self.delay = u64::parse_from_text_area(&self.delay_duration);
+                self.delay = DurationSpec::parse_from_text_area(&self.delay_duration);
             }
             return true;
         }
@@ -105,7 +115,7 @@ impl Widget for &mut DelayWidget<'_> {
             ));
 
         self.delay_duration.set_cursor_visibility(self.interacting);
-        self.delay_duration.set_dim_placeholder("50");
+        self.delay_duration.set_dim_placeholder("50ms");
         self.delay_duration.set_cursor_line_style(Style::default());
         self.delay_duration
             .set_block(Block::roundedt("Duration").highlight_if(self.interacting));
@@ -114,10 +124,25 @@ impl Widget for &mut DelayWidget<'_> {
         }
         self.delay_duration.render(delay_duration_area, buf);
 
-        let [delay_count_info, _excess_info] =
+        let [delay_count_info, achieved_info] =
             Layout::horizontal([Constraint::Max(30), Constraint::Fill(1)]).areas(info_area);
         Paragraph::new(format!("{} packets", self.delayed_packet_count))
             .block(Block::bordered().title("Delayed packets"))
             .render(delay_count_info, buf);
+        let achieved = match &self.achieved {
+            Some(latency) => {
+                let millis = |duration: Duration| duration.as_secs_f64() * 1000.0;
+                format!(
+                    "p50 {:.1}ms, p99 {:.1}ms, max {:.1}ms",
+                    millis(latency.p50),
+                    millis(latency.p99),
+                    millis(latency.max)
+                )
+            }
+            None => "-".to_string(),
+        };
+        Paragraph::new(achieved)
+            .block(Block::bordered().title("Achieved delay"))
+            .render(achieved_info, buf);
     }
 }
\ No newline at end of file
@@ -0,0 +1,225 @@
+use crate::cli::settings::content_match::ContentMatchOptions;
+use crate::cli::settings::dns::DnsAction;
+use crate::cli::tui::traits::{DisplayName, HandleInput, IsActive, KeyBindings};
+use crate::cli::tui::widgets::utils::block_ext::RoundedBlockExt;
+use crate::cli::tui::widgets::utils::style_textarea_based_on_validation;
+use crate::cli::tui::widgets::utils::textarea_ext::TextAreaExt;
+use crate::cli::tui::widgets::utils::textarea_parsing::ParseFromTextArea;
+use crate::network::modules::stats::dns_stats::DnsSnapshot;
+use crate::network::types::duration_spec::DurationSpec;
+use crate::network::types::name_pattern::NamePattern;
+use ratatui::buffer::Buffer;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Margin, Rect};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::Span;
+use ratatui::widgets::{Block, Paragraph, Widget};
+use tui_textarea::TextArea;
+
+pub struct DnsWidget<'a> {
+    title: String,
+    names_text_area: TextArea<'a>,
+    delay_text_area: TextArea<'a>,
+    pub action: DnsAction,
+    is_active: bool,
+    interacting: bool,
+    pub content_match: Option<ContentMatchOptions>,
+    pub names: Result<Vec<NamePattern>, String>,
+    pub delay: Result<DurationSpec, String>,
+    selected: usize,
+    delayed_count: usize,
+    total_affected: usize,
+    most_affected: Vec<(String, usize, usize)>,
+}
+
+impl Default for DnsWidget<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DnsWidget<'_> {
+    pub fn new() -> Self {
+        DnsWidget {
+            title: "DNS".to_string(),
+            names_text_area: TextArea::default(),
+            delay_text_area: TextArea::default(),
+            action: DnsAction::Delay,
+            is_active: false,
+            interacting: false,
+            content_match: None,
+            names: Ok(Vec::new()),
+            delay: Ok(DurationSpec::from_millis(500)),
+            selected: 0,
+            delayed_count: 0,
+            total_affected: 0,
+            most_affected: Vec::new(),
+        }
+    }
+
+    pub fn set_names(&mut self, names: &[NamePattern]) {
+        let text = names
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.names_text_area.set_text(&text);
+        self.names = Ok(names.to_vec());
+    }
+
+    pub fn set_delay(&mut self, delay: DurationSpec) {
+        self.delay_text_area.set_text(&delay.to_string());
+        self.delay = Ok(delay);
+    }
+
+    pub fn update_data(&mut self, stats: &DnsSnapshot) {
+        self.delayed_count = stats.delayed_count;
+        self.total_affected = stats.total_affected();
+        self.most_affected = stats
+            .most_affected(3)
+            .into_iter()
+            .map(|(name, name_stats)| (name.to_string(), name_stats.affected, name_stats.queries))
+            .collect();
+    }
+}
+
+/// Parses a comma separated list of name patterns. An empty list matches all queries.
+fn parse_names(text_area: &TextArea) -> Result<Vec<NamePattern>, String> {
+    let line = text_area.lines().first().map(String::as_str).unwrap_or("");
+    line.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(NamePattern::new)
+        .collect()
+}
+
+impl HandleInput for DnsWidget<'_> {
+    fn handle_input(&mut self, key: KeyEvent) -> bool {
+        if !self.interacting {
+            if key.code == KeyCode::Enter && key.kind == KeyEventKind::Press {
+                self.interacting = true;
+                return true;
+            }
+        } else {
+            if let KeyCode::Enter | KeyCode::Esc = key.code {
+                self.interacting = false;
+                return false;
+            }
+            if key.code == KeyCode::Down && self.selected < 2 {
+                self.selected += 1;
+            }
+            if key.code == KeyCode::Up && self.selected > 0 {
+                self.selected -= 1;
+            }
+            match self.selected {
+                0 if self.names_text_area.input(key) => {
+                    self.names = parse_names(&self.names_text_area);
+                }
+                1 if key.code == KeyCode::Char(' ') && key.kind == KeyEventKind::Press => {
+                    self.action = self.action.next();
+                }
+                2 if self.delay_text_area.input(key) => {
+                    self.delay = DurationSpec::parse_from_text_area(&self.delay_text_area);
+                }
+                _ => {}
+            }
+
+            return true;
+        }
+        false
+    }
+}
+
+impl DisplayName for DnsWidget<'_> {
+    fn name(&self) -> &str {
+        &self.title
+    }
+}
+
+impl KeyBindings for DnsWidget<'_> {
+    fn key_bindings(&self) -> String {
+        "Exit: Esc | Navigation: Up and Down | Cycle action: Space".to_string()
+    }
+}
+
+impl IsActive for DnsWidget<'_> {
+    fn is_active(&self) -> bool {
+        self.is_active
+    }
+
+    fn set_active(&mut self, state: bool) {
+        self.is_active = state;
+    }
+}
+
+impl Widget for &mut DnsWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        let [names_area, action_area, delay_area, info_area] = Layout::horizontal([
+            Constraint::Max(30),
+            Constraint::Max(12),
+            Constraint::Max(10),
+            Constraint::Min(25),
+        ])
+        .areas(area.inner(Margin {
+            horizontal: 1,
+            vertical: 1,
+        }));
+
+        self.names_text_area
+            .set_cursor_visibility(self.interacting && self.selected == 0);
+        self.names_text_area.set_dim_placeholder("*.example.com");
+        self.names_text_area.set_cursor_line_style(Style::default());
+        self.names_text_area.set_block(
+            Block::roundedt("Names").highlight_if(self.interacting && self.selected == 0),
+        );
+        if !self.names_text_area.lines()[0].is_empty() {
+            style_textarea_based_on_validation(&mut self.names_text_area, &self.names);
+        }
+        self.names_text_area.render(names_area, buf);
+
+        let mut action_span = Span::from(self.action.to_string());
+        if self.selected == 1 && self.interacting {
+            action_span = action_span.add_modifier(Modifier::RAPID_BLINK);
+        }
+        Paragraph::new(action_span)
+            .block(Block::roundedt("Action").highlight_if(self.interacting && self.selected == 1))
+            .render(action_area, buf);
+
+        self.delay_text_area
+            .set_cursor_visibility(self.interacting && self.selected == 2);
+        self.delay_text_area.set_dim_placeholder("500ms");
+        self.delay_text_area.set_cursor_line_style(Style::default());
+        self.delay_text_area.set_block(
+            Block::roundedt("Delay").highlight_if(self.interacting && self.selected == 2),
+        );
+        if !self.delay_text_area.lines()[0].is_empty() {
+            style_textarea_based_on_validation(&mut self.delay_text_area, &self.delay);
+        }
+        self.delay_text_area.render(delay_area, buf);
+
+        let [delayed_info, affected_info, names_info] = Layout::horizontal([
+            Constraint::Max(10),
+            Constraint::Max(10),
+            Constraint::Fill(1),
+        ])
+        .areas(info_area);
+        Paragraph::new(format!("{}", self.delayed_count))
+            .block(Block::bordered().title("Delayed"))
+            .render(delayed_info, buf);
+        Paragraph::new(format!("{}", self.total_affected))
+            .block(Block::bordered().title("Affected"))
+            .render(affected_info, buf);
+        let most_affected = self
+            .most_affected
+            .iter()
+            .map(|(name, affected, queries)| format!("{} {}/{}", name, affected, queries))
+            .collect::<Vec<_>>()
+            .join(" | ");
+        Paragraph::new(most_affected)
+            .block(Block::bordered().title("Affected/Queries by name"))
+            .render(names_info, buf);
+    }
+}
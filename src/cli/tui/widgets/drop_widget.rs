@@ -1,24 +1,33 @@
+use crate::cli::settings::content_match::ContentMatchOptions;
+use crate::cli::settings::drop::DropCurve;
 use crate::cli::tui::traits::{DisplayName, HandleInput, IsActive, KeyBindings};
 use crate::cli::tui::widgets::utils::block_ext::RoundedBlockExt;
 use crate::cli::tui::widgets::utils::style_textarea_based_on_validation;
 use crate::cli::tui::widgets::utils::textarea_ext::TextAreaExt;
 use crate::cli::tui::widgets::utils::textarea_parsing::ParseFromTextArea;
-use crate::network::modules::stats::drop_stats::DropStats;
+use crate::network::modules::stats::drop_stats::DropSnapshot;
 use crate::network::types::probability::Probability;
 use ratatui::buffer::Buffer;
 use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
 use ratatui::layout::{Constraint, Layout, Margin, Rect};
-use ratatui::style::Style;
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::Span;
 use ratatui::widgets::{Block, Paragraph, Widget};
 use tui_textarea::TextArea;
 
 pub struct DropWidget<'a> {
     title: String,
     probability_text_area: TextArea<'a>,
+    capacity_text_area: TextArea<'a>,
+    pub curve: DropCurve,
     is_active: bool,
     interacting: bool,
+    pub content_match: Option<ContentMatchOptions>,
     pub probability: Result<Probability, String>,
+    pub capacity: Result<Option<usize>, String>,
+    selected: usize,
     drop_rate: f64,
+    effective_probability: f64,
     dropped_packets: usize,
     total_packets: usize,
 }
@@ -34,10 +43,16 @@ impl DropWidget<'_> {
         DropWidget {
             title: "Drop".to_string(),
             probability_text_area: TextArea::default(),
+            capacity_text_area: TextArea::default(),
+            curve: DropCurve::Linear,
             is_active: false,
             interacting: false,
+            content_match: None,
             probability: Ok(Probability::default()),
+            capacity: Ok(None),
+            selected: 0,
             drop_rate: 0.0,
+            effective_probability: 0.0,
             dropped_packets: 0,
             total_packets: 0,
         }
@@ -49,13 +64,30 @@ impl DropWidget<'_> {
         self.probability = Ok(probability);
     }
 
-    pub fn update_data(&mut self, stats: &DropStats) {
-        self.drop_rate = stats.recent_drop_rate();
+    pub fn set_capacity(&mut self, capacity: Option<usize>) {
+        let text = capacity
+            .map(|capacity| capacity.to_string())
+            .unwrap_or_default();
+        self.capacity_text_area.set_text(&text);
+        self.capacity = Ok(capacity);
+    }
+
+    pub fn update_data(&mut self, stats: &DropSnapshot) {
+        self.drop_rate = stats.recent_drop_rate;
+        self.effective_probability = stats.effective_probability;
         self.dropped_packets = stats.total_dropped;
         self.total_packets = stats.total_packets;
     }
 }
 
+/// Parses the capacity in KB/s. An empty text area disables the congestion-based drop mode.
+fn parse_capacity(text_area: &TextArea) -> Result<Option<usize>, String> {
+    match text_area.lines().first().map(|line| line.trim()) {
+        None | Some("") => Ok(None),
+        Some(_) => usize::parse_from_text_area(text_area).map(Some),
+    }
+}
+
 impl HandleInput for DropWidget<'_> {
     fn handle_input(&mut self, key: KeyEvent) -> bool {
         if !self.interacting {
@@ -68,8 +100,24 @@ impl HandleInput for DropWidget<'_> {
                 self.interacting = false;
                 return false;
             }
-            if self.probability_text_area.input(key) {
-                self.probability = Probability::parse_from_text_area(&self.probability_text_area);
+            if key.code == KeyCode::Down && self.selected < 2 {
+                self.selected += 1;
+            }
+            if key.code == KeyCode::Up && self.selected > 0 {
+                self.selected -= 1;
+            }
+            match self.selected {
+                0 if self.probability_text_area.input(key) => {
+                    self.probability =
+                        Probability::parse_from_text_area(&self.probability_text_area);
+                }
+                1 if self.capacity_text_area.input(key) => {
+                    self.capacity = parse_capacity(&self.capacity_text_area);
+                }
+                2 if key.code == KeyCode::Char(' ') && key.kind == KeyEventKind::Press => {
+                    self.curve = self.curve.next();
+                }
+                _ => {}
             }
             return true;
         }
@@ -85,7 +133,7 @@ impl DisplayName for DropWidget<'_> {
 
 impl KeyBindings for DropWidget<'_> {
     fn key_bindings(&self) -> String {
-        "Exit: Esc".to_string()
+        "Exit: Esc | Navigation: Up and Down | Cycle curve: Space".to_string()
     }
 }
 
@@ -104,33 +152,63 @@ impl Widget for &mut DropWidget<'_> {
     where
         Self: Sized,
     {
-        let [drop_probability_area, info_area] =
-            Layout::horizontal([Constraint::Max(10), Constraint::Min(25)]).areas(area.inner(
-                Margin {
-                    horizontal: 1,
-                    vertical: 1,
-                },
-            ));
+        let [drop_probability_area, capacity_area, curve_area, info_area] = Layout::horizontal([
+            Constraint::Max(12),
+            Constraint::Max(15),
+            Constraint::Max(8),
+            Constraint::Min(25),
+        ])
+        .areas(area.inner(Margin {
+            horizontal: 1,
+            vertical: 1,
+        }));
 
         self.probability_text_area
-            .set_cursor_visibility(self.interacting);
+            .set_cursor_visibility(self.interacting && self.selected == 0);
         self.probability_text_area.set_dim_placeholder("0.1");
         self.probability_text_area
             .set_cursor_line_style(Style::default());
-        self.probability_text_area
-            .set_block(Block::roundedt("Probability").highlight_if(self.interacting));
+        self.probability_text_area.set_block(
+            Block::roundedt("Probability").highlight_if(self.interacting && self.selected == 0),
+        );
         if !self.probability_text_area.lines()[0].is_empty() {
             style_textarea_based_on_validation(&mut self.probability_text_area, &self.probability);
         }
         self.probability_text_area
             .render(drop_probability_area, buf);
 
-        let [drop_rate_info, drop_count_info, _excess_info] = Layout::horizontal([
-            Constraint::Max(12),
-            Constraint::Max(18),
-            Constraint::Fill(1),
-        ])
-        .areas(info_area);
+        self.capacity_text_area
+            .set_cursor_visibility(self.interacting && self.selected == 1);
+        self.capacity_text_area.set_dim_placeholder("off");
+        self.capacity_text_area
+            .set_cursor_line_style(Style::default());
+        self.capacity_text_area.set_block(
+            Block::roundedt("Capacity KB/s").highlight_if(self.interacting && self.selected == 1),
+        );
+        if !self.capacity_text_area.lines()[0].is_empty() {
+            style_textarea_based_on_validation(&mut self.capacity_text_area, &self.capacity);
+        }
+        self.capacity_text_area.render(capacity_area, buf);
+
+        let mut curve_span = Span::from(self.curve.to_string());
+        if self.selected == 2 && self.interacting {
+            curve_span = curve_span.add_modifier(Modifier::RAPID_BLINK);
+        }
+        Paragraph::new(curve_span)
+            .block(Block::roundedt("Curve").highlight_if(self.interacting && self.selected == 2))
+            .render(curve_area, buf);
+
+        let [effective_loss_info, drop_rate_info, drop_count_info, _excess_info] =
+            Layout::horizontal([
+                Constraint::Max(16),
+                Constraint::Max(12),
+                Constraint::Max(18),
+                Constraint::Fill(1),
+            ])
+            .areas(info_area);
+        Paragraph::new(format!("{:.2}%", self.effective_probability * 100.0))
+            .block(Block::bordered().title("Effective loss"))
+            .render(effective_loss_info, buf);
         Paragraph::new(format!("{:.2}%", self.drop_rate * 100.0))
             .block(Block::bordered().title("Drop rate"))
             .render(drop_rate_info, buf);
@@ -139,4 +217,4 @@ impl Widget for &mut DropWidget<'_> {
             .block(Block::bordered().title("Drop count"))
             .render(drop_count_info, buf);
     }
-}
\ No newline at end of file
+}
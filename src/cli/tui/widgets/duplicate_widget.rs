@@ -1,9 +1,10 @@
+use crate::cli::settings::content_match::ContentMatchOptions;
 use crate::cli::tui::traits::{DisplayName, HandleInput, IsActive, KeyBindings};
 use crate::cli::tui::widgets::utils::block_ext::RoundedBlockExt;
 use crate::cli::tui::widgets::utils::style_textarea_based_on_validation;
 use crate::cli::tui::widgets::utils::textarea_ext::TextAreaExt;
 use crate::cli::tui::widgets::utils::textarea_parsing::ParseFromTextArea;
-use crate::network::modules::stats::duplicate_stats::DuplicateStats;
+use crate::network::modules::stats::duplicate_stats::DuplicateSnapshot;
 use crate::network::types::probability::Probability;
 use ratatui::buffer::Buffer;
 use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
@@ -18,6 +19,7 @@ pub struct DuplicateWidget<'a> {
     duplicate_count_text_area: TextArea<'a>,
     is_active: bool,
     interacting: bool,
+    pub content_match: Option<ContentMatchOptions>,
     pub probability: Result<Probability, String>,
     pub duplicate_count: Result<usize, String>,
     selected: usize,
@@ -38,6 +40,7 @@ impl DuplicateWidget<'_> {
             duplicate_count_text_area: TextArea::default(),
             is_active: false,
             interacting: false,
+            content_match: None,
             probability: Ok(Probability::default()),
             duplicate_count: Ok(1),
             selected: 0,
@@ -57,8 +60,8 @@ impl DuplicateWidget<'_> {
         self.duplicate_count = Ok(duplicate_count);
     }
 
-    pub(crate) fn update_data(&mut self, stats: &DuplicateStats) {
-        self.duplication_multiplier = stats.recent_duplication_multiplier();
+    pub(crate) fn update_data(&mut self, stats: &DuplicateSnapshot) {
+        self.duplication_multiplier = stats.recent_duplication_multiplier;
     }
 }
 
@@ -0,0 +1,213 @@
+use crate::cli::tui::statistics_history::StatisticsHistory;
+use crate::network::modules::stats::latency_stats::LatencySnapshot;
+use crate::network::modules::stats::util::histogram::LatencyPercentiles;
+use crate::network::modules::stats::StatisticsSnapshot;
+use ratatui::buffer::Buffer;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Style};
+use ratatui::symbols::Marker;
+use ratatui::widgets::{
+    Axis, Block, Borders, Chart, Dataset, GraphType, Paragraph, Sparkline, Widget,
+};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+pub struct GraphsWidget {
+    pub(crate) open: bool,
+    pub history: StatisticsHistory,
+    latency: LatencySnapshot,
+}
+
+impl Default for GraphsWidget {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GraphsWidget {
+    pub fn new() -> Self {
+        GraphsWidget {
+            open: false,
+            history: StatisticsHistory::default(),
+            latency: LatencySnapshot::default(),
+        }
+    }
+
+    pub fn input(&mut self, key: KeyEvent) {
+        if KeyCode::Char('g') == key.code {
+            self.open = !self.open;
+        }
+    }
+
+    pub fn update_data(&mut self, stats: &StatisticsSnapshot) {
+        self.history.sample(stats);
+        self.latency = stats.latency_stats.clone();
+    }
+
+    fn render_packet_rates(&self, area: Rect, buf: &mut Buffer) {
+        let capacity = self.history.capacity();
+        let to_points = |series: &VecDeque<f64>| -> Vec<(f64, f64)> {
+            // Align the newest sample to the right edge of the chart
+            let offset = capacity - series.len();
+            series
+                .iter()
+                .enumerate()
+                .map(|(i, value)| ((offset + i) as f64, *value))
+                .collect()
+        };
+        let packets_in = to_points(&self.history.packets_in);
+        let packets_out = to_points(&self.history.packets_out);
+        let max_rate = self
+            .history
+            .packets_in
+            .iter()
+            .chain(self.history.packets_out.iter())
+            .fold(1.0f64, |max, value| max.max(*value));
+
+        let current = |series: &VecDeque<f64>| series.back().copied().unwrap_or(0.0);
+        let datasets = vec![
+            Dataset::default()
+                .name(format!("in {:.0}/s", current(&self.history.packets_in)))
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(Color::LightGreen))
+                .data(&packets_in),
+            Dataset::default()
+                .name(format!("out {:.0}/s", current(&self.history.packets_out)))
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(Color::LightBlue))
+                .data(&packets_out),
+        ];
+        let window = format!("-{}s", self.history.window().as_secs());
+        Chart::new(datasets)
+            .block(Block::bordered().title("Packets per second"))
+            .x_axis(
+                Axis::default()
+                    .bounds([0.0, capacity.saturating_sub(1) as f64])
+                    .labels([window.as_str(), "now"]),
+            )
+            .y_axis(
+                Axis::default()
+                    .bounds([0.0, max_rate])
+                    .labels(["0".to_string(), format!("{:.0}", max_rate)]),
+            )
+            .render(area, buf);
+    }
+}
+
+/// Width of the latency table, including its border.
+const LATENCY_WIDTH: u16 = 44;
+
+/// Renders the latency percentiles of all sent packets and of the packets each module held.
+fn render_latency(latency: &LatencySnapshot, area: Rect, buf: &mut Buffer) {
+    let millis = |duration: Duration| duration.as_secs_f64() * 1000.0;
+    let row = |name: &str, percentiles: &LatencyPercentiles| {
+        format!(
+            "{:<9}{:>8.1}{:>8.1}{:>8.1}{:>8.1}",
+            name,
+            millis(percentiles.p50),
+            millis(percentiles.p90),
+            millis(percentiles.p99),
+            millis(percentiles.max)
+        )
+    };
+    let mut lines = vec![
+        format!("{:<9}{:>8}{:>8}{:>8}{:>8}", "", "p50", "p90", "p99", "max"),
+        row("all", &latency.overall),
+    ];
+    lines.extend(
+        latency
+            .modules
+            .iter()
+            .map(|(module, percentiles)| row(module, percentiles)),
+    );
+    Paragraph::new(lines.join("\n"))
+        .block(Block::bordered().title("Latency (ms)"))
+        .render(area, buf);
+}
+
+/// Renders the most recent samples that fit in `area` as a sparkline, scaling values by `scale`
+/// since sparklines only display integers.
+fn render_sparkline(
+    title: String,
+    series: &VecDeque<f64>,
+    scale: f64,
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    let width = area.width.saturating_sub(2) as usize;
+    let data: Vec<u64> = series
+        .iter()
+        .skip(series.len().saturating_sub(width))
+        .map(|value| (value * scale).round() as u64)
+        .collect();
+    Sparkline::default()
+        .block(Block::bordered().title(title))
+        .style(Style::default().fg(Color::Cyan))
+        .data(&data)
+        .render(area, buf);
+}
+
+impl Widget for &mut GraphsWidget {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        if !self.open {
+            Block::bordered()
+                .borders(Borders::TOP)
+                .title("[G]-Graphs")
+                .render(area, buf);
+            return;
+        }
+
+        let [chart_area, latency_area, sparklines_area] = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(LATENCY_WIDTH),
+            Constraint::Fill(1),
+        ])
+        .areas(area);
+        self.render_packet_rates(chart_area, buf);
+        render_latency(&self.latency, latency_area, buf);
+
+        let [top_area, bottom_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Fill(1)]).areas(sparklines_area);
+        let [drop_area, throughput_area] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]).areas(top_area);
+        let [delay_area, reorder_area] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]).areas(bottom_area);
+
+        let history = &self.history;
+        let current = |series: &VecDeque<f64>| series.back().copied().unwrap_or(0.0);
+        render_sparkline(
+            format!("Drop rate {:.2}%", current(&history.drop_rate) * 100.0),
+            &history.drop_rate,
+            10_000.0,
+            drop_area,
+            buf,
+        );
+        render_sparkline(
+            format!("Throughput {:.2} KB/s", current(&history.throughput)),
+            &history.throughput,
+            1.0,
+            throughput_area,
+            buf,
+        );
+        render_sparkline(
+            format!("Delay queue {}", current(&history.delay_queue)),
+            &history.delay_queue,
+            1.0,
+            delay_area,
+            buf,
+        );
+        render_sparkline(
+            format!("Reorder heap {}", current(&history.reorder_heap)),
+            &history.reorder_heap,
+            1.0,
+            reorder_area,
+            buf,
+        );
+    }
+}
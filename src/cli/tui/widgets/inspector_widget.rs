@@ -0,0 +1,287 @@
+use crate::cli::tui::traits::KeyBindings;
+use crate::cli::tui::widgets::utils::block_ext::RoundedBlockExt;
+use crate::cli::tui::widgets::utils::printable_symbol;
+use crate::network::modules::stats::inspector_stats::InspectedPacket;
+use crate::network::modules::stats::PacketProcessingStatistics;
+use crate::network::packet::protocol;
+use crate::network::types::packet_actions::PacketActions;
+use ratatui::buffer::Buffer;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph, Row, Table, Widget};
+use std::collections::VecDeque;
+use std::net::IpAddr;
+
+pub struct InspectorWidget {
+    pub(crate) open: bool,
+    pub(crate) focused: bool,
+    pub(crate) paused: bool,
+    capacity: usize,
+    next_sequence: u64,
+    packets: VecDeque<InspectedPacket>,
+    /// Index of the selected packet; `None` follows the newest packet
+    selected: Option<usize>,
+}
+
+impl Default for InspectorWidget {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InspectorWidget {
+    pub fn new() -> Self {
+        InspectorWidget {
+            open: false,
+            focused: false,
+            paused: false,
+            capacity: 1000,
+            next_sequence: 0,
+            packets: VecDeque::new(),
+            selected: None,
+        }
+    }
+
+    pub fn input(&mut self, key: KeyEvent) {
+        if !self.focused {
+            if KeyCode::Char('i') == key.code {
+                self.open = true;
+                self.focused = true;
+            }
+            return;
+        }
+        match key.code {
+            KeyCode::Char('i') => {
+                self.open = false;
+                self.focused = false;
+            }
+            KeyCode::Esc => self.focused = false,
+            KeyCode::Char(' ') => self.paused = !self.paused,
+            KeyCode::Up => {
+                let newest = self.packets.len().saturating_sub(1);
+                self.selected = Some(self.selected.unwrap_or(newest).saturating_sub(1));
+            }
+            KeyCode::Down => {
+                self.selected = match self.selected {
+                    Some(index) if index + 1 < self.packets.len() => Some(index + 1),
+                    _ => None,
+                };
+            }
+            KeyCode::End => self.selected = None,
+            _ => {}
+        }
+    }
+
+    /// Returns `true` while the processing thread should record packets for the inspector.
+    pub fn is_recording(&self) -> bool {
+        self.open && !self.paused
+    }
+
+    pub fn update_data(&mut self, stats: &PacketProcessingStatistics) {
+        if !self.is_recording() {
+            return;
+        }
+        for packet in stats.inspected_since(self.next_sequence) {
+            self.next_sequence = packet.sequence + 1;
+            if self.packets.len() == self.capacity {
+                self.packets.pop_front();
+                // Keep the selection on the same packet as the list shifts
+                self.selected = self.selected.map(|index| index.saturating_sub(1));
+            }
+            self.packets.push_back(packet);
+        }
+    }
+
+    fn selected_index(&self) -> Option<usize> {
+        match self.selected {
+            Some(index) => Some(index),
+            None => self.packets.len().checked_sub(1),
+        }
+    }
+
+    fn render_packet_list(&self, area: Rect, buf: &mut Buffer) {
+        let visible_rows = area.height.saturating_sub(3) as usize;
+        let selected = self.selected_index();
+        // Scroll so that the selected packet is the last visible row
+        let end = selected
+            .map_or(0, |index| index + 1)
+            .max(visible_rows.min(self.packets.len()));
+        let start = end.saturating_sub(visible_rows);
+
+        let rows: Vec<Row> = self
+            .packets
+            .range(start..end)
+            .enumerate()
+            .map(|(i, packet)| {
+                let row = Row::new(vec![
+                    Span::raw(format!("{:.3}", packet.time.as_secs_f64())),
+                    Span::raw(if packet.outbound { "out" } else { "in" }),
+                    Span::raw(format_endpoint(packet.source, packet.source_port)),
+                    Span::raw(format_endpoint(packet.destination, packet.destination_port)),
+                    Span::raw(format_protocol(packet.protocol)),
+                    Span::raw(packet.length.to_string()),
+                    Span::styled(
+                        packet.actions.to_string(),
+                        Style::default().fg(action_color(packet.actions)),
+                    ),
+                ]);
+                if Some(start + i) == selected && self.selected.is_some() {
+                    row.add_modifier(Modifier::REVERSED)
+                } else {
+                    row
+                }
+            })
+            .collect();
+
+        let mut title = "[I]-Inspector".to_string();
+        if self.paused {
+            title.push_str(" (paused)");
+        }
+        Table::new(
+            rows,
+            [
+                Constraint::Length(10),
+                Constraint::Length(3),
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+                Constraint::Length(6),
+                Constraint::Length(5),
+                Constraint::Length(18),
+            ],
+        )
+        .header(
+            Row::new(vec![
+                "Time",
+                "Dir",
+                "Source",
+                "Destination",
+                "Proto",
+                "Len",
+                "Action",
+            ])
+            .add_modifier(Modifier::BOLD),
+        )
+        .block(Block::bordered().title(title).highlight_if(self.focused))
+        .render(area, buf);
+    }
+
+    fn render_hex_dump(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered().title("Hex dump");
+        let packet = match self
+            .selected_index()
+            .and_then(|index| self.packets.get(index))
+        {
+            Some(packet) => packet,
+            None => {
+                block.render(area, buf);
+                return;
+            }
+        };
+        let lines = hex_dump_lines(&packet.data, area.width.saturating_sub(2));
+        Paragraph::new(lines).block(block).render(area, buf);
+    }
+}
+
+/// Formats `data` as rows of offset, hex bytes and printable characters fitting in `width`.
+fn hex_dump_lines(data: &[u8], width: u16) -> Vec<Line<'static>> {
+    // Each byte takes three columns as hex and one as a symbol after the 6 column offset
+    let bytes_per_line = ((width as usize).saturating_sub(7) / 4).clamp(1, 16);
+    data.chunks(bytes_per_line)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let hex: String = chunk.iter().map(|byte| format!("{:02x} ", byte)).collect();
+            let symbols: String = chunk.iter().map(|byte| printable_symbol(*byte)).collect();
+            Line::from(vec![
+                Span::styled(
+                    format!("{:04x}  ", i * bytes_per_line),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::raw(format!("{:width$}", hex, width = bytes_per_line * 3)),
+                Span::raw(symbols),
+            ])
+        })
+        .collect()
+}
+
+fn format_endpoint(address: Option<IpAddr>, port: Option<u16>) -> String {
+    match (address, port) {
+        (Some(IpAddr::V6(address)), Some(port)) => format!("[{}]:{}", address, port),
+        (Some(address), Some(port)) => format!("{}:{}", address, port),
+        (Some(address), None) => address.to_string(),
+        (None, _) => "?".to_string(),
+    }
+}
+
+fn format_protocol(protocol: Option<u8>) -> String {
+    match protocol {
+        Some(protocol::TCP) => "TCP".to_string(),
+        Some(protocol::UDP) => "UDP".to_string(),
+        Some(protocol::ICMP) => "ICMP".to_string(),
+        Some(protocol::ICMPV6) => "ICMPv6".to_string(),
+        Some(other) => other.to_string(),
+        None => "?".to_string(),
+    }
+}
+
+fn action_color(actions: PacketActions) -> Color {
+    if actions.contains(PacketActions::DROPPED) {
+        Color::LightRed
+    } else if actions.contains(PacketActions::TAMPERED) {
+        Color::LightMagenta
+    } else if actions.contains(PacketActions::DUPLICATED) {
+        Color::LightBlue
+    } else if actions.contains(PacketActions::DELAYED) {
+        Color::Yellow
+    } else {
+        Color::LightGreen
+    }
+}
+
+impl KeyBindings for InspectorWidget {
+    fn key_bindings(&self) -> String {
+        "Exit: Esc | Close: i | Pause: Space | Scroll: Up and Down | Follow newest: End".to_string()
+    }
+}
+
+impl Widget for &mut InspectorWidget {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        if !self.open {
+            Block::bordered()
+                .borders(Borders::TOP)
+                .title("[I]-Inspector")
+                .render(area, buf);
+            return;
+        }
+        let [list_area, hex_area] =
+            Layout::horizontal([Constraint::Fill(3), Constraint::Fill(2)]).areas(area);
+        self.render_packet_list(list_area, buf);
+        self.render_hex_dump(hex_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_dump_lines() {
+        let lines = hex_dump_lines(b"fumble\x00\x01", 7 + 4 * 4);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].to_string(), "0000  66 75 6d 62 fumb");
+        assert_eq!(lines[1].to_string(), "0004  6c 65 00 01 le��");
+    }
+
+    #[test]
+    fn test_format_endpoint() {
+        let v4: IpAddr = "10.0.0.1".parse().unwrap();
+        let v6: IpAddr = "::1".parse().unwrap();
+        assert_eq!(format_endpoint(Some(v4), Some(53)), "10.0.0.1:53");
+        assert_eq!(format_endpoint(Some(v6), Some(53)), "[::1]:53");
+        assert_eq!(format_endpoint(Some(v4), None), "10.0.0.1");
+    }
+}
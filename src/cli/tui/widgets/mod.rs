@@ -1,11 +1,17 @@
 pub mod bandwidth_widget;
+pub mod config_widget;
 pub mod custom_widget;
 pub mod delay_widget;
+pub mod dns_widget;
 pub mod drop_widget;
 pub mod duplicate_widget;
 pub mod filter_widget;
+pub mod graphs_widget;
+pub mod inspector_widget;
 pub mod logs_widget;
 pub mod reorder_widget;
 pub mod tamper_widget;
 pub mod throttle_widget;
+pub mod topology_widget;
+pub mod trace_widget;
 pub mod utils;
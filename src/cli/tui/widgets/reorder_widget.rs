@@ -1,9 +1,11 @@
+use crate::cli::settings::content_match::ContentMatchOptions;
 use crate::cli::tui::traits::{DisplayName, HandleInput, IsActive, KeyBindings};
 use crate::cli::tui::widgets::utils::block_ext::RoundedBlockExt;
 use crate::cli::tui::widgets::utils::style_textarea_based_on_validation;
 use crate::cli::tui::widgets::utils::textarea_ext::TextAreaExt;
 use crate::cli::tui::widgets::utils::textarea_parsing::ParseFromTextArea;
-use crate::network::modules::stats::reorder_stats::ReorderStats;
+use crate::network::modules::stats::reorder_stats::ReorderSnapshot;
+use crate::network::types::duration_spec::DurationSpec;
 use crate::network::types::probability::Probability;
 use ratatui::buffer::Buffer;
 use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
@@ -18,8 +20,9 @@ pub struct ReorderWidget<'a> {
     delay_duration_text_area: TextArea<'a>,
     is_active: bool,
     interacting: bool,
+    pub content_match: Option<ContentMatchOptions>,
     pub probability: Result<Probability, String>,
-    pub delay_duration: Result<u64, String>,
+    pub delay_duration: Result<DurationSpec, String>,
     selected: usize,
     reorder_rate: f64,
     delayed_packets: usize,
@@ -39,8 +42,9 @@ impl ReorderWidget<'_> {
             delay_duration_text_area: TextArea::default(),
             is_active: false,
             interacting: false,
+            content_match: None,
             probability: Ok(Probability::default()),
-            delay_duration: Ok(0),
+            delay_duration: Ok(DurationSpec::default()),
             selected: 0,
             reorder_rate: 0.0,
             delayed_packets: 0,
@@ -53,14 +57,14 @@ impl ReorderWidget<'_> {
         self.probability = Ok(probability);
     }
 
-    pub fn set_delay_duration(&mut self, delay_duration_ms: u64) {
+    pub fn set_delay_duration(&mut self, delay_duration: DurationSpec) {
         self.delay_duration_text_area
-            .set_text(&delay_duration_ms.to_string());
-        self.delay_duration = Ok(delay_duration_ms);
+            .set_text(&delay_duration.to_string());
+        self.delay_duration = Ok(delay_duration);
     }
 
-    pub(crate) fn update_data(&mut self, stats: &ReorderStats) {
-        self.reorder_rate = stats.recent_reorder_rate();
+    pub(crate) fn update_data(&mut self, stats: &ReorderSnapshot) {
+        self.reorder_rate = stats.recent_reorder_rate;
         self.delayed_packets = stats.delayed_packets;
     }
 }
@@ -93,7 +97,7 @@ impl HandleInput for ReorderWidget<'_> {
                 1 => {
                     if self.delay_duration_text_area.input(key) {
                         self.delay_duration =
-                            u64::parse_from_text_area(&self.delay_duration_text_area);
+                            DurationSpec::parse_from_text_area(&self.delay_duration_text_area);
                     }
                 }
                 _ => {}
@@ -157,7 +161,7 @@ impl Widget for &mut ReorderWidget<'_> {
 
         self.delay_duration_text_area
             .set_cursor_visibility(self.interacting && self.selected == 1);
-        self.delay_duration_text_area.set_dim_placeholder("30");
+        self.delay_duration_text_area.set_dim_placeholder("30ms");
         self.delay_duration_text_area
             .set_cursor_line_style(Style::default());
         self.delay_duration_text_area.set_block(
@@ -1,9 +1,10 @@
+use crate::cli::settings::content_match::ContentMatchOptions;
 use crate::cli::tui::traits::{DisplayName, HandleInput, IsActive, KeyBindings};
 use crate::cli::tui::widgets::utils::block_ext::RoundedBlockExt;
-use crate::cli::tui::widgets::utils::style_textarea_based_on_validation;
 use crate::cli::tui::widgets::utils::textarea_ext::TextAreaExt;
 use crate::cli::tui::widgets::utils::textarea_parsing::ParseFromTextArea;
-use crate::network::modules::stats::tamper_stats::TamperStats;
+use crate::cli::tui::widgets::utils::{printable_symbol, style_textarea_based_on_validation};
+use crate::network::modules::stats::tamper_stats::TamperSnapshot;
 use crate::network::types::probability::Probability;
 use ratatui::buffer::Buffer;
 use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
@@ -21,6 +22,7 @@ pub struct TamperWidget<'a> {
     pub recalculate_checksums: bool,
     is_active: bool,
     interacting: bool,
+    pub content_match: Option<ContentMatchOptions>,
     selected: usize,
     pub probability: Result<Probability, String>,
     pub tamper_amount: Result<Probability, String>,
@@ -44,6 +46,7 @@ impl TamperWidget<'_> {
             recalculate_checksums: true,
             is_active: false,
             interacting: false,
+            content_match: None,
             selected: 0,
             probability: Ok(Probability::default()),
             tamper_amount: Ok(Probability::default()),
@@ -65,10 +68,10 @@ impl TamperWidget<'_> {
         self.probability = Ok(probability);
     }
 
-    pub(crate) fn update_data(&mut self, stats: &TamperStats) {
-        self.data = stats.data.clone();
-        self.tamper_flags = stats.tamper_flags.clone();
-        self.checksum_valid = stats.checksum_valid;
+    pub(crate) fn update_data(&mut self, stats: &TamperSnapshot) {
+        self.data = stats.sample.data.clone();
+        self.tamper_flags = stats.sample.tamper_flags.clone();
+        self.checksum_valid = stats.sample.checksum_valid;
     }
 }
 
@@ -211,16 +214,7 @@ fn highlight_tampered_data(data: Vec<u8>, width: u16, flags: Vec<bool>) -> Vec<S
         .zip(flags)
         .take(width as usize)
         .map(|(byte, is_tampered)| {
-            let symbol = char::try_from(byte);
-            let symbol = match symbol {
-                Ok(c)
-                    if c.is_ascii_alphanumeric()
-                        || [' ', '.', ',', '!', '?', ':', ';', '-'].contains(&c) =>
-                {
-                    c
-                }
-                _ => '�',
-            };
+            let symbol = printable_symbol(byte);
             if is_tampered {
                 Span::styled(symbol.to_string(), Style::default().fg(Color::LightRed))
             } else {
@@ -228,4 +222,4 @@ fn highlight_tampered_data(data: Vec<u8>, width: u16, flags: Vec<bool>) -> Vec<S
             }
         })
         .collect()
-}
\ No newline at end of file
+}
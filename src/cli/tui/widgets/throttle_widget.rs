@@ -1,9 +1,11 @@
+use crate::cli::settings::content_match::ContentMatchOptions;
 use crate::cli::tui::traits::{DisplayName, HandleInput, IsActive, KeyBindings};
 use crate::cli::tui::widgets::utils::block_ext::RoundedBlockExt;
 use crate::cli::tui::widgets::utils::style_textarea_based_on_validation;
 use crate::cli::tui::widgets::utils::textarea_ext::TextAreaExt;
 use crate::cli::tui::widgets::utils::textarea_parsing::ParseFromTextArea;
-use crate::network::modules::stats::throttle_stats::ThrottleStats;
+use crate::network::modules::stats::throttle_stats::ThrottleSnapshot;
+use crate::network::types::duration_spec::DurationSpec;
 use crate::network::types::probability::Probability;
 use ratatui::buffer::Buffer;
 use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
@@ -20,8 +22,9 @@ pub struct ThrottleWidget<'a> {
     pub drop: bool,
     is_active: bool,
     interacting: bool,
+    pub content_match: Option<ContentMatchOptions>,
     pub probability: Result<Probability, String>,
-    pub throttle_duration: Result<u64, String>,
+    pub throttle_duration: Result<DurationSpec, String>,
     selected: usize,
     is_throttling: bool,
     dropped_count: usize,
@@ -42,8 +45,9 @@ impl ThrottleWidget<'_> {
             drop: false,
             is_active: false,
             interacting: false,
+            content_match: None,
             probability: Ok(Probability::default()),
-            throttle_duration: Ok(0),
+            throttle_duration: Ok(DurationSpec::default()),
             selected: 0,
             is_throttling: false,
             dropped_count: 0,
@@ -56,13 +60,13 @@ impl ThrottleWidget<'_> {
         self.probability = Ok(probability);
     }
 
-    pub fn set_throttle_duration(&mut self, throttle_duration_ms: u64) {
+    pub fn set_throttle_duration(&mut self, throttle_duration: DurationSpec) {
         self.throttle_duration_text_area
-            .set_text(&throttle_duration_ms.to_string());
-        self.throttle_duration = Ok(throttle_duration_ms);
+            .set_text(&throttle_duration.to_string());
+        self.throttle_duration = Ok(throttle_duration);
     }
 
-    pub fn update_data(&mut self, stats: &ThrottleStats) {
+    pub fn update_data(&mut self, stats: &ThrottleSnapshot) {
         self.is_throttling = stats.is_throttling;
         self.dropped_count = stats.dropped_count;
     }
@@ -96,7 +100,7 @@ impl HandleInput for ThrottleWidget<'_> {
                 1 => {
                     if self.throttle_duration_text_area.input(key) {
                         self.throttle_duration =
-                            u64::parse_from_text_area(&self.throttle_duration_text_area);
+                            DurationSpec::parse_from_text_area(&self.throttle_duration_text_area);
                     }
                 }
                 2 => {
@@ -166,7 +170,7 @@ impl Widget for &mut ThrottleWidget<'_> {
 
         self.throttle_duration_text_area
             .set_cursor_visibility(self.interacting && self.selected == 1);
-        self.throttle_duration_text_area.set_dim_placeholder("30");
+        self.throttle_duration_text_area.set_dim_placeholder("30ms");
         self.throttle_duration_text_area
             .set_cursor_line_style(Style::default());
         self.throttle_duration_text_area.set_block(
@@ -0,0 +1,171 @@
+use crate::cli::settings::content_match::ContentMatchOptions;
+use crate::cli::settings::topology::{TopologyProfile, TopologyRoute};
+use crate::cli::tui::traits::{DisplayName, HandleInput, IsActive, KeyBindings};
+use crate::cli::tui::widgets::utils::block_ext::RoundedBlockExt;
+use crate::cli::tui::widgets::utils::style_textarea_based_on_validation;
+use crate::cli::tui::widgets::utils::textarea_ext::TextAreaExt;
+use crate::network::modules::stats::topology_stats::TopologySnapshot;
+use ratatui::buffer::Buffer;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Margin, Rect};
+use ratatui::style::Style;
+use ratatui::widgets::{Block, Paragraph, Widget};
+use tui_textarea::TextArea;
+
+pub struct TopologyWidget<'a> {
+    title: String,
+    default_profile_text_area: TextArea<'a>,
+    is_active: bool,
+    interacting: bool,
+    pub content_match: Option<ContentMatchOptions>,
+    /// Routes can only be configured through the command line or a config file
+    pub routes: Vec<TopologyRoute>,
+    pub default_profile: Result<Option<TopologyProfile>, String>,
+    delayed_packets: usize,
+    unmatched: usize,
+    regions: Vec<String>,
+}
+
+impl Default for TopologyWidget<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TopologyWidget<'_> {
+    pub fn new() -> Self {
+        TopologyWidget {
+            title: "Topology".to_string(),
+            default_profile_text_area: TextArea::default(),
+            is_active: false,
+            interacting: false,
+            content_match: None,
+            routes: Vec::new(),
+            default_profile: Ok(None),
+            delayed_packets: 0,
+            unmatched: 0,
+            regions: Vec::new(),
+        }
+    }
+
+    pub fn set_default_profile(&mut self, default_profile: Option<&TopologyProfile>) {
+        let text = default_profile
+            .map(|profile| profile.to_string())
+            .unwrap_or_default();
+        self.default_profile_text_area.set_text(&text);
+        self.default_profile = Ok(default_profile.cloned());
+    }
+
+    pub fn update_data(&mut self, stats: &TopologySnapshot) {
+        self.delayed_packets = stats.delayed_packets;
+        self.unmatched = stats.unmatched;
+        self.regions = stats
+            .sorted_regions()
+            .into_iter()
+            .map(|(name, region)| format!("{} {} ({} lost)", name, region.packets, region.dropped))
+            .collect();
+    }
+}
+
+/// Parses the default profile. An empty text area means no default profile.
+fn parse_default_profile(text_area: &TextArea) -> Result<Option<TopologyProfile>, String> {
+    match text_area.lines().first().map(|line| line.trim()) {
+        None | Some("") => Ok(None),
+        Some(line) => line.parse().map(Some),
+    }
+}
+
+impl HandleInput for TopologyWidget<'_> {
+    fn handle_input(&mut self, key: KeyEvent) -> bool {
+        if !self.interacting {
+            if key.code == KeyCode::Enter && key.kind == KeyEventKind::Press {
+                self.interacting = true;
+                return true;
+            }
+        } else {
+            if let KeyCode::Enter | KeyCode::Esc = key.code {
+                self.interacting = false;
+                return false;
+            }
+            if self.default_profile_text_area.input(key) {
+                self.default_profile = parse_default_profile(&self.default_profile_text_area);
+            }
+            return true;
+        }
+        false
+    }
+}
+
+impl DisplayName for TopologyWidget<'_> {
+    fn name(&self) -> &str {
+        &self.title
+    }
+}
+
+impl KeyBindings for TopologyWidget<'_> {
+    fn key_bindings(&self) -> String {
+        "Exit: Esc".to_string()
+    }
+}
+
+impl IsActive for TopologyWidget<'_> {
+    fn is_active(&self) -> bool {
+        self.is_active
+    }
+
+    fn set_active(&mut self, state: bool) {
+        self.is_active = state;
+    }
+}
+
+impl Widget for &mut TopologyWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        let [default_profile_area, info_area] =
+            Layout::horizontal([Constraint::Max(30), Constraint::Min(25)]).areas(area.inner(
+                Margin {
+                    horizontal: 1,
+                    vertical: 1,
+                },
+            ));
+
+        self.default_profile_text_area
+            .set_cursor_visibility(self.interacting);
+        self.default_profile_text_area
+            .set_dim_placeholder("default: 20ms, 1% loss");
+        self.default_profile_text_area
+            .set_cursor_line_style(Style::default());
+        self.default_profile_text_area
+            .set_block(Block::roundedt("Default profile").highlight_if(self.interacting));
+        if !self.default_profile_text_area.lines()[0].is_empty() {
+            style_textarea_based_on_validation(
+                &mut self.default_profile_text_area,
+                &self.default_profile,
+            );
+        }
+        self.default_profile_text_area
+            .render(default_profile_area, buf);
+
+        let [routes_info, delayed_info, unmatched_info, regions_info] = Layout::horizontal([
+            Constraint::Max(10),
+            Constraint::Max(10),
+            Constraint::Max(11),
+            Constraint::Fill(1),
+        ])
+        .areas(info_area);
+        Paragraph::new(format!("{}", self.routes.len()))
+            .block(Block::bordered().title("Routes"))
+            .render(routes_info, buf);
+        Paragraph::new(format!("{}", self.delayed_packets))
+            .block(Block::bordered().title("Delayed"))
+            .render(delayed_info, buf);
+        Paragraph::new(format!("{}", self.unmatched))
+            .block(Block::bordered().title("Unmatched"))
+            .render(unmatched_info, buf);
+        Paragraph::new(self.regions.join(" | "))
+            .block(Block::bordered().title("Packets by region"))
+            .render(regions_info, buf);
+    }
+}
@@ -0,0 +1,283 @@
+use crate::cli::settings::content_match::ContentMatchOptions;
+use crate::cli::settings::trace::{TraceEnd, TraceFile};
+use crate::cli::tui::traits::{DisplayName, HandleInput, IsActive, KeyBindings};
+use crate::cli::tui::widgets::utils::block_ext::RoundedBlockExt;
+use crate::cli::tui::widgets::utils::style_textarea_based_on_validation;
+use crate::cli::tui::widgets::utils::textarea_ext::TextAreaExt;
+use crate::network::modules::stats::trace_stats::TraceSnapshot;
+use ratatui::buffer::Buffer;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Margin, Rect};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::Span;
+use ratatui::widgets::{Block, Paragraph, Widget};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tui_textarea::TextArea;
+
+pub struct TraceWidget<'a> {
+    title: String,
+    file_text_area: TextArea<'a>,
+    pub end: TraceEnd,
+    is_active: bool,
+    interacting: bool,
+    pub content_match: Option<ContentMatchOptions>,
+    /// The trace file applied, set when the file in the text area is confirmed and can be read
+    pub file: Option<PathBuf>,
+    /// Directory a relative file is read from, see [`TraceOptions`](crate::cli::settings::trace::TraceOptions)
+    pub base_dir: Option<PathBuf>,
+    /// Result of reading the file in the text area when it was last confirmed
+    validation: Result<Option<PathBuf>, String>,
+    selected: usize,
+    delayed_packets: usize,
+    dropped_packets: usize,
+    elapsed: Duration,
+    period: Duration,
+}
+
+impl Default for TraceWidget<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TraceWidget<'_> {
+    pub fn new() -> Self {
+        TraceWidget {
+            title: "Trace".to_string(),
+            file_text_area: TextArea::default(),
+            end: TraceEnd::Loop,
+            is_active: false,
+            interacting: false,
+            content_match: None,
+            file: None,
+            base_dir: None,
+            validation: Ok(None),
+            selected: 0,
+            delayed_packets: 0,
+            dropped_packets: 0,
+            elapsed: Duration::ZERO,
+            period: Duration::ZERO,
+        }
+    }
+
+    pub fn set_file(&mut self, file: Option<&Path>) {
+        let text = file
+            .map(|file| file.display().to_string())
+            .unwrap_or_default();
+        self.file_text_area.set_text(&text);
+        self.file = file.map(Path::to_path_buf);
+        self.validation = Ok(self.file.clone());
+    }
+
+    pub fn update_data(&mut self, stats: &TraceSnapshot) {
+        self.delayed_packets = stats.delayed_packets;
+        self.dropped_packets = stats.dropped_packets;
+        self.elapsed = stats.elapsed;
+        self.period = stats.period;
+    }
+
+    /// Describes how far the replay of the trace got.
+    fn position(&self) -> String {
+        if self.file.is_none() || self.period.is_zero() {
+            return "-".to_string();
+        }
+        let (elapsed, period) = (self.elapsed, self.period);
+        let loops = elapsed.as_nanos() / period.as_nanos();
+        if self.end == TraceEnd::Stop && loops > 0 {
+            return "ended".to_string();
+        }
+        let position = elapsed.as_secs_f64() % period.as_secs_f64();
+        let position = format!("{:.1}s / {:.1}s", position, period.as_secs_f64());
+        if loops > 0 {
+            format!("{}, loop {}", position, loops + 1)
+        } else {
+            position
+        }
+    }
+}
+
+/// Checks that the trace file named in the text area can be read, relative to `base_dir`, and
+/// returns its path. An empty text area means no trace.
+fn read_file(text_area: &TextArea, base_dir: Option<&Path>) -> Result<Option<PathBuf>, String> {
+    let file = match text_area.lines().first().map(|line| line.trim()) {
+        None | Some("") => return Ok(None),
+        Some(line) => PathBuf::from(line),
+    };
+    let path = base_dir.map_or_else(|| file.clone(), |base_dir| base_dir.join(&file));
+    TraceFile::read(&path).map(|_| Some(file))
+}
+
+impl HandleInput for TraceWidget<'_> {
+    fn handle_input(&mut self, key: KeyEvent) -> bool {
+        if !self.interacting {
+            if key.code == KeyCode::Enter && key.kind == KeyEventKind::Press {
+                self.interacting = true;
+                return true;
+            }
+        } else {
+            if key.code == KeyCode::Esc {
+                // Edits of the file that were not confirmed are discarded
+                self.interacting = false;
+                self.set_file(self.file.clone().as_deref());
+                return false;
+            }
+            if key.code == KeyCode::Enter {
+                if key.kind != KeyEventKind::Press {
+                    return true;
+                }
+                // The file is only read once confirmed, not on every key
+                self.validation = read_file(&self.file_text_area, self.base_dir.as_deref());
+                if let Ok(file) = &self.validation {
+                    self.file = file.clone();
+                    self.interacting = false;
+                    return false;
+                }
+                return true;
+            }
+            if key.code == KeyCode::Down && self.selected < 1 {
+                self.selected += 1;
+            }
+            if key.code == KeyCode::Up && self.selected > 0 {
+                self.selected -= 1;
+            }
+            match self.selected {
+                0 => {
+                    self.file_text_area.input(key);
+                }
+                1 if key.code == KeyCode::Char(' ') && key.kind == KeyEventKind::Press => {
+                    self.end = self.end.next();
+                }
+                _ => {}
+            }
+
+            return true;
+        }
+        false
+    }
+}
+
+impl DisplayName for TraceWidget<'_> {
+    fn name(&self) -> &str {
+        &self.title
+    }
+}
+
+impl KeyBindings for TraceWidget<'_> {
+    fn key_bindings(&self) -> String {
+        "Exit: Esc | Confirm: Enter | Navigation: Up and Down | Cycle end: Space".to_string()
+    }
+}
+
+impl IsActive for TraceWidget<'_> {
+    fn is_active(&self) -> bool {
+        self.is_active
+    }
+
+    fn set_active(&mut self, state: bool) {
+        self.is_active = state;
+    }
+}
+
+impl Widget for &mut TraceWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        let [file_area, end_area, info_area] = Layout::horizontal([
+            Constraint::Max(40),
+            Constraint::Max(10),
+            Constraint::Min(25),
+        ])
+        .areas(area.inner(Margin {
+            horizontal: 1,
+            vertical: 1,
+        }));
+
+        self.file_text_area
+            .set_cursor_visibility(self.interacting && self.selected == 0);
+        self.file_text_area.set_dim_placeholder("traces/lte.csv");
+        self.file_text_area.set_cursor_line_style(Style::default());
+        self.file_text_area.set_block(
+            Block::roundedt("File").highlight_if(self.interacting && self.selected == 0),
+        );
+        if !self.file_text_area.lines()[0].is_empty() {
+            style_textarea_based_on_validation(&mut self.file_text_area, &self.validation);
+        }
+        self.file_text_area.render(file_area, buf);
+
+        let mut end_span = Span::from(self.end.to_string());
+        if self.selected == 1 && self.interacting {
+            end_span = end_span.add_modifier(Modifier::RAPID_BLINK);
+        }
+        Paragraph::new(end_span)
+            .block(Block::roundedt("At end").highlight_if(self.interacting && self.selected == 1))
+            .render(end_area, buf);
+
+        let [position_info, delayed_info, dropped_info] = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Max(10),
+            Constraint::Max(10),
+        ])
+        .areas(info_area);
+        Paragraph::new(self.position())
+            .block(Block::bordered().title("Position"))
+            .render(position_info, buf);
+        Paragraph::new(format!("{}", self.delayed_packets))
+            .block(Block::bordered().title("Delayed"))
+            .render(delayed_info, buf);
+        Paragraph::new(format!("{}", self.dropped_packets))
+            .block(Block::bordered().title("Lost"))
+            .render(dropped_info, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::crossterm::event::KeyModifiers;
+    use std::fs;
+
+    fn press(widget: &mut TraceWidget, code: KeyCode) -> bool {
+        widget.handle_input(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn type_text(widget: &mut TraceWidget, text: &str) {
+        for c in text.chars() {
+            press(widget, KeyCode::Char(c));
+        }
+    }
+
+    #[test]
+    fn test_file_is_read_when_confirmed() {
+        let dir = std::env::temp_dir();
+        fs::write(dir.join("fumble_test_trace_widget.csv"), "0,50\n1s,80\n").unwrap();
+        let mut widget = TraceWidget::new();
+        widget.base_dir = Some(dir.clone());
+        press(&mut widget, KeyCode::Enter);
+
+        // A file that cannot be read is not applied, and the widget waits for a correction
+        type_text(&mut widget, "fumble_test_trace_widget");
+        assert!(widget.validation.is_ok());
+        assert!(press(&mut widget, KeyCode::Enter));
+        assert!(widget.validation.is_err());
+        assert_eq!(widget.file, None);
+
+        type_text(&mut widget, ".csv");
+        assert!(!press(&mut widget, KeyCode::Enter));
+        assert_eq!(
+            widget.file,
+            Some(PathBuf::from("fumble_test_trace_widget.csv"))
+        );
+        fs::remove_file(dir.join("fumble_test_trace_widget.csv")).unwrap();
+
+        // Unconfirmed edits are discarded
+        press(&mut widget, KeyCode::Enter);
+        type_text(&mut widget, "x");
+        press(&mut widget, KeyCode::Esc);
+        assert_eq!(
+            widget.file_text_area.lines()[0],
+            "fumble_test_trace_widget.csv"
+        );
+    }
+}
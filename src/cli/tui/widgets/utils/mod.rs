@@ -32,4 +32,13 @@ where
             true
         }
     }
-}
\ No newline at end of file
+}
+
+/// Returns the character used to display `byte` in data views, or `�` for bytes without a
+/// readable representation.
+pub(crate) fn printable_symbol(byte: u8) -> char {
+    match char::from(byte) {
+        c if c.is_ascii_alphanumeric() || [' ', '.', ',', '!', '?', ':', ';', '-'].contains(&c) => c,
+        _ => '�',
+    }
+}
@@ -1,4 +1,6 @@
+use crate::cli::settings::dns::DnsAction;
 use crate::cli::settings::packet_manipulation::PacketManipulationSettings;
+use crate::network::types::bit_rate::BitRate;
 use log::info;
 
 pub fn log_initialization_info(filter: &Option<String>, settings: &PacketManipulationSettings) {
@@ -6,21 +8,27 @@ pub fn log_initialization_info(filter: &Option<String>, settings: &PacketManipul
         info!("Traffic filer: {}", traffic_filter);
     }
     if let Some(drop) = &settings.drop {
-        info!("Dropping packets with probability: {}", drop.probability);
+        match drop.capacity {
+            Some(capacity) => info!(
+                "Dropping packets with probability up to {} using a {} curve towards a capacity of {} KB/s",
+                drop.probability, drop.curve, capacity
+            ),
+            None => info!("Dropping packets with probability: {}", drop.probability),
+        }
     }
     if let Some(delay) = &settings.delay {
-        info!("Delaying packets for: {} ms", delay.duration)
+        info!("Delaying packets for: {}", delay.duration)
     }
     if let Some(throttle) = &settings.throttle {
         info!(
-            "Throttling packets with probability of {} ms with a throttle duration of {}. \
+            "Throttling packets with probability of {} with a throttle duration of {}. \
         Throttle packet dropping: {}",
             throttle.probability, throttle.duration, throttle.drop
         )
     }
     if let Some(reorder) = &settings.reorder {
         info!(
-            "Reordering packets with probability {} and maximum random delay of: {} ms",
+            "Reordering packets with probability {} and maximum random delay of: {}",
             reorder.probability, reorder.max_delay
         )
     }
@@ -41,6 +49,46 @@ pub fn log_initialization_info(filter: &Option<String>, settings: &PacketManipul
         }
     }
     if let Some(bandwidth) = &settings.bandwidth {
-        info!("Limiting bandwidth to: {} KB/s", bandwidth.limit)
+        let describe = |limit: Option<BitRate>| {
+            limit.map_or("no limit".to_string(), |limit| limit.to_string())
+        };
+        info!(
+            "Limiting bandwidth to: {} up, {} down, counting {} bytes of overhead per packet",
+            describe(bandwidth.limit_for(true)),
+            describe(bandwidth.limit_for(false)),
+            bandwidth.overhead
+        )
+    }
+    if let Some(dns) = &settings.dns {
+        let names = if dns.names.is_empty() {
+            "all queries".to_string()
+        } else {
+            dns.names
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match dns.action {
+            DnsAction::Delay => info!("Delaying DNS responses for {} by: {}", names, dns.delay),
+            action => info!("Applying DNS action {} to responses for {}", action, names),
+        }
+    }
+    if let Some(topology) = &settings.topology {
+        for route in &topology.routes {
+            info!("Applying topology route: {}", route)
+        }
+        if let Some(default_profile) = &topology.default_profile {
+            info!("Applying default topology profile: {}", default_profile)
+        }
+    }
+    if let Some(trace) = &settings.trace {
+        if let Some(file) = trace.resolved_file() {
+            info!(
+                "Replaying trace {}, at its end: {}",
+                file.display(),
+                trace.end
+            )
+        }
     }
 }
@@ -1,6 +1,11 @@
 use clap::Parser;
 use env_logger::Env;
-use fumble::cli::config::config_options::ConfigOptions;
+use fumble::cli::command::{Command, SessionArgs, EXIT_FAILURE};
+use fumble::cli::config::config_command::{list_configs, run_config_command};
+use fumble::cli::config::config_options::{resolve_config_path, ConfigOptions};
+use fumble::cli::config::config_watcher::watch_config;
+use fumble::cli::config::presets::run_presets_command;
+use fumble::cli::settings::trace::TraceFile;
 use fumble::cli::tui::cli_ext::{CliExt, TuiStateExt};
 use fumble::cli::tui::custom_logger::{init_logger, set_logger_console_state};
 use fumble::cli::tui::state::TuiState;
@@ -8,20 +13,64 @@ use fumble::cli::tui::terminal::TerminalManager;
 use fumble::cli::tui::{input, ui};
 use fumble::cli::utils::logging::log_initialization_info;
 use fumble::cli::Cli;
+use fumble::network::modules::stats::summary_report::SummaryReport;
 use fumble::network::modules::stats::{initialize_statistics, PacketProcessingStatistics};
+use fumble::network::processing::packet_benchmark::run_bench_command;
 use fumble::network::processing::packet_processing::start_packet_processing;
 use fumble::network::processing::packet_receiving::receive_packets;
-use log::{debug, error, info};
+use fumble::network::processing::packet_replaying::replay_packets;
+use fumble::network::utils::pcap::{read_pcap, PcapError};
+use log::{debug, error, info, warn};
+use std::fs::File;
 use std::process::exit;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{mpsc, Arc, Mutex, RwLock};
+use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
 use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 use windivert::error::WinDivertError;
 
+/// How long packets held by the manipulation modules get to leave once a replay finished.
+const REPLAY_LINGER: Duration = Duration::from_secs(1);
+
 fn main() -> Result<(), WinDivertError> {
     let mut cli = Cli::parse();
 
+    let mut deprecations = Vec::new();
+    let mut replay = None;
+    match cli.command.take() {
+        Some(Command::Config(command)) => {
+            initialize_logging();
+            exit(run_config_command(command));
+        }
+        Some(Command::Presets { command }) => {
+            initialize_logging();
+            exit(run_presets_command(command));
+        }
+        Some(Command::Bench(args)) => exit(run_bench_command(&args)),
+        Some(Command::Run(args)) => args.apply_to(&mut cli),
+        Some(Command::Tui(args)) => {
+            args.apply_to(&mut cli);
+            cli.tui = true;
+        }
+        Some(Command::Replay(args)) => {
+            SessionArgs {
+                use_config: args.use_config,
+                options: args.options,
+                auto_stop: args.auto_stop,
+                packet_manipulation_settings: args.packet_manipulation_settings,
+                ..Default::default()
+            }
+            .apply_to(&mut cli);
+            replay = Some((args.file, args.speed, !args.inbound));
+        }
+        None => {
+            if cli.tui {
+                deprecations.push("--tui is deprecated, use `fumble tui` instead.");
+            }
+        }
+    }
+
     let mut should_start_tui = false;
     if cli.tui {
         should_start_tui = true;
@@ -31,8 +80,12 @@ fn main() -> Result<(), WinDivertError> {
     }
 
     debug!("Parsed CLI arguments: {:?}", &cli);
+    for deprecation in deprecations {
+        warn!("{}", deprecation);
+    }
 
     if let Some(file_name) = &cli.config.create_default {
+        warn!("--create-default is deprecated, use `fumble config create` instead.");
         // Create a default config file and exit
         ConfigOptions::create_default_config(file_name)?;
         info!(
@@ -43,41 +96,109 @@ fn main() -> Result<(), WinDivertError> {
     }
 
     if cli.config.list_configs {
-        // List all config files in the current directory
-        match ConfigOptions::list_all_configs() {
-            Ok(configs) => {
-                for config in configs {
-                    println!("{}", config);
-                }
-            }
-            Err(e) => error!("Failed to list configs: {}", e),
-        }
-        return Ok(());
+        warn!("--list-configs is deprecated, use `fumble config list` instead.");
+        exit(list_configs());
     }
 
     // Load configuration from file if specified
     if let Some(file_name) = &cli.config.use_config {
-        let loaded_settings = ConfigOptions::load_existing_config(file_name)?;
-        cli.packet_manipulation_settings = loaded_settings;
+        let document = match ConfigOptions::load_existing_config(file_name) {
+            Ok(document) => document,
+            Err(e) => {
+                error!("Failed to load configuration {:?}:\n{}", file_name, e);
+                exit(EXIT_FAILURE);
+            }
+        };
+        cli.packet_manipulation_settings = document.settings;
+        // Options given on the command line take precedence over the config file
+        if cli.filter.is_none() {
+            cli.filter = document.filter;
+        }
+        if cli.options.seed.is_none() {
+            cli.options.seed = document.options.seed;
+        }
+        if cli.options.drain_policy.is_none() {
+            cli.options.drain_policy = document.options.drain_policy;
+        }
+        if cli.options.workers.is_none() {
+            cli.options.workers = document.options.workers;
+        }
+        cli.tui_preferences = document.tui;
         info!("Loaded configuration from {:?}", file_name);
     }
 
+    // Read the capture up front so a bad file fails before any thread starts
+    let replay = match replay {
+        Some((file, speed, outbound)) => {
+            match File::open(&file)
+                .map_err(PcapError::from)
+                .and_then(read_pcap)
+            {
+                Ok(packets) => Some((packets, speed, outbound)),
+                Err(e) => {
+                    error!("Cannot replay {:?}: {}", file, e);
+                    exit(EXIT_FAILURE);
+                }
+            }
+        }
+        None => None,
+    };
+
+    // Read the trace file when the run starts, so a bad file fails before any thread starts
+    let trace_file = match cli
+        .packet_manipulation_settings
+        .trace
+        .as_ref()
+        .and_then(|trace| trace.resolved_file())
+    {
+        Some(path) => match TraceFile::read(&path) {
+            Ok(file) => Some(file),
+            Err(e) => {
+                error!("{}", e);
+                exit(EXIT_FAILURE);
+            }
+        },
+        None => None,
+    };
+
     log_initialization_info(&cli.filter, &cli.packet_manipulation_settings);
+    if let Some(file) = &trace_file {
+        info!("Read {}", file);
+    }
 
+    let started = Instant::now();
     let running = Arc::new(AtomicBool::new(true));
     let shutdown_triggered = Arc::new(AtomicBool::new(false));
     setup_ctrlc_handler(running.clone(), shutdown_triggered.clone());
 
     let (packet_sender, packet_receiver) = mpsc::channel();
 
+    let watch_config_file = match (cli.config.watch_config, &cli.config.use_config) {
+        (true, Some(file_name)) => Some(resolve_config_path(file_name)),
+        _ => None,
+    };
     let cli_thread_safe = Arc::new(Mutex::new(cli));
 
-    // Start the packet receiving thread
-    let cli_for_reading = cli_thread_safe.clone();
-    let packet_receiver_handle = thread::spawn({
-        let running = running.clone();
-        move || receive_packets(packet_sender, running, cli_for_reading)
-    });
+    // Start the packet receiving thread, or the replaying thread in its place
+    let packet_receiver_handle = match replay {
+        Some((packets, speed, outbound)) => thread::spawn({
+            let running = running.clone();
+            move || {
+                let result =
+                    replay_packets(packets, packet_sender, running.clone(), speed, outbound);
+                thread::sleep(REPLAY_LINGER);
+                running.store(false, Ordering::SeqCst);
+                result
+            }
+        }),
+        None => {
+            let cli_for_reading = cli_thread_safe.clone();
+            thread::spawn({
+                let running = running.clone();
+                move || receive_packets(packet_sender, running, cli_for_reading)
+            })
+        }
+    };
 
     // Start packet processing thread
     let statistics = initialize_statistics();
@@ -93,25 +214,75 @@ fn main() -> Result<(), WinDivertError> {
                 packet_receiver,
                 running,
                 statistics_for_processing,
+                trace_file,
             )
         }
     });
 
+    // Start the config watching thread, the TUI owns the settings in TUI mode
+    let mut config_watcher_handle = None;
+    if let Some(path) = watch_config_file {
+        if should_start_tui {
+            warn!("--watch-config is ignored in TUI mode.");
+        } else {
+            let cli_for_watching = cli_thread_safe.clone();
+            config_watcher_handle = Some(thread::spawn({
+                let running = running.clone();
+                move || watch_config(path, cli_for_watching, running)
+            }));
+        }
+    }
+
     if should_start_tui {
-        tui(cli_thread_safe, statistics, running, shutdown_triggered)?;
+        tui(
+            cli_thread_safe.clone(),
+            statistics.clone(),
+            running,
+            shutdown_triggered,
+        )?;
     }
 
     wait_for_thread(packet_sender_handle, "Packet sending");
     debug!("Awaiting packet receiving thread termination...");
     wait_for_thread(packet_receiver_handle, "Packet receiving");
+    if let Some(handle) = config_watcher_handle {
+        if handle.join().is_err() {
+            error!("Failed to join config watching thread");
+        }
+    }
+
+    if !should_start_tui {
+        report_summary(&cli_thread_safe, &statistics, started.elapsed());
+    }
 
     info!("Application shutdown complete.");
     Ok(())
 }
 
+/// Prints the totals of the finished run and writes them to the report file, if one was given.
+fn report_summary(
+    cli: &Arc<Mutex<Cli>>,
+    statistics: &PacketProcessingStatistics,
+    elapsed: Duration,
+) {
+    let cli = cli.lock().unwrap();
+    let report = SummaryReport::new(
+        &statistics.capture(),
+        &cli.packet_manipulation_settings,
+        elapsed,
+    );
+    print!("{}", report);
+    if let Some(path) = &cli.auto_stop.report {
+        match report.save_to_file(path) {
+            Ok(()) => info!("Summary report written to {:?}", path),
+            Err(e) => error!("Failed to write summary report to {:?}: {}", path, e),
+        }
+    }
+}
+
 fn tui(
     cli: Arc<Mutex<Cli>>,
-    statistics: Arc<RwLock<PacketProcessingStatistics>>,
+    statistics: Arc<PacketProcessingStatistics>,
     running: Arc<AtomicBool>,
     shutdown_triggered: Arc<AtomicBool>,
 ) -> Result<(), WinDivertError> {
@@ -123,6 +294,7 @@ fn tui(
         while running.load(Ordering::SeqCst) {
             terminal_manager.draw(|f| ui::ui(f, &mut tui_state))?;
             let should_quit = input::handle_input(&mut tui_state)?;
+            tui_state.handle_config_request();
             if should_quit {
                 shutdown_triggered.store(true, Ordering::SeqCst);
                 break;
@@ -170,4 +342,4 @@ fn setup_ctrlc_handler(running: Arc<AtomicBool>, shutdown_triggered: Arc<AtomicB
 
 fn initialize_logging() {
     env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
-}
\ No newline at end of file
+}
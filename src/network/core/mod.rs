@@ -1 +1,2 @@
+pub mod packet_buffer;
 pub mod packet_data;
@@ -0,0 +1,163 @@
+use std::fmt;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+
+/// Largest packet WinDivert can capture: an IP packet of 64 KiB plus an IPv6 header.
+pub const MAX_PACKET_SIZE: usize = 40 + 0xFFFF;
+
+/// Most unused buffers a pool keeps around for reuse.
+const MAX_POOLED_BUFFERS: usize = 4096;
+/// Largest buffer a pool keeps, enough for a packet of a 1500 byte MTU. Larger ones are freed,
+/// so a burst of large packets does not stay allocated once it passed.
+const MAX_POOLED_CAPACITY: usize = 2048;
+
+/// Unused packet buffers, shared between the thread that fills them and the threads that drop
+/// them once the packets were sent or dropped.
+#[derive(Clone, Default)]
+pub struct BufferPool {
+    free: Arc<Mutex<Vec<Vec<u8>>>>,
+}
+
+impl BufferPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Copies `data` into a buffer from the pool, allocating only if none is available.
+    pub fn copy_from_slice(&self, data: &[u8]) -> PacketBuffer {
+        let mut buffer = self.take();
+        buffer.extend_from_slice(data);
+        PacketBuffer {
+            data: Arc::new(buffer),
+            pool: Some(self.clone()),
+        }
+    }
+
+    fn take(&self) -> Vec<u8> {
+        self.free
+            .lock()
+            .ok()
+            .and_then(|mut free| free.pop())
+            .unwrap_or_default()
+    }
+
+    fn recycle(&self, mut buffer: Vec<u8>) {
+        if buffer.capacity() > MAX_POOLED_CAPACITY {
+            return;
+        }
+        buffer.clear();
+        if let Ok(mut free) = self.free.lock() {
+            if free.len() < MAX_POOLED_BUFFERS {
+                free.push(buffer);
+            }
+        }
+    }
+}
+
+/// The bytes of a packet. Clones share the bytes until one of them is modified, and the storage
+/// goes back to its pool once the last clone is dropped.
+#[derive(Clone)]
+pub struct PacketBuffer {
+    data: Arc<Vec<u8>>,
+    pool: Option<BufferPool>,
+}
+
+impl PacketBuffer {
+    /// Returns the bytes for modification, copying them first if they are shared with a clone.
+    pub fn to_mut(&mut self) -> &mut Vec<u8> {
+        if Arc::get_mut(&mut self.data).is_none() {
+            let mut copy = match &self.pool {
+                Some(pool) => pool.take(),
+                None => Vec::with_capacity(self.data.len()),
+            };
+            copy.extend_from_slice(&self.data);
+            self.data = Arc::new(copy);
+        }
+        // The bytes are no longer shared, so this does not copy them again
+        Arc::make_mut(&mut self.data)
+    }
+
+    /// Returns whether the bytes are shared with a clone.
+    pub fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.data) > 1
+    }
+}
+
+impl From<Vec<u8>> for PacketBuffer {
+    fn from(data: Vec<u8>) -> Self {
+        PacketBuffer {
+            data: Arc::new(data),
+            pool: None,
+        }
+    }
+}
+
+impl Deref for PacketBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl fmt::Debug for PacketBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.data.iter()).finish()
+    }
+}
+
+impl Drop for PacketBuffer {
+    fn drop(&mut self) {
+        if let (Some(pool), Some(data)) = (&self.pool, Arc::get_mut(&mut self.data)) {
+            pool.recycle(std::mem::take(data));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn available(pool: &BufferPool) -> usize {
+        pool.free.lock().unwrap().len()
+    }
+
+    #[test]
+    fn test_clones_share_until_modified() {
+        let pool = BufferPool::new();
+        let original = pool.copy_from_slice(&[1, 2, 3]);
+        let mut duplicate = original.clone();
+        assert!(original.is_shared());
+        assert_eq!(original.as_ptr(), duplicate.as_ptr());
+
+        duplicate.to_mut()[0] = 9;
+        assert!(!original.is_shared());
+        assert_eq!(original[..], [1, 2, 3]);
+        assert_eq!(duplicate[..], [9, 2, 3]);
+    }
+
+    #[test]
+    fn test_buffers_return_to_pool() {
+        let pool = BufferPool::new();
+        let buffer = pool.copy_from_slice(&[0; 1500]);
+        let clone = buffer.clone();
+        drop(buffer);
+        assert_eq!(available(&pool), 0);
+        drop(clone);
+        assert_eq!(available(&pool), 1);
+
+        // The recycled buffer keeps its capacity, so a packet of the same size needs no allocation
+        let reused = pool.copy_from_slice(&[0; 1500]);
+        assert_eq!(available(&pool), 0);
+        assert_eq!(reused.len(), 1500);
+    }
+
+    #[test]
+    fn test_jumbo_packets_are_not_truncated() {
+        let pool = BufferPool::new();
+        let data = vec![7; MAX_PACKET_SIZE];
+        assert_eq!(pool.copy_from_slice(&data)[..], data[..]);
+        // Buffers that large are not kept for reuse
+        assert_eq!(available(&pool), 0);
+    }
+}
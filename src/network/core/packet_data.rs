@@ -1,22 +1,86 @@
+use crate::network::core::packet_buffer::PacketBuffer;
+use crate::network::types::held_by::HeldBy;
+use crate::network::types::packet_actions::PacketActions;
+use std::borrow::Cow;
 use std::time::Instant;
+use windivert::address::WinDivertAddress;
+use windivert::error::WinDivertError;
 use windivert::layer::NetworkLayer;
 use windivert::packet::WinDivertPacket;
+use windivert_sys::ChecksumFlags;
 
+/// Most packets WinDivert receives or sends in a single call.
+pub const MAX_BATCH_SIZE: usize = 0xFF;
+
+/// Packets handed from the receiving thread to the processing thread in one go.
+pub type PacketBatch = Vec<PacketData>;
+
+/// A captured packet whose bytes can be shared between duplicates, see [`PacketBuffer`].
 #[derive(Clone)]
-pub struct PacketData<'a> {
-    pub packet: WinDivertPacket<'a, NetworkLayer>,
+pub struct Packet {
+    pub address: WinDivertAddress<NetworkLayer>,
+    pub data: PacketBuffer,
+}
+
+impl Packet {
+    /// Borrows the packet in the form WinDivert sends.
+    pub fn as_windivert(&self) -> WinDivertPacket<'_, NetworkLayer> {
+        WinDivertPacket {
+            address: self.address.clone(),
+            data: Cow::Borrowed(&self.data),
+        }
+    }
+
+    pub fn recalculate_checksums(&mut self, flags: ChecksumFlags) -> Result<(), WinDivertError> {
+        let mut packet = WinDivertPacket {
+            address: self.address.clone(),
+            data: Cow::Owned(std::mem::take(self.data.to_mut())),
+        };
+        let result = packet.recalculate_checksums(flags);
+        self.address = packet.address;
+        *self.data.to_mut() = packet.data.into_owned();
+        result
+    }
+}
+
+#[derive(Clone)]
+pub struct PacketData {
+    pub packet: Packet,
     pub arrival_time: Instant,
+    /// What the manipulation modules did to the packet so far.
+    pub actions: PacketActions,
+    /// The modules that held the packet back so far.
+    pub held_by: HeldBy,
+}
+
+impl PacketData {
+    /// Marks the packet as delayed by `module`, which holds it back.
+    pub fn hold(&mut self, module: HeldBy) {
+        self.actions.insert(PacketActions::DELAYED);
+        self.held_by.insert(module);
+    }
 }
 
-impl<'a> From<WinDivertPacket<'a, NetworkLayer>> for PacketData<'a> {
-    fn from(packet: WinDivertPacket<'a, NetworkLayer>) -> Self {
+impl From<Packet> for PacketData {
+    fn from(packet: Packet) -> Self {
         PacketData {
             packet,
             arrival_time: Instant::now(),
+            actions: PacketActions::default(),
+            held_by: HeldBy::default(),
         }
     }
 }
 
+impl From<WinDivertPacket<'_, NetworkLayer>> for PacketData {
+    fn from(packet: WinDivertPacket<'_, NetworkLayer>) -> Self {
+        PacketData::from(Packet {
+            address: packet.address,
+            data: PacketBuffer::from(packet.data.into_owned()),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::network::core::packet_data::PacketData;
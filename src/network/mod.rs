@@ -1,5 +1,6 @@
-mod core;
+pub mod core;
 pub mod modules;
+pub mod packet;
 pub mod processing;
 pub mod types;
-pub(crate) mod utils;
+pub mod utils;
@@ -1,97 +1,199 @@
+use crate::cli::settings::bandwidth::BandwidthOptions;
 use crate::network::core::packet_data::PacketData;
 use crate::network::modules::stats::bandwidth_stats::BandwidthStats;
+use crate::network::types::bit_rate::BitRate;
+use crate::network::types::held_by::HeldBy;
 use log::trace;
 use std::collections::VecDeque;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 const MAX_BUFFER_SIZE: usize = 10 * 1024 * 1024; // 10 MB in bytes
 
-pub fn bandwidth_limiter<'a>(
-    packets: &mut Vec<PacketData<'a>>,
-    buffer: &mut VecDeque<PacketData<'a>>,
-    total_buffer_size: &mut usize,
-    last_send_time: &mut Instant,
-    bandwidth_limit_kbps: usize,
-    stats: &mut BandwidthStats,
-) {
-    let incoming_packet_count = packets.len();
-    stats.storage_packet_count += incoming_packet_count;
-    add_packets_to_buffer(buffer, packets, total_buffer_size);
-    maintain_buffer_size(buffer, total_buffer_size, stats);
+/// Packets held back by the bandwidth limit of one direction.
+pub struct BandwidthQueue {
+    pub packets: VecDeque<PacketData>,
+    /// Bytes of the held packets
+    pub total_size: usize,
+    /// When packets were last sent, from which the bandwidth for the next ones accumulates
+    pub last_send_time: Instant,
+}
 
-    let now = Instant::now();
-    let elapsed = now.duration_since(*last_send_time).as_secs_f64();
-    let bytes_allowed = (((bandwidth_limit_kbps * 1024) as f64) * elapsed) as usize;
+impl Default for BandwidthQueue {
+    fn default() -> Self {
+        BandwidthQueue {
+            packets: VecDeque::new(),
+            total_size: 0,
+            last_send_time: Instant::now(),
+        }
+    }
+}
 
-    let mut bytes_sent = 0;
-    let mut to_send = Vec::new();
+impl BandwidthQueue {
+    pub fn len(&self) -> usize {
+        self.packets.len()
+    }
 
-    while let Some(packet_data) = buffer.front() {
-        let packet_size = packet_data.packet.data.len();
-        if bytes_sent + packet_size > bytes_allowed {
-            break;
+    pub fn is_empty(&self) -> bool {
+        self.packets.is_empty()
+    }
+
+    /// Returns the bytes `limit` allows to send from the last send until `now`.
+    pub fn bytes_allowed(&self, limit: BitRate, now: Instant) -> usize {
+        let elapsed = now.saturating_duration_since(self.last_send_time);
+        (limit.bytes_per_second() * elapsed.as_secs_f64()) as usize
+    }
+
+    /// Removes the packets at the front that fit into `bytes_allowed`, counting `overhead` bytes
+    /// for each packet on top of its size.
+    pub fn take(&mut self, mut bytes_allowed: usize, overhead: usize) -> Vec<PacketData> {
+        let mut packets = Vec::new();
+        while let Some(packet_data) = self.packets.front() {
+            let packet_size = packet_data.packet.data.len();
+            if packet_size + overhead > bytes_allowed {
+                break;
+            }
+            bytes_allowed -= packet_size + overhead;
+            self.total_size -= packet_size;
+            packets.extend(self.packets.pop_front());
         }
-        bytes_sent += packet_size;
-        if let Some(packet) = remove_packet_from_buffer(buffer, total_buffer_size, stats) {
-            to_send.push(packet);
+        packets
+    }
+
+    /// Returns when `limit` allows sending the packet at the front, or `None` if nothing is held
+    /// or the limit is zero, which never allows it.
+    pub fn next_release(&self, limit: BitRate, overhead: usize) -> Option<Instant> {
+        self.packets
+            .front()
+            .filter(|_| !limit.is_zero())
+            .map(|packet_data| {
+                let packet_size = (packet_data.packet.data.len() + overhead) as f64;
+                self.last_send_time
+                    + Duration::from_secs_f64(packet_size / limit.bytes_per_second())
+            })
+    }
+}
+
+/// The packets held back by the bandwidth limits, one queue per direction so a saturated
+/// upload does not hold back downloads.
+#[derive(Default)]
+pub struct BandwidthQueues {
+    pub upload: BandwidthQueue,
+    pub download: BandwidthQueue,
+}
+
+impl BandwidthQueues {
+    pub fn len(&self) -> usize {
+        self.upload.len() + self.download.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.upload.is_empty() && self.download.is_empty()
+    }
+
+    /// Returns the queue of packets going out, or in if `outbound` is false.
+    pub fn queue_mut(&mut self, outbound: bool) -> &mut BandwidthQueue {
+        if outbound {
+            &mut self.upload
+        } else {
+            &mut self.download
         }
     }
 
-    packets.extend(to_send);
+    /// Returns both queues, each with whether it holds outbound packets.
+    pub fn directions(&self) -> [(bool, &BandwidthQueue); 2] {
+        [(true, &self.upload), (false, &self.download)]
+    }
 
-    if bytes_sent > 0 {
-        trace!("Limit: {}, Bytes Allowed {}, Incoming Packets: {}, Packets Sent: {}, Buffer Element Count: {}, Total Buffer Size: {}, Bytes Sent: {}",
-           bandwidth_limit_kbps, bytes_allowed, incoming_packet_count, packets.len(), buffer.len(), total_buffer_size, bytes_sent);
-        stats.record(bytes_sent);
-        *last_send_time = now;
+    pub fn directions_mut(&mut self) -> [(bool, &mut BandwidthQueue); 2] {
+        [(true, &mut self.upload), (false, &mut self.download)]
     }
 }
 
-fn add_packet_to_buffer<'a>(
-    buffer: &mut VecDeque<PacketData<'a>>,
-    packet: PacketData<'a>,
-    total_size: &mut usize,
-) {
-    *total_size += packet.packet.data.len();
-    buffer.push_back(packet);
+pub fn bandwidth_limiter(
+    packets: &mut Vec<PacketData>,
+    queues: &mut BandwidthQueues,
+    options: &BandwidthOptions,
+    stats: &BandwidthStats,
+) -> Vec<PacketData> {
+    let incoming_packet_count = packets.len();
+    let incoming_outbound_count = packets
+        .iter()
+        .filter(|packet_data| packet_data.packet.address.outbound())
+        .count();
+    stats.storage_packet_count.add(incoming_packet_count);
+    add_packets_to_buffer(queues, packets);
+
+    let now = Instant::now();
+    let mut dropped = Vec::new();
+    for (outbound, queue) in queues.directions_mut() {
+        dropped.extend(maintain_buffer_size(queue, stats));
+        let limit = options.limit_for(outbound);
+        // Packets in a direction without a limit are not held back
+        let bytes_allowed = limit.map_or(usize::MAX, |limit| queue.bytes_allowed(limit, now));
+        let to_send = queue.take(bytes_allowed, options.overhead);
+        stats.storage_packet_count.sub(to_send.len());
+        let bytes_sent: usize = to_send
+            .iter()
+            .map(|packet_data| packet_data.packet.data.len())
+            .sum();
+
+        // Incoming packets which did not fit into this send window stay buffered
+        let incoming = if outbound {
+            incoming_outbound_count
+        } else {
+            incoming_packet_count - incoming_outbound_count
+        };
+        for packet_data in queue.packets.iter_mut().rev().take(incoming) {
+            packet_data.hold(HeldBy::BANDWIDTH);
+        }
+
+        if bytes_sent > 0 {
+            trace!("Limit: {:?}, Outbound: {}, Bytes Allowed {}, Incoming Packets: {}, Packets Sent: {}, Buffer Element Count: {}, Total Buffer Size: {}, Bytes Sent: {}",
+               limit, outbound, bytes_allowed, incoming, to_send.len(), queue.len(), queue.total_size, bytes_sent);
+            stats.record(bytes_sent);
+            queue.last_send_time = now;
+        }
+        packets.extend(to_send);
+    }
+    dropped
+}
+
+fn add_packet_to_buffer(queue: &mut BandwidthQueue, packet: PacketData) {
+    queue.total_size += packet.packet.data.len();
+    queue.packets.push_back(packet);
 }
 
-fn add_packets_to_buffer<'a>(
-    buffer: &mut VecDeque<PacketData<'a>>,
-    packets: &mut Vec<PacketData<'a>>,
-    total_size: &mut usize,
-) {
-    while let Some(packet) = packets.pop() {
-        add_packet_to_buffer(buffer, packet, total_size);
+fn add_packets_to_buffer(queues: &mut BandwidthQueues, packets: &mut Vec<PacketData>) {
+    for packet in packets.drain(..) {
+        let queue = queues.queue_mut(packet.packet.address.outbound());
+        add_packet_to_buffer(queue, packet);
     }
 }
 
-fn remove_packet_from_buffer<'a>(
-    buffer: &mut VecDeque<PacketData<'a>>,
-    total_size: &mut usize,
-    stats: &mut BandwidthStats,
-) -> Option<PacketData<'a>> {
-    if let Some(packet) = buffer.pop_front() {
-        *total_size -= packet.packet.data.len();
-        stats.storage_packet_count = stats.storage_packet_count.saturating_sub(1);
+fn remove_packet_from_buffer(
+    queue: &mut BandwidthQueue,
+    stats: &BandwidthStats,
+) -> Option<PacketData> {
+    if let Some(packet) = queue.packets.pop_front() {
+        queue.total_size -= packet.packet.data.len();
+        stats.storage_packet_count.sub(1);
         Some(packet)
     } else {
         None
     }
 }
 
-fn maintain_buffer_size(
-    buffer: &mut VecDeque<PacketData<'_>>,
-    total_size: &mut usize,
-    stats: &mut BandwidthStats,
-) {
-    while *total_size > MAX_BUFFER_SIZE {
-        if remove_packet_from_buffer(buffer, total_size, stats).is_some() {
+fn maintain_buffer_size(queue: &mut BandwidthQueue, stats: &BandwidthStats) -> Vec<PacketData> {
+    let mut dropped = Vec::new();
+    while queue.total_size > MAX_BUFFER_SIZE {
+        if let Some(packet) = remove_packet_from_buffer(queue, stats) {
             // Packet removed from buffer to maintain size limit
+            dropped.push(packet);
         } else {
             break; // No more packets to remove
         }
     }
+    dropped
 }
 
 #[cfg(test)]
@@ -102,38 +204,43 @@ mod tests {
         add_packet_to_buffer, add_packets_to_buffer, bandwidth_limiter, remove_packet_from_buffer,
         MAX_BUFFER_SIZE,
     };
-    use std::collections::VecDeque;
     use std::time::Duration;
     use windivert::layer::NetworkLayer;
     use windivert::packet::WinDivertPacket;
 
     /// Safely creates a dummy packet with a specified length.
     /// Assumes the vector created with the specified length is valid for packet creation.
-    fn create_dummy_packet<'a>(length: usize) -> WinDivertPacket<'a, NetworkLayer> {
+    fn create_dummy_packet(length: usize) -> WinDivertPacket<'static, NetworkLayer> {
         let data = vec![1; length];
         unsafe { WinDivertPacket::<NetworkLayer>::new(data) }
     }
 
+    fn create_outbound_packet(length: usize) -> WinDivertPacket<'static, NetworkLayer> {
+        let mut packet = create_dummy_packet(length);
+        packet.address.set_outbound(true);
+        packet
+    }
+
+    /// Options limiting both directions to `kilobytes` KB/s.
+    fn limited_to(kilobytes: u64) -> BandwidthOptions {
+        BandwidthOptions {
+            limit: Some(BitRate::from_kibibytes(kilobytes)),
+            ..Default::default()
+        }
+    }
+
     #[test]
     fn test_basic_bandwidth_limiting() {
         let mut packets = vec![
             PacketData::from(create_dummy_packet(1000)),
             PacketData::from(create_dummy_packet(1000)),
         ];
-        let mut buffer = VecDeque::new();
-        let total_buffer_size: &mut usize = &mut 0usize;
-        let mut last_send_time = Instant::now() - Duration::from_secs(1);
-        let bandwidth_limit = 1; // 1 KB/s
-        let mut stats = BandwidthStats::new(0.5);
-
-        bandwidth_limiter(
-            &mut packets,
-            &mut buffer,
-            total_buffer_size,
-            &mut last_send_time,
-            bandwidth_limit,
-            &mut stats,
-        );
+        let mut queues = BandwidthQueues::default();
+        queues.download.last_send_time = Instant::now() - Duration::from_secs(1);
+        let options = limited_to(1); // 1 KB/s
+        let stats = BandwidthStats::new(0.5);
+
+        bandwidth_limiter(&mut packets, &mut queues, &options, &stats);
 
         assert!(packets.len() <= 1);
     }
@@ -141,29 +248,25 @@ mod tests {
     #[test]
     fn test_exceeding_buffer_size() {
         let mut packets = Vec::new();
-        let mut buffer = VecDeque::new();
-        let mut total_buffer_size = 0;
+        let mut queues = BandwidthQueues::default();
 
         // Fill the buffer with packets to exceed the max total size
-        while total_buffer_size < MAX_BUFFER_SIZE + 10_000 {
+        while queues.download.total_size < MAX_BUFFER_SIZE + 10_000 {
             let packet = PacketData::from(create_dummy_packet(1000));
-            total_buffer_size += packet.packet.data.len();
-            buffer.push_back(packet);
+            queues.download.total_size += packet.packet.data.len();
+            queues.download.packets.push_back(packet);
         }
-        let mut last_send_time = Instant::now();
-        let bandwidth_limit = 100; // High enough to not limit the test
-        let mut stats = BandwidthStats::new(0.5);
-
-        bandwidth_limiter(
-            &mut packets,
-            &mut buffer,
-            &mut total_buffer_size,
-            &mut last_send_time,
-            bandwidth_limit,
-            &mut stats,
-        );
-
-        let actual_total_size: usize = buffer.iter().map(|p| p.packet.data.len()).sum();
+        let options = limited_to(100); // High enough to not limit the test
+        let stats = BandwidthStats::new(0.5);
+
+        bandwidth_limiter(&mut packets, &mut queues, &options, &stats);
+
+        let actual_total_size: usize = queues
+            .download
+            .packets
+            .iter()
+            .map(|p| p.packet.data.len())
+            .sum();
         assert!(actual_total_size <= MAX_BUFFER_SIZE);
     }
 
@@ -173,20 +276,12 @@ mod tests {
             PacketData::from(create_dummy_packet(1000)),
             PacketData::from(create_dummy_packet(1000)),
         ];
-        let mut buffer = VecDeque::new();
-        let mut total_buffer_size = 0;
-        let mut last_send_time = Instant::now() - Duration::from_secs(1);
-        let bandwidth_limit = 10_000; // 10 MB/s
-        let mut stats = BandwidthStats::new(0.5);
-
-        bandwidth_limiter(
-            &mut packets,
-            &mut buffer,
-            &mut total_buffer_size,
-            &mut last_send_time,
-            bandwidth_limit,
-            &mut stats,
-        );
+        let mut queues = BandwidthQueues::default();
+        queues.download.last_send_time = Instant::now() - Duration::from_secs(1);
+        let options = limited_to(10_000); // 10 MB/s
+        let stats = BandwidthStats::new(0.5);
+
+        bandwidth_limiter(&mut packets, &mut queues, &options, &stats);
 
         assert_eq!(packets.len(), 2);
     }
@@ -197,103 +292,150 @@ mod tests {
             PacketData::from(create_dummy_packet(1000)),
             PacketData::from(create_dummy_packet(1000)),
         ];
-        let mut buffer = VecDeque::new();
-        let mut total_buffer_size = 0;
-        let mut last_send_time = Instant::now();
-        let bandwidth_limit = 0; // 0 KB/s
-        let mut stats = BandwidthStats::new(0.5);
-
-        bandwidth_limiter(
-            &mut packets,
-            &mut buffer,
-            &mut total_buffer_size,
-            &mut last_send_time,
-            bandwidth_limit,
-            &mut stats,
-        );
+        let mut queues = BandwidthQueues::default();
+        let options = limited_to(0); // 0 KB/s
+        let stats = BandwidthStats::new(0.5);
+
+        bandwidth_limiter(&mut packets, &mut queues, &options, &stats);
 
         assert!(packets.is_empty());
-        assert_eq!(buffer.len(), 2);
+        assert_eq!(queues.len(), 2);
     }
 
     #[test]
     fn test_empty_packet_vector() {
         let mut packets = Vec::new();
-        let mut buffer = VecDeque::new();
-        let mut total_buffer_size = 0;
-        let mut last_send_time = Instant::now();
-        let bandwidth_limit = 10_000; // 10 MB/s
-        let mut stats = BandwidthStats::new(0.5);
-
-        bandwidth_limiter(
-            &mut packets,
-            &mut buffer,
-            &mut total_buffer_size,
-            &mut last_send_time,
-            bandwidth_limit,
-            &mut stats,
-        );
+        let mut queues = BandwidthQueues::default();
+        let options = limited_to(10_000); // 10 MB/s
+        let stats = BandwidthStats::new(0.5);
+
+        bandwidth_limiter(&mut packets, &mut queues, &options, &stats);
 
         // Since the packets vector was empty, buffer should remain empty and nothing should be sent
         assert!(packets.is_empty());
-        assert!(buffer.is_empty());
+        assert!(queues.is_empty());
+    }
+
+    #[test]
+    fn test_directions_are_limited_separately() {
+        let mut packets = vec![
+            PacketData::from(create_outbound_packet(1000)),
+            PacketData::from(create_dummy_packet(1000)),
+        ];
+        let mut queues = BandwidthQueues::default();
+        // Uploads are blocked, downloads have no limit
+        let options = BandwidthOptions {
+            upload: Some(BitRate::from_bits_per_second(0)),
+            ..Default::default()
+        };
+        let stats = BandwidthStats::new(0.5);
+
+        bandwidth_limiter(&mut packets, &mut queues, &options, &stats);
+
+        assert_eq!(packets.len(), 1);
+        assert!(!packets[0].packet.address.outbound());
+        assert_eq!(queues.upload.len(), 1);
+        assert!(queues.upload.packets[0].held_by.contains(HeldBy::BANDWIDTH));
+        assert!(queues.download.is_empty());
+        assert_eq!(stats.snapshot().storage_packet_count, 1);
+    }
+
+    #[test]
+    fn test_overhead_counts_against_the_limit() {
+        let send = |overhead: usize| {
+            let mut packets = vec![
+                PacketData::from(create_dummy_packet(500)),
+                PacketData::from(create_dummy_packet(500)),
+            ];
+            let mut queues = BandwidthQueues::default();
+            queues.download.last_send_time = Instant::now() - Duration::from_millis(1010);
+            let options = BandwidthOptions {
+                limit: Some("1KB/s".parse().unwrap()),
+                overhead,
+                ..Default::default()
+            };
+            bandwidth_limiter(
+                &mut packets,
+                &mut queues,
+                &options,
+                &BandwidthStats::new(0.5),
+            );
+            packets.len()
+        };
+        assert_eq!(send(0), 2);
+        assert_eq!(send(38), 1);
     }
 
     #[test]
     fn test_add_packet_to_buffer() {
-        let mut buffer = VecDeque::new();
-        let mut total_size = 0;
+        let mut queue = BandwidthQueue::default();
         let packet = PacketData::from(create_dummy_packet(1000));
 
-        add_packet_to_buffer(&mut buffer, packet.clone(), &mut total_size);
+        add_packet_to_buffer(&mut queue, packet.clone());
 
-        assert_eq!(buffer.len(), 1);
-        assert_eq!(total_size, 1000);
-        assert_eq!(buffer.front().unwrap().packet.data.len(), 1000);
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.total_size, 1000);
+        assert_eq!(queue.packets.front().unwrap().packet.data.len(), 1000);
     }
 
     #[test]
     fn test_add_packets_to_buffer() {
-        let mut buffer = VecDeque::new();
-        let mut total_size = 0;
+        let mut queues = BandwidthQueues::default();
         let mut packets = vec![
             PacketData::from(create_dummy_packet(1000)),
             PacketData::from(create_dummy_packet(2000)),
+            PacketData::from(create_outbound_packet(500)),
         ];
 
-        add_packets_to_buffer(&mut buffer, &mut packets, &mut total_size);
+        add_packets_to_buffer(&mut queues, &mut packets);
 
-        assert_eq!(buffer.len(), 2);
-        assert_eq!(total_size, 3000);
-        assert_eq!(buffer.pop_front().unwrap().packet.data.len(), 2000);
-        assert_eq!(buffer.pop_front().unwrap().packet.data.len(), 1000);
+        assert_eq!(queues.download.len(), 2);
+        assert_eq!(queues.download.total_size, 3000);
+        assert_eq!(queues.upload.total_size, 500);
+        let download = &mut queues.download.packets;
+        assert_eq!(download.pop_front().unwrap().packet.data.len(), 1000);
+        assert_eq!(download.pop_front().unwrap().packet.data.len(), 2000);
+    }
+
+    #[test]
+    fn test_packets_are_released_in_arrival_order() {
+        let mut packets: Vec<_> = (1..=5)
+            .map(|i| PacketData::from(create_dummy_packet(i * 100)))
+            .collect();
+        let mut queues = BandwidthQueues::default();
+        queues.download.last_send_time = Instant::now() - Duration::from_secs(1);
+        let options = limited_to(10_000); // 10 MB/s
+        let stats = BandwidthStats::new(0.5);
+
+        bandwidth_limiter(&mut packets, &mut queues, &options, &stats);
+
+        let sizes: Vec<_> = packets.iter().map(|p| p.packet.data.len()).collect();
+        assert_eq!(sizes, vec![100, 200, 300, 400, 500]);
     }
 
     #[test]
     fn test_remove_packet_from_buffer() {
-        let mut buffer = VecDeque::new();
-        let mut total_size = 0;
+        let mut queue = BandwidthQueue::default();
         let packet = PacketData::from(create_dummy_packet(1000));
-        add_packet_to_buffer(&mut buffer, packet.clone(), &mut total_size);
-        let mut stats = BandwidthStats::new(0.5);
+        add_packet_to_buffer(&mut queue, packet.clone());
+        let stats = BandwidthStats::new(0.5);
 
-        let removed_packet = remove_packet_from_buffer(&mut buffer, &mut total_size, &mut stats);
+        let removed_packet = remove_packet_from_buffer(&mut queue, &stats);
 
         assert_eq!(removed_packet.unwrap().packet.data.len(), 1000);
-        assert_eq!(buffer.len(), 0);
-        assert_eq!(total_size, 0);
+        assert_eq!(queue.len(), 0);
+        assert_eq!(queue.total_size, 0);
     }
 
     #[test]
     fn test_remove_packet_from_empty_buffer() {
-        let mut buffer = VecDeque::new();
-        let mut total_size = 0;
-        let mut stats = BandwidthStats::new(0.5);
+        let mut queue = BandwidthQueue::default();
+        let stats = BandwidthStats::new(0.5);
 
-        let removed_packet = remove_packet_from_buffer(&mut buffer, &mut total_size, &mut stats);
+        let removed_packet = remove_packet_from_buffer(&mut queue, &stats);
 
         assert!(removed_packet.is_none());
-        assert_eq!(buffer.len(), 0);
-        assert_eq!(total_size, 0);
+        assert_eq!(queue.len(), 0);
+        assert_eq!(queue.total_size, 0);
     }
-}
\ No newline at end of file
+}
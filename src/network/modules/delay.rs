@@ -1,14 +1,22 @@
 use crate::network::core::packet_data::PacketData;
 use crate::network::modules::stats::delay_stats::DelayStats;
+use crate::network::types::held_by::HeldBy;
 use std::collections::VecDeque;
 use std::time::Duration;
 
-pub fn delay_packets<'a>(
-    packets: &mut Vec<PacketData<'a>>,
-    storage: &mut VecDeque<PacketData<'a>>,
+pub fn delay_packets(
+    packets: &mut Vec<PacketData>,
+    storage: &mut VecDeque<PacketData>,
     delay: Duration,
-    stats: &mut DelayStats,
+    stats: &DelayStats,
 ) {
+    let previously_delayed = storage.len();
+    if !delay.is_zero() {
+        stats.total_packets.add(packets.len());
+        for packet_data in packets.iter_mut() {
+            packet_data.hold(HeldBy::DELAY);
+        }
+    }
     storage.extend(packets.drain(..));
 
     while let Some(packet_data) = storage.pop_front() {
@@ -19,5 +27,5 @@ pub fn delay_packets<'a>(
             break;
         }
     }
-    stats.delayed_package_count(storage.len())
+    stats.delayed_package_count(previously_delayed, storage.len())
 }
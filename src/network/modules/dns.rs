@@ -0,0 +1,300 @@
+use crate::cli::settings::dns::DnsAction;
+use crate::network::core::packet_data::PacketData;
+use crate::network::modules::stats::dns_stats::DnsStats;
+use crate::network::packet::dns::{DnsMessage, HEADER_LEN, RCODE_NXDOMAIN, RCODE_SERVFAIL};
+use crate::network::packet::{dns, read_u16, IpHeader, PacketError, PacketView, TransportHeader};
+use crate::network::types::held_by::HeldBy;
+use crate::network::types::name_pattern::NamePattern;
+use crate::network::types::packet_actions::PacketActions;
+use log::error;
+use std::collections::VecDeque;
+use std::time::Duration;
+use windivert_sys::ChecksumFlags;
+
+/// Flag bits cleared when rewriting a response: AA, TC, AD and the response code.
+const REWRITE_CLEARED_FLAGS: u16 = 0x0400 | 0x0200 | 0x0020 | 0x000F;
+
+pub fn dns_packets(
+    packets: &mut Vec<PacketData>,
+    storage: &mut VecDeque<PacketData>,
+    names: &[NamePattern],
+    action: DnsAction,
+    delay: Duration,
+    stats: &DnsStats,
+) -> Vec<PacketData> {
+    let previously_delayed = storage.len();
+    let mut passed = Vec::with_capacity(packets.len());
+    let mut dropped = Vec::new();
+    for mut packet_data in packets.drain(..) {
+        let (name, is_response) = match dns_question(&packet_data.packet.data) {
+            Some(question) => question,
+            None => {
+                passed.push(packet_data);
+                continue;
+            }
+        };
+        if !names.is_empty() && !names.iter().any(|pattern| pattern.matches(&name)) {
+            passed.push(packet_data);
+            continue;
+        }
+        if !is_response {
+            stats.record_query(&name);
+            passed.push(packet_data);
+            continue;
+        }
+
+        match action {
+            DnsAction::Delay => {
+                stats.record_affected(&name);
+                packet_data.hold(HeldBy::DNS);
+                storage.push_back(packet_data);
+            }
+            DnsAction::Drop => {
+                stats.record_affected(&name);
+                dropped.push(packet_data);
+            }
+            DnsAction::ServFail | DnsAction::NxDomain => {
+                let rcode = if action == DnsAction::ServFail {
+                    RCODE_SERVFAIL
+                } else {
+                    RCODE_NXDOMAIN
+                };
+                match rewrite_response_code(packet_data.packet.data.to_mut(), rcode) {
+                    Ok(()) => {
+                        stats.record_affected(&name);
+                        if let Err(e) = packet_data
+                            .packet
+                            .recalculate_checksums(ChecksumFlags::new())
+                        {
+                            error!("Error recalculating checksums: {}", e);
+                        }
+                        packet_data.actions.insert(PacketActions::TAMPERED);
+                    }
+                    Err(e) => {
+                        error!("Failed to rewrite DNS response: {}", e);
+                        stats.rewrite_failures.increment();
+                    }
+                }
+                passed.push(packet_data);
+            }
+        }
+    }
+
+    while let Some(packet_data) = storage.pop_front() {
+        if packet_data.arrival_time.elapsed() >= delay {
+            passed.push(packet_data);
+        } else {
+            storage.push_front(packet_data);
+            break;
+        }
+    }
+
+    *packets = passed;
+    stats
+        .delayed_count
+        .adjust(previously_delayed, storage.len());
+    dropped
+}
+
+/// Returns the first question name of a DNS message carried over UDP port 53
+/// and whether the message is a response.
+fn dns_question(data: &[u8]) -> Option<(String, bool)> {
+    let view = PacketView::parse(data).ok()?;
+    if !matches!(view.transport(), TransportHeader::Udp(_)) {
+        return None;
+    }
+    if view.source_port() != Some(dns::PORT) && view.destination_port() != Some(dns::PORT) {
+        return None;
+    }
+    let message = DnsMessage::parse(view.payload()).ok()?;
+    let question = message.first_question().ok()??;
+    Some((question.name, message.is_response()))
+}
+
+/// Rewrites a DNS response into an empty response with the given response code.
+///
+/// Only the first question is kept, the answer, authority and additional sections are removed,
+/// and the UDP and IP length fields are updated. Checksums must be recalculated afterwards.
+pub fn rewrite_response_code(data: &mut Vec<u8>, rcode: u8) -> Result<(), PacketError> {
+    let (is_ipv4, udp_offset, message_offset, message_len) = {
+        let view = PacketView::parse(data)?;
+        let message = DnsMessage::parse(view.payload())?;
+        let message_len = match message.first_question()? {
+            Some(question) => question.end,
+            None => HEADER_LEN,
+        };
+        let udp_offset = view.ip().header_len();
+        (
+            matches!(view.ip(), IpHeader::V4(_)),
+            udp_offset,
+            view.payload_range().start,
+            message_len,
+        )
+    };
+
+    data.truncate(message_offset + message_len);
+    let flags = read_u16(data, message_offset + 2);
+    write_u16(
+        data,
+        message_offset + 2,
+        (flags & !REWRITE_CLEARED_FLAGS) | rcode as u16,
+    );
+    let question_count = if message_len > HEADER_LEN { 1 } else { 0 };
+    write_u16(data, message_offset + 4, question_count);
+    data[message_offset + 6..message_offset + HEADER_LEN].fill(0);
+
+    let udp_len = data.len() - udp_offset;
+    write_u16(data, udp_offset + 4, udp_len as u16);
+    if is_ipv4 {
+        let total_len = data.len();
+        write_u16(data, 2, total_len as u16);
+    } else {
+        let payload_len = data.len() - 40;
+        write_u16(data, 4, payload_len as u16);
+    }
+    Ok(())
+}
+
+fn write_u16(data: &mut [u8], offset: usize, value: u16) {
+    data[offset..offset + 2].copy_from_slice(&value.to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::packet::dns::test_messages::query;
+    use crate::network::packet::protocol;
+    use crate::network::packet::test_packets::{ipv4, ipv6, udp};
+    use windivert::layer::NetworkLayer;
+    use windivert::packet::WinDivertPacket;
+
+    fn response_with_answer(name: &str) -> Vec<u8> {
+        let mut message = query(9, name, true);
+        message[7] = 1; // one answer
+        message.extend_from_slice(&[0xC0, 12, 0, 1, 0, 1, 0, 0, 0, 60, 0, 4, 93, 184, 216, 34]);
+        message
+    }
+
+    fn packet_data(data: Vec<u8>) -> PacketData {
+        unsafe { PacketData::from(WinDivertPacket::<NetworkLayer>::new(data)) }
+    }
+
+    #[test]
+    fn test_rewrite_ipv4_response() {
+        let message = response_with_answer("www.example.com");
+        let mut data = ipv4(protocol::UDP, &udp(53, 40000, &message));
+        rewrite_response_code(&mut data, RCODE_NXDOMAIN).unwrap();
+
+        let view = PacketView::parse(&data).unwrap();
+        let rewritten = DnsMessage::parse(view.payload()).unwrap();
+        assert!(rewritten.is_response());
+        assert_eq!(rewritten.response_code(), RCODE_NXDOMAIN);
+        assert_eq!(rewritten.question_count(), 1);
+        assert_eq!(rewritten.answer_count(), 0);
+        assert_eq!(
+            rewritten.first_question().unwrap().unwrap().name,
+            "www.example.com"
+        );
+        assert_eq!(view.payload().len(), message.len() - 16);
+        assert_eq!(read_u16(&data, 2) as usize, data.len());
+        assert_eq!(read_u16(&data, 24) as usize, data.len() - 20);
+    }
+
+    #[test]
+    fn test_rewrite_ipv6_response() {
+        let message = response_with_answer("example.com");
+        let mut data = ipv6(protocol::UDP, &[], &udp(53, 40000, &message));
+        rewrite_response_code(&mut data, RCODE_SERVFAIL).unwrap();
+
+        let view = PacketView::parse(&data).unwrap();
+        let rewritten = DnsMessage::parse(view.payload()).unwrap();
+        assert_eq!(rewritten.response_code(), RCODE_SERVFAIL);
+        assert_eq!(read_u16(&data, 4) as usize, data.len() - 40);
+        assert_eq!(read_u16(&data, 44) as usize, data.len() - 40);
+    }
+
+    #[test]
+    fn test_drop_matching_responses() {
+        let mut packets = vec![
+            packet_data(ipv4(
+                protocol::UDP,
+                &udp(40000, 53, &query(1, "a.example.com", false)),
+            )),
+            packet_data(ipv4(
+                protocol::UDP,
+                &udp(53, 40000, &query(1, "a.example.com", true)),
+            )),
+            packet_data(ipv4(
+                protocol::UDP,
+                &udp(53, 40000, &query(2, "other.org", true)),
+            )),
+            packet_data(ipv4(protocol::UDP, &udp(1000, 2000, b"not dns"))),
+        ];
+        let names = vec![NamePattern::new("*.example.com").unwrap()];
+        let mut storage = VecDeque::new();
+        let stats = DnsStats::new();
+
+        dns_packets(
+            &mut packets,
+            &mut storage,
+            &names,
+            DnsAction::Drop,
+            Duration::ZERO,
+            &stats,
+        );
+
+        assert_eq!(packets.len(), 3);
+        let names = stats.snapshot().names;
+        let name_stats = names["a.example.com"];
+        assert_eq!(name_stats.queries, 1);
+        assert_eq!(name_stats.affected, 1);
+        assert!(!names.contains_key("other.org"));
+    }
+
+    #[test]
+    fn test_delay_matching_responses() {
+        let mut packets = vec![packet_data(ipv4(
+            protocol::UDP,
+            &udp(53, 40000, &query(1, "example.com", true)),
+        ))];
+        let mut storage = VecDeque::new();
+        let stats = DnsStats::new();
+
+        dns_packets(
+            &mut packets,
+            &mut storage,
+            &[],
+            DnsAction::Delay,
+            Duration::from_secs(60),
+            &stats,
+        );
+
+        assert!(packets.is_empty());
+        assert_eq!(storage.len(), 1);
+        assert_eq!(stats.snapshot().delayed_count, 1);
+    }
+    #[test]
+    fn test_rewritten_responses_are_tampered() {
+        let mut packets = vec![packet_data(ipv4(
+            protocol::UDP,
+            &udp(53, 40000, &response_with_answer("example.com")),
+        ))];
+        let mut storage = VecDeque::new();
+        let stats = DnsStats::new();
+
+        dns_packets(
+            &mut packets,
+            &mut storage,
+            &[],
+            DnsAction::ServFail,
+            Duration::ZERO,
+            &stats,
+        );
+
+        assert_eq!(packets.len(), 1);
+        assert!(packets[0].actions.contains(PacketActions::TAMPERED));
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.names["example.com"].affected, 1);
+        assert_eq!(snapshot.rewrite_failures, 0);
+    }
+}
@@ -1,27 +1,70 @@
+use crate::cli::settings::drop::DropCurve;
 use crate::network::core::packet_data::PacketData;
 use crate::network::modules::stats::drop_stats::DropStats;
 use crate::network::types::probability::Probability;
+use crate::network::utils::random;
 use rand::Rng;
 
+/// Drops packets with `drop_probability`. If a `capacity` in KB/s is given, the probability
+/// instead follows `curve` based on the throughput offered to this module.
+/// Returns the dropped packets.
 pub fn drop_packets(
     packets: &mut Vec<PacketData>,
     drop_probability: Probability,
-    stats: &mut DropStats,
-) {
-    let mut rng = rand::thread_rng();
+    capacity: Option<usize>,
+    curve: DropCurve,
+    stats: &DropStats,
+) -> Vec<PacketData> {
+    let mut rng = random::rng();
 
-    // We use retain with a side effect: recording the drop stats
-    packets.retain(|_| {
-        let drop = rng.random::<f64>() < drop_probability.value();
+    let offered_bytes = packets
+        .iter()
+        .map(|packet_data| packet_data.packet.data.len())
+        .sum();
+    stats.record_throughput(offered_bytes);
+
+    let effective_probability = match capacity {
+        Some(capacity) => congestion_probability(
+            drop_probability,
+            curve,
+            stats.recent_throughput() / capacity.max(1) as f64,
+        ),
+        None => drop_probability.value(),
+    };
+    stats.effective_probability.set(effective_probability);
+
+    let (dropped, passed): (Vec<_>, Vec<_>) = packets.drain(..).partition(|_| {
+        let drop = rng.random::<f64>() < effective_probability;
         stats.record(drop);
-        !drop
+        drop
     });
+    *packets = passed;
+    dropped
+}
+
+/// Returns the drop probability for a link at `utilization` (throughput divided by capacity).
+fn congestion_probability(max_probability: Probability, curve: DropCurve, utilization: f64) -> f64 {
+    let max_probability = max_probability.value();
+    let probability = match curve {
+        DropCurve::Linear => max_probability * utilization.min(1.0),
+        DropCurve::Red => {
+            if utilization < 0.5 {
+                0.0
+            } else if utilization < 1.0 {
+                max_probability * (utilization - 0.5) / 0.5
+            } else {
+                max_probability + (1.0 - max_probability) * (utilization - 1.0)
+            }
+        }
+    };
+    probability.clamp(0.0, 1.0)
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::cli::settings::drop::DropCurve;
     use crate::network::core::packet_data::PacketData;
-    use crate::network::modules::drop::drop_packets;
+    use crate::network::modules::drop::{congestion_probability, drop_packets};
     use crate::network::modules::stats::drop_stats::DropStats;
     use crate::network::types::probability::Probability;
     use windivert::layer::NetworkLayer;
@@ -33,13 +76,51 @@ mod tests {
             let mut packets = vec![PacketData::from(WinDivertPacket::<NetworkLayer>::new(
                 vec![1, 2, 3],
             ))];
-            let mut drop_stats = DropStats::new(0.3);
+            let drop_stats = DropStats::new(0.3);
             drop_packets(
                 &mut packets,
                 Probability::new(1.0).unwrap(),
-                &mut drop_stats,
+                None,
+                DropCurve::Linear,
+                &drop_stats,
             );
             assert!(packets.is_empty())
         }
     }
+
+    #[test]
+    fn test_idle_link_does_not_drop() {
+        unsafe {
+            let mut packets = vec![PacketData::from(WinDivertPacket::<NetworkLayer>::new(
+                vec![1, 2, 3],
+            ))];
+            let drop_stats = DropStats::new(0.3);
+            drop_packets(
+                &mut packets,
+                Probability::new(1.0).unwrap(),
+                Some(1000),
+                DropCurve::Linear,
+                &drop_stats,
+            );
+            assert_eq!(packets.len(), 1);
+            assert_eq!(drop_stats.snapshot().effective_probability, 0.0);
+        }
+    }
+
+    #[test]
+    fn test_linear_curve() {
+        let max = Probability::new(0.2).unwrap();
+        assert_eq!(congestion_probability(max, DropCurve::Linear, 0.0), 0.0);
+        assert!((congestion_probability(max, DropCurve::Linear, 0.5) - 0.1).abs() < 1e-9);
+        assert_eq!(congestion_probability(max, DropCurve::Linear, 3.0), 0.2);
+    }
+
+    #[test]
+    fn test_red_curve() {
+        let max = Probability::new(0.2).unwrap();
+        assert_eq!(congestion_probability(max, DropCurve::Red, 0.4), 0.0);
+        assert!((congestion_probability(max, DropCurve::Red, 0.75) - 0.1).abs() < 1e-9);
+        assert!((congestion_probability(max, DropCurve::Red, 1.5) - 0.6).abs() < 1e-9);
+        assert_eq!(congestion_probability(max, DropCurve::Red, 2.5), 1.0);
+    }
 }
@@ -1,6 +1,8 @@
 use crate::network::core::packet_data::PacketData;
 use crate::network::modules::stats::duplicate_stats::DuplicateStats;
+use crate::network::types::packet_actions::PacketActions;
 use crate::network::types::probability::Probability;
+use crate::network::utils::random;
 use rand::Rng;
 use std::vec::Vec;
 
@@ -8,15 +10,17 @@ pub fn duplicate_packets(
     packets: &mut Vec<PacketData>,
     count: usize,
     probability: Probability,
-    stats: &mut DuplicateStats,
+    stats: &DuplicateStats,
 ) {
-    let mut rng = rand::thread_rng();
+    let mut rng = random::rng();
     let mut duplicate_packets = Vec::with_capacity(packets.len() * count);
 
-    for packet_data in packets.iter() {
+    for packet_data in packets.iter_mut() {
         if rng.random::<f64>() < probability.value() {
+            packet_data.actions.insert(PacketActions::DUPLICATED);
             for _ in 1..=count {
-                duplicate_packets.push(PacketData::from(packet_data.packet.clone()));
+                // Duplicates share the arrival time, so their latency counts from the original
+                duplicate_packets.push(packet_data.clone());
             }
             stats.record(1 + count);
         } else {
@@ -43,9 +47,9 @@ mod tests {
             ))];
             let original_len = original_packets.len();
             let mut packets = original_packets.clone();
-            let mut stats = DuplicateStats::new(0.05);
+            let stats = DuplicateStats::new(0.05);
 
-            duplicate_packets(&mut packets, 3, Probability::new(1.0).unwrap(), &mut stats);
+            duplicate_packets(&mut packets, 3, Probability::new(1.0).unwrap(), &stats);
 
             // Ensure three times as many packets
             assert_eq!(packets.len(), original_len * 4);
@@ -56,6 +60,15 @@ mod tests {
                     assert_eq!(packet_data.packet.data[..], [1, 2, 3]);
                 }
             }
+
+            // Duplicates share the original's bytes until one of them is modified
+            assert!(packets
+                .iter()
+                .all(|packet_data| packet_data.packet.data.as_ptr()
+                    == packets[0].packet.data.as_ptr()));
+            packets[1].packet.data.to_mut()[0] = 9;
+            assert_eq!(packets[0].packet.data[..], [1, 2, 3]);
+            assert_eq!(packets[1].packet.data[..], [9, 2, 3]);
         }
     }
 }
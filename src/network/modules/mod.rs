@@ -1,8 +1,11 @@
 pub mod bandwidth;
 pub mod delay;
+pub mod dns;
 pub mod drop;
 pub mod duplicate;
 pub mod reorder;
 pub mod stats;
 pub mod tamper;
 pub mod throttle;
+pub mod topology;
+pub mod trace;
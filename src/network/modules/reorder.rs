@@ -1,38 +1,43 @@
 use crate::network::core::packet_data::PacketData;
 use crate::network::modules::stats::reorder_stats::ReorderStats;
 use crate::network::types::delayed_packet::DelayedPacket;
+use crate::network::types::held_by::HeldBy;
 use crate::network::types::probability::Probability;
+use crate::network::utils::random;
 use log::{error, warn};
+use rand::Rng;
 use std::collections::BinaryHeap;
 use std::time::{Duration, Instant};
 
-pub fn reorder_packets<'a>(
-    packets: &mut Vec<PacketData<'a>>,
-    storage: &mut BinaryHeap<DelayedPacket<'a>>,
+pub fn reorder_packets(
+    packets: &mut Vec<PacketData>,
+    storage: &mut BinaryHeap<DelayedPacket>,
     reorder_probability: Probability,
     max_delay: Duration,
-    stats: &mut ReorderStats,
+    stats: &ReorderStats,
 ) {
-    if max_delay.as_millis() == 0 {
+    if max_delay.is_zero() {
         warn!("Max delay cannot be zero. Skipping packet reordering.");
         return;
     }
 
+    let previously_delayed = storage.len();
     let mut skipped_packets = Vec::new(); // Temporary storage for packets to be skipped
 
-    for packet in packets.drain(..) {
-        if rand::random::<f64>() >= reorder_probability.value() {
+    for mut packet in packets.drain(..) {
+        if random::rng().random::<f64>() >= reorder_probability.value() {
             skipped_packets.push(packet); // Store skipped packets
             stats.record(false);
             continue;
         }
 
-        let delay = Duration::from_millis((rand::random::<u128>() % max_delay.as_millis()) as u64);
+        let delay =
+            Duration::from_nanos((random::rng().random::<u128>() % max_delay.as_nanos()) as u64);
+        packet.hold(HeldBy::REORDER);
         let delayed_packet = DelayedPacket::new(packet, delay);
         storage.push(delayed_packet);
         stats.record(true);
     }
-    stats.delayed_packets = storage.len();
 
     packets.append(&mut skipped_packets); // Append skipped packets back to the original packets vector
 
@@ -49,4 +54,7 @@ pub fn reorder_packets<'a>(
             break;
         }
     }
+    stats
+        .delayed_packets
+        .adjust(previously_delayed, storage.len());
 }
@@ -0,0 +1,187 @@
+use crate::network::modules::stats::util::counter::{Counter, CounterMap};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Packets dropped per module, and the packets held across a reset, so every packet that was
+/// received can be accounted for.
+#[derive(Debug, Default)]
+pub struct AccountingStats {
+    dropped: CounterMap<Counter>,
+    /// Packets the modules held when the statistics were last reset
+    carried_over: Counter,
+}
+
+/// The accounting statistics at one point in time.
+#[derive(Debug, Clone, Default)]
+pub struct AccountingSnapshot {
+    pub dropped: BTreeMap<String, usize>,
+    pub carried_over: usize,
+}
+
+impl AccountingStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_dropped(&self, module: &str, count: usize) {
+        if count > 0 {
+            self.dropped.record(module, |dropped| dropped.add(count));
+        }
+    }
+
+    pub fn snapshot(&self) -> AccountingSnapshot {
+        AccountingSnapshot {
+            dropped: self.dropped.collect(Counter::get).into_iter().collect(),
+            carried_over: self.carried_over.get(),
+        }
+    }
+
+    /// Forgets the dropped packets. The `queued` packets the modules still hold are counted as
+    /// carried over, as they were received before the reset.
+    pub fn reset(&self, queued: usize) {
+        self.dropped.clear();
+        self.carried_over.reset();
+        self.carried_over.add(queued);
+    }
+}
+
+/// Where the packets of a run, or of an interval of it, went.
+///
+/// Every packet that came in, was carried over from before or created by duplication, was either
+/// sent, dropped, discarded or is still queued:
+/// `carried_over + received + duplicated = sent + dropped + discarded + queued`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PacketAccounting {
+    /// Packets held by the modules at the start
+    pub carried_over: usize,
+    pub received: usize,
+    /// Extra packets created by the duplicate module
+    pub duplicated: usize,
+    pub sent: usize,
+    /// Packets dropped, per module
+    pub dropped: BTreeMap<String, usize>,
+    /// Packets given up by modules that were stopped, paused or turned off
+    pub discarded: usize,
+    /// Packets held at the end, per module
+    pub queued: BTreeMap<String, usize>,
+}
+
+impl PacketAccounting {
+    pub fn total_dropped(&self) -> usize {
+        self.dropped.values().sum()
+    }
+
+    pub fn total_queued(&self) -> usize {
+        self.queued.values().sum()
+    }
+
+    /// Returns the number of packets that came in but are not accounted for, or a negative
+    /// number if more packets left than came in. Zero when every packet is accounted for.
+    pub fn imbalance(&self) -> isize {
+        let incoming = self.carried_over + self.received + self.duplicated;
+        let outgoing = self.sent + self.total_dropped() + self.discarded + self.total_queued();
+        incoming as isize - outgoing as isize
+    }
+
+    /// Returns the accounting of the interval since `earlier`, which was taken from the same
+    /// statistics. Counters reset in between count from zero.
+    pub fn since(&self, earlier: &PacketAccounting) -> PacketAccounting {
+        let difference = |current: usize, earlier: usize| {
+            if current >= earlier {
+                current - earlier
+            } else {
+                current
+            }
+        };
+        PacketAccounting {
+            carried_over: earlier.total_queued(),
+            received: difference(self.received, earlier.received),
+            duplicated: difference(self.duplicated, earlier.duplicated),
+            sent: difference(self.sent, earlier.sent),
+            dropped: self
+                .dropped
+                .iter()
+                .map(|(module, dropped)| {
+                    let earlier = earlier.dropped.get(module).copied().unwrap_or(0);
+                    (module.clone(), difference(*dropped, earlier))
+                })
+                .filter(|(_, dropped)| *dropped > 0)
+                .collect(),
+            discarded: difference(self.discarded, earlier.discarded),
+            queued: self.queued.clone(),
+        }
+    }
+}
+
+impl fmt::Display for PacketAccounting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let per_module = |counts: &BTreeMap<String, usize>| {
+            counts
+                .iter()
+                .filter(|(_, count)| **count > 0)
+                .map(|(module, count)| format!("{}: {}", module, count))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        write!(
+            f,
+            "Received: {}, Duplicated: {}, Sent: {}, Dropped: {}",
+            self.received,
+            self.duplicated,
+            self.sent,
+            self.total_dropped()
+        )?;
+        if self.total_dropped() > 0 {
+            write!(f, " ({})", per_module(&self.dropped))?;
+        }
+        write!(f, ", Discarded: {}", self.discarded)?;
+        write!(f, ", Queued: {}", self.total_queued())?;
+        if self.total_queued() > 0 {
+            write!(f, " ({})", per_module(&self.queued))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accounting(received: usize, sent: usize, dropped: usize, queued: usize) -> PacketAccounting {
+        PacketAccounting {
+            received,
+            sent,
+            dropped: BTreeMap::from([("drop".to_string(), dropped)]),
+            queued: BTreeMap::from([("delay".to_string(), queued)]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_interval_carries_over_queued_packets() {
+        let earlier = accounting(10, 5, 1, 4);
+        let current = PacketAccounting {
+            duplicated: 2,
+            ..accounting(20, 14, 3, 5)
+        };
+        assert_eq!(earlier.imbalance(), 0);
+        assert_eq!(current.imbalance(), 0);
+
+        let interval = current.since(&earlier);
+        assert_eq!(interval.carried_over, 4);
+        assert_eq!((interval.received, interval.sent), (10, 9));
+        assert_eq!(interval.total_dropped(), 2);
+        assert_eq!(interval.imbalance(), 0);
+        assert_eq!(
+            interval.to_string(),
+            "Received: 10, Duplicated: 2, Sent: 9, Dropped: 2 (drop: 2), Discarded: 0, \
+             Queued: 5 (delay: 5)"
+        );
+    }
+
+    #[test]
+    fn test_missing_packets_are_an_imbalance() {
+        assert_eq!(accounting(10, 5, 1, 0).imbalance(), 4);
+        assert_eq!(accounting(10, 12, 0, 0).imbalance(), -2);
+    }
+}
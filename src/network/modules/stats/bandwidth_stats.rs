@@ -1,40 +1,50 @@
-use crate::network::modules::stats::util::ewma::Ewma;
-use std::time::{Duration, Instant};
+use crate::network::modules::stats::util::counter::{Counter, Gauge};
+use crate::network::modules::stats::util::throughput::ThroughputMeter;
+use std::time::Duration;
 
 pub struct BandwidthStats {
-    pub(crate) storage_packet_count: usize,
-    pub(crate) total_byte_count: usize,
-    ewma: Ewma,
-    recent_byte_sent: usize,
-    recent_timer: Instant,
-    update_interval: Duration,
+    pub(crate) storage_packet_count: Gauge,
+    pub(crate) total_byte_count: Counter,
+    throughput: ThroughputMeter,
+}
+
+/// The bandwidth statistics at one point in time.
+#[derive(Debug, Clone, Default)]
+pub struct BandwidthSnapshot {
+    pub storage_packet_count: usize,
+    pub total_byte_count: usize,
+    /// The throughput sent by the bandwidth module in KB/s
+    pub recent_throughput: f64,
 }
 
 impl BandwidthStats {
     pub fn new(alpha: f64) -> Self {
         BandwidthStats {
-            storage_packet_count: 0,
-            total_byte_count: 0,
-            ewma: Ewma::new(alpha),
-            recent_byte_sent: 0,
-            recent_timer: Instant::now(),
-            update_interval: Duration::from_millis(100),
+            storage_packet_count: Gauge::default(),
+            total_byte_count: Counter::default(),
+            throughput: ThroughputMeter::new(alpha, Duration::from_millis(100)),
         }
     }
 
-    pub fn record(&mut self, bytes_sent: usize) {
-        self.total_byte_count += bytes_sent;
-        self.recent_byte_sent += bytes_sent;
-        if self.recent_timer.elapsed() >= self.update_interval {
-            self.ewma.update(
-                (self.recent_byte_sent as f64 / 1024f64) / self.update_interval.as_secs_f64(),
-            );
-            self.recent_byte_sent = 0;
-            self.recent_timer = Instant::now();
-        }
+    pub fn record(&self, bytes_sent: usize) {
+        self.total_byte_count.add(bytes_sent);
+        self.throughput.record(bytes_sent);
     }
 
     pub fn recent_throughput(&self) -> f64 {
-        self.ewma.get().unwrap_or(0.0)
+        self.throughput.throughput()
+    }
+
+    pub fn snapshot(&self) -> BandwidthSnapshot {
+        BandwidthSnapshot {
+            storage_packet_count: self.storage_packet_count.get(),
+            total_byte_count: self.total_byte_count.get(),
+            recent_throughput: self.recent_throughput(),
+        }
+    }
+
+    pub fn reset(&self) {
+        self.total_byte_count.reset();
+        self.throughput.reset();
     }
 }
@@ -1,5 +1,16 @@
+use crate::network::modules::stats::util::counter::{Counter, Gauge};
+
 pub struct DelayStats {
-    pub(crate) delayed_package_count: usize,
+    /// Packets held back by the delay
+    pub(crate) total_packets: Counter,
+    pub(crate) delayed_package_count: Gauge,
+}
+
+/// The delay statistics at one point in time.
+#[derive(Debug, Clone, Default)]
+pub struct DelaySnapshot {
+    pub total_packets: usize,
+    pub delayed_package_count: usize,
 }
 
 impl Default for DelayStats {
@@ -11,11 +22,24 @@ impl Default for DelayStats {
 impl DelayStats {
     pub fn new() -> Self {
         DelayStats {
-            delayed_package_count: 0,
+            total_packets: Counter::default(),
+            delayed_package_count: Gauge::default(),
+        }
+    }
+
+    /// Records that a delay queue changed from `previous` to `current` packets.
+    pub fn delayed_package_count(&self, previous: usize, current: usize) {
+        self.delayed_package_count.adjust(previous, current);
+    }
+
+    pub fn snapshot(&self) -> DelaySnapshot {
+        DelaySnapshot {
+            total_packets: self.total_packets.get(),
+            delayed_package_count: self.delayed_package_count.get(),
         }
     }
 
-    pub fn delayed_package_count(&mut self, value: usize) {
-        self.delayed_package_count = value;
+    pub fn reset(&self) {
+        self.total_packets.reset();
     }
 }
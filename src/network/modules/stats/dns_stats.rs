@@ -0,0 +1,152 @@
+use crate::network::modules::stats::util::counter::{Counter, CounterMap, Gauge};
+use std::collections::HashMap;
+
+/// Counters for a single DNS query name.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DnsNameStats {
+    /// Number of matching queries seen
+    pub queries: usize,
+    /// Number of matching responses that were delayed, dropped or rewritten
+    pub affected: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct DnsNameCounters {
+    queries: Counter,
+    affected: Counter,
+}
+
+/// Most DNS names counted separately. Without a name filter every name queried is counted, so
+/// the names after these are counted together under
+/// [`OTHER_NAME`](crate::network::modules::stats::util::counter::OTHER_NAME).
+pub const MAX_DNS_NAMES: usize = 1000;
+
+pub struct DnsStats {
+    names: CounterMap<DnsNameCounters>,
+    pub(crate) delayed_count: Gauge,
+    /// Responses that could not be rewritten and were passed on unchanged
+    pub(crate) rewrite_failures: Counter,
+}
+
+/// The DNS statistics at one point in time.
+#[derive(Debug, Clone, Default)]
+pub struct DnsSnapshot {
+    pub names: HashMap<String, DnsNameStats>,
+    pub delayed_count: usize,
+    pub rewrite_failures: usize,
+}
+
+impl Default for DnsStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DnsStats {
+    pub fn new() -> Self {
+        DnsStats {
+            names: CounterMap::with_max_names(MAX_DNS_NAMES),
+            delayed_count: Gauge::default(),
+            rewrite_failures: Counter::default(),
+        }
+    }
+
+    pub fn record_query(&self, name: &str) {
+        self.names
+            .record(name, |counters| counters.queries.increment());
+    }
+
+    pub fn record_affected(&self, name: &str) {
+        self.names
+            .record(name, |counters| counters.affected.increment());
+    }
+
+    pub fn snapshot(&self) -> DnsSnapshot {
+        DnsSnapshot {
+            names: self.names.collect(|counters| DnsNameStats {
+                queries: counters.queries.get(),
+                affected: counters.affected.get(),
+            }),
+            delayed_count: self.delayed_count.get(),
+            rewrite_failures: self.rewrite_failures.get(),
+        }
+    }
+
+    pub fn reset(&self) {
+        self.names.clear();
+        self.rewrite_failures.reset();
+    }
+}
+
+impl DnsSnapshot {
+    pub fn total_affected(&self) -> usize {
+        self.names.values().map(|stats| stats.affected).sum()
+    }
+
+    /// Returns up to `count` names, most affected first.
+    pub fn most_affected(&self, count: usize) -> Vec<(&str, DnsNameStats)> {
+        let mut names: Vec<(&str, DnsNameStats)> = self
+            .names
+            .iter()
+            .map(|(name, stats)| (name.as_str(), *stats))
+            .collect();
+        names.sort_by(|a, b| {
+            b.1.affected
+                .cmp(&a.1.affected)
+                .then(b.1.queries.cmp(&a.1.queries))
+                .then(a.0.cmp(b.0))
+        });
+        names.truncate(count);
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::modules::stats::util::counter::OTHER_NAME;
+
+    #[test]
+    fn test_most_affected() {
+        let stats = DnsStats::new();
+        stats.record_query("a.example.com");
+        stats.record_query("b.example.com");
+        stats.record_affected("b.example.com");
+        stats.record_affected("b.example.com");
+        stats.record_affected("c.example.com");
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.total_affected(), 3);
+        let top = snapshot.most_affected(2);
+        assert_eq!(top.len(), 2);
+        assert_eq!(top[0].0, "b.example.com");
+        assert_eq!(
+            top[0].1,
+            DnsNameStats {
+                queries: 1,
+                affected: 2
+            }
+        );
+        assert_eq!(top[1].0, "c.example.com");
+    }
+
+    #[test]
+    fn test_names_beyond_limit_are_counted_as_other() {
+        let stats = DnsStats::new();
+        for index in 0..MAX_DNS_NAMES + 5 {
+            stats.record_query(&format!("host{}.example.com", index));
+        }
+        stats.record_affected("late.example.com");
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.names.len(), MAX_DNS_NAMES + 1);
+        assert_eq!(
+            snapshot.names[OTHER_NAME],
+            DnsNameStats {
+                queries: 5,
+                affected: 1
+            }
+        );
+        assert_eq!(snapshot.names["host0.example.com"].queries, 1);
+    }
+}
@@ -0,0 +1,75 @@
+use crate::network::modules::stats::util::counter::{Counter, CounterMap};
+use std::collections::BTreeMap;
+
+/// Packets a module was holding when it had to give them up, because processing stopped, was
+/// paused or the module was turned off.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DrainCounts {
+    /// Packets sent, either right away or at the time the module would have released them
+    pub flushed: usize,
+    /// Packets discarded
+    pub discarded: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct DrainCounters {
+    flushed: Counter,
+    discarded: Counter,
+}
+
+#[derive(Debug, Default)]
+pub struct DrainStats {
+    modules: CounterMap<DrainCounters>,
+}
+
+/// The drain statistics at one point in time.
+#[derive(Debug, Clone, Default)]
+pub struct DrainSnapshot {
+    /// Counts per module name, for the modules that had packets drained
+    pub modules: BTreeMap<String, DrainCounts>,
+}
+
+impl DrainStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_flushed(&self, module: &str, count: usize) {
+        self.modules
+            .record(module, |counters| counters.flushed.add(count));
+    }
+
+    pub fn record_discarded(&self, module: &str, count: usize) {
+        self.modules
+            .record(module, |counters| counters.discarded.add(count));
+    }
+
+    pub fn snapshot(&self) -> DrainSnapshot {
+        DrainSnapshot {
+            modules: self
+                .modules
+                .collect(|counters| DrainCounts {
+                    flushed: counters.flushed.get(),
+                    discarded: counters.discarded.get(),
+                })
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    pub fn reset(&self) {
+        self.modules.clear();
+    }
+}
+
+impl DrainSnapshot {
+    /// Returns the counts summed over all modules.
+    pub fn total(&self) -> DrainCounts {
+        self.modules
+            .values()
+            .fold(DrainCounts::default(), |total, counts| DrainCounts {
+                flushed: total.flushed + counts.flushed,
+                discarded: total.discarded + counts.discarded,
+            })
+    }
+}
@@ -1,24 +1,43 @@
+use crate::network::modules::stats::util::counter::{AtomicF64, Counter};
 use crate::network::modules::stats::util::ewma::Ewma;
+use crate::network::modules::stats::util::throughput::ThroughputMeter;
+use std::time::Duration;
 
 pub struct DropStats {
+    pub(crate) total_packets: Counter,
+    pub(crate) total_dropped: Counter,
+    /// The drop probability applied to the most recent packets
+    pub(crate) effective_probability: AtomicF64,
+    ewma: Ewma,
+    throughput: ThroughputMeter,
+}
+
+/// The drop statistics at one point in time.
+#[derive(Debug, Clone, Default)]
+pub struct DropSnapshot {
     pub total_packets: usize,
     pub total_dropped: usize,
-    ewma: Ewma,
+    pub effective_probability: f64,
+    pub recent_drop_rate: f64,
+    /// The throughput offered to the drop module in KB/s
+    pub recent_throughput: f64,
 }
 
 impl DropStats {
     pub fn new(alpha: f64) -> Self {
         Self {
-            total_packets: 0,
-            total_dropped: 0,
+            total_packets: Counter::default(),
+            total_dropped: Counter::default(),
+            effective_probability: AtomicF64::new(0.0),
             ewma: Ewma::new(alpha),
+            throughput: ThroughputMeter::new(0.3, Duration::from_millis(100)),
         }
     }
 
-    pub fn record(&mut self, dropped: bool) {
-        self.total_packets += 1;
+    pub fn record(&self, dropped: bool) {
+        self.total_packets.increment();
         if dropped {
-            self.total_dropped += 1;
+            self.total_dropped.increment();
         }
 
         // Update the EWMA with the new drop status (1.0 if dropped, 0.0 if not)
@@ -26,6 +45,35 @@ impl DropStats {
         self.ewma.update(current_drop_rate);
     }
 
+    /// Records the bytes offered to the drop module, before any packets are dropped.
+    pub fn record_throughput(&self, bytes: usize) {
+        self.throughput.record(bytes);
+    }
+
+    /// The throughput offered to the drop module in KB/s.
+    pub fn recent_throughput(&self) -> f64 {
+        self.throughput.throughput()
+    }
+
+    pub fn snapshot(&self) -> DropSnapshot {
+        DropSnapshot {
+            total_packets: self.total_packets.get(),
+            total_dropped: self.total_dropped.get(),
+            effective_probability: self.effective_probability.get(),
+            recent_drop_rate: self.ewma.get().unwrap_or(0.0),
+            recent_throughput: self.recent_throughput(),
+        }
+    }
+
+    pub fn reset(&self) {
+        self.total_packets.reset();
+        self.total_dropped.reset();
+        self.ewma.reset();
+        self.throughput.reset();
+    }
+}
+
+impl DropSnapshot {
     pub fn total_drop_rate(&self) -> f64 {
         if self.total_packets == 0 {
             0.0
@@ -33,8 +81,4 @@ impl DropStats {
             self.total_dropped as f64 / self.total_packets as f64
         }
     }
-
-    pub fn recent_drop_rate(&self) -> f64 {
-        self.ewma.get().unwrap_or(0.0)
-    }
 }
@@ -1,28 +1,53 @@
+use crate::network::modules::stats::util::counter::Counter;
 use crate::network::modules::stats::util::ewma::Ewma;
 
 pub struct DuplicateStats {
-    pub(crate) incoming_packet_count: usize,
-    pub(crate) outgoing_packet_count: usize,
+    pub(crate) incoming_packet_count: Counter,
+    pub(crate) outgoing_packet_count: Counter,
     ewma: Ewma,
 }
 
+/// The duplicate statistics at one point in time.
+#[derive(Debug, Clone, Default)]
+pub struct DuplicateSnapshot {
+    pub incoming_packet_count: usize,
+    pub outgoing_packet_count: usize,
+    pub recent_duplication_multiplier: f64,
+}
+
 impl DuplicateStats {
     pub fn new(alpha: f64) -> Self {
         DuplicateStats {
-            incoming_packet_count: 0,
-            outgoing_packet_count: 0,
+            incoming_packet_count: Counter::default(),
+            outgoing_packet_count: Counter::default(),
             ewma: Ewma::new(alpha),
         }
     }
 
-    pub fn record(&mut self, outgoing_count: usize) {
-        self.incoming_packet_count += 1;
-        self.outgoing_packet_count += outgoing_count;
+    pub fn record(&self, outgoing_count: usize) {
+        self.incoming_packet_count.increment();
+        self.outgoing_packet_count.add(outgoing_count);
 
         let current_duplication_multiplier = outgoing_count as f64;
         self.ewma.update(current_duplication_multiplier);
     }
 
+    pub fn snapshot(&self) -> DuplicateSnapshot {
+        DuplicateSnapshot {
+            incoming_packet_count: self.incoming_packet_count.get(),
+            outgoing_packet_count: self.outgoing_packet_count.get(),
+            recent_duplication_multiplier: self.ewma.get().unwrap_or(1.0),
+        }
+    }
+
+    pub fn reset(&self) {
+        self.incoming_packet_count.reset();
+        self.outgoing_packet_count.reset();
+        self.ewma.reset();
+    }
+}
+
+impl DuplicateSnapshot {
     pub fn total_duplication_multiplier(&self) -> f64 {
         if self.outgoing_packet_count == 0 {
             1.0
@@ -30,8 +55,4 @@ impl DuplicateStats {
             self.outgoing_packet_count as f64 / self.incoming_packet_count as f64
         }
     }
-
-    pub fn recent_duplication_multiplier(&self) -> f64 {
-        self.ewma.get().unwrap_or(1.0)
-    }
 }
@@ -0,0 +1,158 @@
+use crate::network::core::packet_data::PacketData;
+use crate::network::packet::PacketView;
+use crate::network::types::packet_actions::PacketActions;
+use std::collections::VecDeque;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+/// Number of leading packet bytes kept for the hex dump of an inspected packet.
+pub const MAX_CAPTURED_BYTES: usize = 512;
+
+/// A packet as seen by the inspector after it left the manipulation modules.
+#[derive(Debug, Clone)]
+pub struct InspectedPacket {
+    /// Increasing number identifying the record, used by readers to fetch only new records
+    pub sequence: u64,
+    /// Arrival time of the packet relative to the start of the inspector
+    pub time: Duration,
+    pub outbound: bool,
+    pub source: Option<IpAddr>,
+    pub destination: Option<IpAddr>,
+    pub protocol: Option<u8>,
+    pub source_port: Option<u16>,
+    pub destination_port: Option<u16>,
+    /// Full length of the packet, which may exceed the captured `data`
+    pub length: usize,
+    pub actions: PacketActions,
+    pub data: Vec<u8>,
+}
+
+/// Bounded ring buffer of the most recently processed packets.
+/// Recording only happens while `enabled` is set, so it costs nothing when no one is looking.
+pub struct InspectorStats {
+    pub enabled: bool,
+    capacity: usize,
+    next_sequence: u64,
+    started: Instant,
+    packets: VecDeque<InspectedPacket>,
+}
+
+impl InspectorStats {
+    pub fn new(capacity: usize) -> Self {
+        InspectorStats {
+            enabled: false,
+            capacity,
+            next_sequence: 0,
+            started: Instant::now(),
+            packets: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn record(&mut self, packet_data: &PacketData) {
+        if !self.enabled || self.capacity == 0 {
+            return;
+        }
+        let data = &packet_data.packet.data;
+        let view = PacketView::parse(data).ok();
+        let inspected_packet = InspectedPacket {
+            sequence: self.next_sequence,
+            time: packet_data
+                .arrival_time
+                .saturating_duration_since(self.started),
+            outbound: packet_data.packet.address.outbound(),
+            source: view.as_ref().map(|view| view.source()),
+            destination: view.as_ref().map(|view| view.destination()),
+            protocol: view.as_ref().map(|view| view.protocol()),
+            source_port: view.as_ref().and_then(|view| view.source_port()),
+            destination_port: view.as_ref().and_then(|view| view.destination_port()),
+            length: data.len(),
+            actions: packet_data.actions,
+            data: data[..data.len().min(MAX_CAPTURED_BYTES)].to_vec(),
+        };
+        self.next_sequence += 1;
+        if self.packets.len() == self.capacity {
+            self.packets.pop_front();
+        }
+        self.packets.push_back(inspected_packet);
+    }
+
+    /// Returns the recorded packets with a sequence number of at least `sequence`, oldest first.
+    pub fn since(&self, sequence: u64) -> impl Iterator<Item = &InspectedPacket> {
+        let skip = match self.packets.front() {
+            Some(first) => sequence.saturating_sub(first.sequence) as usize,
+            None => 0,
+        };
+        self.packets.iter().skip(skip)
+    }
+
+    pub fn len(&self) -> usize {
+        self.packets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.packets.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::packet::protocol;
+    use crate::network::packet::test_packets::{ipv4, udp};
+    use windivert::layer::NetworkLayer;
+    use windivert::packet::WinDivertPacket;
+
+    fn packet_data(payload: &[u8]) -> PacketData {
+        let data = ipv4(protocol::UDP, &udp(40000, 53, payload));
+        unsafe { PacketData::from(WinDivertPacket::<NetworkLayer>::new(data)) }
+    }
+
+    #[test]
+    fn test_recording_is_disabled_by_default() {
+        let mut stats = InspectorStats::new(4);
+        stats.record(&packet_data(b"abc"));
+        assert!(stats.is_empty());
+    }
+
+    #[test]
+    fn test_record_parses_headers() {
+        let mut stats = InspectorStats::new(4);
+        stats.enabled = true;
+        let mut packet = packet_data(b"abc");
+        packet.actions.insert(PacketActions::DROPPED);
+        stats.record(&packet);
+
+        let inspected = stats.since(0).next().unwrap();
+        assert_eq!(inspected.protocol, Some(protocol::UDP));
+        assert_eq!(inspected.source_port, Some(40000));
+        assert_eq!(inspected.destination_port, Some(53));
+        assert_eq!(inspected.length, packet.packet.data.len());
+        assert!(inspected.actions.contains(PacketActions::DROPPED));
+        assert!(!inspected.outbound);
+    }
+
+    #[test]
+    fn test_ring_buffer_is_bounded() {
+        let mut stats = InspectorStats::new(3);
+        stats.enabled = true;
+        for _ in 0..5 {
+            stats.record(&packet_data(b"abc"));
+        }
+        assert_eq!(stats.len(), 3);
+        let sequences: Vec<u64> = stats.since(0).map(|packet| packet.sequence).collect();
+        assert_eq!(sequences, vec![2, 3, 4]);
+        let sequences: Vec<u64> = stats.since(4).map(|packet| packet.sequence).collect();
+        assert_eq!(sequences, vec![4]);
+        assert_eq!(stats.since(5).count(), 0);
+    }
+
+    #[test]
+    fn test_large_packets_are_truncated() {
+        let mut stats = InspectorStats::new(1);
+        stats.enabled = true;
+        stats.record(&packet_data(&[0u8; 2 * MAX_CAPTURED_BYTES]));
+        let inspected = stats.since(0).next().unwrap();
+        assert_eq!(inspected.data.len(), MAX_CAPTURED_BYTES);
+        assert!(inspected.length > MAX_CAPTURED_BYTES);
+    }
+}
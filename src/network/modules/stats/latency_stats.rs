@@ -0,0 +1,116 @@
+use crate::network::core::packet_data::PacketData;
+use crate::network::modules::stats::util::histogram::{LatencyHistogram, LatencyPercentiles};
+use crate::network::types::held_by::HeldBy;
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+/// How long sent packets spent inside fumble, from being received to being sent, overall and for
+/// the packets each module held back.
+pub struct LatencyStats {
+    overall: LatencyHistogram,
+    /// One histogram per module in [`HeldBy::MODULES`]
+    held: Vec<LatencyHistogram>,
+}
+
+/// The latency statistics at one point in time.
+#[derive(Debug, Clone, Default)]
+pub struct LatencySnapshot {
+    pub overall: LatencyPercentiles,
+    /// Latency of the packets held by each module, for the modules that held any
+    pub modules: BTreeMap<String, LatencyPercentiles>,
+}
+
+impl Default for LatencyStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LatencyStats {
+    pub fn new() -> Self {
+        LatencyStats {
+            overall: LatencyHistogram::new(),
+            held: HeldBy::MODULES
+                .iter()
+                .map(|_| LatencyHistogram::new())
+                .collect(),
+        }
+    }
+
+    /// Records the time `packets`, which are being sent now, spent since they were received.
+    pub fn record_sent(&self, packets: &[PacketData]) {
+        if packets.is_empty() {
+            return;
+        }
+        let now = Instant::now();
+        for packet_data in packets {
+            let latency = now.saturating_duration_since(packet_data.arrival_time);
+            self.overall.record(latency);
+            for index in packet_data.held_by.indices() {
+                self.held[index].record(latency);
+            }
+        }
+    }
+
+    pub fn snapshot(&self) -> LatencySnapshot {
+        LatencySnapshot {
+            overall: self.overall.percentiles(),
+            modules: HeldBy::MODULES
+                .iter()
+                .zip(&self.held)
+                .map(|((_, name), histogram)| (name.to_string(), histogram.percentiles()))
+                .filter(|(_, percentiles)| percentiles.count > 0)
+                .collect(),
+        }
+    }
+
+    pub fn reset(&self) {
+        self.overall.reset();
+        for histogram in &self.held {
+            histogram.reset();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use windivert::layer::NetworkLayer;
+    use windivert::packet::WinDivertPacket;
+
+    fn packet_data(age: Duration, held_by: HeldBy) -> PacketData {
+        // Safety: the address is zeroed, which is a valid network layer address
+        let mut packet_data =
+            PacketData::from(unsafe { WinDivertPacket::<NetworkLayer>::new(vec![0x45]) });
+        packet_data.arrival_time = Instant::now() - age;
+        packet_data.held_by = held_by;
+        packet_data
+    }
+
+    #[test]
+    fn test_latency_is_attributed_to_holding_modules() {
+        let stats = LatencyStats::new();
+        let mut held = HeldBy::DELAY;
+        held.insert(HeldBy::BANDWIDTH);
+        stats.record_sent(&[
+            packet_data(Duration::ZERO, HeldBy::default()),
+            packet_data(Duration::from_millis(50), HeldBy::DELAY),
+            packet_data(Duration::from_millis(80), held),
+        ]);
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.overall.count, 3);
+        assert!(snapshot.overall.max >= Duration::from_millis(80));
+        assert_eq!(
+            snapshot.modules.keys().collect::<Vec<_>>(),
+            ["bandwidth", "delay"]
+        );
+        assert_eq!(snapshot.modules["delay"].count, 2);
+        assert!(snapshot.modules["delay"].p50 >= Duration::from_millis(49));
+        assert_eq!(snapshot.modules["bandwidth"].count, 1);
+
+        stats.reset();
+        assert!(stats.snapshot().modules.is_empty());
+    }
+}
@@ -1,23 +1,54 @@
-use crate::network::modules::stats::bandwidth_stats::BandwidthStats;
-use crate::network::modules::stats::delay_stats::DelayStats;
-use crate::network::modules::stats::drop_stats::DropStats;
-use crate::network::modules::stats::duplicate_stats::DuplicateStats;
-use crate::network::modules::stats::reorder_stats::ReorderStats;
-use crate::network::modules::stats::tamper_stats::TamperStats;
-use crate::network::modules::stats::throttle_stats::ThrottleStats;
-use std::sync::{Arc, RwLock};
+use crate::network::core::packet_data::PacketData;
+use crate::network::modules::stats::accounting_stats::{
+    AccountingSnapshot, AccountingStats, PacketAccounting,
+};
+use crate::network::modules::stats::bandwidth_stats::{BandwidthSnapshot, BandwidthStats};
+use crate::network::modules::stats::delay_stats::{DelaySnapshot, DelayStats};
+use crate::network::modules::stats::dns_stats::{DnsSnapshot, DnsStats};
+use crate::network::modules::stats::drain_stats::{DrainSnapshot, DrainStats};
+use crate::network::modules::stats::drop_stats::{DropSnapshot, DropStats};
+use crate::network::modules::stats::duplicate_stats::{DuplicateSnapshot, DuplicateStats};
+use crate::network::modules::stats::inspector_stats::{InspectedPacket, InspectorStats};
+use crate::network::modules::stats::latency_stats::{LatencySnapshot, LatencyStats};
+use crate::network::modules::stats::reorder_stats::{ReorderSnapshot, ReorderStats};
+use crate::network::modules::stats::tamper_stats::{TamperSnapshot, TamperStats};
+use crate::network::modules::stats::throttle_stats::{ThrottleSnapshot, ThrottleStats};
+use crate::network::modules::stats::topology_stats::{TopologySnapshot, TopologyStats};
+use crate::network::modules::stats::trace_stats::{TraceSnapshot, TraceStats};
+use crate::network::modules::stats::util::counter::Counter;
+use crate::network::utils::snapshot::Snapshot;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::time::Duration;
 
+pub mod accounting_stats;
 pub mod bandwidth_stats;
 pub mod delay_stats;
+pub mod dns_stats;
+pub mod drain_stats;
 pub mod drop_stats;
 pub mod duplicate_stats;
+pub mod inspector_stats;
+pub mod latency_stats;
 pub mod reorder_stats;
+pub mod summary_report;
 pub mod tamper_stats;
 pub mod throttle_stats;
+pub mod topology_stats;
+pub mod trace_stats;
 pub mod util;
 
+/// Statistics recorded by the processing threads while they handle packets.
+///
+/// Every counter is updated atomically, so recording never waits on other threads. Readers such
+/// as the TUI do not read the counters one by one, but the [`StatisticsSnapshot`] last published
+/// with [`publish`](Self::publish), which the packet processing does periodically.
 pub struct PacketProcessingStatistics {
+    /// Total number of packets received for processing
+    pub received_packets: Counter,
+    /// Total number of packets sent after processing
+    pub sent_packets: Counter,
     pub drop_stats: DropStats,
     pub delay_stats: DelayStats,
     pub throttle_stats: ThrottleStats,
@@ -25,17 +56,218 @@ pub struct PacketProcessingStatistics {
     pub tamper_stats: TamperStats,
     pub duplicate_stats: DuplicateStats,
     pub bandwidth_stats: BandwidthStats,
+    pub dns_stats: DnsStats,
+    pub topology_stats: TopologyStats,
+    pub trace_stats: TraceStats,
+    /// Packets given up by the modules when stopping, pausing or turning a module off
+    pub drain_stats: DrainStats,
+    /// Packets dropped per module, so every packet can be accounted for
+    pub accounting_stats: AccountingStats,
+    /// Time sent packets spent between being received and being sent
+    pub latency_stats: LatencyStats,
+    inspecting: AtomicBool,
+    /// Recently processed packets, recorded while the TUI packet inspector is open
+    inspector_stats: Mutex<InspectorStats>,
+    published: Snapshot<StatisticsSnapshot>,
+}
+
+/// The statistics of all modules at one point in time.
+#[derive(Debug, Clone, Default)]
+pub struct StatisticsSnapshot {
+    pub received_packets: usize,
+    pub sent_packets: usize,
+    pub drop_stats: DropSnapshot,
+    pub delay_stats: DelaySnapshot,
+    pub throttle_stats: ThrottleSnapshot,
+    pub reorder_stats: ReorderSnapshot,
+    pub tamper_stats: TamperSnapshot,
+    pub duplicate_stats: DuplicateSnapshot,
+    pub bandwidth_stats: BandwidthSnapshot,
+    pub dns_stats: DnsSnapshot,
+    pub topology_stats: TopologySnapshot,
+    pub trace_stats: TraceSnapshot,
+    pub drain_stats: DrainSnapshot,
+    pub accounting_stats: AccountingSnapshot,
+    pub latency_stats: LatencySnapshot,
+}
+
+impl Default for PacketProcessingStatistics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PacketProcessingStatistics {
+    pub fn new() -> Self {
+        PacketProcessingStatistics {
+            received_packets: Counter::default(),
+            sent_packets: Counter::default(),
+            drop_stats: DropStats::new(0.005),
+            delay_stats: DelayStats::new(),
+            throttle_stats: ThrottleStats::new(),
+            reorder_stats: ReorderStats::new(0.005),
+            tamper_stats: TamperStats::new(Duration::from_millis(500)),
+            duplicate_stats: DuplicateStats::new(0.005),
+            bandwidth_stats: BandwidthStats::new(0.005),
+            dns_stats: DnsStats::new(),
+            topology_stats: TopologyStats::new(),
+            trace_stats: TraceStats::new(),
+            drain_stats: DrainStats::new(),
+            accounting_stats: AccountingStats::new(),
+            latency_stats: LatencyStats::new(),
+            inspecting: AtomicBool::new(false),
+            inspector_stats: Mutex::new(InspectorStats::new(1000)),
+            published: Snapshot::new(StatisticsSnapshot::default()),
+        }
+    }
+
+    /// Reads the current value of every counter.
+    pub fn capture(&self) -> StatisticsSnapshot {
+        StatisticsSnapshot {
+            received_packets: self.received_packets.get(),
+            sent_packets: self.sent_packets.get(),
+            drop_stats: self.drop_stats.snapshot(),
+            delay_stats: self.delay_stats.snapshot(),
+            throttle_stats: self.throttle_stats.snapshot(),
+            reorder_stats: self.reorder_stats.snapshot(),
+            tamper_stats: self.tamper_stats.snapshot(),
+            duplicate_stats: self.duplicate_stats.snapshot(),
+            bandwidth_stats: self.bandwidth_stats.snapshot(),
+            dns_stats: self.dns_stats.snapshot(),
+            topology_stats: self.topology_stats.snapshot(),
+            trace_stats: self.trace_stats.snapshot(),
+            drain_stats: self.drain_stats.snapshot(),
+            accounting_stats: self.accounting_stats.snapshot(),
+            latency_stats: self.latency_stats.snapshot(),
+        }
+    }
+
+    /// Captures the counters and makes them the snapshot returned to readers.
+    pub fn publish(&self) {
+        self.published.publish(self.capture());
+    }
+
+    /// Returns the snapshot published last.
+    pub fn snapshot(&self) -> Arc<StatisticsSnapshot> {
+        self.published.load()
+    }
+
+    /// Sets all counters back to zero and publishes the result. The numbers of packets the
+    /// modules currently hold are kept, as those packets are still held. Packets processed while
+    /// resetting may be counted only partially.
+    pub fn reset(&self) {
+        let queued = self.capture().accounting().total_queued();
+        self.received_packets.reset();
+        self.sent_packets.reset();
+        self.drop_stats.reset();
+        self.delay_stats.reset();
+        self.throttle_stats.reset();
+        self.reorder_stats.reset();
+        self.tamper_stats.reset();
+        self.duplicate_stats.reset();
+        self.bandwidth_stats.reset();
+        self.dns_stats.reset();
+        self.topology_stats.reset();
+        self.trace_stats.reset();
+        self.drain_stats.reset();
+        self.accounting_stats.reset(queued);
+        self.latency_stats.reset();
+        self.publish();
+    }
+
+    /// Starts or stops recording processed packets for the packet inspector.
+    pub fn set_inspecting(&self, inspecting: bool) {
+        if self.inspecting.swap(inspecting, Ordering::Relaxed) != inspecting {
+            self.inspector().enabled = inspecting;
+        }
+    }
+
+    pub fn is_inspecting(&self) -> bool {
+        self.inspecting.load(Ordering::Relaxed)
+    }
+
+    /// Records `packets` for the packet inspector, if it is recording.
+    pub fn inspect<'a>(&self, packets: impl IntoIterator<Item = &'a PacketData>) {
+        if self.is_inspecting() {
+            let mut inspector = self.inspector();
+            for packet_data in packets {
+                inspector.record(packet_data);
+            }
+        }
+    }
+
+    /// Returns the inspected packets with a sequence number of at least `sequence`.
+    pub fn inspected_since(&self, sequence: u64) -> Vec<InspectedPacket> {
+        self.inspector().since(sequence).cloned().collect()
+    }
+
+    fn inspector(&self) -> MutexGuard<'_, InspectorStats> {
+        self.inspector_stats
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl StatisticsSnapshot {
+    /// Returns where the packets counted in this snapshot went.
+    pub fn accounting(&self) -> PacketAccounting {
+        let queued = BTreeMap::from([
+            ("delay", self.delay_stats.delayed_package_count),
+            ("dns", self.dns_stats.delayed_count),
+            ("throttle", self.throttle_stats.queued_packets),
+            ("reorder", self.reorder_stats.delayed_packets),
+            ("topology", self.topology_stats.delayed_packets),
+            ("trace", self.trace_stats.delayed_packets),
+            ("bandwidth", self.bandwidth_stats.storage_packet_count),
+        ]);
+        PacketAccounting {
+            carried_over: self.accounting_stats.carried_over,
+            received: self.received_packets,
+            duplicated: self
+                .duplicate_stats
+                .outgoing_packet_count
+                .saturating_sub(self.duplicate_stats.incoming_packet_count),
+            sent: self.sent_packets,
+            dropped: self.accounting_stats.dropped.clone(),
+            discarded: self.drain_stats.total().discarded,
+            queued: queued
+                .into_iter()
+                .map(|(module, count)| (module.to_string(), count))
+                .collect(),
+        }
+    }
 }
 
 // Function to initialize the statistics
-pub fn initialize_statistics() -> Arc<RwLock<PacketProcessingStatistics>> {
-    Arc::new(RwLock::new(PacketProcessingStatistics {
-        drop_stats: DropStats::new(0.005),
-        delay_stats: DelayStats::new(),
-        throttle_stats: ThrottleStats::new(),
-        reorder_stats: ReorderStats::new(0.005),
-        tamper_stats: TamperStats::new(Duration::from_millis(500)),
-        duplicate_stats: DuplicateStats::new(0.005),
-        bandwidth_stats: BandwidthStats::new(0.005),
-    }))
+pub fn initialize_statistics() -> Arc<PacketProcessingStatistics> {
+    Arc::new(PacketProcessingStatistics::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reset_zeroes_counters_and_publishes() {
+        let statistics = PacketProcessingStatistics::new();
+        statistics.received_packets.add(10);
+        statistics.drop_stats.record(true);
+        statistics.dns_stats.record_query("example.com");
+        statistics.delay_stats.delayed_package_count(0, 3);
+        assert_eq!(statistics.snapshot().received_packets, 0);
+
+        statistics.publish();
+        let snapshot = statistics.snapshot();
+        assert_eq!(snapshot.received_packets, 10);
+        assert_eq!(snapshot.drop_stats.total_dropped, 1);
+
+        statistics.reset();
+        let snapshot = statistics.snapshot();
+        assert_eq!(snapshot.received_packets, 0);
+        assert_eq!(snapshot.drop_stats.total_packets, 0);
+        assert_eq!(snapshot.drop_stats.recent_drop_rate, 0.0);
+        assert!(snapshot.dns_stats.names.is_empty());
+        // Packets still held by a module are not forgotten
+        assert_eq!(snapshot.delay_stats.delayed_package_count, 3);
+    }
 }
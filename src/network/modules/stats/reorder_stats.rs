@@ -1,32 +1,59 @@
+use crate::network::modules::stats::util::counter::{Counter, Gauge};
 use crate::network::modules::stats::util::ewma::Ewma;
 
 pub struct ReorderStats {
-    pub(crate) total_packets: usize,
-    pub(crate) reordered_packets: usize,
-    pub(crate) delayed_packets: usize,
+    pub(crate) total_packets: Counter,
+    pub(crate) reordered_packets: Counter,
+    pub(crate) delayed_packets: Gauge,
     ewma: Ewma,
 }
 
+/// The reorder statistics at one point in time.
+#[derive(Debug, Clone, Default)]
+pub struct ReorderSnapshot {
+    pub total_packets: usize,
+    pub reordered_packets: usize,
+    pub delayed_packets: usize,
+    pub recent_reorder_rate: f64,
+}
+
 impl ReorderStats {
     pub fn new(alpha: f64) -> Self {
         ReorderStats {
-            total_packets: 0,
-            reordered_packets: 0,
-            delayed_packets: 0,
+            total_packets: Counter::default(),
+            reordered_packets: Counter::default(),
+            delayed_packets: Gauge::default(),
             ewma: Ewma::new(alpha),
         }
     }
 
-    pub fn record(&mut self, reordered: bool) {
-        self.total_packets += 1;
+    pub fn record(&self, reordered: bool) {
+        self.total_packets.increment();
         if reordered {
-            self.reordered_packets += 1;
+            self.reordered_packets.increment();
         }
 
         let current_reorder_rate = if reordered { 1.0 } else { 0.0 };
         self.ewma.update(current_reorder_rate);
     }
 
+    pub fn snapshot(&self) -> ReorderSnapshot {
+        ReorderSnapshot {
+            total_packets: self.total_packets.get(),
+            reordered_packets: self.reordered_packets.get(),
+            delayed_packets: self.delayed_packets.get(),
+            recent_reorder_rate: self.ewma.get().unwrap_or(0.0),
+        }
+    }
+
+    pub fn reset(&self) {
+        self.total_packets.reset();
+        self.reordered_packets.reset();
+        self.ewma.reset();
+    }
+}
+
+impl ReorderSnapshot {
     pub fn total_reorder_rate(&self) -> f64 {
         if self.total_packets == 0 {
             0.0
@@ -34,8 +61,4 @@ impl ReorderStats {
             self.reordered_packets as f64 / self.total_packets as f64
         }
     }
-
-    pub fn recent_reorder_rate(&self) -> f64 {
-        self.ewma.get().unwrap_or(0.0)
-    }
 }
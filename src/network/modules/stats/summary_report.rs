@@ -0,0 +1,509 @@
+use crate::cli::settings::packet_manipulation::PacketManipulationSettings;
+use crate::network::modules::stats::util::histogram::LatencyPercentiles;
+use crate::network::modules::stats::StatisticsSnapshot;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::Formatter;
+use std::path::Path;
+use std::time::Duration;
+use std::{fs, io};
+
+/// Totals of a finished run, for the modules that were enabled when it ended.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SummaryReport {
+    /// How long the run took, in seconds
+    pub duration: f64,
+    pub received_packets: usize,
+    pub sent_packets: usize,
+    /// Extra packets created by the duplicate module, included in `sent_packets`
+    pub duplicated_packets: usize,
+    /// Packets held by modules that stopped, were paused or turned off and then sent anyway,
+    /// included in `sent_packets`
+    pub flushed_packets: usize,
+    /// Packets held by modules that stopped, were paused or turned off and then discarded
+    pub discarded_packets: usize,
+    /// Flushed and discarded packets per module
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub drained: BTreeMap<String, DrainSummary>,
+    /// Dropped packets per module
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub dropped: BTreeMap<String, usize>,
+    /// Time the sent packets spent between being received and being sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency: Option<LatencySummary>,
+    /// Latency of the packets held by each module
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub module_latency: BTreeMap<String, LatencySummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drop: Option<DropSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delay: Option<DelaySummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub throttle: Option<ThrottleSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reorder: Option<ReorderSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tamper: Option<TamperSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicate: Option<DuplicateSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bandwidth: Option<BandwidthSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dns: Option<DnsSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topology: Option<TopologySummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace: Option<TraceSummary>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DrainSummary {
+    pub flushed: usize,
+    pub discarded: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct LatencySummary {
+    pub packets: usize,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+impl From<&LatencyPercentiles> for LatencySummary {
+    fn from(percentiles: &LatencyPercentiles) -> Self {
+        let millis = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        LatencySummary {
+            packets: percentiles.count,
+            p50_ms: millis(percentiles.p50),
+            p90_ms: millis(percentiles.p90),
+            p99_ms: millis(percentiles.p99),
+            max_ms: millis(percentiles.max),
+        }
+    }
+}
+
+impl fmt::Display for LatencySummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "p50 {:.2}ms, p90 {:.2}ms, p99 {:.2}ms, max {:.2}ms",
+            self.p50_ms, self.p90_ms, self.p99_ms, self.max_ms
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DropSummary {
+    pub packets: usize,
+    pub dropped: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DelaySummary {
+    pub delayed: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ThrottleSummary {
+    pub dropped: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ReorderSummary {
+    pub packets: usize,
+    pub reordered: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TamperSummary {
+    pub packets: usize,
+    pub tampered: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DuplicateSummary {
+    pub incoming: usize,
+    pub outgoing: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct BandwidthSummary {
+    pub bytes: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DnsSummary {
+    pub queries: usize,
+    pub affected: usize,
+    pub rewrite_failures: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TopologySummary {
+    pub unmatched: usize,
+    pub regions: BTreeMap<String, RegionSummary>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RegionSummary {
+    pub packets: usize,
+    pub dropped: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TraceSummary {
+    pub packets: usize,
+    pub dropped: usize,
+}
+
+impl SummaryReport {
+    pub fn new(
+        statistics: &StatisticsSnapshot,
+        settings: &PacketManipulationSettings,
+        duration: Duration,
+    ) -> Self {
+        let drained = statistics.drain_stats.total();
+        let accounting = statistics.accounting();
+        SummaryReport {
+            duration: duration.as_secs_f64(),
+            received_packets: statistics.received_packets,
+            sent_packets: statistics.sent_packets,
+            duplicated_packets: accounting.duplicated,
+            flushed_packets: drained.flushed,
+            discarded_packets: drained.discarded,
+            drained: statistics
+                .drain_stats
+                .modules
+                .iter()
+                .map(|(module, counts)| {
+                    (
+                        module.to_string(),
+                        DrainSummary {
+                            flushed: counts.flushed,
+                            discarded: counts.discarded,
+                        },
+                    )
+                })
+                .collect(),
+            dropped: accounting.dropped,
+            latency: Some(&statistics.latency_stats.overall)
+                .filter(|latency| latency.count > 0)
+                .map(LatencySummary::from),
+            module_latency: statistics
+                .latency_stats
+                .modules
+                .iter()
+                .map(|(module, latency)| (module.clone(), LatencySummary::from(latency)))
+                .collect(),
+            drop: settings.drop.as_ref().map(|_| DropSummary {
+                packets: statistics.drop_stats.total_packets,
+                dropped: statistics.drop_stats.total_dropped,
+            }),
+            delay: settings.delay.as_ref().map(|_| DelaySummary {
+                delayed: statistics.delay_stats.total_packets,
+            }),
+            throttle: settings.throttle.as_ref().map(|_| ThrottleSummary {
+                dropped: statistics.throttle_stats.dropped_count,
+            }),
+            reorder: settings.reorder.as_ref().map(|_| ReorderSummary {
+                packets: statistics.reorder_stats.total_packets,
+                reordered: statistics.reorder_stats.reordered_packets,
+            }),
+            tamper: settings.tamper.as_ref().map(|_| TamperSummary {
+                packets: statistics.tamper_stats.total_packets,
+                tampered: statistics.tamper_stats.tampered_packets,
+            }),
+            duplicate: settings.duplicate.as_ref().map(|_| DuplicateSummary {
+                incoming: statistics.duplicate_stats.incoming_packet_count,
+                outgoing: statistics.duplicate_stats.outgoing_packet_count,
+            }),
+            bandwidth: settings.bandwidth.as_ref().map(|_| BandwidthSummary {
+                bytes: statistics.bandwidth_stats.total_byte_count,
+            }),
+            dns: settings.dns.as_ref().map(|_| DnsSummary {
+                queries: statistics
+                    .dns_stats
+                    .names
+                    .values()
+                    .map(|name| name.queries)
+                    .sum(),
+                affected: statistics.dns_stats.total_affected(),
+                rewrite_failures: statistics.dns_stats.rewrite_failures,
+            }),
+            topology: settings.topology.as_ref().map(|_| TopologySummary {
+                unmatched: statistics.topology_stats.unmatched,
+                regions: statistics
+                    .topology_stats
+                    .sorted_regions()
+                    .into_iter()
+                    .map(|(name, stats)| {
+                        (
+                            name.to_string(),
+                            RegionSummary {
+                                packets: stats.packets,
+                                dropped: stats.dropped,
+                            },
+                        )
+                    })
+                    .collect(),
+            }),
+            trace: settings.trace.as_ref().map(|_| TraceSummary {
+                packets: statistics.trace_stats.total_packets,
+                dropped: statistics.trace_stats.dropped_packets,
+            }),
+        }
+    }
+
+    pub fn to_toml_string(&self) -> io::Result<String> {
+        toml::to_string_pretty(self).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_toml_string()?)
+    }
+}
+
+impl fmt::Display for SummaryReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Summary after {:.1}s:", self.duration)?;
+        writeln!(
+            f,
+            "  Received: {}, Sent: {}, Duplicated: {}",
+            self.received_packets, self.sent_packets, self.duplicated_packets
+        )?;
+        if !self.dropped.is_empty() {
+            let per_module: Vec<String> = self
+                .dropped
+                .iter()
+                .map(|(module, dropped)| format!("{}: {}", module, dropped))
+                .collect();
+            writeln!(
+                f,
+                "  Dropped: {} ({})",
+                self.dropped.values().sum::<usize>(),
+                per_module.join(", ")
+            )?;
+        }
+        if !self.drained.is_empty() {
+            writeln!(
+                f,
+                "  Drained: {} sent, {} discarded",
+                self.flushed_packets, self.discarded_packets
+            )?;
+            for (module, counts) in &self.drained {
+                writeln!(
+                    f,
+                    "    {}: {} sent, {} discarded",
+                    module, counts.flushed, counts.discarded
+                )?;
+            }
+        }
+        if let Some(latency) = &self.latency {
+            writeln!(f, "  Latency: {} ({} packets)", latency, latency.packets)?;
+            for (module, latency) in &self.module_latency {
+                writeln!(
+                    f,
+                    "    {}: {} ({} packets)",
+                    module, latency, latency.packets
+                )?;
+            }
+        }
+        if let Some(drop) = &self.drop {
+            writeln!(
+                f,
+                "  Drop: {} of {} packets dropped ({:.2}%)",
+                drop.dropped,
+                drop.packets,
+                percentage(drop.dropped, drop.packets)
+            )?;
+        }
+        if let Some(delay) = &self.delay {
+            writeln!(f, "  Delay: {} packets delayed", delay.delayed)?;
+        }
+        if let Some(throttle) = &self.throttle {
+            writeln!(f, "  Throttle: {} packets dropped", throttle.dropped)?;
+        }
+        if let Some(reorder) = &self.reorder {
+            writeln!(
+                f,
+                "  Reorder: {} of {} packets reordered ({:.2}%)",
+                reorder.reordered,
+                reorder.packets,
+                percentage(reorder.reordered, reorder.packets)
+            )?;
+        }
+        if let Some(tamper) = &self.tamper {
+            writeln!(
+                f,
+                "  Tamper: {} of {} packets tampered ({:.2}%)",
+                tamper.tampered,
+                tamper.packets,
+                percentage(tamper.tampered, tamper.packets)
+            )?;
+        }
+        if let Some(duplicate) = &self.duplicate {
+            writeln!(
+                f,
+                "  Duplicate: {} packets in, {} out",
+                duplicate.incoming, duplicate.outgoing
+            )?;
+        }
+        if let Some(bandwidth) = &self.bandwidth {
+            writeln!(f, "  Bandwidth: {} bytes sent", bandwidth.bytes)?;
+        }
+        if let Some(dns) = &self.dns {
+            writeln!(
+                f,
+                "  DNS: {} of {} matching queries affected",
+                dns.affected, dns.queries
+            )?;
+            if dns.rewrite_failures > 0 {
+                writeln!(
+                    f,
+                    "  DNS: {} responses could not be rewritten",
+                    dns.rewrite_failures
+                )?;
+            }
+        }
+        if let Some(topology) = &self.topology {
+            writeln!(
+                f,
+                "  Topology: {} packets matched no profile",
+                topology.unmatched
+            )?;
+            for (name, region) in &topology.regions {
+                writeln!(
+                    f,
+                    "    {}: {} packets, {} dropped",
+                    name, region.packets, region.dropped
+                )?;
+            }
+        }
+        if let Some(trace) = &self.trace {
+            writeln!(
+                f,
+                "  Trace: {} of {} packets dropped ({:.2}%)",
+                trace.dropped,
+                trace.packets,
+                percentage(trace.dropped, trace.packets)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn percentage(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 / total as f64 * 100.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::settings::delay::DelayOptions;
+    use crate::cli::settings::drop::DropOptions;
+    use crate::cli::settings::tamper::TamperOptions;
+    use crate::network::core::packet_data::PacketData;
+    use crate::network::modules::stats::PacketProcessingStatistics;
+    use crate::network::types::held_by::HeldBy;
+    use windivert::layer::NetworkLayer;
+    use windivert::packet::WinDivertPacket;
+
+    #[test]
+    fn test_report_includes_enabled_modules() {
+        let statistics = PacketProcessingStatistics::new();
+        statistics.received_packets.add(10);
+        statistics.sent_packets.add(8);
+        statistics.drain_stats.record_discarded("delay", 1);
+        for packet in 0..10 {
+            statistics.drop_stats.record(packet == 0);
+        }
+        statistics.accounting_stats.record_dropped("drop", 1);
+        let settings = PacketManipulationSettings {
+            drop: Some(DropOptions::default()),
+            ..Default::default()
+        };
+        let report = SummaryReport::new(&statistics.capture(), &settings, Duration::from_secs(5));
+        assert_eq!(
+            report.drop,
+            Some(DropSummary {
+                packets: 10,
+                dropped: 1
+            })
+        );
+        assert!(report.reorder.is_none());
+        // No packet was sent through the modules
+        assert!(report.latency.is_none());
+
+        let text = report.to_string();
+        assert!(text.contains("Received: 10, Sent: 8, Duplicated: 0"));
+        assert!(text.contains("  Dropped: 1 (drop: 1)\n"));
+        assert!(text.contains("Drop: 1 of 10 packets dropped (10.00%)"));
+        let toml = report.to_toml_string().unwrap();
+        assert!(text.contains("    delay: 0 sent, 1 discarded\n"));
+        assert!(toml.contains("discarded_packets = 1\n"));
+        assert!(toml.contains("[drained.delay]\nflushed = 0\ndiscarded = 1\n"));
+        assert!(toml.contains("[dropped]\ndrop = 1\n"));
+        assert!(toml.contains("[drop]\npackets = 10\ndropped = 1\n"));
+    }
+
+    #[test]
+    fn test_report_includes_delay_and_tamper() {
+        let statistics = PacketProcessingStatistics::new();
+        statistics.delay_stats.total_packets.add(6);
+        statistics.tamper_stats.total_packets.add(4);
+        statistics.tamper_stats.tampered_packets.add(1);
+        let settings = PacketManipulationSettings {
+            delay: Some(DelayOptions::default()),
+            tamper: Some(TamperOptions::default()),
+            ..Default::default()
+        };
+        let report = SummaryReport::new(&statistics.capture(), &settings, Duration::from_secs(1));
+        assert_eq!(report.delay, Some(DelaySummary { delayed: 6 }));
+        assert_eq!(
+            report.tamper,
+            Some(TamperSummary {
+                packets: 4,
+                tampered: 1
+            })
+        );
+
+        let text = report.to_string();
+        assert!(text.contains("  Delay: 6 packets delayed\n"));
+        assert!(text.contains("  Tamper: 1 of 4 packets tampered (25.00%)\n"));
+        let toml = report.to_toml_string().unwrap();
+        assert!(toml.contains("[delay]\ndelayed = 6\n"));
+        assert!(toml.contains("[tamper]\npackets = 4\ntampered = 1\n"));
+    }
+
+    #[test]
+    fn test_report_includes_latency() {
+        let statistics = PacketProcessingStatistics::new();
+        // Safety: the address is zeroed, which is a valid network layer address
+        let mut packet_data =
+            PacketData::from(unsafe { WinDivertPacket::<NetworkLayer>::new(vec![0x45]) });
+        packet_data.arrival_time -= Duration::from_millis(30);
+        packet_data.hold(HeldBy::DELAY);
+        statistics.latency_stats.record_sent(&[packet_data]);
+
+        let settings = PacketManipulationSettings::default();
+        let report = SummaryReport::new(&statistics.capture(), &settings, Duration::from_secs(1));
+        let latency = report.latency.unwrap();
+        assert_eq!(latency.packets, 1);
+        assert!(latency.p50_ms >= 30.0 && latency.p50_ms == latency.max_ms);
+        assert_eq!(report.module_latency["delay"], latency);
+
+        let text = report.to_string();
+        assert!(text.contains("  Latency: p50 "));
+        assert!(text.contains("    delay: p50 "));
+        let toml = report.to_toml_string().unwrap();
+        assert!(toml.contains("[latency]\npackets = 1\n"));
+        assert!(toml.contains("[module_latency.delay]\npackets = 1\n"));
+    }
+}
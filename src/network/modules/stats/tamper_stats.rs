@@ -1,30 +1,99 @@
-use std::ops::Sub;
+use crate::network::modules::stats::util::counter::Counter;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
 use std::time::{Duration, Instant};
 
 pub struct TamperStats {
-    pub(crate) data: Vec<u8>,
-    pub(crate) tamper_flags: Vec<bool>,
-    pub(crate) checksum_valid: bool,
-    pub last_update: Instant,
+    pub(crate) total_packets: Counter,
+    /// Packets whose payload was changed
+    pub(crate) tampered_packets: Counter,
+    sample: Mutex<TamperSample>,
+    started: Instant,
+    /// Time of the last sample, in nanoseconds since `started`
+    last_update: AtomicU64,
     pub update_interval: Duration,
 }
 
-impl TamperStats {
-    pub fn new(refresh_interval: Duration) -> Self {
-        TamperStats {
+/// The payload of a recently tampered packet, refreshed at most once per update interval.
+#[derive(Debug, Clone)]
+pub struct TamperSample {
+    pub data: Vec<u8>,
+    pub tamper_flags: Vec<bool>,
+    pub checksum_valid: bool,
+}
+
+/// The tamper statistics at one point in time.
+#[derive(Debug, Clone, Default)]
+pub struct TamperSnapshot {
+    pub total_packets: usize,
+    pub tampered_packets: usize,
+    pub sample: TamperSample,
+}
+
+impl Default for TamperSample {
+    fn default() -> Self {
+        TamperSample {
             data: vec![],
             tamper_flags: vec![],
             checksum_valid: true,
-            last_update: Instant::now().sub(refresh_interval),
+        }
+    }
+}
+
+impl TamperStats {
+    pub fn new(refresh_interval: Duration) -> Self {
+        TamperStats {
+            total_packets: Counter::default(),
+            tampered_packets: Counter::default(),
+            sample: Mutex::new(TamperSample::default()),
+            started: Instant::now(),
+            last_update: AtomicU64::new(0),
             update_interval: refresh_interval,
         }
     }
 
-    pub fn should_update(&mut self) -> bool {
-        self.last_update.elapsed() >= self.update_interval
+    /// Returns whether the sample is due for a refresh. Only one of the threads asking at the
+    /// same time is told so.
+    pub fn should_update(&self) -> bool {
+        let now = self.started.elapsed().as_nanos() as u64;
+        let last_update = self.last_update.load(Ordering::Relaxed);
+        // The first sample is taken right away
+        let due = last_update == 0
+            || Duration::from_nanos(now.saturating_sub(last_update)) >= self.update_interval;
+        due && self
+            .last_update
+            .compare_exchange(
+                last_update,
+                now.max(1),
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            )
+            .is_ok()
+    }
+
+    /// Returns the current sample.
+    pub fn sample(&self) -> TamperSample {
+        self.sample
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn snapshot(&self) -> TamperSnapshot {
+        TamperSnapshot {
+            total_packets: self.total_packets.get(),
+            tampered_packets: self.tampered_packets.get(),
+            sample: self.sample(),
+        }
+    }
+
+    pub fn update(&self, sample: TamperSample) {
+        *self.sample.lock().unwrap_or_else(|e| e.into_inner()) = sample;
     }
 
-    pub fn updated(&mut self) {
-        self.last_update = Instant::now();
+    pub fn reset(&self) {
+        self.total_packets.reset();
+        self.tampered_packets.reset();
+        self.update(TamperSample::default());
     }
 }
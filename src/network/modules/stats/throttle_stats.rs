@@ -1,6 +1,18 @@
+use crate::network::modules::stats::util::counter::{Counter, Gauge};
+use std::sync::atomic::{AtomicBool, Ordering};
+
 pub struct ThrottleStats {
-    pub(crate) is_throttling: bool,
-    pub(crate) dropped_count: usize,
+    pub(crate) is_throttling: AtomicBool,
+    pub(crate) dropped_count: Counter,
+    pub(crate) queued_packets: Gauge,
+}
+
+/// The throttle statistics at one point in time.
+#[derive(Debug, Clone, Default)]
+pub struct ThrottleSnapshot {
+    pub is_throttling: bool,
+    pub dropped_count: usize,
+    pub queued_packets: usize,
 }
 
 impl Default for ThrottleStats {
@@ -12,8 +24,25 @@ impl Default for ThrottleStats {
 impl ThrottleStats {
     pub fn new() -> Self {
         ThrottleStats {
-            is_throttling: false,
-            dropped_count: 0,
+            is_throttling: AtomicBool::new(false),
+            dropped_count: Counter::default(),
+            queued_packets: Gauge::default(),
+        }
+    }
+
+    pub fn set_throttling(&self, is_throttling: bool) {
+        self.is_throttling.store(is_throttling, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> ThrottleSnapshot {
+        ThrottleSnapshot {
+            is_throttling: self.is_throttling.load(Ordering::Relaxed),
+            dropped_count: self.dropped_count.get(),
+            queued_packets: self.queued_packets.get(),
         }
     }
+
+    pub fn reset(&self) {
+        self.dropped_count.reset();
+    }
 }
@@ -0,0 +1,85 @@
+use crate::network::modules::stats::util::counter::{Counter, CounterMap, Gauge};
+use std::collections::HashMap;
+
+/// Counters for the packets handled by a single topology profile.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RegionStats {
+    pub packets: usize,
+    pub dropped: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct RegionCounters {
+    packets: Counter,
+    dropped: Counter,
+}
+
+pub struct TopologyStats {
+    regions: CounterMap<RegionCounters>,
+    /// Packets whose remote address matched no route and no default profile
+    pub(crate) unmatched: Counter,
+    pub(crate) delayed_packets: Gauge,
+}
+
+/// The topology statistics at one point in time.
+#[derive(Debug, Clone, Default)]
+pub struct TopologySnapshot {
+    pub regions: HashMap<String, RegionStats>,
+    /// Packets whose remote address matched no route and no default profile
+    pub unmatched: usize,
+    pub delayed_packets: usize,
+}
+
+impl Default for TopologyStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TopologyStats {
+    pub fn new() -> Self {
+        TopologyStats {
+            regions: CounterMap::default(),
+            unmatched: Counter::default(),
+            delayed_packets: Gauge::default(),
+        }
+    }
+
+    pub fn record(&self, region: &str, dropped: bool) {
+        self.regions.record(region, |counters| {
+            counters.packets.increment();
+            if dropped {
+                counters.dropped.increment();
+            }
+        });
+    }
+
+    pub fn snapshot(&self) -> TopologySnapshot {
+        TopologySnapshot {
+            regions: self.regions.collect(|counters| RegionStats {
+                packets: counters.packets.get(),
+                dropped: counters.dropped.get(),
+            }),
+            unmatched: self.unmatched.get(),
+            delayed_packets: self.delayed_packets.get(),
+        }
+    }
+
+    pub fn reset(&self) {
+        self.regions.clear();
+        self.unmatched.reset();
+    }
+}
+
+impl TopologySnapshot {
+    /// Returns the per-region counters sorted by region name.
+    pub fn sorted_regions(&self) -> Vec<(&str, RegionStats)> {
+        let mut regions: Vec<(&str, RegionStats)> = self
+            .regions
+            .iter()
+            .map(|(name, stats)| (name.as_str(), *stats))
+            .collect();
+        regions.sort_by(|a, b| a.0.cmp(b.0));
+        regions
+    }
+}
@@ -0,0 +1,73 @@
+use crate::network::modules::stats::util::counter::{Counter, Gauge};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+pub struct TraceStats {
+    pub(crate) total_packets: Counter,
+    /// Packets lost to the loss of the trace, an outage or a queue longer than the trace allows
+    pub(crate) dropped_packets: Counter,
+    pub(crate) delayed_packets: Gauge,
+    /// Nanoseconds since the replay started
+    elapsed_nanos: AtomicU64,
+    /// Nanoseconds the replayed trace lasts, 0 before one is replayed
+    period_nanos: AtomicU64,
+}
+
+/// The trace statistics at one point in time.
+#[derive(Debug, Clone, Default)]
+pub struct TraceSnapshot {
+    pub total_packets: usize,
+    pub dropped_packets: usize,
+    pub delayed_packets: usize,
+    /// Time since the replay started
+    pub elapsed: Duration,
+    /// Length of the replayed trace, zero before one is replayed
+    pub period: Duration,
+}
+
+impl Default for TraceStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TraceStats {
+    pub fn new() -> Self {
+        TraceStats {
+            total_packets: Counter::default(),
+            dropped_packets: Counter::default(),
+            delayed_packets: Gauge::default(),
+            elapsed_nanos: AtomicU64::new(0),
+            period_nanos: AtomicU64::new(0),
+        }
+    }
+
+    pub fn record(&self, dropped: bool) {
+        self.total_packets.increment();
+        if dropped {
+            self.dropped_packets.increment();
+        }
+    }
+
+    pub fn set_position(&self, elapsed: Duration, period: Duration) {
+        self.elapsed_nanos
+            .store(elapsed.as_nanos() as u64, Ordering::Relaxed);
+        self.period_nanos
+            .store(period.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> TraceSnapshot {
+        TraceSnapshot {
+            total_packets: self.total_packets.get(),
+            dropped_packets: self.dropped_packets.get(),
+            delayed_packets: self.delayed_packets.get(),
+            elapsed: Duration::from_nanos(self.elapsed_nanos.load(Ordering::Relaxed)),
+            period: Duration::from_nanos(self.period_nanos.load(Ordering::Relaxed)),
+        }
+    }
+
+    pub fn reset(&self) {
+        self.total_packets.reset();
+        self.dropped_packets.reset();
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::RwLock;
+
+/// A count that only goes up, recorded from any number of threads without locking.
+///
+/// # Example
+///
+/// ```rust
+/// use fumble::network::modules::stats::util::counter::Counter;
+/// let counter = Counter::default();
+/// counter.add(2);
+/// counter.increment();
+/// assert_eq!(counter.get(), 3);
+/// counter.reset();
+/// assert_eq!(counter.get(), 0);
+/// ```
+#[derive(Debug, Default)]
+pub struct Counter(AtomicUsize);
+
+impl Counter {
+    pub fn add(&self, count: usize) {
+        self.0.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn increment(&self) {
+        self.add(1);
+    }
+
+    pub fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    pub fn reset(&self) {
+        self.0.store(0, Ordering::Relaxed);
+    }
+}
+
+/// A number of things currently held, such as queued packets, that every thread holding some
+/// adjusts by the change it made. It never drops below zero.
+#[derive(Debug, Default)]
+pub struct Gauge(AtomicUsize);
+
+impl Gauge {
+    pub fn add(&self, count: usize) {
+        self.0.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn sub(&self, count: usize) {
+        let _ = self
+            .0
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |current| {
+                Some(current.saturating_sub(count))
+            });
+    }
+
+    /// Applies the change of a holder going from `previous` to `current`.
+    pub fn adjust(&self, previous: usize, current: usize) {
+        if current > previous {
+            self.add(current - previous);
+        } else {
+            self.sub(previous - current);
+        }
+    }
+
+    pub fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// A floating point value shared between threads, where the last write wins.
+#[derive(Debug, Default)]
+pub struct AtomicF64(AtomicU64);
+
+impl AtomicF64 {
+    pub fn new(value: f64) -> Self {
+        AtomicF64(AtomicU64::new(value.to_bits()))
+    }
+
+    pub fn set(&self, value: f64) {
+        self.0.store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::Relaxed))
+    }
+}
+
+/// Name under which a [`CounterMap`] counts the names it has no room left for.
+pub const OTHER_NAME: &str = "(other)";
+
+/// Counters kept per name, such as per DNS query name or topology region.
+///
+/// Looking up a name that was seen before only takes the lock for reading, so threads recording
+/// at the same time do not wait on each other. Only the first packet of a new name locks for
+/// writing.
+///
+/// A map of names that are not known in advance, such as every DNS name queried, can be limited
+/// with [`with_max_names`](Self::with_max_names). Once it is full, new names are counted together
+/// under [`OTHER_NAME`].
+#[derive(Debug)]
+pub struct CounterMap<C> {
+    counters: RwLock<HashMap<String, C>>,
+    max_names: usize,
+}
+
+impl<C> Default for CounterMap<C> {
+    fn default() -> Self {
+        Self::with_max_names(usize::MAX)
+    }
+}
+
+impl<C> CounterMap<C> {
+    /// Creates a map keeping at most `max_names` names besides [`OTHER_NAME`].
+    pub fn with_max_names(max_names: usize) -> Self {
+        CounterMap {
+            counters: RwLock::new(HashMap::new()),
+            max_names,
+        }
+    }
+}
+
+impl<C: Default> CounterMap<C> {
+    /// Calls `record` with the counters of `name`, creating them if needed, or with those of
+    /// [`OTHER_NAME`] if the map is full.
+    pub fn record(&self, name: &str, record: impl FnOnce(&C)) {
+        {
+            let counters = self.counters.read().unwrap_or_else(|e| e.into_inner());
+            let counters = counters.get(name).or_else(|| {
+                // Names that do not fit are counted without locking for writing once the
+                // overflow counters exist
+                (self.tracked(&counters) >= self.max_names)
+                    .then(|| counters.get(OTHER_NAME))
+                    .flatten()
+            });
+            if let Some(counters) = counters {
+                record(counters);
+                return;
+            }
+        }
+        let mut counters = self.counters.write().unwrap_or_else(|e| e.into_inner());
+        let name = if counters.contains_key(name) || self.tracked(&counters) < self.max_names {
+            name
+        } else {
+            OTHER_NAME
+        };
+        record(counters.entry(name.to_string()).or_default());
+    }
+
+    /// Returns the number of names kept, not counting [`OTHER_NAME`].
+    fn tracked(&self, counters: &HashMap<String, C>) -> usize {
+        counters.len() - usize::from(counters.contains_key(OTHER_NAME))
+    }
+
+    /// Returns the values read by `read` for every name, in no particular order.
+    pub fn collect<T>(&self, read: impl Fn(&C) -> T) -> HashMap<String, T> {
+        let counters = self.counters.read().unwrap_or_else(|e| e.into_inner());
+        counters
+            .iter()
+            .map(|(name, counters)| (name.clone(), read(counters)))
+            .collect()
+    }
+
+    /// Forgets all names.
+    pub fn clear(&self) {
+        self.counters
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_counts_from_many_threads() {
+        let counter = Arc::new(Counter::default());
+        let counters = Arc::new(CounterMap::<Counter>::default());
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let counter = counter.clone();
+                let counters = counters.clone();
+                thread::spawn(move || {
+                    for i in 0..1000 {
+                        counter.increment();
+                        counters.record(if i % 2 == 0 { "even" } else { "odd" }, |c| c.increment());
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(counter.get(), 4000);
+        let counts = counters.collect(Counter::get);
+        assert_eq!((counts["even"], counts["odd"]), (2000, 2000));
+    }
+
+    #[test]
+    fn test_full_map_counts_new_names_as_other() {
+        let counters = CounterMap::<Counter>::with_max_names(2);
+        for name in ["a", "b", "c", "a", "d"] {
+            counters.record(name, Counter::increment);
+        }
+        let counts = counters.collect(Counter::get);
+        assert_eq!(counts.len(), 3);
+        assert_eq!((counts["a"], counts["b"]), (2, 1));
+        assert_eq!(counts[OTHER_NAME], 2);
+    }
+
+    #[test]
+    fn test_gauge_never_goes_negative() {
+        let gauge = Gauge::default();
+        gauge.adjust(0, 3);
+        gauge.adjust(3, 1);
+        assert_eq!(gauge.get(), 1);
+        gauge.sub(5);
+        assert_eq!(gauge.get(), 0);
+    }
+}
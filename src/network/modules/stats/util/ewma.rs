@@ -1,3 +1,5 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
 /// A structure that computes the Exponentially Weighted Moving Average (EWMA) of a sequence of values.
 ///
 /// EWMA is a type of infinite impulse response filter that applies weighting factors which
@@ -7,14 +9,16 @@
 /// # Fields
 ///
 /// * `alpha` - The smoothing factor, between 0 and 1. A higher value discounts older observations faster.
-/// * `current_value` - The current value of the EWMA after processing the latest input.
-///                     Initially, this will be `None` until the first value is processed.
+/// * `current_value` - The bits of the current value of the EWMA after processing the latest input.
+///                     Initially, this will be `NONE` until the first value is processed.
+///
+/// Updates take `&self` and never lock, so the average can be shared between threads.
 ///
 /// # Example
 ///
 /// ```rust
 /// use fumble::network::modules::stats::util::ewma::Ewma;
-/// let mut ewma = Ewma::new(0.5);
+/// let ewma = Ewma::new(0.5);
 /// ewma.update(10.0);
 /// assert_eq!(ewma.get(), Some(10.0));
 /// ewma.update(20.0);
@@ -22,9 +26,12 @@
 /// ```
 pub struct Ewma {
     alpha: f64,
-    current_value: Option<f64>,
+    current_value: AtomicU64,
 }
 
+/// Marks an `Ewma` without a value, a NaN that no update produces from valid values.
+const NONE: u64 = u64::MAX;
+
 impl Ewma {
     /// Creates a new `Ewma` instance with the specified smoothing factor `alpha`.
     ///
@@ -50,7 +57,7 @@ impl Ewma {
         );
         Ewma {
             alpha,
-            current_value: None,
+            current_value: AtomicU64::new(NONE),
         }
     }
 
@@ -68,18 +75,30 @@ impl Ewma {
     ///
     /// ```rust
     /// use fumble::network::modules::stats::util::ewma::Ewma;
-    /// let mut ewma = Ewma::new(0.5);
+    /// let ewma = Ewma::new(0.5);
     /// ewma.update(10.0);
     /// assert_eq!(ewma.get(), Some(10.0));
     /// ewma.update(20.0);
     /// assert_eq!(ewma.get(), Some(15.0)); // 0.5 * 10.0 + 0.5 * 20.0 = 15.0
     /// ```
-    pub fn update(&mut self, new_value: f64) -> f64 {
-        self.current_value = Some(match self.current_value {
-            Some(current) => current * (1.0 - self.alpha) + new_value * self.alpha,
-            None => new_value, // If no previous value exists, just set to new_value
-        });
-        self.current_value.unwrap()
+    pub fn update(&self, new_value: f64) -> f64 {
+        let mut current = self.current_value.load(Ordering::Relaxed);
+        loop {
+            let updated = match current {
+                NONE => new_value, // If no previous value exists, just set to new_value
+                bits => f64::from_bits(bits) * (1.0 - self.alpha) + new_value * self.alpha,
+            };
+            // Retry if another thread updated the value in the meantime
+            match self.current_value.compare_exchange_weak(
+                current,
+                updated.to_bits(),
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return updated,
+                Err(actual) => current = actual,
+            }
+        }
     }
 
     /// Retrieves the current EWMA value.
@@ -93,12 +112,20 @@ impl Ewma {
     ///
     /// ```rust
     /// use fumble::network::modules::stats::util::ewma::Ewma;
-    /// let mut ewma = Ewma::new(0.5);
+    /// let ewma = Ewma::new(0.5);
     /// assert_eq!(ewma.get(), None);
     /// ewma.update(10.0);
     /// assert_eq!(ewma.get(), Some(10.0));
     /// ```
     pub fn get(&self) -> Option<f64> {
-        self.current_value
+        match self.current_value.load(Ordering::Relaxed) {
+            NONE => None,
+            bits => Some(f64::from_bits(bits)),
+        }
+    }
+
+    /// Forgets all values, as if `update` was never called.
+    pub fn reset(&self) {
+        self.current_value.store(NONE, Ordering::Relaxed);
     }
 }
@@ -0,0 +1,187 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Bits of precision kept for every value, so values are recorded within 1/128 of their size.
+const SUB_BUCKET_BITS: u32 = 7;
+const SUB_BUCKETS: usize = 1 << SUB_BUCKET_BITS;
+/// Largest value recorded exactly, in microseconds: a little over an hour. Longer durations
+/// count as this one.
+const MAX_VALUE: u64 = (1 << 32) - 1;
+/// Values below `2 * SUB_BUCKETS` get a bucket each, every doubling above that `SUB_BUCKETS`.
+const BUCKETS: usize = 2 * SUB_BUCKETS + (32 - SUB_BUCKET_BITS as usize - 1) * SUB_BUCKETS;
+
+/// A histogram of durations in the style of an HDR histogram, recorded from any number of threads
+/// without locking.
+///
+/// Durations are counted in microsecond buckets whose width grows with the value, so small and
+/// large durations alike are kept to within 1% while the histogram has a fixed size.
+///
+/// # Example
+///
+/// ```rust
+/// use fumble::network::modules::stats::util::histogram::LatencyHistogram;
+/// use std::time::Duration;
+/// let histogram = LatencyHistogram::new();
+/// for millis in 1..=100 {
+///     histogram.record(Duration::from_millis(millis));
+/// }
+/// let percentiles = histogram.percentiles();
+/// assert_eq!(percentiles.count, 100);
+/// assert_eq!(percentiles.max, Duration::from_millis(100));
+/// assert!(percentiles.p50.abs_diff(Duration::from_millis(50)) < Duration::from_micros(500));
+/// ```
+pub struct LatencyHistogram {
+    buckets: Box<[AtomicU64]>,
+    count: AtomicU64,
+    max: AtomicU64,
+}
+
+/// The percentiles of the durations recorded by a [`LatencyHistogram`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LatencyPercentiles {
+    pub count: usize,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        LatencyHistogram {
+            buckets: (0..BUCKETS).map(|_| AtomicU64::new(0)).collect(),
+            count: AtomicU64::new(0),
+            max: AtomicU64::new(0),
+        }
+    }
+
+    pub fn record(&self, duration: Duration) {
+        let value = (duration.as_micros() as u64).min(MAX_VALUE);
+        self.buckets[bucket_index(value)].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.max.fetch_max(value, Ordering::Relaxed);
+    }
+
+    /// Returns the duration that `quantile` of the recorded durations do not exceed, or zero if
+    /// nothing was recorded.
+    pub fn value_at_quantile(&self, quantile: f64) -> Duration {
+        let count = self.count.load(Ordering::Relaxed);
+        if count == 0 {
+            return Duration::ZERO;
+        }
+        let rank = ((quantile.clamp(0.0, 1.0) * count as f64).ceil() as u64).max(1);
+        let max = self.max.load(Ordering::Relaxed);
+        let mut seen = 0;
+        for (index, bucket) in self.buckets.iter().enumerate() {
+            seen += bucket.load(Ordering::Relaxed);
+            if seen >= rank {
+                return Duration::from_micros(highest_in_bucket(index).min(max));
+            }
+        }
+        // Recorded while reading, the count ran ahead of the buckets
+        Duration::from_micros(max)
+    }
+
+    pub fn percentiles(&self) -> LatencyPercentiles {
+        LatencyPercentiles {
+            count: self.count.load(Ordering::Relaxed) as usize,
+            p50: self.value_at_quantile(0.5),
+            p90: self.value_at_quantile(0.9),
+            p99: self.value_at_quantile(0.99),
+            max: Duration::from_micros(self.max.load(Ordering::Relaxed)),
+        }
+    }
+
+    pub fn reset(&self) {
+        for bucket in self.buckets.iter() {
+            bucket.store(0, Ordering::Relaxed);
+        }
+        self.count.store(0, Ordering::Relaxed);
+        self.max.store(0, Ordering::Relaxed);
+    }
+}
+
+impl fmt::Display for LatencyPercentiles {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let millis = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        write!(
+            f,
+            "p50 {:.2}ms, p90 {:.2}ms, p99 {:.2}ms, max {:.2}ms ({} packets)",
+            millis(self.p50),
+            millis(self.p90),
+            millis(self.p99),
+            millis(self.max),
+            self.count
+        )
+    }
+}
+
+/// Returns the bucket of `value`, which keeps its highest [`SUB_BUCKET_BITS`] + 1 bits.
+fn bucket_index(value: u64) -> usize {
+    if value < 2 * SUB_BUCKETS as u64 {
+        return value as usize;
+    }
+    let shift = 63 - value.leading_zeros() - SUB_BUCKET_BITS;
+    let sub_bucket = (value >> shift) as usize - SUB_BUCKETS;
+    2 * SUB_BUCKETS + (shift as usize - 1) * SUB_BUCKETS + sub_bucket
+}
+
+/// Returns the highest value that falls into the bucket at `index`.
+fn highest_in_bucket(index: usize) -> u64 {
+    if index < 2 * SUB_BUCKETS {
+        return index as u64;
+    }
+    let offset = index - 2 * SUB_BUCKETS;
+    let shift = offset / SUB_BUCKETS + 1;
+    let sub_bucket = (offset % SUB_BUCKETS + SUB_BUCKETS) as u64;
+    ((sub_bucket + 1) << shift) - 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buckets_cover_every_value() {
+        assert_eq!(bucket_index(0), 0);
+        assert_eq!(bucket_index(255), 255);
+        assert_eq!(bucket_index(MAX_VALUE), BUCKETS - 1);
+        for value in [1, 255, 256, 257, 1000, 123_456, 9_999_999, MAX_VALUE] {
+            let index = bucket_index(value);
+            assert!(highest_in_bucket(index) >= value);
+            // Within 1% of the value
+            assert!(highest_in_bucket(index) - value <= value / 100, "{}", value);
+            if index > 0 {
+                assert!(highest_in_bucket(index - 1) < value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_percentiles_of_recorded_durations() {
+        let histogram = LatencyHistogram::new();
+        assert_eq!(histogram.percentiles(), LatencyPercentiles::default());
+        for _ in 0..98 {
+            histogram.record(Duration::from_millis(10));
+        }
+        histogram.record(Duration::from_millis(20));
+        histogram.record(Duration::from_secs(2));
+
+        let percentiles = histogram.percentiles();
+        assert_eq!(percentiles.count, 100);
+        assert!(percentiles.p50.abs_diff(Duration::from_millis(10)) < Duration::from_micros(100));
+        assert_eq!(percentiles.p90, percentiles.p50);
+        assert!(percentiles.p99.abs_diff(Duration::from_millis(20)) < Duration::from_micros(200));
+        assert_eq!(percentiles.max, Duration::from_secs(2));
+
+        histogram.reset();
+        assert_eq!(histogram.percentiles().count, 0);
+    }
+}
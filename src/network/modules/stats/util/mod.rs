@@ -1 +1,4 @@
+pub mod counter;
 pub mod ewma;
+pub mod histogram;
+pub mod throughput;
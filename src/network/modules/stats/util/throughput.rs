@@ -0,0 +1,94 @@
+use crate::network::modules::stats::util::ewma::Ewma;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Measures throughput in KB/s, smoothed with an [`Ewma`] over fixed update intervals.
+///
+/// Bytes are accumulated until `update_interval` has elapsed, at which point the rate over
+/// that interval is fed into the moving average. Recording zero bytes still advances the
+/// interval, so the throughput decays towards zero when traffic stops. Any number of threads
+/// can record at the same time; whichever finds the interval elapsed updates the average.
+///
+/// # Example
+///
+/// ```rust
+/// use fumble::network::modules::stats::util::throughput::ThroughputMeter;
+/// use std::time::Duration;
+/// let meter = ThroughputMeter::new(0.5, Duration::ZERO);
+/// meter.record(0);
+/// assert_eq!(meter.throughput(), 0.0);
+/// ```
+pub struct ThroughputMeter {
+    ewma: Ewma,
+    recent_bytes: AtomicUsize,
+    started: Instant,
+    /// Time of the last update, in nanoseconds since `started`
+    recent_timer: AtomicU64,
+    update_interval: Duration,
+}
+
+impl ThroughputMeter {
+    /// Creates a meter with the EWMA smoothing factor `alpha` and the interval over which rates are measured.
+    pub fn new(alpha: f64, update_interval: Duration) -> Self {
+        ThroughputMeter {
+            ewma: Ewma::new(alpha),
+            recent_bytes: AtomicUsize::new(0),
+            started: Instant::now(),
+            recent_timer: AtomicU64::new(0),
+            update_interval,
+        }
+    }
+
+    /// Records `bytes` transferred, updating the throughput once the update interval has elapsed.
+    pub fn record(&self, bytes: usize) {
+        self.recent_bytes.fetch_add(bytes, Ordering::Relaxed);
+        let now = self.started.elapsed().as_nanos() as u64;
+        let last_update = self.recent_timer.load(Ordering::Relaxed);
+        let elapsed = Duration::from_nanos(now.saturating_sub(last_update));
+        // Only the thread that moves the timer forward updates the average
+        if elapsed >= self.update_interval
+            && self
+                .recent_timer
+                .compare_exchange(last_update, now, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+        {
+            // Use the actual elapsed time, as recording may happen later than the interval
+            let seconds = elapsed.as_secs_f64().max(f64::EPSILON);
+            let recent_bytes = self.recent_bytes.swap(0, Ordering::Relaxed);
+            self.ewma.update((recent_bytes as f64 / 1024f64) / seconds);
+        }
+    }
+
+    /// The smoothed throughput in KB/s, or `0.0` before the first update.
+    pub fn throughput(&self) -> f64 {
+        self.ewma.get().unwrap_or(0.0)
+    }
+
+    /// Forgets the measured throughput and the bytes recorded since the last update.
+    pub fn reset(&self) {
+        self.ewma.reset();
+        self.recent_bytes.store(0, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accumulates_until_interval_elapsed() {
+        let meter = ThroughputMeter::new(1.0, Duration::from_secs(3600));
+        meter.record(1024);
+        assert_eq!(meter.throughput(), 0.0);
+        assert_eq!(meter.recent_bytes.load(Ordering::Relaxed), 1024);
+    }
+
+    #[test]
+    fn test_measures_rate() {
+        let mut meter = ThroughputMeter::new(1.0, Duration::from_millis(10));
+        meter.started = Instant::now() - Duration::from_secs(1);
+        meter.record(10 * 1024);
+        let throughput = meter.throughput();
+        assert!(throughput > 9.0 && throughput <= 10.0, "{}", throughput);
+    }
+}
@@ -1,6 +1,9 @@
 use crate::network::core::packet_data::PacketData;
 use crate::network::modules::stats::tamper_stats::TamperStats;
+use crate::network::packet::PacketView;
+use crate::network::types::packet_actions::PacketActions;
 use crate::network::types::probability::Probability;
+use crate::network::utils::random;
 use log::error;
 use rand::Rng;
 use std::collections::HashSet;
@@ -11,11 +14,14 @@ pub fn tamper_packets(
     tamper_probability: Probability,
     tamper_amount: Probability,
     recalculate_checksums: bool,
-    stats: &mut TamperStats,
+    stats: &TamperStats,
 ) {
-    let should_update_stats = stats.should_update();
+    let should_update_stats = !packets.is_empty() && stats.should_update();
+    // The sample shown by the TUI is refreshed from the last packet of the batch
+    let mut sample = should_update_stats.then(|| stats.sample());
+    stats.total_packets.add(packets.len());
     for packet_data in packets.iter_mut() {
-        let should_skip = rand::random::<f64>() >= tamper_probability.value();
+        let should_skip = random::rng().random::<f64>() >= tamper_probability.value();
 
         if should_skip && !should_update_stats {
             continue;
@@ -23,30 +29,20 @@ pub fn tamper_packets(
 
         let data = packet_data.packet.data.to_mut();
 
-        let (ip_header_len, protocol) = match get_ip_version(data) {
-            Some((4, data)) => parse_ipv4_header(data),
-            Some((6, data)) => parse_ipv6_header(data),
-            _ => {
-                error!("Unsupported IP version");
+        let payload_offset = match PacketView::parse(data) {
+            Ok(view) => view.payload_range().start,
+            Err(e) => {
+                error!("Failed to parse packet: {}", e);
                 continue;
             }
         };
-
-        let total_header_len = match protocol {
-            17 => parse_udp_header(data, ip_header_len), // UDP
-            6 => parse_tcp_header(data, ip_header_len),  // TCP
-            _ => ip_header_len,                          // Unsupported protocols
-        };
-
-        let payload_offset = total_header_len;
         let payload_length = data.len() - payload_offset;
 
         if should_skip {
-            if should_update_stats {
-                stats.data = data[payload_offset..].to_owned();
-                stats.tamper_flags = vec![false; stats.data.len()];
-                stats.checksum_valid = true;
-                stats.updated();
+            if let Some(sample) = sample.as_mut() {
+                sample.data = data[payload_offset..].to_owned();
+                sample.tamper_flags = vec![false; sample.data.len()];
+                sample.checksum_valid = true;
             }
             continue;
         }
@@ -54,12 +50,13 @@ pub fn tamper_packets(
         if payload_length > 0 {
             let bytes_to_tamper = (payload_length as f64 * tamper_amount.value()).ceil() as usize;
             let tampered_indices = apply_tampering(&mut data[payload_offset..], bytes_to_tamper);
+            packet_data.actions.insert(PacketActions::TAMPERED);
+            stats.tampered_packets.increment();
 
-            if should_update_stats {
+            if let Some(sample) = sample.as_mut() {
                 let tampered_flags = calculate_tampered_flags(data.len(), &tampered_indices);
-                stats.tamper_flags = tampered_flags;
-                stats.data = data[payload_offset..].to_owned();
-                stats.updated();
+                sample.tamper_flags = tampered_flags;
+                sample.data = data[payload_offset..].to_owned();
             }
         }
 
@@ -72,20 +69,22 @@ pub fn tamper_packets(
             }
         }
 
-        if should_update_stats {
-            stats.checksum_valid = packet_data.packet.address.ip_checksum()
+        if let Some(sample) = sample.as_mut() {
+            sample.checksum_valid = packet_data.packet.address.ip_checksum()
                 && packet_data.packet.address.tcp_checksum()
                 && packet_data.packet.address.udp_checksum();
-            stats.updated();
         }
     }
+    if let Some(sample) = sample {
+        stats.update(sample);
+    }
 }
 
 fn apply_tampering(data: &mut [u8], bytes_to_tamper: usize) -> HashSet<usize> {
     let mut tampered_indices = HashSet::new();
     let mut tampered_count = 0;
     let data_len = data.len();
-    let mut rng = rand::thread_rng();
+    let mut rng = random::rng();
 
     while tampered_count < bytes_to_tamper && tampered_count < data_len {
         let index = rng.gen_range(0..data.len());
@@ -115,36 +114,6 @@ fn calculate_tampered_flags(data_len: usize, tampered_indices: &HashSet<usize>)
     tampered_flags
 }
 
-fn get_ip_version(data: &[u8]) -> Option<(u8, &[u8])> {
-    if data.is_empty() {
-        return None;
-    }
-    let version = data[0] >> 4;
-    Some((version, data))
-}
-
-fn parse_ipv4_header(data: &[u8]) -> (usize, u8) {
-    let header_length = ((data[0] & 0x0F) * 4) as usize;
-    let protocol = data[9]; // Protocol field
-    (header_length, protocol)
-}
-
-fn parse_ipv6_header(data: &[u8]) -> (usize, u8) {
-    let header_length = 40; // IPv6 header is always 40 bytes
-    let next_header = data[6]; // Next header field
-    (header_length, next_header)
-}
-
-fn parse_udp_header(_data: &[u8], ip_header_len: usize) -> usize {
-    let udp_header_len = 8; // UDP header is always 8 bytes
-    ip_header_len + udp_header_len
-}
-
-fn parse_tcp_header(data: &[u8], ip_header_len: usize) -> usize {
-    let tcp_data_offset = (data[ip_header_len + 12] >> 4) * 4;
-    ip_header_len + tcp_data_offset as usize
-}
-
 fn bit_manipulation(
     data: &mut [u8],
     byte_index: usize,
@@ -1,34 +1,45 @@
 use crate::network::core::packet_data::PacketData;
 use crate::network::modules::stats::throttle_stats::ThrottleStats;
+use crate::network::types::held_by::HeldBy;
 use crate::network::types::probability::Probability;
-use rand::{thread_rng, Rng};
+use crate::network::utils::random;
+use rand::Rng;
 use std::collections::VecDeque;
 use std::time::{Duration, Instant};
 
-pub fn throttle_packages<'a>(
-    packets: &mut Vec<PacketData<'a>>,
-    storage: &mut VecDeque<PacketData<'a>>,
+pub fn throttle_packages(
+    packets: &mut Vec<PacketData>,
+    storage: &mut VecDeque<PacketData>,
     throttled_start_time: &mut Instant,
     throttle_probability: Probability,
     throttle_duration: Duration,
     drop: bool,
-    stats: &mut ThrottleStats,
-) {
+    stats: &ThrottleStats,
+) -> Vec<PacketData> {
+    let previously_queued = storage.len();
+    let mut dropped = Vec::new();
     if is_throttled(throttle_duration, throttled_start_time) {
         if drop {
-            stats.dropped_count += packets.len();
-            packets.clear();
+            stats.dropped_count.add(packets.len());
+            dropped.append(packets);
         } else {
-            storage.extend(packets.drain(..));
+            storage.extend(packets.drain(..).map(|mut packet_data| {
+                packet_data.hold(HeldBy::THROTTLE);
+                packet_data
+            }));
         }
-        stats.is_throttling = true;
+        stats.set_throttling(true);
     } else {
         packets.extend(storage.drain(..));
-        if thread_rng().gen_bool(throttle_probability.value()) {
+        if random::rng().gen_bool(throttle_probability.value()) {
             *throttled_start_time = Instant::now();
         }
-        stats.is_throttling = false;
+        stats.set_throttling(false);
     }
+    stats
+        .queued_packets
+        .adjust(previously_queued, storage.len());
+    dropped
 }
 
 fn is_throttled(throttle_duration: Duration, throttled_start_time: &mut Instant) -> bool {
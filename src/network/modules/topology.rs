@@ -0,0 +1,164 @@
+use crate::cli::settings::topology::TopologyOptions;
+use crate::network::core::packet_data::PacketData;
+use crate::network::modules::stats::topology_stats::TopologyStats;
+use crate::network::packet::IpHeader;
+use crate::network::types::delayed_packet::DelayedPacket;
+use crate::network::types::held_by::HeldBy;
+use crate::network::utils::random;
+use rand::Rng;
+use std::collections::BinaryHeap;
+use std::net::IpAddr;
+use std::time::Instant;
+
+pub fn topology_packets(
+    packets: &mut Vec<PacketData>,
+    storage: &mut BinaryHeap<DelayedPacket>,
+    topology: &TopologyOptions,
+    stats: &TopologyStats,
+) -> Vec<PacketData> {
+    let previously_delayed = storage.len();
+    let mut rng = random::rng();
+    let mut passed_packets = Vec::new();
+    let mut dropped_packets = Vec::new();
+
+    for mut packet_data in packets.drain(..) {
+        let profile = remote_address(
+            &packet_data.packet.data,
+            packet_data.packet.address.outbound(),
+        )
+        .and_then(|address| topology.profile_for(address));
+        let profile = match profile {
+            Some(profile) => profile,
+            None => {
+                stats.unmatched.increment();
+                passed_packets.push(packet_data);
+                continue;
+            }
+        };
+
+        let dropped = rng.gen_bool(profile.loss.value());
+        stats.record(&profile.name, dropped);
+        if dropped {
+            dropped_packets.push(packet_data);
+            continue;
+        }
+        if profile.delay.is_zero() {
+            passed_packets.push(packet_data);
+        } else {
+            packet_data.hold(HeldBy::TOPOLOGY);
+            storage.push(DelayedPacket::new(packet_data, profile.delay.duration()));
+        }
+    }
+
+    packets.append(&mut passed_packets);
+
+    let now = Instant::now();
+    while storage
+        .peek()
+        .is_some_and(|delayed_packet| delayed_packet.delay_until <= now)
+    {
+        if let Some(delayed_packet) = storage.pop() {
+            packets.push(delayed_packet.packet);
+        }
+    }
+    stats
+        .delayed_packets
+        .adjust(previously_delayed, storage.len());
+    dropped_packets
+}
+
+/// Returns the address of the remote host: the destination of outbound packets
+/// and the source of inbound packets.
+fn remote_address(data: &[u8], outbound: bool) -> Option<IpAddr> {
+    let ip = IpHeader::parse(data).ok()?;
+    Some(if outbound {
+        ip.destination()
+    } else {
+        ip.source()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::modules::stats::topology_stats::RegionStats;
+    use crate::network::packet::protocol;
+    use crate::network::packet::test_packets::{ipv4, udp};
+    use windivert::layer::NetworkLayer;
+    use windivert::packet::WinDivertPacket;
+
+    fn inbound_packet() -> PacketData {
+        // The test packet source is 192.168.1.10
+        let data = ipv4(protocol::UDP, &udp(1000, 2000, b"payload"));
+        unsafe { PacketData::from(WinDivertPacket::<NetworkLayer>::new(data)) }
+    }
+
+    fn options(routes: &[&str], default_profile: Option<&str>) -> TopologyOptions {
+        TopologyOptions {
+            routes: routes.iter().map(|route| route.parse().unwrap()).collect(),
+            default_profile: default_profile.map(|profile| profile.parse().unwrap()),
+            content_match: None,
+        }
+    }
+
+    #[test]
+    fn test_remote_address() {
+        let data = ipv4(protocol::UDP, &udp(1000, 2000, &[]));
+        assert_eq!(
+            remote_address(&data, false),
+            Some("192.168.1.10".parse().unwrap())
+        );
+        assert_eq!(
+            remote_address(&data, true),
+            Some("93.184.216.34".parse().unwrap())
+        );
+        assert_eq!(remote_address(&[], true), None);
+    }
+
+    #[test]
+    fn test_delays_matching_region() {
+        let topology = options(&["192.168.0.0/16 = lan: 10000ms"], None);
+        let mut packets = vec![inbound_packet()];
+        let mut storage = BinaryHeap::new();
+        let stats = TopologyStats::new();
+
+        topology_packets(&mut packets, &mut storage, &topology, &stats);
+
+        assert!(packets.is_empty());
+        assert_eq!(storage.len(), 1);
+        assert_eq!(stats.snapshot().regions["lan"].packets, 1);
+    }
+
+    #[test]
+    fn test_full_loss_drops_packets() {
+        let topology = options(&["10.0.0.0/8 = far: 5ms"], Some("default: 0ms, 100% loss"));
+        let mut packets = vec![inbound_packet(), inbound_packet()];
+        let mut storage = BinaryHeap::new();
+        let stats = TopologyStats::new();
+
+        topology_packets(&mut packets, &mut storage, &topology, &stats);
+
+        assert!(packets.is_empty());
+        assert!(storage.is_empty());
+        assert_eq!(
+            stats.snapshot().regions["default"],
+            RegionStats {
+                packets: 2,
+                dropped: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_unmatched_packets_pass() {
+        let topology = options(&["10.0.0.0/8 = far: 5ms"], None);
+        let mut packets = vec![inbound_packet()];
+        let mut storage = BinaryHeap::new();
+        let stats = TopologyStats::new();
+
+        topology_packets(&mut packets, &mut storage, &topology, &stats);
+
+        assert_eq!(packets.len(), 1);
+        assert_eq!(stats.snapshot().unmatched, 1);
+    }
+}
@@ -0,0 +1,355 @@
+use crate::cli::settings::trace::{TraceEnd, TraceFile, TraceOptions};
+use crate::network::core::packet_data::PacketData;
+use crate::network::modules::stats::trace_stats::TraceStats;
+use crate::network::types::delayed_packet::DelayedPacket;
+use crate::network::types::held_by::HeldBy;
+use crate::network::types::trace::{Trace, TraceConditions, DELIVERY_OPPORTUNITY_BYTES};
+use crate::network::utils::random;
+use rand::Rng;
+use std::collections::BinaryHeap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Longest a packet waits for the link of the trace before it is dropped, like a full queue.
+const MAX_QUEUEING_DELAY: Duration = Duration::from_secs(5);
+
+/// The state of the replay of a trace by one processing thread.
+#[derive(Default)]
+pub struct TraceReplay {
+    pub storage: BinaryHeap<DelayedPacket>,
+    /// The trace being replayed, so the link starts over empty when another one is loaded
+    trace: Option<Arc<Trace>>,
+    /// When the link of a CSV trace is done sending the packets accepted so far
+    link_free_at: Option<Instant>,
+    /// When the last accepted packet is released, so packets leave in the order they came
+    last_release: Option<Instant>,
+    /// Index of the next unused delivery opportunity of a Mahimahi trace, counted across loops
+    next_opportunity: u64,
+    /// Bytes left in the delivery opportunity before `next_opportunity`
+    opportunity_bytes_left: usize,
+}
+
+impl TraceReplay {
+    /// Forgets the state of the link if `file` is not the trace replayed so far.
+    fn follow(&mut self, file: &TraceFile) {
+        if self
+            .trace
+            .as_ref()
+            .is_some_and(|trace| Arc::ptr_eq(trace, &file.trace))
+        {
+            return;
+        }
+        self.trace = Some(file.trace.clone());
+        self.link_free_at = None;
+        self.last_release = None;
+        self.next_opportunity = 0;
+        self.opportunity_bytes_left = 0;
+    }
+
+    /// Returns when a packet of `size` bytes arriving at `now` is released by the link of a CSV
+    /// trace under `conditions`, or `None` if it is lost.
+    fn schedule_conditions(
+        &mut self,
+        conditions: &TraceConditions,
+        size: usize,
+        now: Instant,
+    ) -> Option<Instant> {
+        if random::rng().gen_bool(conditions.loss.value()) {
+            return None;
+        }
+        let transmission = match conditions.bandwidth {
+            // A bandwidth of zero is an outage
+            Some(bandwidth) if bandwidth.is_zero() => return None,
+            Some(bandwidth) => Duration::from_secs_f64(size as f64 / bandwidth.bytes_per_second()),
+            None => Duration::ZERO,
+        };
+        let start = self.link_free_at.map_or(now, |free_at| free_at.max(now));
+        if start - now > MAX_QUEUEING_DELAY {
+            return None;
+        }
+        let sent = start + transmission;
+        self.link_free_at = Some(sent);
+        Some(self.in_order(sent + conditions.latency))
+    }
+
+    /// Returns when a packet of `size` bytes arriving `elapsed` into the replay is delivered by
+    /// the delivery opportunities `times` of a Mahimahi trace lasting `period`, or `None` if it
+    /// would wait too long. Opportunities that passed without a packet to deliver are lost.
+    fn schedule_opportunities(
+        &mut self,
+        times: &[Duration],
+        period: Duration,
+        started: Instant,
+        elapsed: Duration,
+        size: usize,
+        end: TraceEnd,
+    ) -> Option<Instant> {
+        let count = times.len() as u64;
+        let period_nanos = period.as_nanos() as u64;
+        let opportunity_at = |index: u64| {
+            Duration::from_nanos(index / count * period_nanos) + times[(index % count) as usize]
+        };
+        let elapsed_nanos = elapsed.as_nanos() as u64;
+        let into_period = Duration::from_nanos(elapsed_nanos % period_nanos);
+        let upcoming = elapsed_nanos / period_nanos * count
+            + times.partition_point(|time| *time < into_period) as u64;
+
+        // The opportunity last used can take more of the packet if it has not passed yet
+        let (mut next, mut bytes_left) = if upcoming < self.next_opportunity {
+            (self.next_opportunity, self.opportunity_bytes_left)
+        } else {
+            (upcoming, 0)
+        };
+        let mut remaining = size.saturating_sub(bytes_left);
+        bytes_left -= size - remaining;
+        while remaining > 0 {
+            let taken = remaining.min(DELIVERY_OPPORTUNITY_BYTES);
+            remaining -= taken;
+            bytes_left = DELIVERY_OPPORTUNITY_BYTES - taken;
+            next += 1;
+        }
+        let mut delivered_at = opportunity_at(next - 1);
+        if end == TraceEnd::Stop {
+            // After the end of the trace the link no longer holds packets back
+            delivered_at = delivered_at.min(period);
+        }
+        let release = started + delivered_at;
+        if release.saturating_duration_since(Instant::now()) > MAX_QUEUEING_DELAY {
+            return None;
+        }
+        self.next_opportunity = next;
+        self.opportunity_bytes_left = bytes_left;
+        Some(self.in_order(release))
+    }
+
+    /// Returns `release`, or the release of the packet accepted before if that is later.
+    fn in_order(&mut self, release: Instant) -> Instant {
+        let release = self.last_release.map_or(release, |last| last.max(release));
+        self.last_release = Some(release);
+        release
+    }
+}
+
+/// Holds `packets` back, or drops them, according to the conditions recorded in the trace of
+/// `file` at the time they arrive, and releases the held packets that are due. Packets pass
+/// unchanged if no trace was read. Returns the dropped packets.
+pub fn trace_packets(
+    packets: &mut Vec<PacketData>,
+    replay: &mut TraceReplay,
+    options: &TraceOptions,
+    file: Option<&TraceFile>,
+    stats: &TraceStats,
+) -> Vec<PacketData> {
+    let previously_delayed = replay.storage.len();
+    let mut dropped_packets = Vec::new();
+
+    if let Some(file) = file {
+        replay.follow(file);
+        let now = Instant::now();
+        let elapsed = now.saturating_duration_since(file.started);
+        let period = file.trace.period();
+        stats.set_position(elapsed, period);
+        let finished = options.end == TraceEnd::Stop && elapsed >= period;
+        let position = Duration::from_nanos((elapsed.as_nanos() % period.as_nanos()) as u64);
+
+        let mut passed_packets = Vec::new();
+        for mut packet_data in packets.drain(..) {
+            if finished {
+                passed_packets.push(packet_data);
+                continue;
+            }
+            let size = packet_data.packet.data.len();
+            let release = match file.trace.as_ref() {
+                Trace::DeliveryOpportunities(times) => replay.schedule_opportunities(
+                    times,
+                    period,
+                    file.started,
+                    elapsed,
+                    size,
+                    options.end,
+                ),
+                Trace::Conditions(_) => file
+                    .trace
+                    .conditions_at(position)
+                    .and_then(|conditions| replay.schedule_conditions(conditions, size, now)),
+            };
+            stats.record(release.is_none());
+            match release {
+                None => dropped_packets.push(packet_data),
+                Some(release) if release <= now => passed_packets.push(packet_data),
+                Some(release) => {
+                    packet_data.hold(HeldBy::TRACE);
+                    replay
+                        .storage
+                        .push(DelayedPacket::new(packet_data, release - now));
+                }
+            }
+        }
+        packets.append(&mut passed_packets);
+    }
+
+    let now = Instant::now();
+    while replay
+        .storage
+        .peek()
+        .is_some_and(|delayed_packet| delayed_packet.delay_until <= now)
+    {
+        if let Some(delayed_packet) = replay.storage.pop() {
+            packets.push(delayed_packet.packet);
+        }
+    }
+    stats
+        .delayed_packets
+        .adjust(previously_delayed, replay.storage.len());
+    dropped_packets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::packet::protocol;
+    use crate::network::packet::test_packets::{ipv4, udp};
+    use std::sync::Arc;
+    use windivert::layer::NetworkLayer;
+    use windivert::packet::WinDivertPacket;
+
+    fn packet_data(payload: usize) -> PacketData {
+        let data = ipv4(protocol::UDP, &udp(1000, 2000, &vec![0; payload]));
+        unsafe { PacketData::from(WinDivertPacket::<NetworkLayer>::new(data)) }
+    }
+
+    fn options(end: TraceEnd) -> TraceOptions {
+        TraceOptions {
+            file: Some("test.trace".into()),
+            end,
+            ..Default::default()
+        }
+    }
+
+    fn file(trace: &str, started: Instant) -> TraceFile {
+        TraceFile {
+            path: "test.trace".into(),
+            trace: Arc::new(Trace::parse(trace).unwrap()),
+            started,
+        }
+    }
+
+    #[test]
+    fn test_holds_packets_for_latency_of_current_row() {
+        let file = file("0,10s\n1s,0\n", Instant::now());
+        let mut packets = vec![packet_data(10), packet_data(10)];
+        let mut replay = TraceReplay::default();
+        let stats = TraceStats::new();
+
+        let dropped = trace_packets(
+            &mut packets,
+            &mut replay,
+            &options(TraceEnd::Loop),
+            Some(&file),
+            &stats,
+        );
+
+        assert!(dropped.is_empty());
+        assert!(packets.is_empty());
+        assert_eq!(replay.storage.len(), 2);
+        assert!(replay
+            .storage
+            .peek()
+            .unwrap()
+            .packet
+            .held_by
+            .contains(HeldBy::TRACE));
+        assert_eq!(stats.snapshot().delayed_packets, 2);
+    }
+
+    #[test]
+    fn test_full_loss_and_outages_drop_packets() {
+        let mut replay = TraceReplay::default();
+        let stats = TraceStats::new();
+        for trace in ["0,0,,100%\n1s,0\n", "0,0,0bps\n1s,0\n"] {
+            let file = file(trace, Instant::now());
+            let mut packets = vec![packet_data(10)];
+            let dropped = trace_packets(
+                &mut packets,
+                &mut replay,
+                &options(TraceEnd::Loop),
+                Some(&file),
+                &stats,
+            );
+            assert_eq!(dropped.len(), 1);
+            assert!(packets.is_empty());
+        }
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.total_packets, 2);
+        assert_eq!(snapshot.dropped_packets, 2);
+    }
+
+    #[test]
+    fn test_bandwidth_spaces_packets_out() {
+        // 1000 bytes take a second at 8kbit/s, so the second packet leaves a second after the first
+        let trace = Arc::new(Trace::parse("0,0,8kbit\n1h,0\n").unwrap());
+        let conditions = *trace.conditions_at(Duration::ZERO).unwrap();
+        let mut replay = TraceReplay::default();
+        let now = Instant::now();
+        let first = replay.schedule_conditions(&conditions, 1000, now).unwrap();
+        let second = replay.schedule_conditions(&conditions, 1000, now).unwrap();
+        assert_eq!(first, now + Duration::from_secs(1));
+        assert_eq!(second, now + Duration::from_secs(2));
+        // Packets that would wait longer than the queue allows are lost
+        for _ in 0..4 {
+            replay.schedule_conditions(&conditions, 1000, now).unwrap();
+        }
+        assert_eq!(replay.schedule_conditions(&conditions, 1000, now), None);
+    }
+
+    #[test]
+    fn test_packets_use_delivery_opportunities() {
+        let times = [Duration::from_millis(100), Duration::from_millis(200)];
+        let period = Duration::from_millis(200);
+        let started = Instant::now();
+        let mut replay = TraceReplay::default();
+        let mut schedule = |elapsed: u64, size: usize| {
+            replay
+                .schedule_opportunities(
+                    &times,
+                    period,
+                    started,
+                    Duration::from_millis(elapsed),
+                    size,
+                    TraceEnd::Loop,
+                )
+                .map(|release| release - started)
+        };
+        // Small packets share an opportunity, a large one spans several, and later loops of the
+        // trace continue after the end of the earlier ones
+        assert_eq!(schedule(0, 1000), Some(Duration::from_millis(100)));
+        assert_eq!(schedule(0, 500), Some(Duration::from_millis(100)));
+        assert_eq!(schedule(0, 2000), Some(Duration::from_millis(300)));
+        // Opportunities that passed without packets are lost
+        assert_eq!(schedule(450, 100), Some(Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn test_stop_lets_packets_through_after_end() {
+        let started = Instant::now() - Duration::from_secs(2);
+        let file = file("0,10s\n1s,10s\n", started);
+        let mut packets = vec![packet_data(10)];
+        let mut replay = TraceReplay::default();
+        let stats = TraceStats::new();
+
+        trace_packets(
+            &mut packets,
+            &mut replay,
+            &options(TraceEnd::Stop),
+            Some(&file),
+            &stats,
+        );
+
+        assert_eq!(packets.len(), 1);
+        assert!(replay.storage.is_empty());
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.total_packets, 0);
+        assert_eq!(snapshot.period, Duration::from_secs(2));
+        assert!(snapshot.elapsed >= Duration::from_secs(2));
+    }
+}
@@ -0,0 +1,214 @@
+use crate::network::packet::{ensure_len, read_u16, PacketError};
+
+pub const PORT: u16 = 53;
+pub const HEADER_LEN: usize = 12;
+
+pub const RCODE_NO_ERROR: u8 = 0;
+pub const RCODE_SERVFAIL: u8 = 2;
+pub const RCODE_NXDOMAIN: u8 = 3;
+
+/// Maximum number of compression pointers followed while reading a name.
+const MAX_POINTER_JUMPS: usize = 16;
+
+/// A bounds-checked view of a DNS message carried in a UDP payload.
+#[derive(Debug, Clone, Copy)]
+pub struct DnsMessage<'a> {
+    data: &'a [u8],
+}
+
+/// The first entry of the question section of a DNS message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DnsQuestion {
+    /// The queried name in dotted form without a trailing dot, e.g. `www.example.com`.
+    pub name: String,
+    pub query_type: u16,
+    pub query_class: u16,
+    /// Offset right after this question within the DNS message.
+    pub end: usize,
+}
+
+impl<'a> DnsMessage<'a> {
+    pub fn parse(data: &'a [u8]) -> Result<Self, PacketError> {
+        ensure_len(data, HEADER_LEN, "DNS")?;
+        Ok(DnsMessage { data })
+    }
+
+    pub fn id(&self) -> u16 {
+        read_u16(self.data, 0)
+    }
+
+    pub fn flags(&self) -> u16 {
+        read_u16(self.data, 2)
+    }
+
+    pub fn is_response(&self) -> bool {
+        self.flags() & 0x8000 != 0
+    }
+
+    pub fn opcode(&self) -> u8 {
+        ((self.flags() >> 11) & 0x0F) as u8
+    }
+
+    pub fn response_code(&self) -> u8 {
+        (self.flags() & 0x000F) as u8
+    }
+
+    pub fn question_count(&self) -> u16 {
+        read_u16(self.data, 4)
+    }
+
+    pub fn answer_count(&self) -> u16 {
+        read_u16(self.data, 6)
+    }
+
+    pub fn authority_count(&self) -> u16 {
+        read_u16(self.data, 8)
+    }
+
+    pub fn additional_count(&self) -> u16 {
+        read_u16(self.data, 10)
+    }
+
+    /// Parses the first question of the message, if there is one.
+    pub fn first_question(&self) -> Result<Option<DnsQuestion>, PacketError> {
+        if self.question_count() == 0 {
+            return Ok(None);
+        }
+        let (name, name_end) = read_name(self.data, HEADER_LEN)?;
+        ensure_len(self.data, name_end + 4, "DNS question")?;
+        Ok(Some(DnsQuestion {
+            name,
+            query_type: read_u16(self.data, name_end),
+            query_class: read_u16(self.data, name_end + 2),
+            end: name_end + 4,
+        }))
+    }
+}
+
+/// Reads a possibly compressed domain name starting at `offset`.
+/// Returns the dotted name and the offset right after the name in the original position.
+pub fn read_name(data: &[u8], offset: usize) -> Result<(String, usize), PacketError> {
+    let mut labels: Vec<String> = Vec::new();
+    let mut position = offset;
+    let mut end = None;
+    let mut jumps = 0;
+
+    loop {
+        ensure_len(data, position + 1, "DNS name")?;
+        let length = data[position] as usize;
+        match length & 0xC0 {
+            0x00 => {
+                if length == 0 {
+                    end.get_or_insert(position + 1);
+                    break;
+                }
+                ensure_len(data, position + 1 + length, "DNS name")?;
+                let label = &data[position + 1..position + 1 + length];
+                labels.push(String::from_utf8_lossy(label).to_ascii_lowercase());
+                position += 1 + length;
+            }
+            0xC0 => {
+                ensure_len(data, position + 2, "DNS name")?;
+                jumps += 1;
+                if jumps > MAX_POINTER_JUMPS {
+                    return Err(PacketError::InvalidHeaderLength {
+                        header: "DNS name",
+                        length: position,
+                    });
+                }
+                end.get_or_insert(position + 2);
+                position = (read_u16(data, position) & 0x3FFF) as usize;
+            }
+            _ => {
+                return Err(PacketError::InvalidHeaderLength {
+                    header: "DNS label",
+                    length,
+                })
+            }
+        }
+    }
+
+    Ok((labels.join("."), end.unwrap_or(position + 1)))
+}
+
+#[cfg(test)]
+pub(crate) mod test_messages {
+    /// Builds a DNS message with a single question for `name`.
+    pub fn query(id: u16, name: &str, response: bool) -> Vec<u8> {
+        let flags: u16 = if response { 0x8180 } else { 0x0100 };
+        let mut data = Vec::new();
+        data.extend_from_slice(&id.to_be_bytes());
+        data.extend_from_slice(&flags.to_be_bytes());
+        data.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]);
+        for label in name.split('.') {
+            data.push(label.len() as u8);
+            data.extend_from_slice(label.as_bytes());
+        }
+        data.push(0);
+        data.extend_from_slice(&[0, 1, 0, 1]);
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_messages::query;
+    use super::*;
+
+    #[test]
+    fn test_parse_query() {
+        let data = query(0xbeef, "WWW.Example.com", false);
+        let message = DnsMessage::parse(&data).unwrap();
+        assert_eq!(message.id(), 0xbeef);
+        assert!(!message.is_response());
+        assert_eq!(message.opcode(), 0);
+        assert_eq!(message.question_count(), 1);
+        let question = message.first_question().unwrap().unwrap();
+        assert_eq!(question.name, "www.example.com");
+        assert_eq!(question.query_type, 1);
+        assert_eq!(question.query_class, 1);
+        assert_eq!(question.end, data.len());
+    }
+
+    #[test]
+    fn test_parse_response_flags() {
+        let data = query(1, "example.com", true);
+        let message = DnsMessage::parse(&data).unwrap();
+        assert!(message.is_response());
+        assert_eq!(message.response_code(), RCODE_NO_ERROR);
+    }
+
+    #[test]
+    fn test_compressed_name() {
+        let mut data = query(1, "example.com", true);
+        let pointer_offset = data.len();
+        // A name consisting of the label "mail" followed by a pointer to offset 12
+        data.extend_from_slice(&[4, b'm', b'a', b'i', b'l', 0xC0, 12]);
+        let (name, end) = read_name(&data, pointer_offset).unwrap();
+        assert_eq!(name, "mail.example.com");
+        assert_eq!(end, data.len());
+    }
+
+    #[test]
+    fn test_pointer_loop_is_rejected() {
+        let mut data = query(1, "a", false);
+        data[12] = 0xC0;
+        data[13] = 12;
+        assert!(read_name(&data, 12).is_err());
+    }
+
+    #[test]
+    fn test_truncated_question() {
+        let data = query(1, "example.com", false);
+        let message = DnsMessage::parse(&data[..20]).unwrap();
+        assert!(matches!(
+            message.first_question(),
+            Err(PacketError::Truncated { .. })
+        ));
+    }
+
+    #[test]
+    fn test_too_short_for_header() {
+        assert!(DnsMessage::parse(&[0; 11]).is_err());
+    }
+}
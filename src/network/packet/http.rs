@@ -0,0 +1,89 @@
+const METHODS: [&str; 9] = [
+    "GET", "POST", "PUT", "DELETE", "HEAD", "OPTIONS", "PATCH", "CONNECT", "TRACE",
+];
+
+/// Returns `true` if the payload looks like the start of an HTTP/1.x request.
+pub fn is_request(payload: &[u8]) -> bool {
+    METHODS.iter().any(|method| {
+        payload.starts_with(method.as_bytes()) && payload.get(method.len()) == Some(&b' ')
+    })
+}
+
+/// Extracts the `Host` header of an HTTP/1.x request at the start of a TCP payload,
+/// lowercased and without a port.
+///
+/// Only the headers contained in this segment are inspected.
+pub fn request_host(payload: &[u8]) -> Option<String> {
+    if !is_request(payload) {
+        return None;
+    }
+    let headers_end = find(payload, b"\r\n\r\n").unwrap_or(payload.len());
+    payload[..headers_end]
+        .split(|&byte| byte == b'\n')
+        .skip(1)
+        .filter_map(|line| std::str::from_utf8(line).ok())
+        .filter_map(|line| line.trim_end_matches('\r').split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("host"))
+        .map(|(_, value)| strip_port(value.trim()).to_ascii_lowercase())
+}
+
+fn strip_port(host: &str) -> &str {
+    if host.starts_with('[') {
+        // IPv6 literal such as [::1]:8080
+        return host
+            .split_once(']')
+            .map(|(address, _)| &address[1..])
+            .unwrap_or(host);
+    }
+    host.split_once(':').map(|(name, _)| name).unwrap_or(host)
+}
+
+/// Returns the position of the first occurrence of `needle` in `haystack`.
+pub fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(0);
+    }
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_host() {
+        let payload = b"GET /index.html HTTP/1.1\r\nUser-Agent: test\r\nHOST: Example.com:8080\r\n\r\n";
+        assert_eq!(request_host(payload), Some("example.com".to_string()));
+    }
+
+    #[test]
+    fn test_ipv6_literal_host() {
+        let payload = b"POST / HTTP/1.1\r\nHost: [::1]:8080\r\n\r\n";
+        assert_eq!(request_host(payload), Some("::1".to_string()));
+    }
+
+    #[test]
+    fn test_missing_host() {
+        assert_eq!(request_host(b"GET / HTTP/1.0\r\n\r\n"), None);
+    }
+
+    #[test]
+    fn test_host_in_body_is_ignored() {
+        let payload = b"POST / HTTP/1.1\r\nContent-Length: 18\r\n\r\nHost: example.com";
+        assert_eq!(request_host(payload), None);
+    }
+
+    #[test]
+    fn test_not_a_request() {
+        assert_eq!(request_host(b"HTTP/1.1 200 OK\r\nHost: a\r\n\r\n"), None);
+        assert!(!is_request(b"GETTER"));
+    }
+
+    #[test]
+    fn test_find() {
+        assert_eq!(find(b"abcdef", b"cd"), Some(2));
+        assert_eq!(find(b"abc", b"abcd"), None);
+    }
+}
@@ -0,0 +1,144 @@
+use crate::network::packet::{ensure_len, read_u16, PacketError};
+use std::net::Ipv4Addr;
+
+pub const MIN_HEADER_LEN: usize = 20;
+
+/// A bounds-checked view of an IPv4 header.
+#[derive(Debug, Clone, Copy)]
+pub struct Ipv4Header<'a> {
+    data: &'a [u8],
+    header_len: usize,
+}
+
+impl<'a> Ipv4Header<'a> {
+    /// Parses the IPv4 header at the start of `data`, including any options.
+    pub fn parse(data: &'a [u8]) -> Result<Self, PacketError> {
+        ensure_len(data, MIN_HEADER_LEN, "IPv4")?;
+        let header_len = ((data[0] & 0x0F) as usize) * 4;
+        if header_len < MIN_HEADER_LEN {
+            return Err(PacketError::InvalidHeaderLength {
+                header: "IPv4",
+                length: header_len,
+            });
+        }
+        ensure_len(data, header_len, "IPv4")?;
+        Ok(Ipv4Header { data, header_len })
+    }
+
+    pub fn header_len(&self) -> usize {
+        self.header_len
+    }
+
+    pub fn total_len(&self) -> u16 {
+        read_u16(self.data, 2)
+    }
+
+    pub fn identification(&self) -> u16 {
+        read_u16(self.data, 4)
+    }
+
+    pub fn ttl(&self) -> u8 {
+        self.data[8]
+    }
+
+    pub fn protocol(&self) -> u8 {
+        self.data[9]
+    }
+
+    pub fn checksum(&self) -> u16 {
+        read_u16(self.data, 10)
+    }
+
+    pub fn source(&self) -> Ipv4Addr {
+        Ipv4Addr::new(self.data[12], self.data[13], self.data[14], self.data[15])
+    }
+
+    pub fn destination(&self) -> Ipv4Addr {
+        Ipv4Addr::new(self.data[16], self.data[17], self.data[18], self.data[19])
+    }
+
+    /// End of the packet according to the total length field, clamped to the buffer.
+    /// A total length of zero (as produced by TCP segmentation offload) means "until the end".
+    pub fn packet_end(&self) -> usize {
+        match self.total_len() as usize {
+            0 => self.data.len(),
+            total_len => total_len.clamp(self.header_len, self.data.len()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::packet::protocol;
+    use crate::network::packet::test_packets::ipv4;
+
+    #[test]
+    fn test_parse_fields() {
+        let data = ipv4(protocol::TCP, &[0; 20]);
+        let header = Ipv4Header::parse(&data).unwrap();
+        assert_eq!(header.header_len(), 20);
+        assert_eq!(header.total_len(), 40);
+        assert_eq!(header.identification(), 0x1234);
+        assert_eq!(header.ttl(), 64);
+        assert_eq!(header.protocol(), protocol::TCP);
+        assert_eq!(header.source(), Ipv4Addr::new(192, 168, 1, 10));
+        assert_eq!(header.destination(), Ipv4Addr::new(93, 184, 216, 34));
+        assert_eq!(header.packet_end(), 40);
+    }
+
+    #[test]
+    fn test_parse_with_options() {
+        let mut data = ipv4(protocol::UDP, &[]);
+        data[0] = 0x46; // 24 byte header
+        data.extend_from_slice(&[1, 1, 1, 0]);
+        let header = Ipv4Header::parse(&data).unwrap();
+        assert_eq!(header.header_len(), 24);
+    }
+
+    #[test]
+    fn test_too_short_for_minimum_header() {
+        let data = [0x45, 0, 0, 20, 0, 0, 0, 0, 64];
+        assert_eq!(
+            Ipv4Header::parse(&data).unwrap_err(),
+            PacketError::Truncated {
+                header: "IPv4",
+                needed: 20,
+                available: 9
+            }
+        );
+    }
+
+    #[test]
+    fn test_options_past_end_of_buffer() {
+        let mut data = ipv4(protocol::UDP, &[]);
+        data[0] = 0x4F; // claims a 60 byte header
+        assert!(matches!(
+            Ipv4Header::parse(&data),
+            Err(PacketError::Truncated { needed: 60, .. })
+        ));
+    }
+
+    #[test]
+    fn test_invalid_ihl() {
+        let mut data = ipv4(protocol::UDP, &[]);
+        data[0] = 0x44;
+        assert_eq!(
+            Ipv4Header::parse(&data).unwrap_err(),
+            PacketError::InvalidHeaderLength {
+                header: "IPv4",
+                length: 16
+            }
+        );
+    }
+
+    #[test]
+    fn test_packet_end_is_clamped() {
+        let mut data = ipv4(protocol::UDP, &[0; 8]);
+        data[2..4].copy_from_slice(&500u16.to_be_bytes());
+        assert_eq!(Ipv4Header::parse(&data).unwrap().packet_end(), 28);
+
+        data[2..4].copy_from_slice(&0u16.to_be_bytes());
+        assert_eq!(Ipv4Header::parse(&data).unwrap().packet_end(), 28);
+    }
+}
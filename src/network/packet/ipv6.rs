@@ -0,0 +1,294 @@
+use crate::network::packet::{ensure_len, read_u16, PacketError};
+use std::net::Ipv6Addr;
+
+pub const FIXED_HEADER_LEN: usize = 40;
+
+pub const HOP_BY_HOP: u8 = 0;
+pub const ROUTING: u8 = 43;
+pub const FRAGMENT: u8 = 44;
+pub const ESP: u8 = 50;
+pub const AUTHENTICATION: u8 = 51;
+pub const NO_NEXT_HEADER: u8 = 59;
+pub const DESTINATION_OPTIONS: u8 = 60;
+pub const MOBILITY: u8 = 135;
+pub const HIP: u8 = 139;
+pub const SHIM6: u8 = 140;
+
+/// Upper bound on the number of extension headers walked, to guard against crafted chains.
+const MAX_EXTENSION_HEADERS: usize = 16;
+
+/// An IPv6 extension header found between the fixed header and the upper layer protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtensionHeader {
+    /// The protocol number identifying this extension header.
+    pub kind: u8,
+    /// Offset of the extension header from the start of the packet.
+    pub offset: usize,
+    pub len: usize,
+}
+
+/// A bounds-checked view of an IPv6 header and its extension header chain.
+#[derive(Debug, Clone, Copy)]
+pub struct Ipv6Header<'a> {
+    data: &'a [u8],
+    header_len: usize,
+    upper_layer_protocol: u8,
+}
+
+pub fn is_extension_header(next_header: u8) -> bool {
+    matches!(
+        next_header,
+        HOP_BY_HOP
+            | ROUTING
+            | FRAGMENT
+            | AUTHENTICATION
+            | DESTINATION_OPTIONS
+            | MOBILITY
+            | HIP
+            | SHIM6
+    )
+}
+
+impl<'a> Ipv6Header<'a> {
+    /// Parses the fixed IPv6 header and walks the extension header chain.
+    pub fn parse(data: &'a [u8]) -> Result<Self, PacketError> {
+        ensure_len(data, FIXED_HEADER_LEN, "IPv6")?;
+        let mut header = Ipv6Header {
+            data,
+            header_len: FIXED_HEADER_LEN,
+            upper_layer_protocol: data[6],
+        };
+        for extension in header.extension_headers() {
+            let extension = extension?;
+            header.header_len = extension.offset + extension.len;
+            header.upper_layer_protocol = data[extension.offset];
+        }
+        Ok(header)
+    }
+
+    /// Iterates over the extension headers following the fixed header.
+    pub fn extension_headers(&self) -> ExtensionHeaders<'a> {
+        ExtensionHeaders {
+            data: self.data,
+            next_header: self.data[6],
+            offset: FIXED_HEADER_LEN,
+            walked: 0,
+            failed: false,
+        }
+    }
+
+    /// Length of the fixed header plus all extension headers.
+    pub fn header_len(&self) -> usize {
+        self.header_len
+    }
+
+    pub fn payload_len(&self) -> u16 {
+        read_u16(self.data, 4)
+    }
+
+    pub fn traffic_class(&self) -> u8 {
+        (self.data[0] << 4) | (self.data[1] >> 4)
+    }
+
+    /// The next header field of the fixed header.
+    pub fn next_header(&self) -> u8 {
+        self.data[6]
+    }
+
+    /// The protocol that follows the last extension header.
+    pub fn upper_layer_protocol(&self) -> u8 {
+        self.upper_layer_protocol
+    }
+
+    pub fn hop_limit(&self) -> u8 {
+        self.data[7]
+    }
+
+    pub fn source(&self) -> Ipv6Addr {
+        Ipv6Addr::from(read_addr(&self.data[8..24]))
+    }
+
+    pub fn destination(&self) -> Ipv6Addr {
+        Ipv6Addr::from(read_addr(&self.data[24..40]))
+    }
+
+    /// End of the packet according to the payload length field, clamped to the buffer.
+    /// A payload length of zero (jumbograms or segmentation offload) means "until the end".
+    pub fn packet_end(&self) -> usize {
+        match self.payload_len() as usize {
+            0 => self.data.len(),
+            payload_len => (FIXED_HEADER_LEN + payload_len).clamp(self.header_len, self.data.len()),
+        }
+    }
+}
+
+fn read_addr(bytes: &[u8]) -> [u8; 16] {
+    let mut addr = [0u8; 16];
+    addr.copy_from_slice(bytes);
+    addr
+}
+
+/// Iterator over IPv6 extension headers, yielding an error if a header is truncated.
+pub struct ExtensionHeaders<'a> {
+    data: &'a [u8],
+    next_header: u8,
+    offset: usize,
+    walked: usize,
+    failed: bool,
+}
+
+impl Iterator for ExtensionHeaders<'_> {
+    type Item = Result<ExtensionHeader, PacketError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || !is_extension_header(self.next_header) {
+            return None;
+        }
+        if self.walked == MAX_EXTENSION_HEADERS {
+            self.failed = true;
+            return Some(Err(PacketError::InvalidHeaderLength {
+                header: "IPv6 extension",
+                length: self.offset,
+            }));
+        }
+
+        let remaining = &self.data[self.offset..];
+        if let Err(e) = ensure_len(remaining, 8, "IPv6 extension") {
+            self.failed = true;
+            return Some(Err(e));
+        }
+        let len = match self.next_header {
+            FRAGMENT => 8,
+            AUTHENTICATION => (remaining[1] as usize + 2) * 4,
+            _ => (remaining[1] as usize + 1) * 8,
+        };
+        if let Err(e) = ensure_len(remaining, len, "IPv6 extension") {
+            self.failed = true;
+            return Some(Err(e));
+        }
+
+        let extension = ExtensionHeader {
+            kind: self.next_header,
+            offset: self.offset,
+            len,
+        };
+        self.next_header = remaining[0];
+        self.offset += len;
+        self.walked += 1;
+        Some(Ok(extension))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::packet::protocol;
+    use crate::network::packet::test_packets::ipv6;
+
+    #[test]
+    fn test_parse_fixed_header() {
+        let data = ipv6(protocol::TCP, &[], &[0; 20]);
+        let header = Ipv6Header::parse(&data).unwrap();
+        assert_eq!(header.header_len(), 40);
+        assert_eq!(header.payload_len(), 20);
+        assert_eq!(header.next_header(), protocol::TCP);
+        assert_eq!(header.upper_layer_protocol(), protocol::TCP);
+        assert_eq!(header.hop_limit(), 64);
+        assert_eq!(header.source(), "2001:db8::1".parse::<Ipv6Addr>().unwrap());
+        assert_eq!(
+            header.destination(),
+            "2001:db8::2".parse::<Ipv6Addr>().unwrap()
+        );
+        assert_eq!(header.packet_end(), 60);
+        assert_eq!(header.extension_headers().count(), 0);
+    }
+
+    /// Builds an extension header of `len` bytes pointing at `next_header`.
+    fn extension(next_header: u8, length_field: u8, len: usize) -> Vec<u8> {
+        let mut data = vec![0u8; len];
+        data[0] = next_header;
+        data[1] = length_field;
+        data
+    }
+
+    #[test]
+    fn test_walk_extension_headers() {
+        let extensions = [
+            extension(ROUTING, 0, 8),
+            extension(AUTHENTICATION, 1, 16),
+            extension(protocol::UDP, 1, 12),
+        ]
+        .concat();
+        let data = ipv6(HOP_BY_HOP, &extensions, &[0; 8]);
+        let header = Ipv6Header::parse(&data).unwrap();
+        let walked: Vec<_> = header
+            .extension_headers()
+            .map(|extension| extension.unwrap())
+            .collect();
+        assert_eq!(
+            walked,
+            vec![
+                ExtensionHeader {
+                    kind: HOP_BY_HOP,
+                    offset: 40,
+                    len: 8
+                },
+                ExtensionHeader {
+                    kind: ROUTING,
+                    offset: 48,
+                    len: 16
+                },
+                ExtensionHeader {
+                    kind: AUTHENTICATION,
+                    offset: 64,
+                    len: 12
+                },
+            ]
+        );
+        assert_eq!(header.header_len(), 76);
+        assert_eq!(header.upper_layer_protocol(), protocol::UDP);
+    }
+
+    #[test]
+    fn test_no_next_header_stops_walk() {
+        let extensions = [NO_NEXT_HEADER, 0, 0, 0, 0, 0, 0, 0];
+        let data = ipv6(DESTINATION_OPTIONS, &extensions, &[]);
+        let header = Ipv6Header::parse(&data).unwrap();
+        assert_eq!(header.header_len(), 48);
+        assert_eq!(header.upper_layer_protocol(), NO_NEXT_HEADER);
+    }
+
+    #[test]
+    fn test_truncated_extension_header() {
+        let extensions = [protocol::UDP, 2, 0, 0, 0, 0, 0, 0]; // claims 24 bytes
+        let data = ipv6(HOP_BY_HOP, &extensions, &[]);
+        assert!(matches!(
+            Ipv6Header::parse(&data),
+            Err(PacketError::Truncated {
+                header: "IPv6 extension",
+                needed: 24,
+                available: 8
+            })
+        ));
+    }
+
+    #[test]
+    fn test_extension_chain_limit() {
+        let extensions: Vec<u8> = (0..=MAX_EXTENSION_HEADERS)
+            .flat_map(|_| [DESTINATION_OPTIONS, 0, 0, 0, 0, 0, 0, 0])
+            .collect();
+        let data = ipv6(DESTINATION_OPTIONS, &extensions, &[]);
+        assert!(matches!(
+            Ipv6Header::parse(&data),
+            Err(PacketError::InvalidHeaderLength { .. })
+        ));
+    }
+
+    #[test]
+    fn test_too_short() {
+        assert!(matches!(
+            Ipv6Header::parse(&[0x60; 39]),
+            Err(PacketError::Truncated { needed: 40, .. })
+        ));
+    }
+}
@@ -0,0 +1,415 @@
+//! Safe, bounds-checked views over raw IP packets.
+//!
+//! The views in this module borrow the packet bytes and never index past the end of the
+//! buffer, so truncated or malformed packets result in a [`PacketError`] instead of a panic.
+//!
+//! ```rust
+//! use fumble::network::packet::{PacketView, TransportHeader};
+//!
+//! // IPv4 + UDP packet from 10.0.0.1:5000 to 10.0.0.2:53 with a 2 byte payload
+//! let data = [
+//!     0x45, 0x00, 0x00, 0x1e, 0x00, 0x00, 0x00, 0x00, 0x40, 0x11, 0x00, 0x00,
+//!     10, 0, 0, 1, 10, 0, 0, 2,
+//!     0x13, 0x88, 0x00, 0x35, 0x00, 0x0a, 0x00, 0x00,
+//!     0xab, 0xcd,
+//! ];
+//! let view = PacketView::parse(&data).unwrap();
+//! assert_eq!(view.destination_port(), Some(53));
+//! assert!(matches!(view.transport(), TransportHeader::Udp(_)));
+//! assert_eq!(view.payload(), &[0xab, 0xcd]);
+//! ```
+
+pub mod dns;
+pub mod http;
+pub mod ipv4;
+pub mod ipv6;
+pub mod tcp;
+pub mod tls;
+pub mod udp;
+
+use crate::network::packet::ipv4::Ipv4Header;
+use crate::network::packet::ipv6::Ipv6Header;
+use crate::network::packet::tcp::TcpHeader;
+use crate::network::packet::udp::UdpHeader;
+use std::net::IpAddr;
+use std::ops::Range;
+use thiserror::Error;
+
+/// IANA protocol numbers used in the IPv4 `protocol` and IPv6 `next header` fields.
+pub mod protocol {
+    pub const ICMP: u8 = 1;
+    pub const TCP: u8 = 6;
+    pub const UDP: u8 = 17;
+    pub const ICMPV6: u8 = 58;
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum PacketError {
+    #[error("Packet is empty")]
+    Empty,
+    #[error("Unsupported IP version {0}")]
+    UnsupportedVersion(u8),
+    #[error("{header} header truncated: needs {needed} bytes but only {available} are available")]
+    Truncated {
+        header: &'static str,
+        needed: usize,
+        available: usize,
+    },
+    #[error("{header} header has an invalid length of {length} bytes")]
+    InvalidHeaderLength { header: &'static str, length: usize },
+}
+
+/// Returns an error if `data` is shorter than `needed` bytes.
+pub(crate) fn ensure_len(
+    data: &[u8],
+    needed: usize,
+    header: &'static str,
+) -> Result<(), PacketError> {
+    if data.len() < needed {
+        Err(PacketError::Truncated {
+            header,
+            needed,
+            available: data.len(),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+pub(crate) fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([data[offset], data[offset + 1]])
+}
+
+pub(crate) fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+/// Returns the IP version stored in the first nibble of the packet.
+pub fn ip_version(data: &[u8]) -> Result<u8, PacketError> {
+    data.first().map(|byte| byte >> 4).ok_or(PacketError::Empty)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum IpHeader<'a> {
+    V4(Ipv4Header<'a>),
+    V6(Ipv6Header<'a>),
+}
+
+impl<'a> IpHeader<'a> {
+    pub fn parse(data: &'a [u8]) -> Result<Self, PacketError> {
+        match ip_version(data)? {
+            4 => Ok(IpHeader::V4(Ipv4Header::parse(data)?)),
+            6 => Ok(IpHeader::V6(Ipv6Header::parse(data)?)),
+            version => Err(PacketError::UnsupportedVersion(version)),
+        }
+    }
+
+    pub fn version(&self) -> u8 {
+        match self {
+            IpHeader::V4(_) => 4,
+            IpHeader::V6(_) => 6,
+        }
+    }
+
+    pub fn source(&self) -> IpAddr {
+        match self {
+            IpHeader::V4(header) => IpAddr::V4(header.source()),
+            IpHeader::V6(header) => IpAddr::V6(header.source()),
+        }
+    }
+
+    pub fn destination(&self) -> IpAddr {
+        match self {
+            IpHeader::V4(header) => IpAddr::V4(header.destination()),
+            IpHeader::V6(header) => IpAddr::V6(header.destination()),
+        }
+    }
+
+    /// The upper layer protocol, after walking any IPv6 extension headers.
+    pub fn protocol(&self) -> u8 {
+        match self {
+            IpHeader::V4(header) => header.protocol(),
+            IpHeader::V6(header) => header.upper_layer_protocol(),
+        }
+    }
+
+    /// Length of the IP header including IPv4 options and IPv6 extension headers.
+    pub fn header_len(&self) -> usize {
+        match self {
+            IpHeader::V4(header) => header.header_len(),
+            IpHeader::V6(header) => header.header_len(),
+        }
+    }
+
+    /// End of the IP packet as declared by its length field, clamped to the buffer length.
+    pub fn packet_end(&self) -> usize {
+        match self {
+            IpHeader::V4(header) => header.packet_end(),
+            IpHeader::V6(header) => header.packet_end(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum TransportHeader<'a> {
+    Tcp(TcpHeader<'a>),
+    Udp(UdpHeader<'a>),
+    /// A protocol fumble does not parse, identified by its protocol number.
+    Other(u8),
+}
+
+impl TransportHeader<'_> {
+    pub fn header_len(&self) -> usize {
+        match self {
+            TransportHeader::Tcp(header) => header.header_len(),
+            TransportHeader::Udp(_) => udp::HEADER_LEN,
+            TransportHeader::Other(_) => 0,
+        }
+    }
+
+    pub fn source_port(&self) -> Option<u16> {
+        match self {
+            TransportHeader::Tcp(header) => Some(header.source_port()),
+            TransportHeader::Udp(header) => Some(header.source_port()),
+            TransportHeader::Other(_) => None,
+        }
+    }
+
+    pub fn destination_port(&self) -> Option<u16> {
+        match self {
+            TransportHeader::Tcp(header) => Some(header.destination_port()),
+            TransportHeader::Udp(header) => Some(header.destination_port()),
+            TransportHeader::Other(_) => None,
+        }
+    }
+}
+
+/// A parsed view of an IP packet and its transport header.
+#[derive(Debug, Clone, Copy)]
+pub struct PacketView<'a> {
+    data: &'a [u8],
+    ip: IpHeader<'a>,
+    transport: TransportHeader<'a>,
+    payload_offset: usize,
+    payload_end: usize,
+}
+
+impl<'a> PacketView<'a> {
+    pub fn parse(data: &'a [u8]) -> Result<Self, PacketError> {
+        let ip = IpHeader::parse(data)?;
+        let ip_header_len = ip.header_len();
+        let packet_end = ip.packet_end().max(ip_header_len);
+        let transport_data = &data[ip_header_len..packet_end];
+
+        let transport = match ip.protocol() {
+            protocol::TCP => TransportHeader::Tcp(TcpHeader::parse(transport_data)?),
+            protocol::UDP => TransportHeader::Udp(UdpHeader::parse(transport_data)?),
+            other => TransportHeader::Other(other),
+        };
+
+        Ok(PacketView {
+            data,
+            ip,
+            transport,
+            payload_offset: ip_header_len + transport.header_len(),
+            payload_end: packet_end,
+        })
+    }
+
+    pub fn ip(&self) -> &IpHeader<'a> {
+        &self.ip
+    }
+
+    pub fn transport(&self) -> &TransportHeader<'a> {
+        &self.transport
+    }
+
+    pub fn version(&self) -> u8 {
+        self.ip.version()
+    }
+
+    pub fn source(&self) -> IpAddr {
+        self.ip.source()
+    }
+
+    pub fn destination(&self) -> IpAddr {
+        self.ip.destination()
+    }
+
+    pub fn protocol(&self) -> u8 {
+        self.ip.protocol()
+    }
+
+    pub fn source_port(&self) -> Option<u16> {
+        self.transport.source_port()
+    }
+
+    pub fn destination_port(&self) -> Option<u16> {
+        self.transport.destination_port()
+    }
+
+    /// Byte range of the transport payload within the packet.
+    pub fn payload_range(&self) -> Range<usize> {
+        self.payload_offset..self.payload_end
+    }
+
+    pub fn payload(&self) -> &'a [u8] {
+        &self.data[self.payload_range()]
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_packets {
+    /// Builds an IPv4 packet with the given protocol and transport bytes.
+    pub fn ipv4(protocol: u8, transport: &[u8]) -> Vec<u8> {
+        let total_len = (20 + transport.len()) as u16;
+        let mut data = vec![
+            0x45, 0x00, 0x00, 0x00, 0x12, 0x34, 0x40, 0x00, 0x40, protocol, 0x00, 0x00, 192, 168,
+            1, 10, 93, 184, 216, 34,
+        ];
+        data[2..4].copy_from_slice(&total_len.to_be_bytes());
+        data.extend_from_slice(transport);
+        data
+    }
+
+    /// Builds an IPv6 packet with the given next header, extension headers and transport bytes.
+    pub fn ipv6(next_header: u8, extensions: &[u8], transport: &[u8]) -> Vec<u8> {
+        let payload_len = (extensions.len() + transport.len()) as u16;
+        let mut data = vec![0x60, 0x00, 0x00, 0x00];
+        data.extend_from_slice(&payload_len.to_be_bytes());
+        data.push(next_header);
+        data.push(64);
+        data.extend_from_slice(&[0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+        data.extend_from_slice(&[0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2]);
+        data.extend_from_slice(extensions);
+        data.extend_from_slice(transport);
+        data
+    }
+
+    pub fn udp(source_port: u16, destination_port: u16, payload: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&source_port.to_be_bytes());
+        data.extend_from_slice(&destination_port.to_be_bytes());
+        data.extend_from_slice(&((8 + payload.len()) as u16).to_be_bytes());
+        data.extend_from_slice(&[0, 0]);
+        data.extend_from_slice(payload);
+        data
+    }
+
+    pub fn tcp(source_port: u16, destination_port: u16, flags: u8, payload: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&source_port.to_be_bytes());
+        data.extend_from_slice(&destination_port.to_be_bytes());
+        data.extend_from_slice(&1000u32.to_be_bytes());
+        data.extend_from_slice(&2000u32.to_be_bytes());
+        data.push(0x50);
+        data.push(flags);
+        data.extend_from_slice(&[0xff, 0xff, 0, 0, 0, 0]);
+        data.extend_from_slice(payload);
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_packets::{ipv4, ipv6, tcp, udp};
+    use super::*;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    fn test_parse_ipv4_udp() {
+        let data = ipv4(protocol::UDP, &udp(5000, 53, b"query"));
+        let view = PacketView::parse(&data).unwrap();
+        assert_eq!(view.version(), 4);
+        assert_eq!(view.source(), IpAddr::V4(Ipv4Addr::new(192, 168, 1, 10)));
+        assert_eq!(
+            view.destination(),
+            IpAddr::V4(Ipv4Addr::new(93, 184, 216, 34))
+        );
+        assert_eq!(view.protocol(), protocol::UDP);
+        assert_eq!(view.source_port(), Some(5000));
+        assert_eq!(view.destination_port(), Some(53));
+        assert_eq!(view.payload_range(), 28..33);
+        assert_eq!(view.payload(), b"query");
+    }
+
+    #[test]
+    fn test_parse_ipv4_tcp() {
+        let data = ipv4(protocol::TCP, &tcp(443, 50000, 0x12, b"hi"));
+        let view = PacketView::parse(&data).unwrap();
+        let TransportHeader::Tcp(header) = view.transport() else {
+            panic!("Expected a TCP header");
+        };
+        assert!(header.flags().syn());
+        assert!(header.flags().ack());
+        assert_eq!(view.payload(), b"hi");
+    }
+
+    #[test]
+    fn test_parse_ipv6_with_extension_headers() {
+        // Hop-by-hop options (8 bytes) followed by a fragment header (8 bytes), then UDP
+        let hop_by_hop = [ipv6::FRAGMENT, 0, 1, 4, 0, 0, 0, 0];
+        let fragment = [protocol::UDP, 0, 0, 0, 0, 0, 0, 1];
+        let extensions = [hop_by_hop, fragment].concat();
+        let data = ipv6(ipv6::HOP_BY_HOP, &extensions, &udp(1234, 53, b"abc"));
+        let view = PacketView::parse(&data).unwrap();
+        assert_eq!(view.version(), 6);
+        assert_eq!(view.protocol(), protocol::UDP);
+        assert_eq!(view.ip().header_len(), 56);
+        assert_eq!(
+            view.source(),
+            IpAddr::V6("2001:db8::1".parse::<Ipv6Addr>().unwrap())
+        );
+        assert_eq!(view.destination_port(), Some(53));
+        assert_eq!(view.payload(), b"abc");
+    }
+
+    #[test]
+    fn test_unknown_protocol_payload_starts_after_ip_header() {
+        let data = ipv4(protocol::ICMP, &[8, 0, 0, 0]);
+        let view = PacketView::parse(&data).unwrap();
+        assert!(matches!(
+            view.transport(),
+            TransportHeader::Other(protocol::ICMP)
+        ));
+        assert_eq!(view.source_port(), None);
+        assert_eq!(view.payload_range(), 20..24);
+    }
+
+    #[test]
+    fn test_empty_packet() {
+        assert_eq!(PacketView::parse(&[]).unwrap_err(), PacketError::Empty);
+    }
+
+    #[test]
+    fn test_unsupported_version() {
+        assert_eq!(
+            PacketView::parse(&[0x50, 0, 0, 0]).unwrap_err(),
+            PacketError::UnsupportedVersion(5)
+        );
+    }
+
+    #[test]
+    fn test_truncated_transport_header() {
+        let mut data = ipv4(protocol::TCP, &tcp(1, 2, 0, &[]));
+        data.truncate(30);
+        data[2..4].copy_from_slice(&30u16.to_be_bytes());
+        assert!(matches!(
+            PacketView::parse(&data),
+            Err(PacketError::Truncated { header: "TCP", .. })
+        ));
+    }
+
+    #[test]
+    fn test_trailing_bytes_after_declared_length_are_not_payload() {
+        let mut data = ipv4(protocol::UDP, &udp(1, 2, b"ab"));
+        data.extend_from_slice(&[0, 0, 0, 0]); // Ethernet padding
+        let view = PacketView::parse(&data).unwrap();
+        assert_eq!(view.payload(), b"ab");
+    }
+}
@@ -0,0 +1,184 @@
+use crate::network::packet::{ensure_len, read_u16, read_u32, PacketError};
+use std::fmt;
+
+pub const MIN_HEADER_LEN: usize = 20;
+
+/// The control bits of a TCP header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TcpFlags(pub u8);
+
+impl TcpFlags {
+    pub const FIN: u8 = 0x01;
+    pub const SYN: u8 = 0x02;
+    pub const RST: u8 = 0x04;
+    pub const PSH: u8 = 0x08;
+    pub const ACK: u8 = 0x10;
+    pub const URG: u8 = 0x20;
+    pub const ECE: u8 = 0x40;
+    pub const CWR: u8 = 0x80;
+
+    pub fn fin(&self) -> bool {
+        self.0 & Self::FIN != 0
+    }
+
+    pub fn syn(&self) -> bool {
+        self.0 & Self::SYN != 0
+    }
+
+    pub fn rst(&self) -> bool {
+        self.0 & Self::RST != 0
+    }
+
+    pub fn psh(&self) -> bool {
+        self.0 & Self::PSH != 0
+    }
+
+    pub fn ack(&self) -> bool {
+        self.0 & Self::ACK != 0
+    }
+
+    pub fn urg(&self) -> bool {
+        self.0 & Self::URG != 0
+    }
+}
+
+impl fmt::Display for TcpFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = [
+            (Self::SYN, "SYN"),
+            (Self::ACK, "ACK"),
+            (Self::FIN, "FIN"),
+            (Self::RST, "RST"),
+            (Self::PSH, "PSH"),
+            (Self::URG, "URG"),
+            (Self::ECE, "ECE"),
+            (Self::CWR, "CWR"),
+        ];
+        let set: Vec<&str> = names
+            .iter()
+            .filter(|(bit, _)| self.0 & bit != 0)
+            .map(|(_, name)| *name)
+            .collect();
+        write!(f, "{}", set.join(","))
+    }
+}
+
+/// A bounds-checked view of a TCP header.
+#[derive(Debug, Clone, Copy)]
+pub struct TcpHeader<'a> {
+    data: &'a [u8],
+    header_len: usize,
+}
+
+impl<'a> TcpHeader<'a> {
+    /// Parses the TCP header at the start of `data`, including any options.
+    pub fn parse(data: &'a [u8]) -> Result<Self, PacketError> {
+        ensure_len(data, MIN_HEADER_LEN, "TCP")?;
+        let header_len = ((data[12] >> 4) as usize) * 4;
+        if header_len < MIN_HEADER_LEN {
+            return Err(PacketError::InvalidHeaderLength {
+                header: "TCP",
+                length: header_len,
+            });
+        }
+        ensure_len(data, header_len, "TCP")?;
+        Ok(TcpHeader { data, header_len })
+    }
+
+    pub fn source_port(&self) -> u16 {
+        read_u16(self.data, 0)
+    }
+
+    pub fn destination_port(&self) -> u16 {
+        read_u16(self.data, 2)
+    }
+
+    pub fn sequence_number(&self) -> u32 {
+        read_u32(self.data, 4)
+    }
+
+    pub fn acknowledgment_number(&self) -> u32 {
+        read_u32(self.data, 8)
+    }
+
+    pub fn header_len(&self) -> usize {
+        self.header_len
+    }
+
+    pub fn flags(&self) -> TcpFlags {
+        TcpFlags(self.data[13])
+    }
+
+    pub fn window_size(&self) -> u16 {
+        read_u16(self.data, 14)
+    }
+
+    pub fn checksum(&self) -> u16 {
+        read_u16(self.data, 16)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::packet::test_packets::tcp;
+
+    #[test]
+    fn test_parse_fields() {
+        let data = tcp(443, 51000, TcpFlags::PSH | TcpFlags::ACK, b"data");
+        let header = TcpHeader::parse(&data).unwrap();
+        assert_eq!(header.source_port(), 443);
+        assert_eq!(header.destination_port(), 51000);
+        assert_eq!(header.sequence_number(), 1000);
+        assert_eq!(header.acknowledgment_number(), 2000);
+        assert_eq!(header.header_len(), 20);
+        assert_eq!(header.window_size(), 0xffff);
+        assert!(header.flags().psh());
+        assert!(header.flags().ack());
+        assert!(!header.flags().syn());
+        assert_eq!(header.flags().to_string(), "ACK,PSH");
+    }
+
+    #[test]
+    fn test_parse_with_options() {
+        let mut data = tcp(1, 2, TcpFlags::SYN, &[]);
+        data[12] = 0x60; // 24 byte header
+        data.extend_from_slice(&[2, 4, 5, 0xb4]);
+        assert_eq!(TcpHeader::parse(&data).unwrap().header_len(), 24);
+    }
+
+    #[test]
+    fn test_truncated() {
+        assert!(matches!(
+            TcpHeader::parse(&[0; 12]),
+            Err(PacketError::Truncated {
+                header: "TCP",
+                needed: 20,
+                available: 12
+            })
+        ));
+    }
+
+    #[test]
+    fn test_data_offset_past_end() {
+        let mut data = tcp(1, 2, 0, &[]);
+        data[12] = 0xF0;
+        assert!(matches!(
+            TcpHeader::parse(&data),
+            Err(PacketError::Truncated { needed: 60, .. })
+        ));
+    }
+
+    #[test]
+    fn test_invalid_data_offset() {
+        let mut data = tcp(1, 2, 0, &[]);
+        data[12] = 0x20;
+        assert_eq!(
+            TcpHeader::parse(&data).unwrap_err(),
+            PacketError::InvalidHeaderLength {
+                header: "TCP",
+                length: 8
+            }
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use crate::network::packet::read_u16;
+
+const CONTENT_TYPE_HANDSHAKE: u8 = 22;
+const HANDSHAKE_CLIENT_HELLO: u8 = 1;
+const EXTENSION_SERVER_NAME: u16 = 0;
+const SERVER_NAME_TYPE_HOST_NAME: u8 = 0;
+
+/// A cursor over a byte slice whose reads return `None` instead of panicking.
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Reader { data, position: 0 }
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        let value = *self.data.get(self.position)?;
+        self.position += 1;
+        Some(value)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.bytes(2).map(|bytes| read_u16(bytes, 0))
+    }
+
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.position..self.position.checked_add(len)?)?;
+        self.position += len;
+        Some(bytes)
+    }
+
+    fn skip(&mut self, len: usize) -> Option<()> {
+        self.bytes(len).map(|_| ())
+    }
+}
+
+/// Extracts the server name indication from a TLS ClientHello at the start of a TCP payload.
+///
+/// Returns `None` if the payload is not a ClientHello or does not contain an SNI extension.
+/// ClientHellos spanning multiple TCP segments are only inspected up to the end of this segment.
+pub fn client_hello_server_name(payload: &[u8]) -> Option<String> {
+    let mut record = Reader::new(payload);
+    if record.u8()? != CONTENT_TYPE_HANDSHAKE {
+        return None;
+    }
+    record.skip(2)?; // legacy record version
+    record.skip(2)?; // record length
+
+    if record.u8()? != HANDSHAKE_CLIENT_HELLO {
+        return None;
+    }
+    record.skip(3)?; // handshake length
+    record.skip(2)?; // client version
+    record.skip(32)?; // random
+    let session_id_len = record.u8()? as usize;
+    record.skip(session_id_len)?;
+    let cipher_suites_len = record.u16()? as usize;
+    record.skip(cipher_suites_len)?;
+    let compression_methods_len = record.u8()? as usize;
+    record.skip(compression_methods_len)?;
+
+    let extensions_len = record.u16()? as usize;
+    let extensions_end = record.position + extensions_len;
+    while record.position + 4 <= extensions_end {
+        let extension_type = record.u16()?;
+        let extension_len = record.u16()? as usize;
+        let extension = record.bytes(extension_len)?;
+        if extension_type == EXTENSION_SERVER_NAME {
+            return parse_server_name_extension(extension);
+        }
+    }
+    None
+}
+
+fn parse_server_name_extension(extension: &[u8]) -> Option<String> {
+    let mut reader = Reader::new(extension);
+    let list_len = reader.u16()? as usize;
+    let list_end = reader.position + list_len;
+    while reader.position + 3 <= list_end {
+        let name_type = reader.u8()?;
+        let name_len = reader.u16()? as usize;
+        let name = reader.bytes(name_len)?;
+        if name_type == SERVER_NAME_TYPE_HOST_NAME {
+            return std::str::from_utf8(name)
+                .ok()
+                .map(|name| name.to_ascii_lowercase());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client_hello(server_name: Option<&str>) -> Vec<u8> {
+        let mut extensions = Vec::new();
+        // An unrelated extension first (supported groups)
+        extensions.extend_from_slice(&[0x00, 0x0a, 0x00, 0x04, 0x00, 0x02, 0x00, 0x1d]);
+        if let Some(name) = server_name {
+            let name = name.as_bytes();
+            let list_len = name.len() + 3;
+            extensions.extend_from_slice(&EXTENSION_SERVER_NAME.to_be_bytes());
+            extensions.extend_from_slice(&((list_len + 2) as u16).to_be_bytes());
+            extensions.extend_from_slice(&(list_len as u16).to_be_bytes());
+            extensions.push(SERVER_NAME_TYPE_HOST_NAME);
+            extensions.extend_from_slice(&(name.len() as u16).to_be_bytes());
+            extensions.extend_from_slice(name);
+        }
+
+        let mut hello = vec![0x03, 0x03];
+        hello.extend_from_slice(&[0x11; 32]);
+        hello.extend_from_slice(&[4, 1, 2, 3, 4]); // session id
+        hello.extend_from_slice(&[0, 2, 0x13, 0x01]); // cipher suites
+        hello.extend_from_slice(&[1, 0]); // compression methods
+        hello.extend_from_slice(&(extensions.len() as u16).to_be_bytes());
+        hello.extend_from_slice(&extensions);
+
+        let mut handshake = vec![HANDSHAKE_CLIENT_HELLO, 0];
+        handshake.extend_from_slice(&(hello.len() as u16).to_be_bytes());
+        handshake.extend_from_slice(&hello);
+
+        let mut record = vec![CONTENT_TYPE_HANDSHAKE, 0x03, 0x01];
+        record.extend_from_slice(&(handshake.len() as u16).to_be_bytes());
+        record.extend_from_slice(&handshake);
+        record
+    }
+
+    #[test]
+    fn test_extracts_server_name() {
+        let payload = client_hello(Some("API.example.com"));
+        assert_eq!(
+            client_hello_server_name(&payload),
+            Some("api.example.com".to_string())
+        );
+    }
+
+    #[test]
+    fn test_no_server_name_extension() {
+        assert_eq!(client_hello_server_name(&client_hello(None)), None);
+    }
+
+    #[test]
+    fn test_truncated_client_hello() {
+        let payload = client_hello(Some("example.com"));
+        for len in 0..payload.len() {
+            assert_eq!(client_hello_server_name(&payload[..len]), None);
+        }
+    }
+
+    #[test]
+    fn test_not_a_handshake() {
+        assert_eq!(client_hello_server_name(b"GET / HTTP/1.1\r\n"), None);
+    }
+}
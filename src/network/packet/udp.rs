@@ -0,0 +1,61 @@
+use crate::network::packet::{ensure_len, read_u16, PacketError};
+
+pub const HEADER_LEN: usize = 8;
+
+/// A bounds-checked view of a UDP header.
+#[derive(Debug, Clone, Copy)]
+pub struct UdpHeader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> UdpHeader<'a> {
+    pub fn parse(data: &'a [u8]) -> Result<Self, PacketError> {
+        ensure_len(data, HEADER_LEN, "UDP")?;
+        Ok(UdpHeader { data })
+    }
+
+    pub fn source_port(&self) -> u16 {
+        read_u16(self.data, 0)
+    }
+
+    pub fn destination_port(&self) -> u16 {
+        read_u16(self.data, 2)
+    }
+
+    /// Length of the UDP header and payload as declared in the header.
+    pub fn length(&self) -> u16 {
+        read_u16(self.data, 4)
+    }
+
+    pub fn checksum(&self) -> u16 {
+        read_u16(self.data, 6)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::packet::test_packets::udp;
+
+    #[test]
+    fn test_parse_fields() {
+        let data = udp(53, 40000, b"response");
+        let header = UdpHeader::parse(&data).unwrap();
+        assert_eq!(header.source_port(), 53);
+        assert_eq!(header.destination_port(), 40000);
+        assert_eq!(header.length(), 16);
+        assert_eq!(header.checksum(), 0);
+    }
+
+    #[test]
+    fn test_truncated() {
+        assert_eq!(
+            UdpHeader::parse(&[0; 7]).unwrap_err(),
+            PacketError::Truncated {
+                header: "UDP",
+                needed: 8,
+                available: 7
+            }
+        );
+    }
+}
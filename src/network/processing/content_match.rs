@@ -0,0 +1,209 @@
+use crate::cli::settings::content_match::ContentMatchOptions;
+use crate::network::core::packet_data::PacketData;
+use crate::network::packet::dns::DnsMessage;
+use crate::network::packet::{dns, http, tls, PacketView, TransportHeader};
+
+/// Returns `true` if the packet matches at least one criterion of the content filter.
+/// An empty content filter matches every packet.
+pub fn packet_matches(content_match: &ContentMatchOptions, data: &[u8]) -> bool {
+    if content_match.is_empty() {
+        return true;
+    }
+    let view = match PacketView::parse(data) {
+        Ok(view) => view,
+        Err(_) => return false,
+    };
+    let payload = view.payload();
+
+    if content_match
+        .payload_bytes
+        .iter()
+        .any(|pattern| pattern.is_found_in(payload))
+    {
+        return true;
+    }
+
+    if let Some(regex) = &content_match.payload_regex {
+        if regex.is_match(payload) {
+            return true;
+        }
+    }
+
+    if !content_match.dns_query.is_empty() {
+        if let Some(name) = dns_query_name(&view) {
+            if content_match.dns_query.iter().any(|p| p.matches(&name)) {
+                return true;
+            }
+        }
+    }
+
+    if let TransportHeader::Tcp(_) = view.transport() {
+        if !content_match.tls_sni.is_empty() {
+            if let Some(name) = tls::client_hello_server_name(payload) {
+                if content_match.tls_sni.iter().any(|p| p.matches(&name)) {
+                    return true;
+                }
+            }
+        }
+        if !content_match.http_host.is_empty() {
+            if let Some(host) = http::request_host(payload) {
+                if content_match.http_host.iter().any(|p| p.matches(&host)) {
+                    return true;
+                }
+            }
+        }
+    }
+
+    false
+}
+
+/// Extracts the question name of a DNS query or response carried over UDP or TCP port 53.
+pub fn dns_query_name(view: &PacketView) -> Option<String> {
+    let is_dns_port =
+        view.source_port() == Some(dns::PORT) || view.destination_port() == Some(dns::PORT);
+    if !is_dns_port {
+        return None;
+    }
+    let message = match view.transport() {
+        TransportHeader::Udp(_) => view.payload(),
+        // DNS over TCP prefixes each message with its length
+        TransportHeader::Tcp(_) => view.payload().get(2..)?,
+        TransportHeader::Other(_) => return None,
+    };
+    DnsMessage::parse(message)
+        .ok()?
+        .first_question()
+        .ok()?
+        .map(|question| question.name)
+}
+
+/// Runs `manipulate` only on the packets matching the content filter.
+/// Packets that don't match skip the manipulation and are put back in front of its output.
+/// Returns whatever `manipulate` returns.
+pub fn with_content_match<R>(
+    packets: &mut Vec<PacketData>,
+    content_match: Option<&ContentMatchOptions>,
+    manipulate: impl FnOnce(&mut Vec<PacketData>) -> R,
+) -> R {
+    let content_match = match content_match {
+        Some(content_match) if !content_match.is_empty() => content_match,
+        _ => return manipulate(packets),
+    };
+
+    let (mut bypassed, mut matched): (Vec<_>, Vec<_>) = packets
+        .drain(..)
+        .partition(|packet_data| !packet_matches(content_match, &packet_data.packet.data));
+    let result = manipulate(&mut matched);
+    bypassed.append(&mut matched);
+    *packets = bypassed;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::packet::dns::test_messages::query;
+    use crate::network::packet::protocol;
+    use crate::network::packet::test_packets::{ipv4, tcp, udp};
+    use windivert::layer::NetworkLayer;
+    use windivert::packet::WinDivertPacket;
+
+    fn options(toml: &str) -> ContentMatchOptions {
+        toml::from_str(toml).unwrap()
+    }
+
+    fn dns_packet(name: &str) -> Vec<u8> {
+        ipv4(protocol::UDP, &udp(40000, 53, &query(7, name, false)))
+    }
+
+    #[test]
+    fn test_empty_filter_matches_everything() {
+        assert!(packet_matches(&ContentMatchOptions::default(), &[]));
+    }
+
+    #[test]
+    fn test_dns_query_match() {
+        let content_match = options(r#"dns_query = ["*.example.com"]"#);
+        assert!(packet_matches(
+            &content_match,
+            &dns_packet("www.example.com")
+        ));
+        assert!(!packet_matches(
+            &content_match,
+            &dns_packet("www.example.org")
+        ));
+    }
+
+    #[test]
+    fn test_dns_over_tcp() {
+        let message = query(7, "api.example.com", false);
+        let mut payload = (message.len() as u16).to_be_bytes().to_vec();
+        payload.extend_from_slice(&message);
+        let data = ipv4(protocol::TCP, &tcp(40000, 53, 0x18, &payload));
+        let content_match = options(r#"dns_query = ["api.example.com"]"#);
+        assert!(packet_matches(&content_match, &data));
+    }
+
+    #[test]
+    fn test_http_host_match() {
+        let request = b"GET / HTTP/1.1\r\nHost: shop.example.com\r\n\r\n";
+        let data = ipv4(protocol::TCP, &tcp(50000, 80, 0x18, request));
+        assert!(packet_matches(
+            &options(r#"http_host = ["*.example.com"]"#),
+            &data
+        ));
+        assert!(!packet_matches(
+            &options(r#"http_host = ["other.com"]"#),
+            &data
+        ));
+    }
+
+    #[test]
+    fn test_payload_bytes_and_regex() {
+        let data = ipv4(protocol::UDP, &udp(1, 2, b"\x01hello world\x02"));
+        assert!(packet_matches(
+            &options(r#"payload_bytes = ["68656c6c6f"]"#),
+            &data
+        ));
+        assert!(packet_matches(
+            &options(r#"payload_regex = "w[aeiou]rld""#),
+            &data
+        ));
+        assert!(!packet_matches(
+            &options(r#"payload_regex = "^world""#),
+            &data
+        ));
+    }
+
+    #[test]
+    fn test_unparseable_packet_does_not_match() {
+        assert!(!packet_matches(
+            &options(r#"payload_regex = ".*""#),
+            &[0x45, 0]
+        ));
+    }
+
+    #[test]
+    fn test_with_content_match_only_manipulates_matching_packets() {
+        let mut packets: Vec<PacketData> = ["www.example.com", "www.example.org"]
+            .iter()
+            .map(|name| unsafe {
+                PacketData::from(WinDivertPacket::<NetworkLayer>::new(dns_packet(name)))
+            })
+            .collect();
+        let content_match = options(r#"dns_query = ["*.example.com"]"#);
+
+        let mut manipulated = 0;
+        with_content_match(&mut packets, Some(&content_match), |matched| {
+            manipulated = matched.len();
+            matched.clear();
+        });
+
+        assert_eq!(manipulated, 1);
+        assert_eq!(packets.len(), 1);
+        assert_eq!(
+            packets[0].packet.data[..],
+            dns_packet("www.example.org")[..]
+        );
+    }
+}
@@ -1,3 +1,8 @@
+pub mod content_match;
+pub mod packet_benchmark;
+pub mod packet_draining;
 pub mod packet_processing;
 pub mod packet_processing_state;
 pub mod packet_receiving;
+pub mod packet_replaying;
+pub mod packet_sharding;
@@ -0,0 +1,190 @@
+use crate::cli::command::{BenchArgs, EXIT_SUCCESS};
+use crate::cli::settings::packet_manipulation::PacketManipulationSettings;
+use crate::cli::settings::runtime_options::DrainPolicy;
+use crate::network::core::packet_buffer::BufferPool;
+use crate::network::core::packet_data::{Packet, PacketData};
+use crate::network::modules::stats::initialize_statistics;
+use crate::network::processing::packet_draining::{drain_packets, ReleaseSchedule};
+use crate::network::processing::packet_processing::process_packets;
+use crate::network::processing::packet_processing_state::PacketProcessingState;
+use std::time::{Duration, Instant};
+use windivert::layer::NetworkLayer;
+use windivert::packet::WinDivertPacket;
+
+/// Size of the IPv4 and UDP headers of the synthetic packets.
+const HEADER_LEN: usize = 28;
+
+/// Settings measured by the benchmark, one module at a time.
+const BENCHMARK_CASES: &[(&str, &str)] = &[
+    ("none", ""),
+    ("drop", "[drop]\nprobability = 0.1\n"),
+    ("delay", "[delay]\nduration = 10\n"),
+    ("throttle", "[throttle]\nprobability = 0.1\nduration = 5\n"),
+    ("reorder", "[reorder]\nprobability = 0.1\nmax_delay = 10\n"),
+    ("tamper", "[tamper]\nprobability = 0.1\namount = 0.1\n"),
+    ("duplicate", "[duplicate]\nprobability = 0.1\ncount = 2\n"),
+    ("bandwidth", "[bandwidth]\nlimit = 1000000\n"),
+    ("dns", "[dns]\ndelay = 10\n"),
+    (
+        "topology",
+        "[topology]\ndefault = \"default: 10ms, 1% loss\"\n",
+    ),
+];
+
+/// Measured cost of running a batch of synthetic packets through the modules.
+#[derive(Debug, Clone)]
+pub struct BenchmarkResult {
+    pub name: &'static str,
+    pub packets: usize,
+    /// Packets that left the modules, including the ones they still held at the end
+    pub sent: usize,
+    pub elapsed: Duration,
+}
+
+impl BenchmarkResult {
+    pub fn nanos_per_packet(&self) -> f64 {
+        self.elapsed.as_nanos() as f64 / self.packets.max(1) as f64
+    }
+
+    pub fn packets_per_second(&self) -> f64 {
+        self.packets as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// Runs the benchmark for every module on its own, then for all modules together.
+pub fn run_benchmark(
+    packets: usize,
+    packet_size: usize,
+    batch_size: usize,
+) -> Vec<BenchmarkResult> {
+    let all: String = BENCHMARK_CASES.iter().map(|(_, config)| *config).collect();
+    BENCHMARK_CASES
+        .iter()
+        .map(|(name, config)| (*name, *config))
+        .chain([("all", all.as_str())])
+        .map(|(name, config)| {
+            let settings: PacketManipulationSettings =
+                toml::from_str(config).expect("benchmark settings are valid");
+            measure(name, &settings, packets, packet_size, batch_size)
+        })
+        .collect()
+}
+
+/// Feeds `packets` synthetic UDP packets of `packet_size` bytes through the modules enabled in
+/// `settings`, `batch_size` at a time as if they were received in batches, and sends whatever
+/// the modules hold at the end at once.
+fn measure(
+    name: &'static str,
+    settings: &PacketManipulationSettings,
+    packets: usize,
+    packet_size: usize,
+    batch_size: usize,
+) -> BenchmarkResult {
+    let statistics = initialize_statistics();
+    let mut state = PacketProcessingState::default();
+    let pool = BufferPool::new();
+    let template = synthetic_packet(packet_size);
+    let mut sent = 0;
+
+    let started = Instant::now();
+    let mut remaining = packets;
+    while remaining > 0 {
+        let count = remaining.min(batch_size);
+        remaining -= count;
+        // Copies each packet into a pooled buffer, like receiving does
+        let mut batch: Vec<PacketData> = (0..count)
+            .map(|_| {
+                PacketData::from(Packet {
+                    address: template.address.clone(),
+                    data: pool.copy_from_slice(&template.data),
+                })
+            })
+            .collect();
+        process_packets(settings, None, &mut batch, &mut state, &statistics);
+        sent += batch.len();
+    }
+    sent += drain_packets(
+        &mut state,
+        None,
+        &ReleaseSchedule::default(),
+        DrainPolicy::Flush,
+        &statistics,
+    )
+    .len();
+
+    BenchmarkResult {
+        name,
+        packets,
+        sent,
+        elapsed: started.elapsed(),
+    }
+}
+
+/// Builds an outbound IPv4 UDP packet of `size` bytes.
+fn synthetic_packet(size: usize) -> Packet {
+    let size = size.max(HEADER_LEN);
+    let mut data = vec![0u8; size];
+    data[..20].copy_from_slice(&[
+        0x45, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40, 0x00, 0x40, 0x11, 0x00, 0x00, 10, 0, 0, 1, 10, 0,
+        0, 2,
+    ]);
+    data[2..4].copy_from_slice(&(size as u16).to_be_bytes());
+    data[20..22].copy_from_slice(&5000u16.to_be_bytes());
+    data[22..24].copy_from_slice(&9000u16.to_be_bytes());
+    data[24..26].copy_from_slice(&((size - 20) as u16).to_be_bytes());
+    // Safety: the address is zeroed, which is a valid network layer address
+    let mut packet = unsafe { WinDivertPacket::<NetworkLayer>::new(data) };
+    packet.address.set_outbound(true);
+    PacketData::from(packet).packet
+}
+
+/// Runs `fumble bench` and prints the cost per packet of each module.
+pub fn run_bench_command(args: &BenchArgs) -> i32 {
+    println!(
+        "{} packets of {} bytes in batches of {}:",
+        args.packets, args.size, args.batch
+    );
+    println!(
+        "{:<10} {:>10} {:>14} {:>10}",
+        "module", "ns/packet", "packets/s", "sent"
+    );
+    for result in run_benchmark(args.packets, args.size as usize, args.batch) {
+        println!(
+            "{:<10} {:>10.1} {:>14.0} {:>10}",
+            result.name,
+            result.nanos_per_packet(),
+            result.packets_per_second(),
+            result.sent
+        );
+    }
+    EXIT_SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::packet::PacketView;
+
+    #[test]
+    fn test_synthetic_packet_is_valid() {
+        let packet = synthetic_packet(512);
+        let view = PacketView::parse(&packet.data).unwrap();
+        assert_eq!(view.destination_port(), Some(9000));
+        assert_eq!(view.payload().len(), 512 - HEADER_LEN);
+    }
+
+    #[test]
+    fn test_benchmark_covers_every_module() {
+        let results = run_benchmark(100, 256, 16);
+        assert_eq!(results.len(), BENCHMARK_CASES.len() + 1);
+        assert_eq!(results.last().unwrap().name, "all");
+        let none = &results[0];
+        assert_eq!((none.packets, none.sent), (100, 100));
+        // Held packets are sent at the end, so only dropping modules lose packets
+        let delay = results
+            .iter()
+            .find(|result| result.name == "delay")
+            .unwrap();
+        assert_eq!(delay.sent, 100);
+    }
+}
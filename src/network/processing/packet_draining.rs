@@ -0,0 +1,416 @@
+use crate::cli::settings::packet_manipulation::PacketManipulationSettings;
+use crate::cli::settings::runtime_options::DrainPolicy;
+use crate::network::core::packet_data::PacketData;
+use crate::network::modules::stats::util::counter::Gauge;
+use crate::network::modules::stats::PacketProcessingStatistics;
+use crate::network::processing::packet_processing_state::PacketProcessingState;
+use crate::network::types::bit_rate::BitRate;
+use log::info;
+use std::time::{Duration, Instant};
+
+/// A module that holds packets across processing iterations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueingModule {
+    Delay,
+    Dns,
+    Throttle,
+    Reorder,
+    Topology,
+    Trace,
+    Bandwidth,
+}
+
+impl QueueingModule {
+    pub const ALL: [QueueingModule; 7] = [
+        QueueingModule::Delay,
+        QueueingModule::Dns,
+        QueueingModule::Throttle,
+        QueueingModule::Reorder,
+        QueueingModule::Topology,
+        QueueingModule::Trace,
+        QueueingModule::Bandwidth,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            QueueingModule::Delay => "delay",
+            QueueingModule::Dns => "dns",
+            QueueingModule::Throttle => "throttle",
+            QueueingModule::Reorder => "reorder",
+            QueueingModule::Topology => "topology",
+            QueueingModule::Trace => "trace",
+            QueueingModule::Bandwidth => "bandwidth",
+        }
+    }
+
+    fn is_enabled(self, settings: &PacketManipulationSettings) -> bool {
+        match self {
+            QueueingModule::Delay => settings.delay.is_some(),
+            QueueingModule::Dns => settings.dns.is_some(),
+            QueueingModule::Throttle => settings.throttle.is_some(),
+            QueueingModule::Reorder => settings.reorder.is_some(),
+            QueueingModule::Topology => settings.topology.is_some(),
+            QueueingModule::Trace => settings.trace.is_some(),
+            QueueingModule::Bandwidth => settings.bandwidth.is_some(),
+        }
+    }
+}
+
+/// Release parameters of the queueing modules, remembered from the last time each module ran so
+/// its packets can still be released on schedule after it was turned off.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReleaseSchedule {
+    pub delay: Duration,
+    pub dns_delay: Duration,
+    pub throttle_duration: Duration,
+    /// Bandwidth limit of outbound packets, `None` if they are not limited
+    pub bandwidth_upload: Option<BitRate>,
+    /// Bandwidth limit of inbound packets, `None` if they are not limited
+    pub bandwidth_download: Option<BitRate>,
+    /// Bytes counted against the bandwidth limit for every packet
+    pub bandwidth_overhead: usize,
+}
+
+impl ReleaseSchedule {
+    pub fn update(&mut self, settings: &PacketManipulationSettings) {
+        if let Some(delay) = &settings.delay {
+            self.delay = delay.duration.duration();
+        }
+        if let Some(dns) = &settings.dns {
+            self.dns_delay = dns.delay.duration();
+        }
+        if let Some(throttle) = &settings.throttle {
+            self.throttle_duration = throttle.duration.duration();
+        }
+        if let Some(bandwidth) = &settings.bandwidth {
+            self.bandwidth_upload = bandwidth.limit_for(true);
+            self.bandwidth_download = bandwidth.limit_for(false);
+            self.bandwidth_overhead = bandwidth.overhead;
+        }
+    }
+
+    /// Returns the bandwidth limit of packets going out, or in if `outbound` is false.
+    fn bandwidth_limit(&self, outbound: bool) -> Option<BitRate> {
+        if outbound {
+            self.bandwidth_upload
+        } else {
+            self.bandwidth_download
+        }
+    }
+
+    /// Returns the earliest time at which one of the modules can release a held packet, or `None`
+    /// if nothing is held. Each module releases its packets in order, so only the packet at the
+    /// front of each queue needs to be considered.
+    pub fn next_release(&self, state: &PacketProcessingState) -> Option<Instant> {
+        let delay = state
+            .delay_storage
+            .front()
+            .map(|packet_data| packet_data.arrival_time + self.delay);
+        let dns = state
+            .dns_delay_storage
+            .front()
+            .map(|packet_data| packet_data.arrival_time + self.dns_delay);
+        // The throttle module still holds packets at the exact end of the throttle period
+        let throttle = (!state.throttle_storage.is_empty()).then(|| {
+            state.throttled_start_time + self.throttle_duration + Duration::from_micros(1)
+        });
+        let reorder = state
+            .reorder_storage
+            .peek()
+            .map(|delayed| delayed.delay_until);
+        let topology = state
+            .topology_storage
+            .peek()
+            .map(|delayed| delayed.delay_until);
+        let trace = state
+            .trace_replay
+            .storage
+            .peek()
+            .map(|delayed| delayed.delay_until);
+        // Waits for enough bandwidth to accumulate for the next packet; a limit of zero never does,
+        // and a direction without a limit releases its packets right away
+        let bandwidth = state
+            .bandwidth_queues
+            .directions()
+            .into_iter()
+            .filter_map(|(outbound, queue)| match self.bandwidth_limit(outbound) {
+                Some(limit) => queue.next_release(limit, self.bandwidth_overhead),
+                None => (!queue.is_empty()).then_some(queue.last_send_time),
+            })
+            .min();
+        [delay, dns, throttle, reorder, topology, trace, bandwidth]
+            .into_iter()
+            .flatten()
+            .min()
+    }
+}
+
+/// Drains the packets held by the modules that are turned off in `settings`, or by every module
+/// if no settings are given, according to `policy`. Returns the packets to send now.
+pub fn drain_packets(
+    state: &mut PacketProcessingState,
+    settings: Option<&PacketManipulationSettings>,
+    schedule: &ReleaseSchedule,
+    policy: DrainPolicy,
+    statistics: &PacketProcessingStatistics,
+) -> Vec<PacketData> {
+    let stats = &statistics.drain_stats;
+    let mut released = Vec::new();
+    for module in QueueingModule::ALL {
+        let previously_held = queued_count(state, module);
+        if settings.is_some_and(|settings| module.is_enabled(settings)) || previously_held == 0 {
+            continue;
+        }
+        match policy {
+            DrainPolicy::Flush => {
+                let packets = take_queued(state, module, None);
+                info!(
+                    "Sent {} packets held by the {} module.",
+                    packets.len(),
+                    module.name()
+                );
+                stats.record_flushed(module.name(), packets.len());
+                released.extend(packets);
+            }
+            DrainPolicy::Schedule => {
+                let packets = take_queued(state, module, Some(schedule));
+                stats.record_flushed(module.name(), packets.len());
+                released.extend(packets);
+            }
+            DrainPolicy::Discard => {
+                let packets = take_queued(state, module, None);
+                info!(
+                    "Discarded {} packets held by the {} module.",
+                    packets.len(),
+                    module.name()
+                );
+                stats.record_discarded(module.name(), packets.len());
+            }
+        }
+        held_packets(statistics, module).adjust(previously_held, queued_count(state, module));
+    }
+    released
+}
+
+/// Returns the statistic counting the packets held by `module`.
+fn held_packets(statistics: &PacketProcessingStatistics, module: QueueingModule) -> &Gauge {
+    match module {
+        QueueingModule::Delay => &statistics.delay_stats.delayed_package_count,
+        QueueingModule::Dns => &statistics.dns_stats.delayed_count,
+        QueueingModule::Throttle => &statistics.throttle_stats.queued_packets,
+        QueueingModule::Reorder => &statistics.reorder_stats.delayed_packets,
+        QueueingModule::Topology => &statistics.topology_stats.delayed_packets,
+        QueueingModule::Trace => &statistics.trace_stats.delayed_packets,
+        QueueingModule::Bandwidth => &statistics.bandwidth_stats.storage_packet_count,
+    }
+}
+
+/// Returns the number of packets held by `module`.
+pub fn queued_count(state: &PacketProcessingState, module: QueueingModule) -> usize {
+    match module {
+        QueueingModule::Delay => state.delay_storage.len(),
+        QueueingModule::Dns => state.dns_delay_storage.len(),
+        QueueingModule::Throttle => state.throttle_storage.len(),
+        QueueingModule::Reorder => state.reorder_storage.len(),
+        QueueingModule::Topology => state.topology_storage.len(),
+        QueueingModule::Trace => state.trace_replay.storage.len(),
+        QueueingModule::Bandwidth => state.bandwidth_queues.len(),
+    }
+}
+
+/// Removes the packets held by `module` in the order it would have released them. With a
+/// schedule, only the packets that are due are removed.
+fn take_queued(
+    state: &mut PacketProcessingState,
+    module: QueueingModule,
+    schedule: Option<&ReleaseSchedule>,
+) -> Vec<PacketData> {
+    let now = Instant::now();
+    let mut packets = Vec::new();
+    match module {
+        QueueingModule::Delay | QueueingModule::Dns => {
+            let (storage, delay) = match module {
+                QueueingModule::Delay => (&mut state.delay_storage, schedule.map(|s| s.delay)),
+                _ => (&mut state.dns_delay_storage, schedule.map(|s| s.dns_delay)),
+            };
+            while let Some(packet_data) = storage.pop_front() {
+                if delay.is_some_and(|delay| packet_data.arrival_time.elapsed() < delay) {
+                    storage.push_front(packet_data);
+                    break;
+                }
+                packets.push(packet_data);
+            }
+        }
+        QueueingModule::Throttle => {
+            let throttled = schedule.is_some_and(|schedule| {
+                state.throttled_start_time.elapsed() <= schedule.throttle_duration
+            });
+            if !throttled {
+                packets.extend(state.throttle_storage.drain(..));
+            }
+        }
+        QueueingModule::Reorder | QueueingModule::Topology | QueueingModule::Trace => {
+            let storage = match module {
+                QueueingModule::Reorder => &mut state.reorder_storage,
+                QueueingModule::Topology => &mut state.topology_storage,
+                _ => &mut state.trace_replay.storage,
+            };
+            while storage
+                .peek()
+                .is_some_and(|delayed| schedule.is_none() || delayed.delay_until <= now)
+            {
+                if let Some(delayed) = storage.pop() {
+                    packets.push(delayed.packet);
+                }
+            }
+        }
+        QueueingModule::Bandwidth => {
+            for (outbound, queue) in state.bandwidth_queues.directions_mut() {
+                let limit = schedule.and_then(|schedule| schedule.bandwidth_limit(outbound));
+                let overhead = schedule.map_or(0, |schedule| schedule.bandwidth_overhead);
+                let bytes_allowed =
+                    limit.map_or(usize::MAX, |limit| queue.bytes_allowed(limit, now));
+                let released = queue.take(bytes_allowed, overhead);
+                if !released.is_empty() {
+                    queue.last_send_time = now;
+                }
+                packets.extend(released);
+            }
+        }
+    }
+    packets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::settings::delay::DelayOptions;
+    use crate::network::packet::protocol;
+    use crate::network::packet::test_packets::{ipv4, udp};
+    use crate::network::types::delayed_packet::DelayedPacket;
+    use windivert::layer::NetworkLayer;
+    use windivert::packet::WinDivertPacket;
+
+    fn packet_data() -> PacketData {
+        let data = ipv4(protocol::UDP, &udp(1000, 53, b"query"));
+        // Safety: the address is zeroed, which is a valid network layer address
+        PacketData::from(unsafe { WinDivertPacket::<NetworkLayer>::new(data) })
+    }
+
+    #[test]
+    fn test_only_disabled_modules_are_drained() {
+        let mut state = PacketProcessingState::default();
+        state.delay_storage.push_back(packet_data());
+        state.throttle_storage.push_back(packet_data());
+        let settings = PacketManipulationSettings {
+            delay: Some(DelayOptions::default()),
+            ..Default::default()
+        };
+        let statistics = PacketProcessingStatistics::new();
+
+        let released = drain_packets(
+            &mut state,
+            Some(&settings),
+            &ReleaseSchedule::default(),
+            DrainPolicy::Flush,
+            &statistics,
+        );
+        assert_eq!(released.len(), 1);
+        assert_eq!(state.delay_storage.len(), 1);
+        assert!(state.throttle_storage.is_empty());
+        let stats = statistics.drain_stats.snapshot();
+        assert_eq!(stats.modules["throttle"].flushed, 1);
+        assert!(!stats.modules.contains_key("delay"));
+    }
+
+    #[test]
+    fn test_discard_drains_everything() {
+        let mut state = PacketProcessingState::default();
+        state.delay_storage.push_back(packet_data());
+        state
+            .reorder_storage
+            .push(DelayedPacket::new(packet_data(), Duration::from_secs(60)));
+        let statistics = PacketProcessingStatistics::new();
+        statistics.delay_stats.delayed_package_count(0, 1);
+
+        let released = drain_packets(
+            &mut state,
+            None,
+            &ReleaseSchedule::default(),
+            DrainPolicy::Discard,
+            &statistics,
+        );
+        assert!(released.is_empty());
+        let stats = statistics.drain_stats.snapshot();
+        assert_eq!(stats.total().discarded, 2);
+        assert_eq!(stats.modules["reorder"].discarded, 1);
+        // The drained packets are no longer counted as held by their modules
+        assert_eq!(statistics.delay_stats.snapshot().delayed_package_count, 0);
+        assert!(QueueingModule::ALL
+            .iter()
+            .all(|module| queued_count(&state, *module) == 0));
+    }
+
+    #[test]
+    fn test_schedule_releases_only_due_packets() {
+        let mut state = PacketProcessingState::default();
+        state
+            .reorder_storage
+            .push(DelayedPacket::new(packet_data(), Duration::from_secs(60)));
+        state
+            .reorder_storage
+            .push(DelayedPacket::new(packet_data(), Duration::ZERO));
+        state.delay_storage.push_back(packet_data());
+        let schedule = ReleaseSchedule {
+            delay: Duration::from_secs(60),
+            ..Default::default()
+        };
+        let statistics = PacketProcessingStatistics::new();
+
+        let released = drain_packets(
+            &mut state,
+            None,
+            &schedule,
+            DrainPolicy::Schedule,
+            &statistics,
+        );
+        assert_eq!(released.len(), 1);
+        assert_eq!(state.reorder_storage.len(), 1);
+        assert_eq!(state.delay_storage.len(), 1);
+        assert_eq!(
+            statistics.drain_stats.snapshot().modules["reorder"].flushed,
+            1
+        );
+    }
+
+    #[test]
+    fn test_next_release_is_earliest_module_deadline() {
+        let mut state = PacketProcessingState::default();
+        let schedule = ReleaseSchedule {
+            delay: Duration::from_millis(200),
+            bandwidth_download: Some(BitRate::from_kibibytes(1)),
+            ..Default::default()
+        };
+        assert_eq!(schedule.next_release(&state), None);
+
+        let delayed = packet_data();
+        let delay_release = delayed.arrival_time + schedule.delay;
+        state.delay_storage.push_back(delayed);
+        assert_eq!(schedule.next_release(&state), Some(delay_release));
+
+        let reordered = DelayedPacket::new(packet_data(), Duration::from_millis(50));
+        let reorder_release = reordered.delay_until;
+        state.reorder_storage.push(reordered);
+        assert_eq!(schedule.next_release(&state), Some(reorder_release));
+
+        // A 1 KB/s limit allows a packet of `len` bytes after `len / 1024` seconds
+        let limited = packet_data();
+        let bandwidth_release = state.bandwidth_queues.download.last_send_time
+            + Duration::from_secs_f64(limited.packet.data.len() as f64 / 1024.0);
+        state.bandwidth_queues.download.packets.push_back(limited);
+        assert_eq!(
+            schedule.next_release(&state),
+            Some(reorder_release.min(bandwidth_release))
+        );
+    }
+}
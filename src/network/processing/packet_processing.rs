@@ -1,32 +1,258 @@
 use crate::cli::settings::packet_manipulation::PacketManipulationSettings;
+use crate::cli::settings::runtime_options::DrainPolicy;
+use crate::cli::settings::trace::TraceFile;
 use crate::cli::Cli;
-use crate::network::core::packet_data::PacketData;
+use crate::network::core::packet_data::{PacketBatch, PacketData, MAX_BATCH_SIZE};
 use crate::network::modules::bandwidth::bandwidth_limiter;
 use crate::network::modules::delay::delay_packets;
+use crate::network::modules::dns::dns_packets;
 use crate::network::modules::drop::drop_packets;
 use crate::network::modules::duplicate::duplicate_packets;
 use crate::network::modules::reorder::reorder_packets;
 use crate::network::modules::stats::PacketProcessingStatistics;
 use crate::network::modules::tamper::tamper_packets;
 use crate::network::modules::throttle::throttle_packages;
+use crate::network::modules::topology::topology_packets;
+use crate::network::modules::trace::trace_packets;
+use crate::network::processing::content_match::with_content_match;
+use crate::network::processing::packet_draining::{
+    drain_packets, queued_count, QueueingModule, ReleaseSchedule,
+};
 use crate::network::processing::packet_processing_state::PacketProcessingState;
+use crate::network::processing::packet_sharding::{
+    flow_shard, has_shared_modules, remove_shared_modules,
+};
+use crate::network::types::packet_actions::PacketActions;
+use crate::network::utils::random;
+use crate::network::utils::snapshot::{Snapshot, SnapshotReader};
 use crate::utils::log_statistics;
-use log::{error, info};
-use std::collections::{BinaryHeap, VecDeque};
+use log::{error, info, warn};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::mpsc::Receiver;
-use std::sync::{Arc, Mutex, RwLock};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::thread::JoinHandle;
 use std::time::{Duration, Instant};
 use windivert::error::WinDivertError;
 use windivert::layer::NetworkLayer;
 use windivert::WinDivert;
 use windivert_sys::WinDivertFlags;
 
+/// Longest time packets are released on schedule after processing stopped, before the remaining
+/// ones are sent at once.
+const MAX_SCHEDULED_DRAIN: Duration = Duration::from_secs(10);
+/// Longest time to wait for a packet or a module deadline, so shutdown and setting changes are
+/// noticed quickly.
+const MAX_WAIT: Duration = Duration::from_millis(50);
+/// How often the statistics are published to their readers.
+const PUBLISH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The part of the [`Cli`] the processing workers need, published to them as a [`Snapshot`] so
+/// they never lock the `Cli` themselves.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ProcessingSettings {
+    pub settings: PacketManipulationSettings,
+    pub seed: Option<u64>,
+    pub drain_policy: DrainPolicy,
+    /// The trace file the trace module replays
+    pub trace_file: Option<TraceFile>,
+}
+
+impl ProcessingSettings {
+    /// Takes the settings of `cli`, keeping `trace_file` while it is still the one set.
+    fn from_cli(cli: &Cli, trace_file: Option<&TraceFile>) -> Self {
+        ProcessingSettings {
+            settings: cli.packet_manipulation_settings.clone(),
+            seed: cli.options.seed,
+            drain_policy: cli.options.drain_policy.unwrap_or_default(),
+            trace_file: read_trace_file(&cli.packet_manipulation_settings, trace_file),
+        }
+    }
+
+    fn matches(&self, cli: &Cli) -> bool {
+        self.settings == cli.packet_manipulation_settings
+            && self.seed == cli.options.seed
+            && self.drain_policy == cli.options.drain_policy.unwrap_or_default()
+    }
+}
+
+/// Returns the trace file `settings` replay. `current` is kept while it is still the file set, so
+/// its replay continues, and another file is read. Without a readable file, the trace module
+/// lets packets through.
+fn read_trace_file(
+    settings: &PacketManipulationSettings,
+    current: Option<&TraceFile>,
+) -> Option<TraceFile> {
+    let path = settings.trace.as_ref()?.resolved_file()?;
+    if let Some(current) = current.filter(|current| current.path == path) {
+        return Some(current.clone());
+    }
+    match TraceFile::read(&path) {
+        Ok(file) => {
+            info!("Read {}", file);
+            Some(file)
+        }
+        Err(e) => {
+            error!("{}", e);
+            None
+        }
+    }
+}
+
+/// Hands the received packets to the processing workers, sharded by flow, and publishes setting
+/// changes to them until `running` is cleared. `trace_file` is the trace file of the settings,
+/// read when the run started.
 pub fn start_packet_processing(
     cli: Arc<Mutex<Cli>>,
-    packet_receiver: Receiver<PacketData>,
+    packet_receiver: Receiver<PacketBatch>,
     running: Arc<AtomicBool>,
-    statistics: Arc<RwLock<PacketProcessingStatistics>>,
+    statistics: Arc<PacketProcessingStatistics>,
+    trace_file: Option<TraceFile>,
+) -> Result<(), WinDivertError> {
+    let started = Instant::now();
+    let (worker_count, stop_at, snapshot) = {
+        let cli = cli.lock().unwrap();
+        (
+            cli.options.worker_count(),
+            cli.auto_stop.duration.map(|duration| started + duration),
+            Arc::new(Snapshot::new(ProcessingSettings::from_cli(
+                &cli,
+                trace_file.as_ref(),
+            ))),
+        )
+    };
+
+    let mut senders = Vec::with_capacity(worker_count);
+    let mut handles: Vec<JoinHandle<Result<(), WinDivertError>>> = Vec::with_capacity(worker_count);
+    for index in 0..worker_count {
+        let (sender, receiver) = mpsc::channel();
+        let handle = thread::spawn({
+            let settings = snapshot.reader();
+            let statistics = statistics.clone();
+            move || run_worker(index, receiver, settings, statistics)
+        });
+        senders.push(sender);
+        handles.push(handle);
+    }
+
+    let log_interval = Duration::from_secs(2);
+    let mut last_log_time = Instant::now();
+    let mut last_publish_time = Instant::now();
+    let mut last_accounting = statistics.capture().accounting();
+    let mut total_received_count = 0;
+    let mut published = snapshot.load();
+
+    info!(
+        "Starting packet interception with {} processing thread(s).",
+        worker_count
+    );
+    while running.load(Ordering::SeqCst) {
+        let timeout = stop_at.map_or(MAX_WAIT, |stop_at| {
+            stop_at
+                .saturating_duration_since(Instant::now())
+                .min(MAX_WAIT)
+        });
+        let mut packets = match packet_receiver.recv_timeout(timeout) {
+            Ok(batch) => batch,
+            Err(RecvTimeoutError::Timeout) => Vec::new(),
+            Err(RecvTimeoutError::Disconnected) => {
+                thread::sleep(timeout);
+                Vec::new()
+            }
+        };
+        while let Ok(batch) = packet_receiver.try_recv() {
+            packets.extend(batch);
+        }
+        total_received_count += packets.len();
+
+        let mut should_log = false;
+        if let Ok(cli) = cli.lock() {
+            if !published.matches(&cli) {
+                snapshot.publish(ProcessingSettings::from_cli(
+                    &cli,
+                    published.trace_file.as_ref(),
+                ));
+                published = snapshot.load();
+            }
+            if cli
+                .auto_stop
+                .should_stop(started.elapsed(), total_received_count)
+            {
+                info!("Run limit reached; initiating shutdown...");
+                running.store(false, Ordering::SeqCst);
+            }
+            should_log = cli.filter.is_some();
+        }
+
+        if !packets.is_empty() {
+            let shared = has_shared_modules(&published.settings);
+            if let Err(index) = dispatch(packets, &senders, shared) {
+                error!(
+                    "Processing thread {} stopped; initiating shutdown...",
+                    index
+                );
+                running.store(false, Ordering::SeqCst);
+            }
+        }
+
+        if last_publish_time.elapsed() >= PUBLISH_INTERVAL {
+            statistics.publish();
+            last_publish_time = Instant::now();
+        }
+
+        // Periodically log the statistics
+        if last_log_time.elapsed() >= log_interval && should_log {
+            let accounting = statistics.snapshot().accounting();
+            log_statistics(&accounting.since(&last_accounting));
+            last_accounting = accounting;
+            last_log_time = Instant::now(); // Reset the timer
+        }
+    }
+
+    let mut result = Ok(());
+    // Closing the channels lets the workers drain what they hold and stop
+    drop(senders);
+    for (index, handle) in handles.into_iter().enumerate() {
+        match handle.join() {
+            Ok(Err(e)) if result.is_ok() => result = Err(e),
+            Ok(_) => {}
+            Err(_) => error!("Processing thread {} panicked", index),
+        }
+    }
+    // Readers see what the workers did while draining
+    statistics.publish();
+    result
+}
+
+/// Splits `packets` by flow over the workers, or hands them all to the first one if `shared`
+/// modules are enabled. Returns the index of a worker that stopped.
+fn dispatch(
+    packets: PacketBatch,
+    workers: &[Sender<PacketBatch>],
+    shared: bool,
+) -> Result<(), usize> {
+    if workers.len() == 1 || shared {
+        return workers[0].send(packets).map_err(|_| 0);
+    }
+    let mut shards = vec![PacketBatch::new(); workers.len()];
+    for packet_data in packets {
+        shards[flow_shard(&packet_data.packet.data, workers.len())].push(packet_data);
+    }
+    for (index, shard) in shards.into_iter().enumerate() {
+        if !shard.is_empty() {
+            workers[index].send(shard).map_err(|_| index)?;
+        }
+    }
+    Ok(())
+}
+
+/// Processes the packets of one share of the flows with a state of its own, sending them with a
+/// WinDivert handle of its own. See [`process_until_closed`].
+fn run_worker(
+    index: usize,
+    packet_receiver: Receiver<PacketBatch>,
+    settings: SnapshotReader<ProcessingSettings>,
+    statistics: Arc<PacketProcessingStatistics>,
 ) -> Result<(), WinDivertError> {
     let wd = WinDivert::<NetworkLayer>::network(
         "false",
@@ -37,135 +263,459 @@ pub fn start_packet_processing(
         error!("Failed to initialize WinDiver: {}", e);
         e
     })?;
+    process_until_closed(index, packet_receiver, settings, &statistics, |packets| {
+        send_packets(&wd, packets)
+    })
+}
 
-    let log_interval = Duration::from_secs(2);
-    let mut last_log_time = Instant::now();
-
-    let mut received_packet_count = 0;
-    let mut sent_packet_count = 0;
-
-    let mut state = PacketProcessingState {
-        delay_storage: VecDeque::new(),
-        throttle_storage: VecDeque::new(),
-        bandwidth_limit_storage: VecDeque::new(),
-        bandwidth_storage_total_size: 0,
-        reorder_storage: BinaryHeap::new(),
-        throttled_start_time: Instant::now(),
-        last_sent_package_time: Instant::now(),
-    };
+/// Processes the packets received until their channel is closed and every batch queued in it was
+/// processed, and then drains what the modules still hold. Only the first worker runs the modules
+/// that manipulate all the traffic together, see [`has_shared_modules`].
+fn process_until_closed(
+    index: usize,
+    packet_receiver: Receiver<PacketBatch>,
+    mut settings: SnapshotReader<ProcessingSettings>,
+    statistics: &PacketProcessingStatistics,
+    mut send: impl FnMut(&[PacketData]) -> Result<(), WinDivertError>,
+) -> Result<(), WinDivertError> {
+    let mut state = PacketProcessingState::default();
+    let mut seed = None;
+    let mut schedule = ReleaseSchedule::default();
+    let mut drain_policy = DrainPolicy::default();
+    let mut published = settings.get().clone();
+    let mut current = worker_settings(index, &published);
 
-    info!("Starting packet interception.");
-    while running.load(Ordering::SeqCst) {
-        let mut packets = Vec::new();
-        // Try to receive packets from the channel
-        while let Ok(packet_data) = packet_receiver.try_recv() {
-            packets.push(packet_data);
-            received_packet_count += 1;
+    loop {
+        // Sleep until a packet arrives or a module is due to release one of the packets it holds
+        let timeout = schedule.next_release(&state).map_or(MAX_WAIT, |deadline| {
+            deadline
+                .saturating_duration_since(Instant::now())
+                .min(MAX_WAIT)
+        });
+        let mut packets = match packet_receiver.recv_timeout(timeout) {
+            Ok(batch) => batch,
+            Err(RecvTimeoutError::Timeout) => Vec::new(),
+            // Processing stopped and every batch sent before was processed
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        while let Ok(batch) = packet_receiver.try_recv() {
+            packets.extend(batch);
         }
+        statistics.received_packets.add(packets.len());
 
-        if let Ok(cli) = cli.lock() {
-            process_packets(
-                &cli.packet_manipulation_settings,
-                &mut packets,
-                &mut state,
-                &statistics,
-            );
+        if !Arc::ptr_eq(settings.get(), &published) {
+            published = settings.get().clone();
+            current = worker_settings(index, &published);
+        }
+        // Reseed whenever the configured seed changes so reloaded configs replay identically.
+        // Each worker gets a seed of its own, and the flows it handles only depend on the flow
+        if let Some(new_seed) = current.seed.filter(|new_seed| seed != Some(*new_seed)) {
+            seed = Some(new_seed);
+            random::seed(new_seed.wrapping_add(index as u64));
+            if index == 0 {
+                info!("Seeded packet manipulation with {}", new_seed);
+            }
         }
+        let mut dropped_packets = process_packets(
+            &current.settings,
+            current.trace_file.as_ref(),
+            &mut packets,
+            &mut state,
+            statistics,
+        );
+        // Packets held by modules that were turned off or paused would otherwise never leave
+        schedule.update(&current.settings);
+        drain_policy = current.drain_policy;
+        packets.extend(drain_packets(
+            &mut state,
+            Some(&current.settings),
+            &schedule,
+            drain_policy,
+            statistics,
+        ));
+
+        send(&packets)?;
 
-        for packet_data in &packets {
-            wd.send(&packet_data.packet).map_err(|e| {
-                error!("Failed to send packet: {}", e);
-                e
-            })?;
-            sent_packet_count += 1;
+        statistics.sent_packets.add(packets.len());
+        statistics.latency_stats.record_sent(&packets);
+        if statistics.is_inspecting() {
+            for packet_data in dropped_packets.iter_mut() {
+                packet_data.actions.insert(PacketActions::DROPPED);
+            }
+            statistics.inspect(dropped_packets.iter().chain(packets.iter()));
         }
+    }
 
-        // Periodically log the statistics
-        if last_log_time.elapsed() >= log_interval && cli.lock().unwrap().filter.is_some() {
-            log_statistics(received_packet_count, sent_packet_count);
-            received_packet_count = 0;
-            sent_packet_count = 0;
-            last_log_time = Instant::now(); // Reset the timer
+    drain_on_shutdown(&mut state, &schedule, drain_policy, statistics, send)
+}
+
+/// Returns the settings the worker `index` processes its packets with. Workers other than the
+/// first never get packets while shared modules are enabled, so they do not run them.
+fn worker_settings(index: usize, published: &Arc<ProcessingSettings>) -> Arc<ProcessingSettings> {
+    if index == 0 || !has_shared_modules(&published.settings) {
+        return published.clone();
+    }
+    let mut settings = ProcessingSettings::clone(published);
+    remove_shared_modules(&mut settings.settings);
+    Arc::new(settings)
+}
+
+/// Drains the packets still held by the modules once processing stopped, sending the released
+/// ones. Packets still scheduled after [`MAX_SCHEDULED_DRAIN`] are sent at once.
+fn drain_on_shutdown(
+    state: &mut PacketProcessingState,
+    schedule: &ReleaseSchedule,
+    drain_policy: DrainPolicy,
+    statistics: &PacketProcessingStatistics,
+    mut send: impl FnMut(&[PacketData]) -> Result<(), WinDivertError>,
+) -> Result<(), WinDivertError> {
+    let deadline = Instant::now() + MAX_SCHEDULED_DRAIN;
+    let mut policy = drain_policy;
+    let is_holding = |state: &PacketProcessingState| {
+        QueueingModule::ALL
+            .iter()
+            .any(|module| queued_count(state, *module) > 0)
+    };
+    while is_holding(state) {
+        if policy == DrainPolicy::Schedule && Instant::now() >= deadline {
+            warn!(
+                "Packets still queued after {:?}; sending them now.",
+                MAX_SCHEDULED_DRAIN
+            );
+            policy = DrainPolicy::Flush;
+        }
+        let packets = drain_packets(state, None, schedule, policy, statistics);
+        send(&packets)?;
+        statistics.sent_packets.add(packets.len());
+        statistics.latency_stats.record_sent(&packets);
+        if policy == DrainPolicy::Schedule && is_holding(state) {
+            // Packets that are never due, e.g. under a zero bandwidth limit, wait for the deadline
+            let release = schedule.next_release(state).unwrap_or(deadline);
+            thread::sleep(
+                release
+                    .min(deadline)
+                    .saturating_duration_since(Instant::now()),
+            );
         }
     }
+    Ok(())
+}
 
+/// Sends `packets` in as few WinDivert calls as possible.
+fn send_packets(
+    wd: &WinDivert<NetworkLayer>,
+    packets: &[PacketData],
+) -> Result<(), WinDivertError> {
+    for chunk in packets.chunks(MAX_BATCH_SIZE) {
+        let batch: Vec<_> = chunk
+            .iter()
+            .map(|packet_data| packet_data.packet.as_windivert())
+            .collect();
+        wd.send_ex(&batch).map_err(|e| {
+            error!("Failed to send packets: {}", e);
+            e
+        })?;
+    }
     Ok(())
 }
 
-pub fn process_packets<'a>(
+/// Runs the enabled manipulation modules over `packets` and returns the packets they dropped.
+/// `trace_file` is the trace replayed by the trace module.
+pub fn process_packets(
     settings: &PacketManipulationSettings,
-    packets: &mut Vec<PacketData<'a>>,
-    state: &mut PacketProcessingState<'a>,
-    statistics: &Arc<RwLock<PacketProcessingStatistics>>,
-) {
+    trace_file: Option<&TraceFile>,
+    packets: &mut Vec<PacketData>,
+    state: &mut PacketProcessingState,
+    statistics: &PacketProcessingStatistics,
+) -> Vec<PacketData> {
+    let mut dropped_packets = Vec::new();
+
     if let Some(drop) = &settings.drop {
-        drop_packets(
-            packets,
-            drop.probability,
-            &mut statistics.write().unwrap().drop_stats,
-        );
+        let dropped = with_content_match(packets, drop.content_match.as_ref(), |packets| {
+            drop_packets(
+                packets,
+                drop.probability,
+                drop.capacity,
+                drop.curve,
+                &statistics.drop_stats,
+            )
+        });
+        record_dropped("drop", dropped, &mut dropped_packets, statistics);
+    }
+
+    if let Some(topology) = &settings.topology {
+        let dropped = with_content_match(packets, topology.content_match.as_ref(), |packets| {
+            topology_packets(
+                packets,
+                &mut state.topology_storage,
+                topology,
+                &statistics.topology_stats,
+            )
+        });
+        record_dropped("topology", dropped, &mut dropped_packets, statistics);
+    }
+
+    if let Some(trace) = &settings.trace {
+        let dropped = with_content_match(packets, trace.content_match.as_ref(), |packets| {
+            trace_packets(
+                packets,
+                &mut state.trace_replay,
+                trace,
+                trace_file,
+                &statistics.trace_stats,
+            )
+        });
+        record_dropped("trace", dropped, &mut dropped_packets, statistics);
+    }
+
+    if let Some(dns) = &settings.dns {
+        let dropped = with_content_match(packets, dns.content_match.as_ref(), |packets| {
+            dns_packets(
+                packets,
+                &mut state.dns_delay_storage,
+                &dns.names,
+                dns.action,
+                dns.delay.duration(),
+                &statistics.dns_stats,
+            )
+        });
+        record_dropped("dns", dropped, &mut dropped_packets, statistics);
     }
 
     if let Some(delay) = &settings.delay {
-        delay_packets(
-            packets,
-            &mut state.delay_storage,
-            Duration::from_millis(delay.duration),
-            &mut statistics.write().unwrap().delay_stats,
-        );
+        with_content_match(packets, delay.content_match.as_ref(), |packets| {
+            delay_packets(
+                packets,
+                &mut state.delay_storage,
+                delay.duration.duration(),
+                &statistics.delay_stats,
+            )
+        });
     }
 
     if let Some(throttle) = &settings.throttle {
-        throttle_packages(
-            packets,
-            &mut state.throttle_storage,
-            &mut state.throttled_start_time,
-            throttle.probability,
-            Duration::from_millis(throttle.duration),
-            throttle.drop,
-            &mut statistics.write().unwrap().throttle_stats,
-        );
+        let dropped = with_content_match(packets, throttle.content_match.as_ref(), |packets| {
+            throttle_packages(
+                packets,
+                &mut state.throttle_storage,
+                &mut state.throttled_start_time,
+                throttle.probability,
+                throttle.duration.duration(),
+                throttle.drop,
+                &statistics.throttle_stats,
+            )
+        });
+        record_dropped("throttle", dropped, &mut dropped_packets, statistics);
     }
 
     if let Some(reorder) = &settings.reorder {
-        reorder_packets(
-            packets,
-            &mut state.reorder_storage,
-            reorder.probability,
-            Duration::from_millis(reorder.max_delay),
-            &mut statistics.write().unwrap().reorder_stats,
-        );
+        with_content_match(packets, reorder.content_match.as_ref(), |packets| {
+            reorder_packets(
+                packets,
+                &mut state.reorder_storage,
+                reorder.probability,
+                reorder.max_delay.duration(),
+                &statistics.reorder_stats,
+            )
+        });
     }
 
     if let Some(tamper) = &settings.tamper {
-        tamper_packets(
-            packets,
-            tamper.probability,
-            tamper.amount,
-            tamper.recalculate_checksums.unwrap_or(true),
-            &mut statistics.write().unwrap().tamper_stats,
-        );
+        with_content_match(packets, tamper.content_match.as_ref(), |packets| {
+            tamper_packets(
+                packets,
+                tamper.probability,
+                tamper.amount,
+                tamper.recalculate_checksums.unwrap_or(true),
+                &statistics.tamper_stats,
+            )
+        });
     }
 
     if let Some(duplicate) = &settings.duplicate {
         if duplicate.count > 1 && duplicate.probability.value() > 0.0 {
-            duplicate_packets(
-                packets,
-                duplicate.count,
-                duplicate.probability,
-                &mut statistics.write().unwrap().duplicate_stats,
-            );
+            with_content_match(packets, duplicate.content_match.as_ref(), |packets| {
+                duplicate_packets(
+                    packets,
+                    duplicate.count,
+                    duplicate.probability,
+                    &statistics.duplicate_stats,
+                )
+            });
         }
     }
 
     if let Some(bandwidth) = &settings.bandwidth {
-        bandwidth_limiter(
-            packets,
-            &mut state.bandwidth_limit_storage,
-            &mut state.bandwidth_storage_total_size,
-            &mut state.last_sent_package_time,
-            bandwidth.limit,
-            &mut statistics.write().unwrap().bandwidth_stats,
+        let dropped = with_content_match(packets, bandwidth.content_match.as_ref(), |packets| {
+            bandwidth_limiter(
+                packets,
+                &mut state.bandwidth_queues,
+                bandwidth,
+                &statistics.bandwidth_stats,
+            )
+        });
+        record_dropped("bandwidth", dropped, &mut dropped_packets, statistics);
+    }
+
+    dropped_packets
+}
+
+/// Adds the packets `module` dropped to `dropped_packets` and counts them for the module.
+fn record_dropped(
+    module: &str,
+    dropped: Vec<PacketData>,
+    dropped_packets: &mut Vec<PacketData>,
+    statistics: &PacketProcessingStatistics,
+) {
+    statistics
+        .accounting_stats
+        .record_dropped(module, dropped.len());
+    dropped_packets.extend(dropped);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::packet::protocol;
+    use crate::network::packet::test_packets::{ipv4, udp};
+    use windivert::packet::WinDivertPacket;
+
+    const SETTINGS: &str = "\
+        [drop]\nprobability = 0.1\n\
+        [delay]\nduration = 2\n\
+        [throttle]\nprobability = 0.2\nduration = 3\ndrop = false\n\
+        [reorder]\nprobability = 0.2\nmax_delay = 4\n\
+        [duplicate]\nprobability = 0.2\ncount = 2\n\
+        [bandwidth]\nlimit = 50\n";
+
+    fn packets(count: usize) -> Vec<PacketData> {
+        (0..count)
+            .map(|i| {
+                let data = ipv4(protocol::UDP, &udp(40000 + i as u16, 53, &[0; 64]));
+                // Safety: the address is zeroed, which is a valid network layer address
+                PacketData::from(unsafe { WinDivertPacket::<NetworkLayer>::new(data) })
+            })
+            .collect()
+    }
+
+    /// Checks that every packet is accounted for, and that the queued counts match the state.
+    fn assert_balanced(statistics: &PacketProcessingStatistics, state: &PacketProcessingState) {
+        let accounting = statistics.capture().accounting();
+        assert_eq!(accounting.imbalance(), 0, "{}", accounting);
+        let queued: usize = QueueingModule::ALL
+            .iter()
+            .map(|module| queued_count(state, *module))
+            .sum();
+        assert_eq!(accounting.total_queued(), queued, "{}", accounting);
+    }
+
+    #[test]
+    fn test_every_packet_is_accounted_for() {
+        random::seed(7);
+        let settings: PacketManipulationSettings = toml::from_str(SETTINGS).unwrap();
+        let statistics = PacketProcessingStatistics::new();
+        let mut state = PacketProcessingState::default();
+        let schedule = ReleaseSchedule::default();
+
+        for round in 0..40 {
+            let mut batch = packets(10);
+            statistics.received_packets.add(batch.len());
+            process_packets(&settings, None, &mut batch, &mut state, &statistics);
+            statistics.sent_packets.add(batch.len());
+            assert_balanced(&statistics, &state);
+
+            if round == 20 {
+                // Packets held across a reset are carried over
+                statistics.reset();
+                assert_balanced(&statistics, &state);
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        let accounting = statistics.capture().accounting();
+        assert!(accounting.duplicated > 0 && accounting.total_dropped() > 0);
+        assert!(accounting.carried_over > 0, "{}", accounting);
+
+        let drained = drain_packets(
+            &mut state,
+            None,
+            &schedule,
+            DrainPolicy::Discard,
+            &statistics,
         );
+        statistics.sent_packets.add(drained.len());
+        assert_balanced(&statistics, &state);
+        assert_eq!(statistics.capture().accounting().total_queued(), 0);
+    }
+
+    #[test]
+    fn test_bandwidth_limit_is_shared_by_workers() {
+        let settings: PacketManipulationSettings =
+            toml::from_str("[bandwidth]\nlimit = 10\n").unwrap();
+        let limit = settings.bandwidth.as_ref().unwrap().limit.unwrap();
+        let statistics = PacketProcessingStatistics::new();
+        let (senders, receivers): (Vec<_>, Vec<_>) = (0..4).map(|_| mpsc::channel()).unzip();
+        let mut workers: Vec<_> = (0..4)
+            .map(|index| {
+                let published = Arc::new(ProcessingSettings {
+                    settings: settings.clone(),
+                    ..Default::default()
+                });
+                let settings = worker_settings(index, &published).settings.clone();
+                (settings, PacketProcessingState::default())
+            })
+            .collect();
+
+        let started = Instant::now();
+        let mut sent_bytes = 0;
+        while started.elapsed() < Duration::from_millis(300) {
+            dispatch(packets(64), &senders, has_shared_modules(&settings)).unwrap();
+            for (receiver, (settings, state)) in receivers.iter().zip(workers.iter_mut()) {
+                while let Ok(mut batch) = receiver.try_recv() {
+                    process_packets(settings, None, &mut batch, state, &statistics);
+                    sent_bytes += batch
+                        .iter()
+                        .map(|packet_data| packet_data.packet.data.len())
+                        .sum::<usize>();
+                }
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        // Each worker limiting the flows it handles would send up to four times as much
+        let allowed = limit.bytes_per_second() * started.elapsed().as_secs_f64();
+        assert!(sent_bytes > 0);
+        assert!(sent_bytes as f64 <= allowed, "{} > {}", sent_bytes, allowed);
+        assert!(workers[1..]
+            .iter()
+            .all(|(_, state)| state.bandwidth_queues.is_empty()));
+    }
+
+    #[test]
+    fn test_batches_queued_at_shutdown_are_processed() {
+        let snapshot = Arc::new(Snapshot::new(ProcessingSettings {
+            settings: toml::from_str("[delay]\nduration = 20\n").unwrap(),
+            drain_policy: DrainPolicy::Schedule,
+            ..Default::default()
+        }));
+        let statistics = PacketProcessingStatistics::new();
+        let (sender, receiver) = mpsc::channel();
+        for _ in 0..3 {
+            sender.send(packets(10)).unwrap();
+        }
+        // The channel is closed with the batches still queued, as on shutdown
+        drop(sender);
+
+        let mut sent = 0;
+        process_until_closed(0, receiver, snapshot.reader(), &statistics, |packets| {
+            sent += packets.len();
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(sent, 30);
+        let accounting = statistics.capture().accounting();
+        assert_eq!(accounting.imbalance(), 0, "{}", accounting);
+        assert_eq!(accounting.total_queued(), 0);
     }
-}
\ No newline at end of file
+}
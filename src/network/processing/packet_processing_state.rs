@@ -1,14 +1,32 @@
 use crate::network::core::packet_data::PacketData;
+use crate::network::modules::bandwidth::BandwidthQueues;
+use crate::network::modules::trace::TraceReplay;
 use crate::network::types::delayed_packet::DelayedPacket;
 use std::collections::{BinaryHeap, VecDeque};
 use std::time::Instant;
 
-pub struct PacketProcessingState<'a> {
-    pub delay_storage: VecDeque<PacketData<'a>>,
-    pub reorder_storage: BinaryHeap<DelayedPacket<'a>>,
-    pub bandwidth_limit_storage: VecDeque<PacketData<'a>>,
-    pub bandwidth_storage_total_size: usize,
-    pub throttle_storage: VecDeque<PacketData<'a>>,
+pub struct PacketProcessingState {
+    pub delay_storage: VecDeque<PacketData>,
+    pub dns_delay_storage: VecDeque<PacketData>,
+    pub reorder_storage: BinaryHeap<DelayedPacket>,
+    pub topology_storage: BinaryHeap<DelayedPacket>,
+    pub trace_replay: TraceReplay,
+    pub bandwidth_queues: BandwidthQueues,
+    pub throttle_storage: VecDeque<PacketData>,
     pub throttled_start_time: Instant,
-    pub last_sent_package_time: Instant,
+}
+
+impl Default for PacketProcessingState {
+    fn default() -> Self {
+        PacketProcessingState {
+            delay_storage: VecDeque::new(),
+            dns_delay_storage: VecDeque::new(),
+            reorder_storage: BinaryHeap::new(),
+            topology_storage: BinaryHeap::new(),
+            trace_replay: TraceReplay::default(),
+            bandwidth_queues: BandwidthQueues::default(),
+            throttle_storage: VecDeque::new(),
+            throttled_start_time: Instant::now(),
+        }
+    }
 }
@@ -1,5 +1,6 @@
 use crate::cli::Cli;
-use crate::network::core::packet_data::PacketData;
+use crate::network::core::packet_buffer::{BufferPool, MAX_PACKET_SIZE};
+use crate::network::core::packet_data::{Packet, PacketBatch, PacketData, MAX_BATCH_SIZE};
 use log::{debug, error};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{mpsc, Arc, Mutex};
@@ -8,12 +9,16 @@ use windivert::layer::NetworkLayer;
 use windivert::{CloseAction, WinDivert};
 use windivert_sys::WinDivertFlags;
 
+/// Room for a full batch of regular sized packets, or a few jumbo ones.
+const RECEIVE_BUFFER_SIZE: usize = 8 * MAX_PACKET_SIZE;
+
 pub fn receive_packets(
-    packet_sender: mpsc::Sender<PacketData<'_>>,
+    packet_sender: mpsc::Sender<PacketBatch>,
     running: Arc<AtomicBool>,
     cli: Arc<Mutex<Cli>>,
 ) -> Result<(), WinDivertError> {
-    let mut buffer = vec![0u8; 1500];
+    let mut buffer = vec![0u8; RECEIVE_BUFFER_SIZE];
+    let pool = BufferPool::new();
     let mut last_filter = String::new();
     let mut wd: Option<WinDivert<NetworkLayer>> = None;
     let mut logged_missing_handle = false;
@@ -61,10 +66,26 @@ pub fn receive_packets(
 
         if let Some(ref wd_handle) = wd {
             logged_missing_handle = false;
-            match wd_handle.recv(Some(&mut buffer)) {
-                Ok(packet) => {
-                    let packet_data = PacketData::from(packet.into_owned());
-                    if packet_sender.send(packet_data).is_err() {
+            // Returns as soon as any packet is available, with whatever else is queued behind it
+            match wd_handle.recv_ex(Some(&mut buffer), MAX_BATCH_SIZE) {
+                Ok(packets) => {
+                    // WinDivert writes the batch back to back into `buffer`, which is reused for
+                    // the next batch, so each packet is copied once into a buffer of its own.
+                    // That buffer comes from the pool and goes back to it once the packet was
+                    // sent or dropped, so once the pool is warm the copy only allocates for
+                    // packets larger than an MTU.
+                    // Receiving each packet into a pooled buffer directly would take one
+                    // WinDivert call per packet instead of one per batch
+                    let batch: PacketBatch = packets
+                        .into_iter()
+                        .map(|packet| {
+                            PacketData::from(Packet {
+                                data: pool.copy_from_slice(&packet.data),
+                                address: packet.address,
+                            })
+                        })
+                        .collect();
+                    if packet_sender.send(batch).is_err() {
                         if should_shutdown(&running) {
                             break;
                         } else {
@@ -97,4 +118,4 @@ fn should_shutdown(running: &Arc<AtomicBool>) -> bool {
         return true;
     }
     false
-}
\ No newline at end of file
+}
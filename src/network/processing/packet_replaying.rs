@@ -0,0 +1,79 @@
+use crate::network::core::packet_data::{PacketBatch, PacketData, MAX_BATCH_SIZE};
+use crate::network::utils::pcap::CapturedPacket;
+use log::{debug, error, info};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+use windivert::error::WinDivertError;
+use windivert::layer::NetworkLayer;
+use windivert::packet::WinDivertPacket;
+
+/// Longest single sleep while waiting for the next packet, so shutdown is noticed quickly.
+const MAX_SLEEP: Duration = Duration::from_millis(50);
+
+/// Feeds captured packets to the processing thread at their recorded times, scaled by `speed`,
+/// in place of packets received from the network.
+pub fn replay_packets(
+    packets: Vec<CapturedPacket>,
+    packet_sender: mpsc::Sender<PacketBatch>,
+    running: Arc<AtomicBool>,
+    speed: f64,
+    outbound: bool,
+) -> Result<(), WinDivertError> {
+    info!("Replaying {} packets at {}x speed.", packets.len(), speed);
+    let start = Instant::now();
+    let mut replayed = 0;
+    let mut batch = PacketBatch::new();
+
+    for captured in packets {
+        let due = start + captured.offset.div_f64(speed);
+        // Packets recorded at the same time are handed over together
+        if Instant::now() < due && !send_batch(&packet_sender, &mut batch, &mut replayed) {
+            return Ok(());
+        }
+        while running.load(Ordering::SeqCst) {
+            let now = Instant::now();
+            if now >= due {
+                break;
+            }
+            thread::sleep((due - now).min(MAX_SLEEP));
+        }
+        if !running.load(Ordering::SeqCst) {
+            debug!("Packet replaying thread exiting due to shutdown signal.");
+            return Ok(());
+        }
+
+        // Safety: the address is zeroed, which is a valid network layer address
+        let mut packet = unsafe { WinDivertPacket::<NetworkLayer>::new(captured.data) };
+        packet.address.set_outbound(outbound);
+        // Marks the packet as injected so WinDivert handles do not capture it again
+        packet.address.set_impostor(true);
+        batch.push(PacketData::from(packet));
+        if batch.len() >= MAX_BATCH_SIZE && !send_batch(&packet_sender, &mut batch, &mut replayed) {
+            return Ok(());
+        }
+    }
+    if send_batch(&packet_sender, &mut batch, &mut replayed) {
+        info!("Replayed {} packets.", replayed);
+    }
+    Ok(())
+}
+
+/// Hands the packets in `batch` to the processing thread, returning whether that succeeded.
+fn send_batch(
+    packet_sender: &mpsc::Sender<PacketBatch>,
+    batch: &mut PacketBatch,
+    replayed: &mut usize,
+) -> bool {
+    if batch.is_empty() {
+        return true;
+    }
+    let count = batch.len();
+    if packet_sender.send(std::mem::take(batch)).is_err() {
+        error!("Failed to send packet data to main thread");
+        return false;
+    }
+    *replayed += count;
+    true
+}
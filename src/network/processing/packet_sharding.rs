@@ -0,0 +1,76 @@
+use crate::cli::settings::packet_manipulation::PacketManipulationSettings;
+use crate::network::packet::{IpHeader, PacketView};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Returns which of `shards` workers processes the packet in `data`.
+///
+/// Packets of the same flow, identified by protocol, addresses and ports, always go to the same
+/// worker, in both directions, so each flow keeps its order. Packets that are not IP go to the
+/// first worker.
+pub fn flow_shard(data: &[u8], shards: usize) -> usize {
+    if shards <= 1 {
+        return 0;
+    }
+    let Ok(ip) = IpHeader::parse(data) else {
+        return 0;
+    };
+    let ports = PacketView::parse(data)
+        .ok()
+        .map(|view| (view.source_port(), view.destination_port()));
+    let (source_port, destination_port) = ports.unwrap_or_default();
+    let source = (ip.source(), source_port);
+    let destination = (ip.destination(), destination_port);
+
+    let mut hasher = DefaultHasher::new();
+    ip.protocol().hash(&mut hasher);
+    source.min(destination).hash(&mut hasher);
+    source.max(destination).hash(&mut hasher);
+    (hasher.finish() % shards as u64) as usize
+}
+
+/// Returns whether `settings` enable a module that manipulates all the traffic together: the
+/// bandwidth limit, throttling or the replay of a trace. Their state is not split over the
+/// workers, so while one of them is enabled every packet goes to the first worker.
+pub fn has_shared_modules(settings: &PacketManipulationSettings) -> bool {
+    settings.bandwidth.is_some() || settings.throttle.is_some() || settings.trace.is_some()
+}
+
+/// Turns off the modules of [`has_shared_modules`] in the settings of a worker other than the
+/// first, which never gets packets while they are enabled.
+pub fn remove_shared_modules(settings: &mut PacketManipulationSettings) {
+    settings.bandwidth = None;
+    settings.throttle = None;
+    settings.trace = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::packet::protocol;
+    use crate::network::packet::test_packets::{ipv4, udp};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_both_directions_share_a_shard() {
+        let request = ipv4(protocol::UDP, &udp(50000, 53, b"query"));
+        let mut response = ipv4(protocol::UDP, &udp(53, 50000, b"answer"));
+        // Swap the addresses so the response travels the other way
+        let (source, destination) = (request[12..16].to_vec(), request[16..20].to_vec());
+        response[12..16].copy_from_slice(&destination);
+        response[16..20].copy_from_slice(&source);
+
+        for shards in 1..8 {
+            assert_eq!(flow_shard(&request, shards), flow_shard(&response, shards));
+        }
+    }
+
+    #[test]
+    fn test_flows_are_spread_over_shards() {
+        let shards: HashSet<usize> = (0..64)
+            .map(|port| flow_shard(&ipv4(protocol::UDP, &udp(40000 + port, 53, b"")), 4))
+            .collect();
+        assert_eq!(shards.len(), 4);
+        assert_eq!(flow_shard(&[0x00, 0x01], 4), 0);
+    }
+}
@@ -0,0 +1,249 @@
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+/// A bandwidth, kept in bits per second.
+///
+/// Parses a number with a unit such as `512kbit`, `2Mbps`, `1.5MB/s` or `64KiB/s`. A lowercase
+/// `b` or `bit` counts bits and an uppercase `B` bytes. The `k`, `M` and `G` prefixes are
+/// decimal, `Ki`, `Mi` and `Gi` binary. A number without a unit is in KB/s of 1024 bytes, the
+/// unit limits were given in before.
+///
+/// # Example
+///
+/// ```rust
+/// use fumble::network::types::bit_rate::BitRate;
+/// let rate: BitRate = "2Mbps".parse().unwrap();
+/// assert_eq!(rate.bits_per_second(), 2_000_000);
+/// assert_eq!("1.5MB/s".parse::<BitRate>().unwrap().bits_per_second(), 12_000_000);
+/// assert_eq!("50".parse::<BitRate>().unwrap(), BitRate::from_kibibytes(50));
+/// assert_eq!(rate.to_string(), "2Mbit/s");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct BitRate(u64);
+
+/// Units [`BitRate`] is displayed in, from the largest, with their size in bits.
+const DISPLAY_UNITS: [(&str, u64); 6] = [
+    ("Gbit/s", 1_000_000_000),
+    ("Mbit/s", 1_000_000),
+    ("kbit/s", 1_000),
+    ("MiB/s", 8 * 1024 * 1024),
+    ("KiB/s", 8 * 1024),
+    ("bit/s", 1),
+];
+
+impl BitRate {
+    pub fn from_bits_per_second(bits: u64) -> Self {
+        BitRate(bits)
+    }
+
+    /// Creates a rate of `kibibytes` KB/s of 1024 bytes, the unit of a number without a unit.
+    pub fn from_kibibytes(kibibytes: u64) -> Self {
+        BitRate(kibibytes.saturating_mul(8 * 1024))
+    }
+
+    pub fn bits_per_second(&self) -> u64 {
+        self.0
+    }
+
+    pub fn bytes_per_second(&self) -> f64 {
+        self.0 as f64 / 8.0
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Creates a rate of `kibibytes` KB/s of 1024 bytes, rounded to whole bits. Returns `None` if
+    /// it is negative, not finite or too large.
+    pub fn from_kibibytes_f64(kibibytes: f64) -> Option<Self> {
+        BitRate::from_bits_f64(kibibytes * 8.0 * 1024.0)
+    }
+
+    /// Creates a rate of `bits` per second, rounded. Returns `None` if it is negative, not finite
+    /// or too large.
+    fn from_bits_f64(bits: f64) -> Option<Self> {
+        let bits = bits.round();
+        (bits.is_finite() && (0.0..=u64::MAX as f64).contains(&bits))
+            .then_some(BitRate(bits as u64))
+    }
+}
+
+/// Returns the size in bits of `unit`, such as `kbit`, `Mbps` or `MiB/s`.
+fn unit_bits(unit: &str) -> Option<u64> {
+    let unit = unit
+        .strip_suffix("/s")
+        .or_else(|| unit.strip_suffix("ps"))
+        .unwrap_or(unit);
+    let (prefix, base) = match unit.char_indices().find(|(_, c)| *c == 'b' || *c == 'B') {
+        Some((index, _)) => unit.split_at(index),
+        None => return None,
+    };
+    let size = match base {
+        "b" | "bit" => 1,
+        "B" => 8,
+        _ => return None,
+    };
+    let multiplier: u64 = match prefix {
+        "" => 1,
+        "k" | "K" => 1_000,
+        "m" | "M" => 1_000_000,
+        "g" | "G" => 1_000_000_000,
+        "Ki" => 1 << 10,
+        "Mi" => 1 << 20,
+        "Gi" => 1 << 30,
+        _ => return None,
+    };
+    Some(size * multiplier)
+}
+
+impl FromStr for BitRate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let invalid = || {
+            format!(
+                "`{}` is not a valid bandwidth, expected e.g. 512kbit, 2Mbps or 1.5MB/s",
+                s
+            )
+        };
+        let split = s
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+        let number: f64 = number.parse().map_err(|_| invalid())?;
+        let unit = unit.trim_start();
+        let bits = if unit.is_empty() {
+            8.0 * 1024.0
+        } else {
+            unit_bits(unit).ok_or_else(invalid)? as f64
+        };
+        BitRate::from_bits_f64(number * bits).ok_or_else(invalid)
+    }
+}
+
+impl fmt::Display for BitRate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // The largest unit the rate is a whole multiple of, so the text parses to the same rate
+        let (unit, bits) = DISPLAY_UNITS
+            .iter()
+            .find(|(_, bits)| self.0 >= *bits && self.0.is_multiple_of(*bits))
+            .unwrap_or(&("bit/s", 1));
+        write!(f, "{}{}", self.0 / bits, unit)
+    }
+}
+
+impl Serialize for BitRate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for BitRate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BitRateVisitor;
+
+        impl Visitor<'_> for BitRateVisitor {
+            type Value = BitRate;
+
+            fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+                write!(f, "a bandwidth such as \"2Mbps\", or a number of KB/s")
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<BitRate, E> {
+                Ok(BitRate::from_kibibytes(value))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<BitRate, E> {
+                u64::try_from(value)
+                    .map(BitRate::from_kibibytes)
+                    .map_err(|_| E::custom(format!("{} is not a valid bandwidth", value)))
+            }
+
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<BitRate, E> {
+                BitRate::from_kibibytes_f64(value)
+                    .ok_or_else(|| E::custom(format!("{} is not a valid bandwidth", value)))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<BitRate, E> {
+                value.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(BitRateVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_units() {
+        let cases = [
+            ("512kbit", 512_000),
+            ("512 kbit/s", 512_000),
+            ("2Mbps", 2_000_000),
+            ("2mbit", 2_000_000),
+            ("1.5MB/s", 12_000_000),
+            ("1.5MBps", 12_000_000),
+            ("64KiB/s", 64 * 1024 * 8),
+            ("1Gbit", 1_000_000_000),
+            ("100bps", 100),
+            ("10B/s", 80),
+            ("50", 50 * 1024 * 8),
+        ];
+        for (text, bits) in cases {
+            assert_eq!(
+                text.parse::<BitRate>().unwrap().bits_per_second(),
+                bits,
+                "{}",
+                text
+            );
+        }
+        for text in ["", "fast", "2M", "2Mi", "2 Tbit", "-1kbit", "1..5MB/s"] {
+            assert!(text.parse::<BitRate>().is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        for text in [
+            "512kbit/s",
+            "2Mbit/s",
+            "50KiB/s",
+            "12Mbit/s",
+            "1001bit/s",
+            "0bit/s",
+        ] {
+            let rate: BitRate = text.parse().unwrap();
+            assert_eq!(rate.to_string(), text);
+        }
+        assert_eq!(BitRate::from_kibibytes(1024).to_string(), "1MiB/s");
+    }
+
+    #[test]
+    fn test_deserializes_numbers_and_strings() {
+        #[derive(Deserialize, Serialize)]
+        struct Limits {
+            number: BitRate,
+            text: BitRate,
+        }
+        let limits: Limits = toml::from_str("number = 50\ntext = \"2Mbps\"\n").unwrap();
+        assert_eq!(limits.number, BitRate::from_kibibytes(50));
+        assert_eq!(limits.text.bits_per_second(), 2_000_000);
+        assert_eq!(
+            toml::to_string(&limits).unwrap(),
+            "number = \"50KiB/s\"\ntext = \"2Mbit/s\"\n"
+        );
+        let limits: Limits = toml::from_str("number = 1.5\ntext = \"1\"\n").unwrap();
+        assert_eq!(limits.number.bits_per_second(), 1536 * 8);
+        for number in ["-1", "-0.5", "inf", "nan"] {
+            let content = format!("number = {}\ntext = \"1\"\n", number);
+            assert!(toml::from_str::<Limits>(&content).is_err(), "{}", number);
+        }
+    }
+}
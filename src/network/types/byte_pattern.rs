@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+/// A sequence of bytes written as hex, e.g. `deadbeef` or `16 03 01`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct BytePattern(Vec<u8>);
+
+impl BytePattern {
+    pub fn new(bytes: Vec<u8>) -> Result<Self, String> {
+        if bytes.is_empty() {
+            Err("Byte pattern cannot be empty".to_string())
+        } else {
+            Ok(BytePattern(bytes))
+        }
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Returns `true` if the pattern occurs anywhere in `data`.
+    pub fn is_found_in(&self, data: &[u8]) -> bool {
+        data.windows(self.0.len()).any(|window| window == self.0)
+    }
+}
+
+impl FromStr for BytePattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex: String = s
+            .trim()
+            .trim_start_matches("0x")
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        // Checked before slicing, as a multi-byte character would not split into digit pairs
+        if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(format!("`{}` is not a valid hex byte sequence", s));
+        }
+        if hex.len() % 2 == 1 {
+            return Err(format!("`{}` has an odd number of hex digits", s));
+        }
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|_| format!("`{}` is not a valid hex byte sequence", s))?;
+        BytePattern::new(bytes)
+    }
+}
+
+impl TryFrom<String> for BytePattern {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<BytePattern> for String {
+    fn from(pattern: BytePattern) -> Self {
+        pattern.to_string()
+    }
+}
+
+impl fmt::Display for BytePattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for byte in &self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        let pattern: BytePattern = "0xDE AD be ef".parse().unwrap();
+        assert_eq!(pattern.bytes(), &[0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(pattern.to_string(), "deadbeef");
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!("abc".parse::<BytePattern>().is_err());
+        assert!("zz".parse::<BytePattern>().is_err());
+        assert!("".parse::<BytePattern>().is_err());
+        // Non-ASCII characters are rejected instead of splitting a character
+        assert!("aéb".parse::<BytePattern>().is_err());
+        assert!("aé".parse::<BytePattern>().is_err());
+    }
+
+    #[test]
+    fn test_is_found_in() {
+        let pattern: BytePattern = "0304".parse().unwrap();
+        assert!(pattern.is_found_in(&[1, 2, 3, 4, 5]));
+        assert!(!pattern.is_found_in(&[3]));
+    }
+}
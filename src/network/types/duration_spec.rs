@@ -0,0 +1,235 @@
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::fmt::Formatter;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// A duration given with a unit, such as `150ms`, `1.5s` or `2m`.
+///
+/// The units are `ns`, `us` (or `µs`), `ms`, `s`, `m` and `h`, so durations below a millisecond
+/// can be given as well, e.g. `0.5ms` or `250us`. A number without a unit is in milliseconds,
+/// the unit durations were given in before.
+///
+/// # Example
+///
+/// ```rust
+/// use fumble::network::types::duration_spec::DurationSpec;
+/// use std::time::Duration;
+/// let spec: DurationSpec = "1.5s".parse().unwrap();
+/// assert_eq!(spec.duration(), Duration::from_millis(1500));
+/// assert_eq!("150".parse::<DurationSpec>().unwrap(), DurationSpec::from_millis(150));
+/// assert_eq!(spec.to_string(), "1500ms");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct DurationSpec(Duration);
+
+/// Units a duration can be given in, with their length in nanoseconds. [`DurationSpec`] is
+/// displayed in the first of them it is a whole multiple of.
+const UNITS: [(&str, u64); 7] = [
+    ("h", 3_600_000_000_000),
+    ("m", 60_000_000_000),
+    ("s", 1_000_000_000),
+    ("ms", 1_000_000),
+    ("us", 1_000),
+    ("µs", 1_000),
+    ("ns", 1),
+];
+
+impl DurationSpec {
+    pub fn new(duration: Duration) -> Self {
+        DurationSpec(duration)
+    }
+
+    pub fn from_millis(millis: u64) -> Self {
+        DurationSpec(Duration::from_millis(millis))
+    }
+
+    /// Creates a duration of `millis` milliseconds, rounded to whole nanoseconds. Returns `None`
+    /// if it is negative, not finite or too long.
+    pub fn from_millis_f64(millis: f64) -> Option<Self> {
+        let nanos = (millis * 1_000_000.0).round();
+        (0.0..=u64::MAX as f64)
+            .contains(&nanos)
+            .then_some(DurationSpec(Duration::from_nanos(nanos as u64)))
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.0
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+}
+
+impl From<Duration> for DurationSpec {
+    fn from(duration: Duration) -> Self {
+        DurationSpec(duration)
+    }
+}
+
+impl From<DurationSpec> for Duration {
+    fn from(spec: DurationSpec) -> Self {
+        spec.0
+    }
+}
+
+/// Parses a number followed by one of the units of [`DurationSpec`], such as `500ms` or `1.5h`,
+/// taking a number without a unit to be in `bare_unit`.
+pub fn parse_duration(value: &str, bare_unit: Duration) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let nanos_per_unit = match unit.trim() {
+        "" => bare_unit.as_nanos() as f64,
+        unit => match UNITS.iter().find(|(name, _)| *name == unit) {
+            Some((_, nanos)) => *nanos as f64,
+            None => {
+                return Err(format!(
+                    "unknown unit `{}`, expected one of ns, us, ms, s, m or h",
+                    unit
+                ))
+            }
+        },
+    };
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("`{}` is not a valid duration", value))?;
+    let nanos = (number * nanos_per_unit).round();
+    if !nanos.is_finite() || nanos > u64::MAX as f64 {
+        return Err(format!("`{}` is too long", value));
+    }
+    Ok(Duration::from_nanos(nanos as u64))
+}
+
+impl FromStr for DurationSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_duration(s, Duration::from_millis(1)).map(DurationSpec)
+    }
+}
+
+impl fmt::Display for DurationSpec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let nanos = self.0.as_nanos();
+        if nanos == 0 {
+            return write!(f, "0ms");
+        }
+        // The largest unit the duration is a whole multiple of, so the text parses to the same
+        // duration
+        let (unit, unit_nanos) = UNITS
+            .iter()
+            .find(|(_, unit_nanos)| nanos.is_multiple_of(*unit_nanos as u128))
+            .unwrap_or(&("ns", 1));
+        write!(f, "{}{}", nanos / *unit_nanos as u128, unit)
+    }
+}
+
+impl Serialize for DurationSpec {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for DurationSpec {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DurationSpecVisitor;
+
+        impl Visitor<'_> for DurationSpecVisitor {
+            type Value = DurationSpec;
+
+            fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+                write!(
+                    f,
+                    "a duration such as \"150ms\", or a number of milliseconds"
+                )
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<DurationSpec, E> {
+                Ok(DurationSpec::from_millis(value))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<DurationSpec, E> {
+                u64::try_from(value)
+                    .map(DurationSpec::from_millis)
+                    .map_err(|_| E::custom(format!("{} is not a valid duration", value)))
+            }
+
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<DurationSpec, E> {
+                DurationSpec::from_millis_f64(value)
+                    .ok_or_else(|| E::custom(format!("{} is not a valid duration", value)))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<DurationSpec, E> {
+                value.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(DurationSpecVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_units() {
+        let cases = [
+            ("150ms", Duration::from_millis(150)),
+            ("150 ms", Duration::from_millis(150)),
+            ("1.5s", Duration::from_millis(1500)),
+            ("2m", Duration::from_secs(120)),
+            ("1h", Duration::from_secs(3600)),
+            ("0.5ms", Duration::from_micros(500)),
+            ("250us", Duration::from_micros(250)),
+            ("250µs", Duration::from_micros(250)),
+            ("10ns", Duration::from_nanos(10)),
+            ("150", Duration::from_millis(150)),
+            ("0", Duration::ZERO),
+        ];
+        for (text, duration) in cases {
+            assert_eq!(
+                text.parse::<DurationSpec>().unwrap().duration(),
+                duration,
+                "{}",
+                text
+            );
+        }
+        for text in ["", "ms", "5d", "-1s", "1..5s", "fast"] {
+            assert!(text.parse::<DurationSpec>().is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        for text in ["150ms", "1500ms", "2m", "1h", "500us", "10ns", "0ms"] {
+            let spec: DurationSpec = text.parse().unwrap();
+            assert_eq!(spec.to_string(), text);
+        }
+    }
+
+    #[test]
+    fn test_deserializes_numbers_and_strings() {
+        #[derive(Deserialize, Serialize)]
+        struct Durations {
+            number: DurationSpec,
+            fraction: DurationSpec,
+            text: DurationSpec,
+        }
+        let durations: Durations =
+            toml::from_str("number = 50\nfraction = 0.25\ntext = \"1.5s\"\n").unwrap();
+        assert_eq!(durations.number, DurationSpec::from_millis(50));
+        assert_eq!(durations.fraction.duration(), Duration::from_micros(250));
+        assert_eq!(durations.text.duration(), Duration::from_millis(1500));
+        assert_eq!(
+            toml::to_string(&durations).unwrap(),
+            "number = \"50ms\"\nfraction = \"250us\"\ntext = \"1500ms\"\n"
+        );
+        assert!(toml::from_str::<Durations>("number = -1\nfraction = 0\ntext = \"1s\"\n").is_err());
+    }
+}
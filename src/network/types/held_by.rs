@@ -0,0 +1,66 @@
+/// Set of modules that held a packet back before it was sent, so the time it spent inside fumble
+/// can be attributed to them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HeldBy(u8);
+
+impl HeldBy {
+    pub const DELAY: HeldBy = HeldBy(1);
+    pub const DNS: HeldBy = HeldBy(1 << 1);
+    pub const THROTTLE: HeldBy = HeldBy(1 << 2);
+    pub const REORDER: HeldBy = HeldBy(1 << 3);
+    pub const TOPOLOGY: HeldBy = HeldBy(1 << 4);
+    pub const BANDWIDTH: HeldBy = HeldBy(1 << 5);
+    pub const TRACE: HeldBy = HeldBy(1 << 6);
+
+    /// Every module that holds packets, with its name, in pipeline order.
+    pub const MODULES: [(HeldBy, &'static str); 7] = [
+        (HeldBy::DELAY, "delay"),
+        (HeldBy::DNS, "dns"),
+        (HeldBy::THROTTLE, "throttle"),
+        (HeldBy::REORDER, "reorder"),
+        (HeldBy::TOPOLOGY, "topology"),
+        (HeldBy::TRACE, "trace"),
+        (HeldBy::BANDWIDTH, "bandwidth"),
+    ];
+
+    pub fn insert(&mut self, modules: HeldBy) {
+        self.0 |= modules.0;
+    }
+
+    pub fn contains(&self, modules: HeldBy) -> bool {
+        self.0 & modules.0 == modules.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns the indices into [`MODULES`](Self::MODULES) of the modules in the set.
+    pub fn indices(self) -> impl Iterator<Item = usize> {
+        HeldBy::MODULES
+            .iter()
+            .enumerate()
+            .filter(move |(_, (module, _))| self.contains(*module))
+            .map(|(index, _)| index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_held_by_indices() {
+        let mut held_by = HeldBy::default();
+        assert!(held_by.is_empty());
+        held_by.insert(HeldBy::BANDWIDTH);
+        held_by.insert(HeldBy::DELAY);
+        assert!(held_by.contains(HeldBy::DELAY));
+        assert!(!held_by.contains(HeldBy::REORDER));
+        let names: Vec<&str> = held_by
+            .indices()
+            .map(|index| HeldBy::MODULES[index].1)
+            .collect();
+        assert_eq!(names, ["delay", "bandwidth"]);
+    }
+}
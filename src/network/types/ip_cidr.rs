@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fmt::Formatter;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+/// An IPv4 or IPv6 network in CIDR notation, e.g. `10.1.0.0/16` or `2001:db8::/32`.
+/// A bare address is treated as a single host network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct IpCidr {
+    network: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpCidr {
+    /// Creates a network from an address and prefix length. Host bits of the address are cleared.
+    pub fn new(address: IpAddr, prefix_len: u8) -> Result<Self, String> {
+        let max_prefix_len = match address {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        if prefix_len > max_prefix_len {
+            return Err(format!(
+                "Prefix length {} is larger than {} for {}",
+                prefix_len, max_prefix_len, address
+            ));
+        }
+        let network = match address {
+            IpAddr::V4(address) => {
+                let masked = u32::from(address) & ipv4_mask(prefix_len);
+                IpAddr::V4(Ipv4Addr::from(masked))
+            }
+            IpAddr::V6(address) => {
+                let masked = u128::from(address) & ipv6_mask(prefix_len);
+                IpAddr::V6(Ipv6Addr::from(masked))
+            }
+        };
+        Ok(IpCidr {
+            network,
+            prefix_len,
+        })
+    }
+
+    pub fn network(&self) -> IpAddr {
+        self.network
+    }
+
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    /// Returns `true` if `address` is in this network. Addresses of the other IP version never match.
+    pub fn contains(&self, address: IpAddr) -> bool {
+        match (self.network, address) {
+            (IpAddr::V4(network), IpAddr::V4(address)) => {
+                u32::from(address) & ipv4_mask(self.prefix_len) == u32::from(network)
+            }
+            (IpAddr::V6(network), IpAddr::V6(address)) => {
+                u128::from(address) & ipv6_mask(self.prefix_len) == u128::from(network)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn ipv4_mask(prefix_len: u8) -> u32 {
+    u32::MAX.checked_shl(32 - prefix_len as u32).unwrap_or(0)
+}
+
+fn ipv6_mask(prefix_len: u8) -> u128 {
+    u128::MAX.checked_shl(128 - prefix_len as u32).unwrap_or(0)
+}
+
+impl FromStr for IpCidr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (address, prefix_len) = match s.split_once('/') {
+            Some((address, prefix_len)) => (address, Some(prefix_len)),
+            None => (s, None),
+        };
+        let address: IpAddr = address
+            .trim()
+            .parse()
+            .map_err(|_| format!("`{}` is not a valid IP address", address))?;
+        let prefix_len = match prefix_len {
+            Some(prefix_len) => prefix_len
+                .trim()
+                .parse()
+                .map_err(|_| format!("`{}` is not a valid prefix length", prefix_len))?,
+            None if address.is_ipv4() => 32,
+            None => 128,
+        };
+        IpCidr::new(address, prefix_len)
+    }
+}
+
+impl TryFrom<String> for IpCidr {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<IpCidr> for String {
+    fn from(cidr: IpCidr) -> Self {
+        cidr.to_string()
+    }
+}
+
+impl fmt::Display for IpCidr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix_len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        let cidr: IpCidr = "10.1.2.3/16".parse().unwrap();
+        assert_eq!(cidr.to_string(), "10.1.0.0/16");
+        assert_eq!("192.168.1.1".parse::<IpCidr>().unwrap().prefix_len(), 32);
+        assert_eq!(
+            "2001:db8::1/32".parse::<IpCidr>().unwrap().to_string(),
+            "2001:db8::/32"
+        );
+    }
+
+    #[test]
+    fn test_contains() {
+        let cidr: IpCidr = "10.1.0.0/16".parse().unwrap();
+        assert!(cidr.contains("10.1.255.1".parse().unwrap()));
+        assert!(!cidr.contains("10.2.0.1".parse().unwrap()));
+        assert!(!cidr.contains("::ffff:10.1.0.1".parse().unwrap()));
+
+        let everything: IpCidr = "0.0.0.0/0".parse().unwrap();
+        assert!(everything.contains("8.8.8.8".parse().unwrap()));
+
+        let v6: IpCidr = "2001:db8::/32".parse().unwrap();
+        assert!(v6.contains("2001:db8:1::1".parse().unwrap()));
+        assert!(!v6.contains("2001:db9::1".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!("10.0.0.0/33".parse::<IpCidr>().is_err());
+        assert!("10.0.0/8".parse::<IpCidr>().is_err());
+        assert!("10.0.0.0/x".parse::<IpCidr>().is_err());
+    }
+}
@@ -1,2 +1,11 @@
+pub mod bit_rate;
+pub mod byte_pattern;
 pub mod delayed_packet;
+pub mod duration_spec;
+pub mod held_by;
+pub mod ip_cidr;
+pub mod name_pattern;
+pub mod packet_actions;
+pub mod payload_regex;
 pub mod probability;
+pub mod trace;
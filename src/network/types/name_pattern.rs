@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+/// A case-insensitive domain name pattern where `*` matches any sequence of characters,
+/// e.g. `*.example.com` or `api-*.example.com`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct NamePattern(String);
+
+impl NamePattern {
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        let pattern = pattern.trim().trim_end_matches('.').to_ascii_lowercase();
+        if pattern.is_empty() {
+            return Err("Name pattern cannot be empty".to_string());
+        }
+        if let Some(c) = pattern
+            .chars()
+            .find(|c| !(c.is_ascii_alphanumeric() || ['.', '-', '_', '*', ':'].contains(c)))
+        {
+            return Err(format!("`{}` contains invalid character `{}`", pattern, c));
+        }
+        Ok(NamePattern(pattern))
+    }
+
+    /// Returns `true` if `name` matches the pattern. A trailing dot on `name` is ignored.
+    pub fn matches(&self, name: &str) -> bool {
+        let name = name.trim_end_matches('.').to_ascii_lowercase();
+        wildcard_match(self.0.as_bytes(), name.as_bytes())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+fn wildcard_match(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == b'*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+impl FromStr for NamePattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        NamePattern::new(s)
+    }
+}
+
+impl TryFrom<String> for NamePattern {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        NamePattern::new(&value)
+    }
+}
+
+impl From<NamePattern> for String {
+    fn from(pattern: NamePattern) -> Self {
+        pattern.0
+    }
+}
+
+impl fmt::Display for NamePattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_match_is_case_insensitive() {
+        let pattern = NamePattern::new("Example.COM.").unwrap();
+        assert!(pattern.matches("example.com"));
+        assert!(pattern.matches("EXAMPLE.com."));
+        assert!(!pattern.matches("www.example.com"));
+    }
+
+    #[test]
+    fn test_wildcard_subdomains() {
+        let pattern = NamePattern::new("*.example.com").unwrap();
+        assert!(pattern.matches("www.example.com"));
+        assert!(pattern.matches("a.b.example.com"));
+        assert!(!pattern.matches("example.com"));
+        assert!(!pattern.matches("www.example.org"));
+    }
+
+    #[test]
+    fn test_wildcard_in_label() {
+        let pattern = NamePattern::new("api-*.example.com").unwrap();
+        assert!(pattern.matches("api-eu.example.com"));
+        assert!(!pattern.matches("web.example.com"));
+        assert!(NamePattern::new("*").unwrap().matches("anything.at.all"));
+    }
+
+    #[test]
+    fn test_invalid_patterns() {
+        assert!(NamePattern::new("").is_err());
+        assert!(NamePattern::new("exa mple.com").is_err());
+    }
+}
@@ -0,0 +1,63 @@
+use std::fmt;
+use std::fmt::Formatter;
+
+/// Set of actions applied to a packet while it passes through the manipulation modules.
+/// An empty set means the packet passed through untouched.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PacketActions(u8);
+
+impl PacketActions {
+    pub const DROPPED: PacketActions = PacketActions(1);
+    pub const DELAYED: PacketActions = PacketActions(1 << 1);
+    pub const DUPLICATED: PacketActions = PacketActions(1 << 2);
+    pub const TAMPERED: PacketActions = PacketActions(1 << 3);
+
+    const NAMES: [(PacketActions, &'static str); 4] = [
+        (PacketActions::DROPPED, "dropped"),
+        (PacketActions::DELAYED, "delayed"),
+        (PacketActions::DUPLICATED, "duplicated"),
+        (PacketActions::TAMPERED, "tampered"),
+    ];
+
+    pub fn insert(&mut self, actions: PacketActions) {
+        self.0 |= actions.0;
+    }
+
+    pub fn contains(&self, actions: PacketActions) -> bool {
+        self.0 & actions.0 == actions.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl fmt::Display for PacketActions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "passed");
+        }
+        let names: Vec<&str> = PacketActions::NAMES
+            .iter()
+            .filter(|(action, _)| self.contains(*action))
+            .map(|(_, name)| *name)
+            .collect();
+        write!(f, "{}", names.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_packet_actions_display() {
+        let mut actions = PacketActions::default();
+        assert_eq!(actions.to_string(), "passed");
+        actions.insert(PacketActions::TAMPERED);
+        actions.insert(PacketActions::DELAYED);
+        assert!(actions.contains(PacketActions::DELAYED));
+        assert!(!actions.contains(PacketActions::DROPPED));
+        assert_eq!(actions.to_string(), "delayed,tampered");
+    }
+}
@@ -0,0 +1,52 @@
+use regex::bytes::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+/// A regular expression matched against raw packet payload bytes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct PayloadRegex(Regex);
+
+impl PayloadRegex {
+    pub fn is_match(&self, payload: &[u8]) -> bool {
+        self.0.is_match(payload)
+    }
+}
+
+impl PartialEq for PayloadRegex {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+impl FromStr for PayloadRegex {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Regex::new(s)
+            .map(PayloadRegex)
+            .map_err(|e| format!("`{}` is not a valid regex: {}", s, e))
+    }
+}
+
+impl TryFrom<String> for PayloadRegex {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<PayloadRegex> for String {
+    fn from(regex: PayloadRegex) -> Self {
+        regex.0.as_str().to_string()
+    }
+}
+
+impl fmt::Display for PayloadRegex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.as_str())
+    }
+}
@@ -4,6 +4,7 @@ use std::fmt::Formatter;
 use std::str::FromStr;
 
 #[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "f64", into = "f64")]
 pub struct Probability(f64);
 
 impl Probability {
@@ -31,6 +32,14 @@ impl FromStr for Probability {
     }
 }
 
+impl TryFrom<f64> for Probability {
+    type Error = String;
+
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        Probability::new(value)
+    }
+}
+
 impl From<Probability> for f64 {
     fn from(prob: Probability) -> Self {
         prob.0
@@ -0,0 +1,240 @@
+use crate::network::types::bit_rate::BitRate;
+use crate::network::types::duration_spec::DurationSpec;
+use crate::network::types::probability::Probability;
+use std::time::Duration;
+
+/// Bytes a delivery opportunity of a Mahimahi trace can deliver: one MTU-sized packet.
+pub const DELIVERY_OPPORTUNITY_BYTES: usize = 1500;
+
+/// Recorded network conditions to replay, read from a trace file.
+///
+/// Two formats are understood, told apart by their content:
+///
+/// - Mahimahi packet delivery traces, with one timestamp in milliseconds per line. Each is an
+///   opportunity to deliver [`DELIVERY_OPPORTUNITY_BYTES`], and the trace lasts until its last
+///   timestamp.
+/// - CSV with `timestamp,latency,bandwidth,loss` rows, e.g. `1.5s,80ms,2Mbps,0.01`. Each row
+///   holds from its timestamp until the next one, and the last row as long as the row before it.
+///   Timestamps and latencies without a unit are in milliseconds. An empty bandwidth does not
+///   limit the link, a bandwidth of zero is an outage, and the loss is a probability or a
+///   percentage such as `1%`. The bandwidth and loss can be left out.
+///
+/// Empty lines and lines starting with `#` are skipped, as is a CSV header.
+///
+/// # Example
+///
+/// ```rust
+/// use fumble::network::types::trace::Trace;
+/// use std::time::Duration;
+/// let trace = Trace::parse("timestamp,latency,bandwidth,loss\n0,50,2Mbps,0\n1s,80ms,1Mbps,1%\n").unwrap();
+/// assert_eq!(trace.period(), Duration::from_secs(2));
+/// let conditions = trace.conditions_at(Duration::from_millis(1500)).unwrap();
+/// assert_eq!(conditions.latency, Duration::from_millis(80));
+/// assert_eq!(Trace::parse("5\n10\n10\n").unwrap().period(), Duration::from_millis(10));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Trace {
+    /// Times into the trace at which a packet can be delivered, in order
+    DeliveryOpportunities(Vec<Duration>),
+    /// Conditions of the link from their time into the trace on, in order
+    Conditions(Vec<TraceConditions>),
+}
+
+/// The conditions of the link from one row of a CSV trace on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TraceConditions {
+    pub at: Duration,
+    pub latency: Duration,
+    /// `None` if the link is not limited
+    pub bandwidth: Option<BitRate>,
+    pub loss: Probability,
+}
+
+impl Trace {
+    pub fn parse(content: &str) -> Result<Trace, String> {
+        let lines: Vec<(usize, &str)> = content
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .collect();
+        if lines.iter().any(|(_, line)| line.contains(',')) {
+            parse_conditions(&lines)
+        } else {
+            parse_delivery_opportunities(&lines)
+        }
+    }
+
+    /// Returns how long the trace lasts before it starts over.
+    pub fn period(&self) -> Duration {
+        match self {
+            Trace::DeliveryOpportunities(times) => times.last().copied().unwrap_or_default(),
+            Trace::Conditions(rows) => match rows.as_slice() {
+                [.., previous, last] => last.at + (last.at - previous.at),
+                _ => Duration::ZERO,
+            },
+        }
+    }
+
+    /// Returns the conditions at `position` into a CSV trace, or `None` for a Mahimahi trace.
+    /// Before the first row, its conditions hold.
+    pub fn conditions_at(&self, position: Duration) -> Option<&TraceConditions> {
+        match self {
+            Trace::DeliveryOpportunities(_) => None,
+            Trace::Conditions(rows) => {
+                let index = rows.partition_point(|row| row.at <= position);
+                rows.get(index.saturating_sub(1))
+            }
+        }
+    }
+}
+
+fn parse_delivery_opportunities(lines: &[(usize, &str)]) -> Result<Trace, String> {
+    let mut times = Vec::with_capacity(lines.len());
+    for (number, line) in lines {
+        let millis: u64 = line.parse().map_err(|_| {
+            format!(
+                "line {}: `{}` is not a timestamp in milliseconds",
+                number, line
+            )
+        })?;
+        let time = Duration::from_millis(millis);
+        if times.last().is_some_and(|last| time < *last) {
+            return Err(format!("line {}: timestamps must not decrease", number));
+        }
+        times.push(time);
+    }
+    if times.last().is_none_or(|last| last.is_zero()) {
+        return Err("a Mahimahi trace needs a timestamp after 0".to_string());
+    }
+    Ok(Trace::DeliveryOpportunities(times))
+}
+
+fn parse_conditions(lines: &[(usize, &str)]) -> Result<Trace, String> {
+    let mut rows: Vec<TraceConditions> = Vec::with_capacity(lines.len());
+    for (index, (number, line)) in lines.iter().enumerate() {
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let at = match fields[0].parse::<DurationSpec>() {
+            Ok(at) => at.duration(),
+            // A header names the columns instead
+            Err(_) if index == 0 => continue,
+            Err(e) => return Err(format!("line {}: {}", number, e)),
+        };
+        let row = parse_row(at, &fields[1..]).map_err(|e| format!("line {}: {}", number, e))?;
+        if rows.last().is_some_and(|last| row.at <= last.at) {
+            return Err(format!("line {}: timestamps must increase", number));
+        }
+        rows.push(row);
+    }
+    if rows.len() < 2 {
+        return Err("a CSV trace needs at least two rows".to_string());
+    }
+    Ok(Trace::Conditions(rows))
+}
+
+/// Parses the latency, bandwidth and loss of a row at `at`.
+fn parse_row(at: Duration, fields: &[&str]) -> Result<TraceConditions, String> {
+    let field = |index: usize| fields.get(index).copied().unwrap_or_default();
+    if fields.len() > 3 {
+        return Err("expected timestamp,latency,bandwidth,loss".to_string());
+    }
+    let latency = match field(0) {
+        "" => return Err("missing latency".to_string()),
+        latency => latency.parse::<DurationSpec>()?.duration(),
+    };
+    let bandwidth = match field(1) {
+        "" => None,
+        bandwidth => Some(bandwidth.parse()?),
+    };
+    let loss = match field(2) {
+        "" => Probability::default(),
+        loss => match loss.strip_suffix('%') {
+            Some(percent) => {
+                let percent: f64 = percent
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("`{}` is not a valid loss", loss))?;
+                Probability::new(percent / 100.0)?
+            }
+            None => loss.parse()?,
+        },
+    };
+    Ok(TraceConditions {
+        at,
+        latency,
+        bandwidth,
+        loss,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_mahimahi_trace() {
+        let trace = Trace::parse("# recorded on a train\n1\n1\n3\n\n8\n").unwrap();
+        assert_eq!(
+            trace,
+            Trace::DeliveryOpportunities(vec![
+                Duration::from_millis(1),
+                Duration::from_millis(1),
+                Duration::from_millis(3),
+                Duration::from_millis(8),
+            ])
+        );
+        assert_eq!(trace.period(), Duration::from_millis(8));
+        assert!(trace.conditions_at(Duration::ZERO).is_none());
+
+        assert!(Trace::parse("").is_err());
+        assert!(Trace::parse("0\n0\n").is_err());
+        assert!(Trace::parse("5\n3\n").is_err());
+        assert!(Trace::parse("5\nsoon\n").is_err());
+    }
+
+    #[test]
+    fn test_parses_csv_trace() {
+        let trace = Trace::parse("0,50\n500ms, 80, 2Mbps, 1%\n1.5s,20ms,,0.5\n").unwrap();
+        let rows = match &trace {
+            Trace::Conditions(rows) => rows,
+            other => panic!("expected a CSV trace, got {:?}", other),
+        };
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].bandwidth, None);
+        assert_eq!(rows[0].loss, Probability::default());
+        assert_eq!(rows[1].at, Duration::from_millis(500));
+        assert_eq!(rows[1].bandwidth.unwrap().bits_per_second(), 2_000_000);
+        assert_eq!(rows[1].loss.value(), 0.01);
+        assert_eq!(rows[2].loss.value(), 0.5);
+        assert_eq!(trace.period(), Duration::from_millis(2500));
+
+        let latency_at = |millis| {
+            trace
+                .conditions_at(Duration::from_millis(millis))
+                .unwrap()
+                .latency
+        };
+        assert_eq!(latency_at(0), Duration::from_millis(50));
+        assert_eq!(latency_at(499), Duration::from_millis(50));
+        assert_eq!(latency_at(500), Duration::from_millis(80));
+        assert_eq!(latency_at(2000), Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_rejects_invalid_csv_traces() {
+        for content in [
+            "0,50\n",
+            "0,50\n0,60\n",
+            "0,50\n1s\n",
+            "0,50\n1s,,1Mbps\n",
+            "0,50\n1s,60,fast\n",
+            "0,50\n1s,60,,150%\n",
+            "0,50\n1s,60,,0.1,extra\n",
+            "0,50\nlater,60\n",
+        ] {
+            assert!(Trace::parse(content).is_err(), "{}", content);
+        }
+        let error = Trace::parse("# comment\ntimestamp,latency\n0,50\n1s,soon\n").unwrap_err();
+        assert!(error.starts_with("line 4:"), "{}", error);
+    }
+}
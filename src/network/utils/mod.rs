@@ -1 +1,4 @@
 pub mod filter;
+pub mod pcap;
+pub mod random;
+pub mod snapshot;
@@ -0,0 +1,241 @@
+use std::io::Read;
+use std::time::Duration;
+use thiserror::Error;
+
+const MAGIC_MICROSECONDS: u32 = 0xa1b2_c3d4;
+const MAGIC_NANOSECONDS: u32 = 0xa1b2_3c4d;
+const GLOBAL_HEADER_LEN: usize = 24;
+const RECORD_HEADER_LEN: usize = 16;
+/// Upper bound for a single record, protecting against corrupt length fields.
+const MAX_RECORD_LEN: usize = 256 * 1024;
+
+/// Link-layer header types from the pcap specification.
+mod link_type {
+    pub const NULL: u32 = 0;
+    pub const ETHERNET: u32 = 1;
+    pub const RAW: u32 = 101;
+    pub const LOOP: u32 = 108;
+    pub const IPV4: u32 = 228;
+    pub const IPV6: u32 = 229;
+}
+
+const ETHERNET_HEADER_LEN: usize = 14;
+const ETHER_TYPE_IPV4: u16 = 0x0800;
+const ETHER_TYPE_IPV6: u16 = 0x86dd;
+const ETHER_TYPE_VLAN: u16 = 0x8100;
+
+#[derive(Debug, Error)]
+pub enum PcapError {
+    #[error("Failed to read capture: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Not a pcap file (magic number {0:#010x}); pcapng captures are not supported")]
+    InvalidMagic(u32),
+    #[error("Unsupported link type {0}, only raw IP and Ethernet captures are supported")]
+    UnsupportedLinkType(u32),
+    #[error("Record {index} is truncated")]
+    Truncated { index: usize },
+    #[error("Record {index} has an invalid length of {length} bytes")]
+    InvalidRecordLength { index: usize, length: usize },
+}
+
+/// An IP packet read from a capture.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedPacket {
+    /// Time of the packet relative to the first packet of the capture
+    pub offset: Duration,
+    /// The packet starting at its IP header
+    pub data: Vec<u8>,
+}
+
+/// Reads all IP packets of a classic pcap capture, stripping link-layer headers.
+/// Frames that do not carry IPv4 or IPv6, such as ARP, are skipped.
+pub fn read_pcap<R: Read>(mut reader: R) -> Result<Vec<CapturedPacket>, PcapError> {
+    let mut header = [0u8; GLOBAL_HEADER_LEN];
+    reader.read_exact(&mut header)?;
+    let (big_endian, nanoseconds) =
+        match u32::from_le_bytes([header[0], header[1], header[2], header[3]]) {
+            MAGIC_MICROSECONDS => (false, false),
+            MAGIC_NANOSECONDS => (false, true),
+            magic if magic.swap_bytes() == MAGIC_MICROSECONDS => (true, false),
+            magic if magic.swap_bytes() == MAGIC_NANOSECONDS => (true, true),
+            magic => return Err(PcapError::InvalidMagic(magic)),
+        };
+    let read_u32 = |bytes: &[u8]| {
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        if big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        }
+    };
+    // The upper bits of the link type field may hold FCS information
+    let link_type = read_u32(&header[20..24]) & 0x0fff_ffff;
+    if !matches!(
+        link_type,
+        link_type::NULL
+            | link_type::ETHERNET
+            | link_type::RAW
+            | link_type::LOOP
+            | link_type::IPV4
+            | link_type::IPV6
+    ) {
+        return Err(PcapError::UnsupportedLinkType(link_type));
+    }
+
+    let mut packets = Vec::new();
+    let mut first_timestamp = None;
+    let mut index = 0;
+    loop {
+        let mut record_header = [0u8; RECORD_HEADER_LEN];
+        match read_full(&mut reader, &mut record_header)? {
+            0 => break,
+            RECORD_HEADER_LEN => {}
+            _ => return Err(PcapError::Truncated { index }),
+        }
+        let seconds = read_u32(&record_header[0..4]) as u64;
+        let fraction = read_u32(&record_header[4..8]) as u64;
+        let length = read_u32(&record_header[8..12]) as usize;
+        if length > MAX_RECORD_LEN {
+            return Err(PcapError::InvalidRecordLength { index, length });
+        }
+        let mut frame = vec![0u8; length];
+        if read_full(&mut reader, &mut frame)? != length {
+            return Err(PcapError::Truncated { index });
+        }
+
+        let timestamp = Duration::from_secs(seconds)
+            + if nanoseconds {
+                Duration::from_nanos(fraction)
+            } else {
+                Duration::from_micros(fraction)
+            };
+        let first_timestamp = *first_timestamp.get_or_insert(timestamp);
+        if let Some(data) = ip_payload(link_type, &frame) {
+            packets.push(CapturedPacket {
+                offset: timestamp.saturating_sub(first_timestamp),
+                data: data.to_vec(),
+            });
+        }
+        index += 1;
+    }
+    Ok(packets)
+}
+
+/// Returns the IP packet carried by a frame of the given link type, if any.
+fn ip_payload(link_type: u32, frame: &[u8]) -> Option<&[u8]> {
+    let data = match link_type {
+        link_type::ETHERNET => {
+            let mut offset = ETHERNET_HEADER_LEN;
+            let mut ether_type = u16::from_be_bytes([*frame.get(12)?, *frame.get(13)?]);
+            if ether_type == ETHER_TYPE_VLAN {
+                ether_type = u16::from_be_bytes([*frame.get(16)?, *frame.get(17)?]);
+                offset += 4;
+            }
+            if ether_type != ETHER_TYPE_IPV4 && ether_type != ETHER_TYPE_IPV6 {
+                return None;
+            }
+            frame.get(offset..)?
+        }
+        // BSD loopback encapsulation starts with a 4 byte address family
+        link_type::NULL | link_type::LOOP => frame.get(4..)?,
+        _ => frame,
+    };
+    match data.first()? >> 4 {
+        4 | 6 => Some(data),
+        _ => None,
+    }
+}
+
+/// Fills `buffer` as far as possible, returning the number of bytes read before the end of input.
+fn read_full<R: Read>(reader: &mut R, buffer: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(read) => filled += read,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::packet::protocol;
+    use crate::network::packet::test_packets::{ipv4, udp};
+
+    fn capture(link_type: u32, records: &[(u32, u32, Vec<u8>)]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&MAGIC_MICROSECONDS.to_le_bytes());
+        data.extend_from_slice(&2u16.to_le_bytes());
+        data.extend_from_slice(&4u16.to_le_bytes());
+        data.extend_from_slice(&[0; 8]);
+        data.extend_from_slice(&65535u32.to_le_bytes());
+        data.extend_from_slice(&link_type.to_le_bytes());
+        for (seconds, microseconds, frame) in records {
+            data.extend_from_slice(&seconds.to_le_bytes());
+            data.extend_from_slice(&microseconds.to_le_bytes());
+            data.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+            data.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+            data.extend_from_slice(frame);
+        }
+        data
+    }
+
+    fn ethernet(ether_type: u16, payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![0u8; 12];
+        frame.extend_from_slice(&ether_type.to_be_bytes());
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    #[test]
+    fn test_read_raw_capture() {
+        let packet = ipv4(protocol::UDP, &udp(1000, 53, b"query"));
+        let data = capture(
+            link_type::RAW,
+            &[(10, 500_000, packet.clone()), (11, 0, packet.clone())],
+        );
+        let packets = read_pcap(data.as_slice()).unwrap();
+        assert_eq!(packets.len(), 2);
+        assert_eq!(packets[0].offset, Duration::ZERO);
+        assert_eq!(packets[1].offset, Duration::from_millis(500));
+        assert_eq!(packets[1].data, packet);
+    }
+
+    #[test]
+    fn test_read_ethernet_capture_skips_non_ip_frames() {
+        let packet = ipv4(protocol::UDP, &udp(1000, 53, b"query"));
+        let data = capture(
+            link_type::ETHERNET,
+            &[
+                (1, 0, ethernet(0x0806, &[0; 28])),
+                (1, 10, ethernet(ETHER_TYPE_IPV4, &packet)),
+            ],
+        );
+        let packets = read_pcap(data.as_slice()).unwrap();
+        assert_eq!(packets.len(), 1);
+        assert_eq!(packets[0].data, packet);
+        assert_eq!(packets[0].offset, Duration::from_micros(10));
+    }
+
+    #[test]
+    fn test_read_invalid_captures() {
+        assert!(matches!(
+            read_pcap(&[0u8; 24][..]),
+            Err(PcapError::InvalidMagic(0))
+        ));
+        assert!(matches!(
+            read_pcap(capture(147, &[]).as_slice()),
+            Err(PcapError::UnsupportedLinkType(147))
+        ));
+        let mut data = capture(link_type::RAW, &[(1, 0, vec![0x45; 20])]);
+        data.truncate(data.len() - 5);
+        assert!(matches!(
+            read_pcap(data.as_slice()),
+            Err(PcapError::Truncated { index: 0 })
+        ));
+    }
+}
@@ -0,0 +1,55 @@
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use std::cell::RefCell;
+
+thread_local! {
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::seed_from_u64(rand::random()));
+}
+
+/// Reseeds the random number generator used by the manipulation modules on the current thread,
+/// making their random decisions repeatable for the same packets and settings.
+pub fn seed(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+/// Returns a handle to the random number generator of the current thread.
+/// It is randomly seeded unless [`seed`] was called on the thread.
+pub fn rng() -> ModuleRng {
+    ModuleRng
+}
+
+/// Handle to the thread local random number generator used by the manipulation modules.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ModuleRng;
+
+impl RngCore for ModuleRng {
+    fn next_u32(&mut self) -> u32 {
+        RNG.with(|rng| rng.borrow_mut().next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        RNG.with(|rng| rng.borrow_mut().next_u64())
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        RNG.with(|rng| rng.borrow_mut().fill_bytes(dst))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn test_seeded_sequences_repeat() {
+        seed(42);
+        let first: Vec<u64> = (0..8).map(|_| rng().random()).collect();
+        seed(42);
+        let second: Vec<u64> = (0..8).map(|_| rng().random()).collect();
+        assert_eq!(first, second);
+        seed(43);
+        let third: Vec<u64> = (0..8).map(|_| rng().random()).collect();
+        assert_ne!(first, third);
+    }
+}
@@ -0,0 +1,91 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// A value that is replaced as a whole and read by many threads.
+///
+/// Readers go through a [`SnapshotReader`], which keeps the last value it saw and only takes the
+/// lock after a newer one was published. Reading an unchanged value is a single atomic load.
+pub struct Snapshot<T> {
+    version: AtomicU64,
+    value: RwLock<Arc<T>>,
+}
+
+impl<T> Snapshot<T> {
+    pub fn new(value: T) -> Self {
+        Snapshot {
+            version: AtomicU64::new(0),
+            value: RwLock::new(Arc::new(value)),
+        }
+    }
+
+    /// Replaces the value seen by all readers.
+    pub fn publish(&self, value: T) {
+        let value = Arc::new(value);
+        match self.value.write() {
+            Ok(mut current) => *current = value,
+            Err(poisoned) => *poisoned.into_inner() = value,
+        }
+        self.version.fetch_add(1, Ordering::Release);
+    }
+
+    /// Returns the current value.
+    pub fn load(&self) -> Arc<T> {
+        match self.value.read() {
+            Ok(value) => value.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    pub fn reader(self: &Arc<Self>) -> SnapshotReader<T> {
+        let version = self.version.load(Ordering::Acquire);
+        SnapshotReader {
+            snapshot: self.clone(),
+            version,
+            value: self.load(),
+        }
+    }
+}
+
+/// A reader of a [`Snapshot`], owned by a single thread.
+pub struct SnapshotReader<T> {
+    snapshot: Arc<Snapshot<T>>,
+    version: u64,
+    value: Arc<T>,
+}
+
+impl<T> SnapshotReader<T> {
+    /// Returns the latest published value.
+    pub fn get(&mut self) -> &Arc<T> {
+        let version = self.snapshot.version.load(Ordering::Acquire);
+        if version != self.version {
+            self.version = version;
+            self.value = self.snapshot.load();
+        }
+        &self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_readers_see_published_values() {
+        let snapshot = Arc::new(Snapshot::new(1));
+        let mut reader = snapshot.reader();
+        assert_eq!(**reader.get(), 1);
+
+        snapshot.publish(2);
+        assert_eq!(**reader.get(), 2);
+        // Unchanged values are not loaded again
+        let first = reader.get().clone();
+        assert!(Arc::ptr_eq(&first, reader.get()));
+
+        let handle = thread::spawn({
+            let mut reader = snapshot.reader();
+            move || **reader.get()
+        });
+        assert_eq!(handle.join().unwrap(), 2);
+    }
+}
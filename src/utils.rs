@@ -1,14 +1,12 @@
+use crate::network::modules::stats::accounting_stats::PacketAccounting;
 use log::info;
 
-pub fn log_statistics(received: usize, sent: usize) {
-    let dropped = received.saturating_sub(sent); // Number of dropped packets
-    let dropped_percentage = if received > 0 {
-        (dropped as f64 / received as f64) * 100.0
+pub fn log_statistics(accounting: &PacketAccounting) {
+    let incoming = accounting.carried_over + accounting.received + accounting.duplicated;
+    let dropped_percentage = if incoming > 0 {
+        (accounting.total_dropped() as f64 / incoming as f64) * 100.0
     } else {
         0.0
     };
-    info!(
-        "Received Packets: {}, Sent Packets: {}, Skipped Packets: {} - {:.2}%",
-        received, sent, dropped, dropped_percentage
-    );
+    info!("{} - {:.2}% dropped", accounting, dropped_percentage);
 }